@@ -1,39 +1,119 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::BufRead;
+use std::io::ErrorKind;
+use std::io::IsTerminal;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
 
 use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
 use anyhow::bail;
+use chrono::DateTime;
+use chrono::Utc;
 use clap::ArgGroup;
+use clap::builder::PossibleValuesParser;
 use codex_common::CliConfigOverrides;
-use codex_common::format_env_display::format_env_display;
+use codex_common::format_env_display::format_env_display_masked;
+use codex_core::config::CONFIG_TOML_FILE;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
+use codex_core::config::McpServerSource;
+use codex_core::config::McpServersConfig;
 use codex_core::config::find_codex_home;
+use codex_core::config::find_or_create_codex_home;
 use codex_core::config::load_global_mcp_servers;
+use codex_core::config::load_mcp_servers_with_cli_overrides;
+use codex_core::config::log_dir;
+use codex_core::config::parse_mcp_servers_toml;
+use codex_core::config::parse_mcp_servers_toml_per_server;
 use codex_core::config::write_global_mcp_servers;
+use codex_core::config_types::MCP_PROTOCOL_VERSIONS;
+use codex_core::config_types::MIN_MAX_REQUESTS_PER_SEC;
+use codex_core::config_types::McpEnabledWhen;
+use codex_core::config_types::McpInheritEnv;
+use codex_core::config_types::McpOAuthClientCredentials;
 use codex_core::config_types::McpServerConfig;
+use codex_core::config_types::McpServerDefaults;
 use codex_core::config_types::McpServerTransportConfig;
 use codex_core::features::Feature;
+use codex_core::mcp::DEFAULT_STARTUP_TIMEOUT;
+use codex_core::mcp::DEFAULT_TOOL_TIMEOUT;
 use codex_core::mcp::auth::compute_auth_statuses;
+use codex_core::mcp::control::MCP_CONTROL_DIR;
+use codex_core::mcp::control::McpControlRequest;
+use codex_core::mcp::control::McpControlResponse;
+use codex_core::mcp::control::McpRestartOutcome;
+use codex_core::mcp::control::running_sessions;
+use codex_core::mcp::env_mask::EnvMaskPatterns;
+use codex_core::mcp::env_mask::MASKED_ENV_VALUE;
+use codex_core::mcp::logs::server_log_path;
+use codex_core::mcp::probe::McpServerProbe;
+use codex_core::mcp::probe::call_server_tool;
+use codex_core::mcp::probe::probe_server;
+use codex_core::mcp::resolve_http_headers;
+use codex_core::mcp::resolve_stdio_server_env;
+use codex_core::mcp::resolve_streamable_http_url;
+use codex_core::mcp::state::load_server_states;
+use codex_core::mcp::state::record_connected;
 use codex_core::protocol::McpAuthStatus;
+use codex_rmcp_client::OAuthCredentialsStoreMode;
 use codex_rmcp_client::delete_oauth_tokens;
 use codex_rmcp_client::perform_oauth_login;
 use codex_rmcp_client::supports_oauth_login;
+use futures::StreamExt;
+use futures::stream;
+use mcp_types::ContentBlock;
+use notify::RecursiveMode;
+use notify::Watcher;
+use owo_colors::OwoColorize;
+use owo_colors::Style;
+use serde::Deserialize;
+use serde::Serialize;
+use serde::de::IgnoredAny;
+use supports_color::Stream;
+use wildmatch::WildMatch;
 
 /// [experimental] Launch Codex as an MCP server or manage configured MCP servers.
 ///
 /// Subcommands:
 /// - `serve`  — run the MCP server on stdio
 /// - `list`   — list configured servers (with `--json`)
-/// - `get`    — show a single server (with `--json`)
+/// - `get`    — show a single server (with `--json`, `--probe`)
+/// - `ping`   — check that a server answers (exit status for scripts)
+/// - `restart` — restart servers in running Codex sessions
+/// - `test-tool` — call one of a server's tools and print the result
+/// - `which`  — find the servers that advertise a tool
+/// - `logs`   — print or follow a stdio server's stderr log
+/// - `env`    — print the environment a stdio server is launched with
 /// - `add`    — add a server launcher entry to `~/.codex/config.toml`
+///   (or import one with `--from-json`)
+/// - `add-many` — add stdio servers listed one per line in a file
+/// - `clone`  — copy a server entry to a new name
 /// - `remove` — delete a server entry
+/// - `validate` — check a file of server definitions without installing it
+/// - `diff`   — compare a file of server definitions with config.toml
+/// - `stats`  — summarize the configured servers
+///
+/// With `--json` before the subcommand, or a subcommand's own `--json`, a
+/// failure is reported on stderr as `{"error": {"message", "context"}}`.
 #[derive(Debug, clap::Parser)]
 pub struct McpCli {
     #[clap(flatten)]
     pub config_overrides: CliConfigOverrides,
 
+    /// Report failures on stderr as a JSON object instead of text.
+    #[arg(long)]
+    pub json: bool,
+
     #[command(subcommand)]
     pub subcommand: McpSubcommand,
 }
@@ -46,12 +126,61 @@ pub enum McpSubcommand {
     /// [experimental] Show details for a configured MCP server.
     Get(GetArgs),
 
+    /// [experimental] Connect to a configured MCP server and report whether it
+    /// is healthy. Exits 0 when healthy, 1 when the connection fails, and 2
+    /// when no server with that name is configured.
+    Ping(PingArgs),
+
+    /// [experimental] Ask every running Codex session (interactive or
+    /// `codex exec`) to stop MCP servers and start them again, then print
+    /// each one's tool count. Exits 1 when a server failed to restart, no
+    /// session is running it, or a session did not answer.
+    Restart(RestartArgs),
+
+    /// [experimental] Connect to a configured MCP server, call one of its
+    /// tools, and print the result. Exits 1 if the call fails or the tool
+    /// reports an error.
+    TestTool(TestToolArgs),
+
+    /// [experimental] Connect to every enabled MCP server and report which
+    /// of them advertise a tool. Exits 1 when none does.
+    Which(WhichArgs),
+
+    /// [experimental] Print the stderr log captured from a stdio MCP server.
+    Logs(LogsArgs),
+
+    /// [experimental] Print the environment a stdio MCP server is launched
+    /// with, one `KEY=VALUE` per line. Values of likely secrets are masked.
+    Env(EnvArgs),
+
+    /// [experimental] Summarize the configured MCP servers: how many are
+    /// enabled, which transports they use, where bearer tokens come from,
+    /// and how many set their own timeouts.
+    Stats(StatsArgs),
+
     /// [experimental] Add a global MCP server entry.
     Add(AddArgs),
 
+    /// [experimental] Add several stdio MCP servers listed one per line as
+    /// `NAME=COMMAND [ARGS...]`, writing the config once.
+    AddMany(AddManyArgs),
+
+    /// [experimental] Copy a global MCP server entry to a new name,
+    /// optionally changing its env or URL.
+    Clone(CloneArgs),
+
     /// [experimental] Remove a global MCP server entry.
     Remove(RemoveArgs),
 
+    /// [experimental] Check a file of MCP server definitions without
+    /// installing it. Reports every problem found and exits non-zero if
+    /// there are any.
+    Validate(ValidateArgs),
+
+    /// [experimental] Compare a file of MCP server definitions with the
+    /// servers in config.toml and print what differs. Nothing is modified.
+    Diff(DiffArgs),
+
     /// [experimental] Authenticate with a configured MCP server via OAuth.
     /// Requires experimental_use_rmcp_client = true in config.toml.
     Login(LoginArgs),
@@ -66,6 +195,88 @@ pub struct ListArgs {
     /// Output the configured servers as JSON.
     #[arg(long)]
     pub json: bool,
+
+    /// With --json, print one compact JSON object per server per line.
+    #[arg(long, requires = "json")]
+    pub compact: bool,
+
+    /// With --json, add each server's effective settings (once defaults and
+    /// `enabled_when` are applied) and where each one came from, as
+    /// `get --include-effective` does.
+    #[arg(long, requires = "json")]
+    pub include_effective: bool,
+
+    /// Column to order servers by. Servers without the column (e.g. `url`
+    /// for stdio servers) are ordered by name.
+    #[arg(long, value_enum, default_value_t = ListSortKey::Name)]
+    pub sort: ListSortKey,
+
+    /// Reverse the sort order.
+    #[arg(long)]
+    pub reverse: bool,
+
+    /// Only list servers whose name matches this shell-style glob
+    /// (`*` matches any run of characters, `?` a single character).
+    #[arg(long, value_name = "PATTERN")]
+    pub glob: Option<String>,
+
+    /// Only list servers tagged with TAG (see `add --tag`).
+    #[arg(long, value_name = "TAG")]
+    pub tag: Option<String>,
+
+    /// Show every environment entry instead of the first few followed by
+    /// `(+N more)`.
+    #[arg(long)]
+    pub show_all_env: bool,
+
+    /// Connect to every enabled server in parallel and group the output into
+    /// healthy and unreachable servers, with latency.
+    #[arg(long)]
+    pub health: bool,
+
+    /// With --health, stop waiting for servers that have not answered after
+    /// this many seconds (default: 30).
+    #[arg(long, value_name = "SECS", requires = "health")]
+    pub health_timeout: Option<f64>,
+
+    /// Redraw the table whenever config.toml changes, until interrupted.
+    #[arg(long, conflicts_with_all = ["json", "health"])]
+    pub watch: bool,
+
+    /// When to color the table.
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ListSortKey {
+    Name,
+    Command,
+    Url,
+    Enabled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColorChoice {
+    /// Color only when stdout is a terminal and `NO_COLOR` is not set.
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether to write ANSI colors to stdout. An explicit `always` wins over
+    /// `NO_COLOR`, as the convention allows.
+    fn enabled(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                    && supports_color::on(Stream::Stdout).is_some()
+            }
+        }
+    }
 }
 
 #[derive(Debug, clap::Parser)]
@@ -76,6 +287,28 @@ pub struct GetArgs {
     /// Output the server configuration as JSON.
     #[arg(long)]
     pub json: bool,
+
+    /// With --json, print the server on a single compact line.
+    #[arg(long, requires = "json")]
+    pub compact: bool,
+
+    /// Connect to the server and report its protocol version, tools, and latency.
+    #[arg(long)]
+    pub probe: bool,
+
+    /// Show every environment entry instead of the first few followed by
+    /// `(+N more)`.
+    #[arg(long)]
+    pub show_all_env: bool,
+
+    /// When to color the output.
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+
+    /// Also show the values that take effect once defaults and `enabled_when`
+    /// are applied, and where each one came from.
+    #[arg(long)]
+    pub include_effective: bool,
 }
 
 #[derive(Debug, clap::Parser)]
@@ -85,18 +318,111 @@ pub struct AddArgs {
 
     #[command(flatten)]
     pub transport_args: AddMcpTransportArgs,
+
+    /// Only expose this tool from the server. May be repeated.
+    #[arg(long = "allow-tool", value_name = "TOOL")]
+    pub allowed_tools: Vec<String>,
+
+    /// Never expose this tool from the server, even if allowed. May be repeated.
+    #[arg(long = "deny-tool", value_name = "TOOL")]
+    pub denied_tools: Vec<String>,
+
+    /// Save the server with `enabled = false` so it is listed but not launched.
+    #[arg(long)]
+    pub disabled: bool,
+
+    /// Add the server even if its name differs from an existing one only by
+    /// case (a warning is printed instead of an error).
+    #[arg(long)]
+    pub force: bool,
+
+    /// Do not warn when an existing stdio server already launches the same
+    /// command and arguments.
+    #[arg(long)]
+    pub allow_duplicate_command: bool,
+
+    /// Do not print the confirmation on success. Errors are still reported.
+    #[arg(short, long)]
+    pub quiet: bool,
+
+    /// Accept a reserved server name (`codex`, `builtin`, or a name starting
+    /// with `_`).
+    #[arg(long)]
+    pub allow_reserved: bool,
+
+    /// Request this MCP protocol version in the `initialize` handshake
+    /// instead of the latest one, for servers that mishandle newer versions.
+    #[arg(
+        long,
+        value_name = "VERSION",
+        value_parser = PossibleValuesParser::new(MCP_PROTOCOL_VERSIONS.iter().copied())
+    )]
+    pub protocol_version: Option<String>,
+
+    /// Note on why the server is configured, shown by `list` and `get`.
+    #[arg(long, value_name = "TEXT")]
+    pub description: Option<String>,
+
+    /// Tag the server for grouping; `list --tag` shows only servers with a
+    /// given tag. May be repeated.
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
+    /// Client name to send in the `initialize` handshake instead of
+    /// Codex's own.
+    #[arg(long, value_name = "NAME")]
+    pub client_name: Option<String>,
+
+    /// Client version to send in the `initialize` handshake instead of the
+    /// Codex version.
+    #[arg(long, value_name = "VERSION")]
+    pub client_version: Option<String>,
+
+    /// Swap the transport of an existing server for the one given by the
+    /// stdio or HTTP flags, keeping every other setting (enabled flag,
+    /// timeouts, tool filters, description) as it is.
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "from_json",
+            "allowed_tools",
+            "denied_tools",
+            "disabled",
+            "protocol_version",
+            "description",
+            "tags",
+            "client_name",
+            "client_version",
+        ]
+    )]
+    pub replace_transport: bool,
+
+    /// Connect to the server and complete `initialize` before saving, and
+    /// save nothing if that fails.
+    #[arg(long)]
+    pub verify: bool,
+
+    /// Skip the connection check that runs after the server is saved. With
+    /// `--quiet` the check still runs; only its `ok` line is suppressed.
+    #[arg(long)]
+    pub no_enable_check: bool,
 }
 
 #[derive(Debug, clap::Args)]
 #[command(
     group(
         ArgGroup::new("transport")
-            .args(["command", "url"])
+            .args(["command", "command_line", "url", "url_env", "from_json"])
             .required(true)
             .multiple(false)
     )
 )]
 pub struct AddMcpTransportArgs {
+    /// Read the full server definition from a JSON file (`-` for stdin), in
+    /// the shape printed by `codex mcp get --json`.
+    #[arg(long = "from-json", value_name = "FILE|-", conflicts_with = "env")]
+    pub from_json: Option<PathBuf>,
+
     #[command(flatten)]
     pub stdio: Option<AddMcpStdioArgs>,
 
@@ -105,6 +431,7 @@ pub struct AddMcpTransportArgs {
 }
 
 #[derive(Debug, clap::Args)]
+#[command(group(ArgGroup::new("stdio_command").args(["command", "command_line"])))]
 pub struct AddMcpStdioArgs {
     /// Command to launch the MCP server.
     /// Use --url for a streamable HTTP server.
@@ -114,36 +441,396 @@ pub struct AddMcpStdioArgs {
         )]
     pub command: Vec<String>,
 
+    /// Command and arguments as a single shell-quoted string, split the way
+    /// a POSIX shell would (e.g. `--command-line "npx -y 'my server'"`).
+    /// An alternative to passing the command after `--`.
+    #[arg(long = "command-line", value_name = "COMMAND")]
+    pub command_line: Option<String>,
+
+    /// Treat the command as an npm package spec and launch it with
+    /// `npx -y`, e.g. `--npx @scope/mcp-server --port 4000`. Any further
+    /// arguments are passed to the server.
+    #[arg(long, requires = "command", conflicts_with_all = ["command_line", "uvx"])]
+    pub npx: bool,
+
+    /// Treat the command as a Python package spec and launch it with `uvx`,
+    /// e.g. `--uvx mcp-server-git --repository .`. Any further arguments are
+    /// passed to the server.
+    #[arg(long, requires = "command", conflicts_with = "command_line")]
+    pub uvx: bool,
+
     /// Environment variables to set when launching the server.
-    /// Only valid with stdio servers.
+    /// Only valid with stdio servers. Wrap the value in quotes to keep
+    /// surrounding whitespace; double quotes also accept `\n` escapes.
     #[arg(
         long,
         value_parser = parse_env_pair,
         value_name = "KEY=VALUE",
     )]
     pub env: Vec<(String, String)>,
+
+    /// Seconds to wait for the server to exit after Codex closes its stdin
+    /// at shutdown before killing it. Only valid with stdio servers.
+    #[arg(
+        long = "shutdown-timeout",
+        value_name = "SECS",
+        requires = "stdio_command"
+    )]
+    pub shutdown_timeout: Option<f64>,
 }
 
 #[derive(Debug, clap::Args)]
+#[command(
+    group(
+        ArgGroup::new("http_url")
+            .args(["url", "url_env"])
+            .multiple(false)
+    )
+)]
 pub struct AddMcpStreamableHttpArgs {
     /// URL for a streamable HTTP MCP server.
     #[arg(long)]
-    pub url: String,
+    pub url: Option<String>,
+
+    /// Environment variable to read the server URL from each time Codex
+    /// connects. The variable is not read when the server is added.
+    #[arg(long = "url-env", value_name = "ENV_VAR")]
+    pub url_env: Option<String>,
 
     /// Optional environment variable to read for a bearer token.
     /// Only valid with streamable HTTP servers.
     #[arg(
         long = "bearer-token-env-var",
         value_name = "ENV_VAR",
-        requires = "url"
+        requires = "http_url"
     )]
     pub bearer_token_env_var: Option<String>,
+
+    /// File to read a bearer token from each time Codex connects, such as a
+    /// token mounted by a secret manager. Only valid with streamable HTTP
+    /// servers.
+    #[arg(
+        long = "bearer-token-file",
+        value_name = "PATH",
+        requires = "http_url",
+        conflicts_with = "bearer_token_env_var"
+    )]
+    pub bearer_token_file: Option<PathBuf>,
+
+    /// Token endpoint for the OAuth client-credentials grant. Tokens are
+    /// fetched on connect and refreshed before they expire.
+    #[arg(
+        long = "oauth-token-url",
+        value_name = "URL",
+        requires_all = ["oauth_client_id", "oauth_client_secret_env_var"],
+        conflicts_with_all = ["bearer_token_env_var", "bearer_token_file"]
+    )]
+    pub oauth_token_url: Option<String>,
+
+    /// OAuth client ID for the client-credentials grant.
+    #[arg(
+        long = "oauth-client-id",
+        value_name = "CLIENT_ID",
+        requires = "oauth_token_url"
+    )]
+    pub oauth_client_id: Option<String>,
+
+    /// Environment variable holding the OAuth client secret.
+    #[arg(
+        long = "oauth-client-secret-env-var",
+        value_name = "ENV_VAR",
+        requires = "oauth_token_url"
+    )]
+    pub oauth_client_secret_env_var: Option<String>,
+
+    /// OAuth scope to request. May be repeated.
+    #[arg(
+        long = "oauth-scope",
+        value_name = "SCOPE",
+        requires = "oauth_token_url"
+    )]
+    pub oauth_scopes: Vec<String>,
+
+    /// Reuse idle connections to the server across requests (the default).
+    #[arg(
+        long = "keep-alive",
+        overrides_with = "no_keep_alive",
+        requires = "http_url"
+    )]
+    pub keep_alive: bool,
+
+    /// Open a new connection for every request to the server.
+    #[arg(
+        long = "no-keep-alive",
+        overrides_with = "keep_alive",
+        requires = "http_url"
+    )]
+    pub no_keep_alive: bool,
+
+    /// Maximum number of idle connections kept open to the server.
+    #[arg(
+        long = "max-idle-connections",
+        value_name = "N",
+        requires = "http_url",
+        conflicts_with = "no_keep_alive"
+    )]
+    pub max_idle_connections: Option<usize>,
+
+    /// Seconds allowed to establish the TCP/TLS connection, separate from the
+    /// startup timeout that bounds the whole initialize handshake.
+    #[arg(long = "connect-timeout", value_name = "SECS", requires = "http_url")]
+    pub connect_timeout: Option<f64>,
+
+    /// Most requests to send to the server per second. Requests over the
+    /// limit wait for their turn instead of failing.
+    #[arg(long = "rate-limit", value_name = "PER_SEC", requires = "http_url")]
+    pub rate_limit: Option<f64>,
+
+    /// PEM client certificate to present for mutual TLS. Requires
+    /// --client-key.
+    #[arg(
+        long = "client-cert",
+        value_name = "PATH",
+        requires_all = ["http_url", "client_key"]
+    )]
+    pub client_cert: Option<PathBuf>,
+
+    /// PEM private key for --client-cert.
+    #[arg(
+        long = "client-key",
+        value_name = "PATH",
+        requires_all = ["http_url", "client_cert"]
+    )]
+    pub client_key: Option<PathBuf>,
+
+    /// PEM bundle of certificate authorities to trust for this server, in
+    /// addition to the system roots.
+    #[arg(long = "ca-cert", value_name = "PATH", requires = "http_url")]
+    pub ca_cert: Option<PathBuf>,
+
+    /// Ping the server every SECS seconds and reconnect when a ping fails.
+    #[arg(
+        long = "health-check-interval",
+        value_name = "SECS",
+        requires = "http_url"
+    )]
+    pub health_check_interval: Option<f64>,
+
+    /// HTTP header whose value is read from an environment variable each
+    /// time Codex connects. May be repeated.
+    #[arg(
+        long = "env-header",
+        value_parser = parse_header_pair,
+        value_name = "HEADER=ENV_VAR",
+        requires = "http_url"
+    )]
+    pub env_headers: Vec<(String, String)>,
+
+    /// Value to send for an --env-header whose environment variable is unset
+    /// or empty. May be repeated.
+    #[arg(
+        long = "env-header-default",
+        value_parser = parse_header_pair,
+        value_name = "HEADER=VALUE",
+        requires = "env_headers"
+    )]
+    pub env_header_defaults: Vec<(String, String)>,
 }
 
 #[derive(Debug, clap::Parser)]
 pub struct RemoveArgs {
     /// Name of the MCP server configuration to remove.
+    #[arg(required_unless_present_any = ["glob", "prune_disabled"])]
+    pub name: Option<String>,
+
+    /// Remove every server whose name matches this shell-style glob. When a
+    /// NAME is also given and a server has exactly that name, only that
+    /// server is removed.
+    #[arg(long, value_name = "PATTERN")]
+    pub glob: Option<String>,
+
+    /// Remove every server saved with `enabled = false`.
+    #[arg(long, conflicts_with_all = ["name", "glob"])]
+    pub prune_disabled: bool,
+
+    /// Remove servers matched by --glob or --prune-disabled without asking
+    /// for confirmation.
+    #[arg(short, long)]
+    pub yes: bool,
+
+    /// Do not print the confirmation on success. Errors are still reported.
+    #[arg(short, long)]
+    pub quiet: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct CloneArgs {
+    /// Name of the MCP server configuration to copy.
+    pub src: String,
+
+    /// Name for the copy. Must not already exist.
+    pub dst: String,
+
+    /// Set an environment variable on the copy, replacing the copied value
+    /// if there is one. May be repeated. Only valid with stdio servers.
+    #[arg(
+        long,
+        value_parser = parse_env_pair,
+        value_name = "KEY=VALUE",
+    )]
+    pub env: Vec<(String, String)>,
+
+    /// URL for the copy. Only valid with streamable HTTP servers; replaces
+    /// a URL read from an environment variable.
+    #[arg(long)]
+    pub url: Option<String>,
+
+    /// Do not print the confirmation on success. Errors are still reported.
+    #[arg(short, long)]
+    pub quiet: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct AddManyArgs {
+    /// File with one `NAME=COMMAND [ARGS...]` entry per line (`-` for
+    /// stdin). The command is split like a shell would; blank lines and
+    /// lines starting with `#` are ignored.
+    #[arg(value_name = "FILE|-")]
+    pub file: PathBuf,
+
+    /// Replace servers that already exist, and add servers whose names
+    /// differ from existing ones only by case.
+    #[arg(long, conflicts_with = "skip_existing")]
+    pub force: bool,
+
+    /// Leave servers that already exist (or differ only by case) untouched
+    /// and add the rest.
+    #[arg(long)]
+    pub skip_existing: bool,
+
+    /// Do not print per-server results on success. Errors are still reported.
+    #[arg(short, long)]
+    pub quiet: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ValidateArgs {
+    /// TOML file with `[mcp_servers.<name>]` tables or an `[[mcp_servers]]`
+    /// array of tables with `name` keys.
+    pub file: PathBuf,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct DiffArgs {
+    /// TOML file with `[mcp_servers.<name>]` tables or an `[[mcp_servers]]`
+    /// array of tables with `name` keys.
+    pub file: PathBuf,
+
+    /// Print the differences as a JSON object.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct PingArgs {
+    /// Name of the MCP server to ping.
+    pub name: String,
+
+    /// Print the result as a single JSON object.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct RestartArgs {
+    /// Names of the MCP servers to restart.
+    #[arg(required_unless_present = "all", conflicts_with = "all")]
+    pub names: Vec<String>,
+
+    /// Restart every server the sessions are running.
+    #[arg(long)]
+    pub all: bool,
+
+    /// Give up on a session that has not answered after this many seconds
+    /// (default: the longest startup_timeout_sec among the servers, plus 5).
+    #[arg(long, value_name = "SECS")]
+    pub timeout: Option<f64>,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct TestToolArgs {
+    /// Name of the MCP server that provides the tool.
+    pub name: String,
+
+    /// Name of the tool to call, as advertised by the server.
+    pub tool: String,
+
+    /// Tool arguments as a JSON object.
+    #[arg(long, value_name = "JSON")]
+    pub args_json: Option<String>,
+
+    /// Print the raw tool result as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct WhichArgs {
+    /// Name of the tool to look for, as advertised by the servers.
+    pub tool: String,
+
+    /// Print the result as JSON.
+    #[arg(long)]
+    pub json: bool,
+
+    /// Stop waiting for servers that have not answered after this many
+    /// seconds (default: 30).
+    #[arg(long, value_name = "SECS")]
+    pub timeout: Option<f64>,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct LogsArgs {
+    /// Name of the MCP server whose log to print.
+    pub name: String,
+
+    /// Keep printing new output as the server writes it.
+    #[arg(short, long)]
+    pub follow: bool,
+
+    /// Only print the last N lines.
+    #[arg(short = 'n', long, value_name = "N")]
+    pub lines: Option<usize>,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct EnvArgs {
+    /// Name of the stdio MCP server whose environment to print.
     pub name: String,
+
+    /// Print the environment as a JSON object.
+    #[arg(long)]
+    pub json: bool,
+
+    /// Print the values of variables whose names suggest secrets (tokens,
+    /// passwords, keys) instead of masking them.
+    #[arg(long)]
+    pub show_secrets: bool,
+
+    /// Only print variables classified as secrets by `mcp_env_mask_patterns`.
+    #[arg(long, conflicts_with = "only_plain")]
+    pub only_secrets: bool,
+
+    /// Only print variables that are not classified as secrets.
+    #[arg(long)]
+    pub only_plain: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct StatsArgs {
+    /// Print the summary as a JSON object.
+    #[arg(long)]
+    pub json: bool,
 }
 
 #[derive(Debug, clap::Parser)]
@@ -158,41 +845,132 @@ pub struct LogoutArgs {
     pub name: String,
 }
 
+impl McpSubcommand {
+    /// Whether the subcommand was asked for JSON output with its own
+    /// `--json`.
+    fn json_output(&self) -> bool {
+        match self {
+            McpSubcommand::List(args) => args.json,
+            McpSubcommand::Get(args) => args.json,
+            McpSubcommand::Ping(args) => args.json,
+            McpSubcommand::TestTool(args) => args.json,
+            McpSubcommand::Which(args) => args.json,
+            McpSubcommand::Env(args) => args.json,
+            McpSubcommand::Stats(args) => args.json,
+            McpSubcommand::Diff(args) => args.json,
+            McpSubcommand::Logs(_)
+            | McpSubcommand::Add(_)
+            | McpSubcommand::AddMany(_)
+            | McpSubcommand::Clone(_)
+            | McpSubcommand::Remove(_)
+            | McpSubcommand::Validate(_)
+            | McpSubcommand::Login(_)
+            | McpSubcommand::Logout(_)
+            | McpSubcommand::Restart(_) => false,
+        }
+    }
+}
+
+/// Exit status when a command fails in JSON mode, matching the status a
+/// returned error gets.
+const JSON_ERROR_EXIT: i32 = 1;
+
 impl McpCli {
     pub async fn run(self) -> Result<()> {
         let McpCli {
             config_overrides,
+            json,
             subcommand,
         } = self;
 
-        match subcommand {
-            McpSubcommand::List(args) => {
-                run_list(&config_overrides, args).await?;
-            }
-            McpSubcommand::Get(args) => {
-                run_get(&config_overrides, args).await?;
-            }
-            McpSubcommand::Add(args) => {
-                run_add(&config_overrides, args).await?;
-            }
-            McpSubcommand::Remove(args) => {
-                run_remove(&config_overrides, args).await?;
-            }
-            McpSubcommand::Login(args) => {
-                run_login(&config_overrides, args).await?;
-            }
-            McpSubcommand::Logout(args) => {
-                run_logout(&config_overrides, args).await?;
+        let json_errors = json || subcommand.json_output();
+        match run_subcommand(config_overrides, subcommand).await {
+            Err(err) if json_errors => {
+                eprintln!("{}", error_json(&err));
+                std::process::exit(JSON_ERROR_EXIT);
             }
+            result => result,
         }
+    }
+}
 
-        Ok(())
+/// `{"error": {"message": ..., "context": [...]}}` for `err`: `message` is
+/// the outermost description and `context` lists the underlying causes,
+/// outermost first.
+fn error_json(err: &anyhow::Error) -> serde_json::Value {
+    serde_json::json!({
+        "error": {
+            "message": err.to_string(),
+            "context": err.chain().skip(1).map(ToString::to_string).collect::<Vec<_>>(),
+        }
+    })
+}
+
+async fn run_subcommand(
+    config_overrides: CliConfigOverrides,
+    subcommand: McpSubcommand,
+) -> Result<()> {
+    match subcommand {
+        McpSubcommand::List(args) => {
+            run_list(&config_overrides, args).await?;
+        }
+        McpSubcommand::Get(args) => {
+            run_get(&config_overrides, args).await?;
+        }
+        McpSubcommand::Ping(args) => {
+            run_ping(&config_overrides, args).await?;
+        }
+        McpSubcommand::Restart(args) => {
+            run_restart(&config_overrides, args).await?;
+        }
+        McpSubcommand::TestTool(args) => {
+            run_test_tool(&config_overrides, args).await?;
+        }
+        McpSubcommand::Which(args) => {
+            run_which(&config_overrides, args).await?;
+        }
+        McpSubcommand::Logs(args) => {
+            run_logs(&config_overrides, args).await?;
+        }
+        McpSubcommand::Env(args) => {
+            run_env(&config_overrides, args).await?;
+        }
+        McpSubcommand::Stats(args) => {
+            run_stats(&config_overrides, args).await?;
+        }
+        McpSubcommand::Add(args) => {
+            run_add(&config_overrides, args).await?;
+        }
+        McpSubcommand::AddMany(args) => {
+            run_add_many(&config_overrides, args).await?;
+        }
+        McpSubcommand::Clone(args) => {
+            run_clone(&config_overrides, args).await?;
+        }
+        McpSubcommand::Remove(args) => {
+            run_remove(&config_overrides, args).await?;
+        }
+        McpSubcommand::Validate(args) => {
+            run_validate(args)?;
+        }
+        McpSubcommand::Diff(args) => {
+            run_diff(args).await?;
+        }
+        McpSubcommand::Login(args) => {
+            run_login(&config_overrides, args).await?;
+        }
+        McpSubcommand::Logout(args) => {
+            run_logout(&config_overrides, args).await?;
+        }
     }
+
+    Ok(())
 }
 
 async fn run_add(config_overrides: &CliConfigOverrides, add_args: AddArgs) -> Result<()> {
     // Validate any provided overrides even though they are not currently applied.
     let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+    let codex_home = find_or_create_codex_home().context("failed to resolve CODEX_HOME")?;
     let config = Config::load_with_cli_overrides(overrides, ConfigOverrides::default())
         .await
         .context("failed to load configuration")?;
@@ -200,20 +978,60 @@ async fn run_add(config_overrides: &CliConfigOverrides, add_args: AddArgs) -> Re
     let AddArgs {
         name,
         transport_args,
+        allowed_tools,
+        denied_tools,
+        disabled,
+        force,
+        allow_duplicate_command,
+        quiet,
+        allow_reserved,
+        protocol_version,
+        description,
+        tags,
+        client_name,
+        client_version,
+        replace_transport,
+        verify,
+        no_enable_check,
     } = add_args;
 
     validate_server_name(&name)?;
+    if !allow_reserved && let Some(reason) = reserved_server_name_reason(&name) {
+        bail!(
+            "server name '{name}' is reserved: {reason}. Pass --allow-reserved to use it anyway."
+        );
+    }
 
-    let codex_home = find_codex_home().context("failed to resolve CODEX_HOME")?;
     let mut servers = load_global_mcp_servers(&codex_home)
         .await
         .with_context(|| format!("failed to load MCP servers from {}", codex_home.display()))?;
 
-    let transport = match transport_args {
-        AddMcpTransportArgs {
-            stdio: Some(stdio), ..
-        } => {
-            let mut command_parts = stdio.command.into_iter();
+    if replace_transport {
+        if !servers.contains_key(&name) {
+            bail!("No MCP server named '{name}' found.");
+        }
+    } else if let Some(existing) = find_case_collision(&servers, &name) {
+        let message =
+            format!("server name '{name}' differs from existing server '{existing}' only by case");
+        if !force {
+            bail!("{message}; pass --force to add it anyway");
+        }
+        eprintln!("warning: {message}");
+    }
+
+    let mut new_entry = match transport_args {
+        AddMcpTransportArgs {
+            from_json: Some(path),
+            ..
+        } => read_server_json(&path)?,
+        AddMcpTransportArgs {
+            stdio: Some(stdio), ..
+        } => {
+            let command = match stdio.command_line {
+                Some(line) => split_command_line(&line)?,
+                None => package_launcher_command(stdio.npx, stdio.uvx, stdio.command)?,
+            };
+            let mut command_parts = command.into_iter();
             let command_bin = command_parts
                 .next()
                 .ok_or_else(|| anyhow!("command is required"))?;
@@ -224,49 +1042,195 @@ async fn run_add(config_overrides: &CliConfigOverrides, add_args: AddArgs) -> Re
             } else {
                 Some(stdio.env.into_iter().collect::<HashMap<_, _>>())
             };
-            McpServerTransportConfig::Stdio {
+            new_server_config(McpServerTransportConfig::Stdio {
                 command: command_bin,
                 args: command_args,
                 env: env_map,
                 env_vars: Vec::new(),
                 cwd: None,
-            }
+                expand_env: false,
+                expand_env_strict: false,
+                shutdown_timeout_sec: parse_timeout(
+                    "shutdown_timeout_sec",
+                    stdio.shutdown_timeout,
+                )?,
+                inherit_env: None,
+            })
         }
         AddMcpTransportArgs {
             streamable_http:
                 Some(AddMcpStreamableHttpArgs {
                     url,
+                    url_env,
                     bearer_token_env_var,
+                    bearer_token_file,
+                    oauth_token_url,
+                    oauth_client_id,
+                    oauth_client_secret_env_var,
+                    oauth_scopes,
+                    keep_alive: _,
+                    no_keep_alive,
+                    max_idle_connections,
+                    connect_timeout,
+                    rate_limit,
+                    client_cert,
+                    client_key,
+                    ca_cert,
+                    health_check_interval,
+                    env_headers,
+                    env_header_defaults,
                 }),
             ..
-        } => McpServerTransportConfig::StreamableHttp {
-            url,
-            bearer_token_env_var,
-            http_headers: None,
-            env_http_headers: None,
-        },
-        AddMcpTransportArgs { .. } => bail!("exactly one of --command or --url must be provided"),
+        } => {
+            let oauth_client_credentials = match (
+                oauth_token_url,
+                oauth_client_id,
+                oauth_client_secret_env_var,
+            ) {
+                (Some(token_url), Some(client_id), Some(client_secret_env_var)) => {
+                    Some(McpOAuthClientCredentials {
+                        token_url,
+                        client_id,
+                        client_secret_env_var,
+                        scopes: oauth_scopes,
+                    })
+                }
+                _ => None,
+            };
+            let connect_timeout_sec = parse_timeout("connect_timeout_sec", connect_timeout)?;
+            if connect_timeout_sec == Some(Duration::ZERO) {
+                bail!("connect_timeout_sec must be positive");
+            }
+            let health_check_interval_sec =
+                parse_timeout("health_check_interval_sec", health_check_interval)?;
+            if health_check_interval_sec == Some(Duration::ZERO) {
+                bail!("health_check_interval_sec must be positive");
+            }
+            if let Some(rate) = rate_limit {
+                validate_rate_limit(rate)?;
+            }
+            let env_http_headers: HashMap<String, String> = env_headers.into_iter().collect();
+            let env_http_header_defaults: HashMap<String, String> =
+                env_header_defaults.into_iter().collect();
+            if let Some(header) = undeclared_env_header_default(
+                Some(&env_http_headers),
+                Some(&env_http_header_defaults),
+            ) {
+                bail!("--env-header-default sets '{header}', which has no matching --env-header");
+            }
+            new_server_config(McpServerTransportConfig::StreamableHttp {
+                url: url.unwrap_or_default(),
+                url_env_var: url_env,
+                bearer_token_env_var,
+                bearer_token_file: file_path_arg("--bearer-token-file", bearer_token_file)?,
+                http_headers: None,
+                env_http_headers: (!env_http_headers.is_empty()).then_some(env_http_headers),
+                env_http_header_defaults: (!env_http_header_defaults.is_empty())
+                    .then_some(env_http_header_defaults),
+                oauth_client_credentials,
+                keep_alive: !no_keep_alive,
+                max_idle_connections,
+                connect_timeout_sec,
+                max_requests_per_sec: rate_limit,
+                client_cert_path: file_path_arg("--client-cert", client_cert)?,
+                client_key_path: file_path_arg("--client-key", client_key)?,
+                ca_cert_path: file_path_arg("--ca-cert", ca_cert)?,
+                health_check_interval_sec,
+            })
+        }
+        AddMcpTransportArgs { .. } => {
+            bail!("exactly one of --command, --url, --url-env, or --from-json must be provided")
+        }
     };
-
-    let new_entry = McpServerConfig {
-        transport: transport.clone(),
-        enabled: true,
-        startup_timeout_sec: None,
-        tool_timeout_sec: None,
+    if replace_transport && let Some(existing) = servers.get(&name) {
+        new_entry = McpServerConfig {
+            transport: new_entry.transport,
+            ..existing.clone()
+        };
+    }
+    if !allowed_tools.is_empty() {
+        new_entry.allowed_tools = Some(allowed_tools);
+    }
+    if !denied_tools.is_empty() {
+        new_entry.denied_tools = Some(denied_tools);
+    }
+    if disabled {
+        new_entry.enabled = false;
+    }
+    if protocol_version.is_some() {
+        new_entry.protocol_version = protocol_version;
+    }
+    if let Some(description) = description {
+        if description.trim().is_empty() {
+            bail!("--description must not be empty");
+        }
+        new_entry.description = Some(description);
+    }
+    if !tags.is_empty() {
+        if tags.iter().any(|tag| tag.trim().is_empty()) {
+            bail!("--tag must not be empty");
+        }
+        new_entry.tags = tags;
+    }
+    validate_client_info(client_name.as_deref(), client_version.as_deref())?;
+    if client_name.is_some() {
+        new_entry.client_name = client_name;
+    }
+    if client_version.is_some() {
+        new_entry.client_version = client_version;
+    }
+    validate_tool_filters(&new_entry)?;
+    if !allow_duplicate_command
+        && let Some(existing) = find_duplicate_command(&servers, &name, &new_entry.transport)
+    {
+        eprintln!(
+            "warning: server '{name}' launches the same command as existing server '{existing}'; pass --allow-duplicate-command to silence this warning"
+        );
+    }
+    let verified_tools = if verify {
+        let (_, mcp) = load_mcp_servers(config_overrides).await?;
+        let effective = new_entry.with_defaults(&mcp.server_defaults);
+        let probe = probe_server(&name, &effective, config.mcp_oauth_credentials_store_mode)
+            .await
+            .with_context(|| format!("failed to verify MCP server '{name}'; nothing was saved"))?;
+        Some(probe.tools.len())
+    } else {
+        None
     };
+    let transport = new_entry.transport.clone();
+    let enabled = new_entry.enabled;
+    // `--verify` already connected, and a disabled server will not start.
+    let check_entry = (!verify && !no_enable_check && enabled).then(|| new_entry.clone());
 
     servers.insert(name.clone(), new_entry);
 
-    write_global_mcp_servers(&codex_home, &servers)
-        .with_context(|| format!("failed to write MCP servers to {}", codex_home.display()))?;
+    write_servers(&codex_home, &servers)?;
+    if verified_tools.is_some() {
+        remember_connection(&codex_home, &name);
+    }
 
-    println!("Added global MCP server '{name}'.");
+    if !quiet {
+        if replace_transport {
+            println!("Replaced the transport of global MCP server '{name}'.");
+        } else {
+            let suffix = if enabled { "" } else { " (disabled)" };
+            println!("Added global MCP server '{name}'{suffix}.");
+        }
+        if let Some(count) = verified_tools {
+            println!("Verified connection: {count} tool(s) available.");
+        }
+    }
 
     if let McpServerTransportConfig::StreamableHttp {
         url,
+        url_env_var: None,
         bearer_token_env_var: None,
+        bearer_token_file: None,
         http_headers,
         env_http_headers,
+        env_http_header_defaults,
+        oauth_client_credentials: None,
+        ..
     } = transport
         && matches!(supports_oauth_login(&url).await, Ok(true))
     {
@@ -275,216 +1239,1070 @@ async fn run_add(config_overrides: &CliConfigOverrides, add_args: AddArgs) -> Re
             &name,
             &url,
             config.mcp_oauth_credentials_store_mode,
-            http_headers.clone(),
+            resolve_http_headers(
+                http_headers.as_ref(),
+                env_http_headers.as_ref(),
+                env_http_header_defaults.as_ref(),
+            ),
             env_http_headers.clone(),
         )
         .await?;
         println!("Successfully logged in.");
     }
 
+    if let Some(entry) = check_entry {
+        let (_, mcp) = load_mcp_servers(config_overrides).await?;
+        let effective = entry.with_defaults(&mcp.server_defaults);
+        // The server is saved either way; this only reports whether it works.
+        match probe_server(&name, &effective, config.mcp_oauth_credentials_store_mode).await {
+            Ok(probe) => {
+                remember_connection(&codex_home, &name);
+                if !quiet {
+                    let count = probe.tools.len();
+                    let noun = if count == 1 { "tool" } else { "tools" };
+                    println!("ok ({count} {noun})");
+                }
+            }
+            Err(err) => eprintln!("warning: could not connect: {err:#}"),
+        }
+    }
+
     Ok(())
 }
 
-async fn run_remove(config_overrides: &CliConfigOverrides, remove_args: RemoveArgs) -> Result<()> {
+async fn run_add_many(
+    config_overrides: &CliConfigOverrides,
+    add_many_args: AddManyArgs,
+) -> Result<()> {
     config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
 
-    let RemoveArgs { name } = remove_args;
+    let AddManyArgs {
+        file,
+        force,
+        skip_existing,
+        quiet,
+    } = add_many_args;
 
-    validate_server_name(&name)?;
+    let contents = if file == Path::new("-") {
+        let mut contents = String::new();
+        std::io::stdin()
+            .read_to_string(&mut contents)
+            .context("failed to read server list from stdin")?;
+        contents
+    } else {
+        std::fs::read_to_string(&file)
+            .with_context(|| format!("failed to read {}", file.display()))?
+    };
+    let entries = match parse_server_list(&contents) {
+        Ok(entries) => entries,
+        Err(problems) => {
+            for problem in &problems {
+                eprintln!("{problem}");
+            }
+            bail!(
+                "{} problem(s) found in {}; nothing was added",
+                problems.len(),
+                file.display()
+            );
+        }
+    };
 
-    let codex_home = find_codex_home().context("failed to resolve CODEX_HOME")?;
+    let codex_home = find_or_create_codex_home().context("failed to resolve CODEX_HOME")?;
     let mut servers = load_global_mcp_servers(&codex_home)
         .await
         .with_context(|| format!("failed to load MCP servers from {}", codex_home.display()))?;
 
-    let removed = servers.remove(&name).is_some();
+    let mut conflicts = Vec::new();
+    let mut results = Vec::new();
+    let mut changed = false;
+    for ServerListEntry {
+        name,
+        command,
+        args,
+        ..
+    } in entries
+    {
+        let conflict = if servers.contains_key(&name) {
+            Some(format!("server '{name}' already exists"))
+        } else {
+            find_case_collision(&servers, &name).map(|existing| {
+                format!(
+                    "server name '{name}' differs from existing server '{existing}' only by case"
+                )
+            })
+        };
+        let verb = match conflict {
+            Some(conflict) if skip_existing => {
+                results.push(format!("Skipped '{name}': {conflict}."));
+                continue;
+            }
+            Some(conflict) if !force => {
+                conflicts.push(format!("{name}: {conflict}"));
+                continue;
+            }
+            Some(_) if servers.contains_key(&name) => "Replaced",
+            _ => "Added",
+        };
 
-    if removed {
-        write_global_mcp_servers(&codex_home, &servers)
-            .with_context(|| format!("failed to write MCP servers to {}", codex_home.display()))?;
+        servers.insert(
+            name.clone(),
+            new_server_config(McpServerTransportConfig::Stdio {
+                command,
+                args,
+                env: None,
+                env_vars: Vec::new(),
+                cwd: None,
+                expand_env: false,
+                expand_env_strict: false,
+                shutdown_timeout_sec: None,
+                inherit_env: None,
+            }),
+        );
+        changed = true;
+        results.push(format!("{verb} global MCP server '{name}'."));
     }
 
-    if removed {
-        println!("Removed global MCP server '{name}'.");
-    } else {
-        println!("No MCP server named '{name}' found.");
+    if !conflicts.is_empty() {
+        for conflict in &conflicts {
+            eprintln!("{conflict}");
+        }
+        bail!(
+            "{} server(s) conflict with existing entries; pass --force to replace them or \
+             --skip-existing to keep them. Nothing was added.",
+            conflicts.len()
+        );
     }
 
+    if changed {
+        write_servers(&codex_home, &servers)?;
+    }
+    if !quiet {
+        for result in &results {
+            println!("{result}");
+        }
+    }
     Ok(())
 }
 
-async fn run_login(config_overrides: &CliConfigOverrides, login_args: LoginArgs) -> Result<()> {
-    let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
-    let config = Config::load_with_cli_overrides(overrides, ConfigOverrides::default())
-        .await
-        .context("failed to load configuration")?;
+/// One `NAME=COMMAND [ARGS...]` line of an `add-many` file.
+#[derive(Debug, PartialEq)]
+struct ServerListEntry {
+    line: usize,
+    name: String,
+    command: String,
+    args: Vec<String>,
+}
 
-    if !config.features.enabled(Feature::RmcpClient) {
-        bail!(
-            "OAuth login is only supported when experimental_use_rmcp_client is true in config.toml."
-        );
+/// Parse an `add-many` server list, collecting every malformed line instead
+/// of stopping at the first one.
+fn parse_server_list(contents: &str) -> std::result::Result<Vec<ServerListEntry>, Vec<String>> {
+    let mut entries: Vec<ServerListEntry> = Vec::new();
+    let mut problems = Vec::new();
+    for (index, raw) in contents.lines().enumerate() {
+        let line = index + 1;
+        let trimmed = raw.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let Some((name, command)) = trimmed.split_once('=') else {
+            problems.push(format!("line {line}: expected NAME=COMMAND"));
+            continue;
+        };
+        let name = name.trim();
+        if let Err(err) = validate_server_name(name) {
+            problems.push(format!("line {line}: {err}"));
+            continue;
+        }
+        if let Some(reason) = reserved_server_name_reason(name) {
+            problems.push(format!(
+                "line {line}: server name '{name}' is reserved: {reason}"
+            ));
+            continue;
+        }
+        if let Some(previous) = entries.iter().find(|entry| entry.name == name) {
+            problems.push(format!(
+                "line {line}: server '{name}' is already listed on line {}",
+                previous.line
+            ));
+            continue;
+        }
+        let Some(words) = shlex::split(command) else {
+            problems.push(format!("line {line}: unbalanced quotes in command"));
+            continue;
+        };
+        let mut words = words.into_iter();
+        let Some(command) = words.next() else {
+            problems.push(format!("line {line}: command is required"));
+            continue;
+        };
+        entries.push(ServerListEntry {
+            line,
+            name: name.to_string(),
+            command,
+            args: words.collect(),
+        });
     }
 
-    let LoginArgs { name } = login_args;
+    if problems.is_empty() {
+        Ok(entries)
+    } else {
+        Err(problems)
+    }
+}
 
-    let Some(server) = config.mcp_servers.get(&name) else {
-        bail!("No MCP server named '{name}' found.");
-    };
+async fn run_clone(config_overrides: &CliConfigOverrides, clone_args: CloneArgs) -> Result<()> {
+    config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
 
-    let (url, http_headers, env_http_headers) = match &server.transport {
-        McpServerTransportConfig::StreamableHttp {
-            url,
-            http_headers,
-            env_http_headers,
-            ..
-        } => (url.clone(), http_headers.clone(), env_http_headers.clone()),
-        _ => bail!("OAuth login is only supported for streamable HTTP servers."),
+    let CloneArgs {
+        src,
+        dst,
+        env,
+        url,
+        quiet,
+    } = clone_args;
+
+    validate_server_name(&dst)?;
+    if let Some(reason) = reserved_server_name_reason(&dst) {
+        bail!("server name '{dst}' is reserved: {reason}");
+    }
+
+    let codex_home = find_codex_home().context("failed to resolve CODEX_HOME")?;
+    let mut servers = load_global_mcp_servers(&codex_home)
+        .await
+        .with_context(|| format!("failed to load MCP servers from {}", codex_home.display()))?;
+
+    let Some(source) = servers.get(&src) else {
+        bail!("No MCP server named '{src}' found.");
     };
+    if servers.contains_key(&dst) {
+        bail!("server '{dst}' already exists; remove it first or pick another name");
+    }
+    if let Some(existing) = find_case_collision(&servers, &dst) {
+        bail!("server name '{dst}' differs from existing server '{existing}' only by case");
+    }
 
-    perform_oauth_login(
-        &name,
-        &url,
-        config.mcp_oauth_credentials_store_mode,
-        http_headers,
-        env_http_headers,
-    )
-    .await?;
-    println!("Successfully logged in to MCP server '{name}'.");
+    let mut clone = source.clone();
+    if !env.is_empty() {
+        let McpServerTransportConfig::Stdio { env: clone_env, .. } = &mut clone.transport else {
+            bail!("--env is only valid for stdio servers, and '{src}' uses streamable HTTP");
+        };
+        clone_env.get_or_insert_with(HashMap::new).extend(env);
+    }
+    if let Some(new_url) = url {
+        let McpServerTransportConfig::StreamableHttp {
+            url, url_env_var, ..
+        } = &mut clone.transport
+        else {
+            bail!("--url is only valid for streamable HTTP servers, and '{src}' uses stdio");
+        };
+        *url = new_url;
+        *url_env_var = None;
+    }
+    validate_transport(&clone.transport)?;
+
+    servers.insert(dst.clone(), clone);
+    write_servers(&codex_home, &servers)?;
+
+    if !quiet {
+        println!("Cloned global MCP server '{src}' to '{dst}'.");
+    }
     Ok(())
 }
 
-async fn run_logout(config_overrides: &CliConfigOverrides, logout_args: LogoutArgs) -> Result<()> {
-    let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
-    let config = Config::load_with_cli_overrides(overrides, ConfigOverrides::default())
-        .await
-        .context("failed to load configuration")?;
+async fn run_remove(config_overrides: &CliConfigOverrides, remove_args: RemoveArgs) -> Result<()> {
+    config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
 
-    let LogoutArgs { name } = logout_args;
+    let RemoveArgs {
+        name,
+        glob,
+        prune_disabled,
+        yes,
+        quiet,
+    } = remove_args;
 
-    let server = config
-        .mcp_servers
-        .get(&name)
-        .ok_or_else(|| anyhow!("No MCP server named '{name}' found in configuration."))?;
+    if let Some(name) = &name {
+        validate_server_name(name)?;
+    }
 
-    let url = match &server.transport {
-        McpServerTransportConfig::StreamableHttp { url, .. } => url.clone(),
-        _ => bail!("OAuth logout is only supported for streamable_http transports."),
+    let codex_home = find_codex_home().context("failed to resolve CODEX_HOME")?;
+    let mut servers = load_global_mcp_servers(&codex_home)
+        .await
+        .with_context(|| format!("failed to load MCP servers from {}", codex_home.display()))?;
+
+    if prune_disabled {
+        return prune_disabled_servers(&codex_home, servers, yes, quiet);
+    }
+
+    let name = match (name, glob) {
+        (Some(name), Some(pattern)) if !servers.contains_key(&name) => {
+            return remove_matching(&codex_home, servers, &pattern, yes, quiet);
+        }
+        (Some(name), _) => name,
+        (None, Some(pattern)) => {
+            return remove_matching(&codex_home, servers, &pattern, yes, quiet);
+        }
+        (None, None) => unreachable!("clap requires NAME, --glob, or --prune-disabled"),
     };
 
-    match delete_oauth_tokens(&name, &url, config.mcp_oauth_credentials_store_mode) {
-        Ok(true) => println!("Removed OAuth credentials for '{name}'."),
-        Ok(false) => println!("No OAuth credentials stored for '{name}'."),
-        Err(err) => return Err(anyhow!("failed to delete OAuth credentials: {err}")),
+    let removed = servers.remove(&name).is_some();
+
+    if removed {
+        write_servers(&codex_home, &servers)?;
+    }
+
+    if !removed {
+        println!("No MCP server named '{name}' found.");
+    } else if !quiet {
+        println!("Removed global MCP server '{name}'.");
     }
 
     Ok(())
 }
 
-async fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) -> Result<()> {
-    let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
-    let config = Config::load_with_cli_overrides(overrides, ConfigOverrides::default())
-        .await
-        .context("failed to load configuration")?;
+/// Remove every server whose name matches `pattern`, after listing the
+/// matches and confirming (unless `yes` is set). With `quiet`, the matches
+/// are only listed when confirmation is needed.
+fn remove_matching(
+    codex_home: &Path,
+    servers: BTreeMap<String, McpServerConfig>,
+    pattern: &str,
+    yes: bool,
+    quiet: bool,
+) -> Result<()> {
+    let glob = server_name_glob(pattern);
+    let matched: Vec<String> = servers
+        .keys()
+        .filter(|name| glob.matches(name))
+        .cloned()
+        .collect();
+    if matched.is_empty() {
+        println!("No MCP servers match '{pattern}'.");
+        return Ok(());
+    }
+    remove_confirmed(codex_home, servers, &matched, yes, quiet)?;
+    Ok(())
+}
 
-    let mut entries: Vec<_> = config.mcp_servers.iter().collect();
-    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
-    let auth_statuses = compute_auth_statuses(
-        config.mcp_servers.iter(),
-        config.mcp_oauth_credentials_store_mode,
-    )
-    .await;
+/// Remove every server with `enabled = false`, confirming like
+/// [`remove_matching`], and report how many were removed.
+fn prune_disabled_servers(
+    codex_home: &Path,
+    servers: BTreeMap<String, McpServerConfig>,
+    yes: bool,
+    quiet: bool,
+) -> Result<()> {
+    let disabled: Vec<String> = servers
+        .iter()
+        .filter(|(_, cfg)| !cfg.enabled)
+        .map(|(name, _)| name.clone())
+        .collect();
+    if disabled.is_empty() {
+        println!("No disabled MCP servers to remove.");
+        return Ok(());
+    }
+    if remove_confirmed(codex_home, servers, &disabled, yes, quiet)? && !quiet {
+        println!("Removed {} disabled MCP server(s).", disabled.len());
+    }
+    Ok(())
+}
 
-    if list_args.json {
-        let json_entries: Vec<_> = entries
-            .into_iter()
-            .map(|(name, cfg)| {
-                let auth_status = auth_statuses
-                    .get(name.as_str())
-                    .copied()
-                    .unwrap_or(McpAuthStatus::Unsupported);
-                let transport = match &cfg.transport {
-                    McpServerTransportConfig::Stdio {
-                        command,
-                        args,
-                        env,
-                        env_vars,
-                        cwd,
-                    } => serde_json::json!({
-                        "type": "stdio",
-                        "command": command,
-                        "args": args,
-                        "env": env,
-                        "env_vars": env_vars,
-                        "cwd": cwd,
-                    }),
-                    McpServerTransportConfig::StreamableHttp {
-                        url,
-                        bearer_token_env_var,
-                        http_headers,
-                        env_http_headers,
-                    } => {
-                        serde_json::json!({
-                            "type": "streamable_http",
-                            "url": url,
-                            "bearer_token_env_var": bearer_token_env_var,
-                            "http_headers": http_headers,
-                            "env_http_headers": env_http_headers,
-                        })
-                    }
-                };
+/// List `matched`, ask for confirmation unless `yes` is set, and remove them
+/// from `servers` in a single write. Returns whether anything was removed.
+fn remove_confirmed(
+    codex_home: &Path,
+    mut servers: BTreeMap<String, McpServerConfig>,
+    matched: &[String],
+    yes: bool,
+    quiet: bool,
+) -> Result<bool> {
+    if !(quiet && yes) {
+        println!(
+            "Matched {} MCP server(s): {}",
+            matched.len(),
+            matched.join(", ")
+        );
+    }
+    if !yes && !confirm("Remove them?")? {
+        println!("Nothing removed.");
+        return Ok(false);
+    }
 
-                serde_json::json!({
-                    "name": name,
-                    "enabled": cfg.enabled,
-                    "transport": transport,
-                    "startup_timeout_sec": cfg
-                        .startup_timeout_sec
-                        .map(|timeout| timeout.as_secs_f64()),
-                    "tool_timeout_sec": cfg
-                        .tool_timeout_sec
-                        .map(|timeout| timeout.as_secs_f64()),
-                    "auth_status": auth_status,
-                })
-            })
-            .collect();
-        let output = serde_json::to_string_pretty(&json_entries)?;
-        println!("{output}");
-        return Ok(());
+    for name in matched {
+        servers.remove(name);
+    }
+    write_servers(codex_home, &servers)?;
+    if !quiet {
+        for name in matched {
+            println!("Removed global MCP server '{name}'.");
+        }
     }
+    Ok(true)
+}
 
-    if entries.is_empty() {
-        println!("No MCP servers configured yet. Try `codex mcp add my-tool -- my-command`.");
+/// Shell-style glob over server names (`*` and `?`), shared by
+/// `list --glob` and `remove --glob`.
+fn server_name_glob(pattern: &str) -> WildMatch {
+    WildMatch::new(pattern)
+}
+
+/// Ask a yes/no question on the terminal. Refuses (rather than assuming an
+/// answer) when stdin is not interactive.
+fn confirm(question: &str) -> Result<bool> {
+    let stdin = std::io::stdin();
+    if !stdin.is_terminal() {
+        bail!("refusing to continue without confirmation; pass --yes to skip the prompt");
+    }
+    print!("{question} [y/N] ");
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    stdin.lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes" | "Yes"))
+}
+
+fn run_validate(validate_args: ValidateArgs) -> Result<()> {
+    let ValidateArgs { file } = validate_args;
+
+    let contents = std::fs::read_to_string(&file)
+        .with_context(|| format!("failed to read {}", file.display()))?;
+    let parsed = parse_mcp_servers_toml_per_server(&contents)
+        .with_context(|| format!("invalid MCP server file {}", file.display()))?;
+
+    let servers: BTreeMap<String, McpServerConfig> = parsed
+        .iter()
+        .filter_map(|(name, server)| Some((name.clone(), server.as_ref().ok()?.clone())))
+        .collect();
+    let mut problems = Vec::new();
+    for (name, server) in &parsed {
+        match server {
+            Ok(server) => problems.extend(
+                server_problems(name, server, &servers)
+                    .into_iter()
+                    .map(|problem| format!("{name}: {problem}")),
+            ),
+            Err(err) => problems.push(format!("{name}: {err}")),
+        }
+    }
+
+    if problems.is_empty() {
+        println!("{}: {} MCP server(s) OK", file.display(), parsed.len());
         return Ok(());
     }
+    for problem in &problems {
+        eprintln!("{problem}");
+    }
+    bail!("{} problem(s) found in {}", problems.len(), file.display());
+}
 
-    let mut stdio_rows: Vec<[String; 7]> = Vec::new();
-    let mut http_rows: Vec<[String; 5]> = Vec::new();
+async fn run_diff(diff_args: DiffArgs) -> Result<()> {
+    let DiffArgs { file, json } = diff_args;
 
-    for (name, cfg) in entries {
-        match &cfg.transport {
-            McpServerTransportConfig::Stdio {
-                command,
-                args,
-                env,
-                env_vars,
+    let contents = std::fs::read_to_string(&file)
+        .with_context(|| format!("failed to read {}", file.display()))?;
+    let incoming = parse_mcp_servers_toml(&contents).with_context(|| {
+        format!(
+            "invalid MCP server file {}; run `codex mcp validate` for details",
+            file.display()
+        )
+    })?;
+    let codex_home = find_codex_home().context("failed to resolve CODEX_HOME")?;
+    let installed = load_global_mcp_servers(&codex_home)
+        .await
+        .with_context(|| format!("failed to load MCP servers from {}", codex_home.display()))?;
+
+    let diff = diff_servers(&installed, &incoming);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff.to_json())?);
+        return Ok(());
+    }
+
+    for (name, fields) in &diff.added {
+        println!("+ {name}");
+        for (field, value) in fields {
+            println!("    {field}: {value}");
+        }
+    }
+    for name in diff.removed.keys() {
+        println!("- {name}");
+    }
+    for (name, changes) in &diff.changed {
+        println!("~ {name}");
+        for change in changes {
+            println!(
+                "    {}: {} -> {}",
+                change.field,
+                field_value_display(change.old.as_ref()),
+                field_value_display(change.new.as_ref())
+            );
+        }
+    }
+    println!(
+        "{} added, {} removed, {} changed, {} unchanged",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.changed.len(),
+        diff.unchanged.len()
+    );
+    Ok(())
+}
+
+/// Differences between the servers in config.toml and those in a file, as
+/// reported by `codex mcp diff`. Servers are described by
+/// [`definition_fields`].
+#[derive(Debug, Default, PartialEq)]
+struct ServersDiff {
+    /// In the file but not installed.
+    added: BTreeMap<String, BTreeMap<String, serde_json::Value>>,
+    /// Installed but not in the file.
+    removed: BTreeMap<String, BTreeMap<String, serde_json::Value>>,
+    changed: BTreeMap<String, Vec<FieldChange>>,
+    unchanged: Vec<String>,
+}
+
+/// A setting that differs between two definitions of a server. `None` means
+/// the setting is unset on that side.
+#[derive(Debug, PartialEq)]
+struct FieldChange {
+    field: String,
+    old: Option<serde_json::Value>,
+    new: Option<serde_json::Value>,
+}
+
+impl ServersDiff {
+    fn to_json(&self) -> serde_json::Value {
+        let changed: serde_json::Map<String, serde_json::Value> = self
+            .changed
+            .iter()
+            .map(|(name, changes)| {
+                let fields: serde_json::Map<String, serde_json::Value> = changes
+                    .iter()
+                    .map(|change| {
+                        let delta = serde_json::json!({
+                            "old": change.old,
+                            "new": change.new,
+                        });
+                        (change.field.clone(), delta)
+                    })
+                    .collect();
+                (name.clone(), serde_json::Value::Object(fields))
+            })
+            .collect();
+        serde_json::json!({
+            "added": self.added,
+            "removed": self.removed,
+            "changed": changed,
+            "unchanged": self.unchanged,
+        })
+    }
+}
+
+fn diff_servers(
+    installed: &BTreeMap<String, McpServerConfig>,
+    incoming: &BTreeMap<String, McpServerConfig>,
+) -> ServersDiff {
+    let mut diff = ServersDiff::default();
+    for (name, server) in installed {
+        if !incoming.contains_key(name) {
+            diff.removed.insert(name.clone(), definition_fields(server));
+        }
+    }
+    for (name, server) in incoming {
+        let new = definition_fields(server);
+        let Some(existing) = installed.get(name) else {
+            diff.added.insert(name.clone(), new);
+            continue;
+        };
+        let old = definition_fields(existing);
+        let mut fields: Vec<&String> = old.keys().chain(new.keys()).collect();
+        fields.sort();
+        fields.dedup();
+        let changes: Vec<FieldChange> = fields
+            .into_iter()
+            .filter(|field| old.get(*field) != new.get(*field))
+            .map(|field| FieldChange {
+                field: field.clone(),
+                old: old.get(field).cloned(),
+                new: new.get(field).cloned(),
+            })
+            .collect();
+        if changes.is_empty() {
+            diff.unchanged.push(name.clone());
+        } else {
+            diff.changed.insert(name.clone(), changes);
+        }
+    }
+    diff
+}
+
+/// The settings of `server` keyed by their `config.toml` names, with unset
+/// ones left out. `transport` holds the transport type.
+fn definition_fields(server: &McpServerConfig) -> BTreeMap<String, serde_json::Value> {
+    let shared = serde_json::json!({
+        "description": server.description,
+        "tags": (!server.tags.is_empty()).then_some(&server.tags),
+        "enabled": server.enabled,
+        "enabled_when": server.enabled_when,
+        "startup_timeout_sec": server
+            .startup_timeout_sec
+            .map(|timeout| timeout.as_secs_f64()),
+        "tool_timeout_sec": server
+            .tool_timeout_sec
+            .map(|timeout| timeout.as_secs_f64()),
+        "tool_timeouts": tool_timeouts_json(server),
+        "allowed_tools": server.allowed_tools,
+        "denied_tools": server.denied_tools,
+        "protocol_version": server.protocol_version,
+        "startup_priority": server.startup_priority,
+        "client_name": server.client_name,
+        "client_version": server.client_version,
+    });
+    let mut fields = BTreeMap::new();
+    for value in [transport_json(&server.transport), shared] {
+        let serde_json::Value::Object(map) = value else {
+            continue;
+        };
+        for (key, value) in map {
+            let key = if key == "type" {
+                "transport".to_string()
+            } else {
+                key
+            };
+            fields.insert(key, value);
+        }
+    }
+    fields.retain(|_, value| !value.is_null());
+    fields
+}
+
+fn field_value_display(value: Option<&serde_json::Value>) -> String {
+    value.map_or_else(|| "(unset)".to_string(), serde_json::Value::to_string)
+}
+
+/// Everything `add` would reject about `server`, plus checks that only a
+/// hand-written definition can fail: zero timeouts and malformed
+/// environment variable names.
+fn server_problems(
+    name: &str,
+    server: &McpServerConfig,
+    servers: &BTreeMap<String, McpServerConfig>,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+    if let Err(err) = validate_server_name(name) {
+        problems.push(err.to_string());
+    }
+    if let Some(reason) = reserved_server_name_reason(name) {
+        problems.push(format!("server name '{name}' is reserved: {reason}"));
+    }
+    if let Some(existing) = find_case_collision(servers, name)
+        && existing < name
+    {
+        problems.push(format!(
+            "server name '{name}' differs from server '{existing}' only by case"
+        ));
+    }
+    if let Err(err) = validate_transport(&server.transport) {
+        problems.push(format!("{err:#}"));
+    }
+
+    let shutdown_timeout_sec = match &server.transport {
+        McpServerTransportConfig::Stdio {
+            shutdown_timeout_sec,
+            ..
+        } => *shutdown_timeout_sec,
+        McpServerTransportConfig::StreamableHttp { .. } => None,
+    };
+    for (field, timeout) in [
+        ("startup_timeout_sec", server.startup_timeout_sec),
+        ("tool_timeout_sec", server.tool_timeout_sec),
+        ("shutdown_timeout_sec", shutdown_timeout_sec),
+    ] {
+        if timeout == Some(Duration::ZERO) {
+            problems.push(format!("{field} must be positive"));
+        }
+    }
+
+    let mut env_names: Vec<(&str, &str)> = Vec::new();
+    match &server.transport {
+        McpServerTransportConfig::Stdio { env, env_vars, .. } => {
+            env_names.extend(env.iter().flatten().map(|(key, _)| ("env", key.as_str())));
+            env_names.extend(env_vars.iter().map(|var| ("env_vars", var.as_str())));
+        }
+        McpServerTransportConfig::StreamableHttp {
+            bearer_token_env_var,
+            env_http_headers,
+            oauth_client_credentials,
+            ..
+        } => {
+            env_names.extend(
+                bearer_token_env_var
+                    .iter()
+                    .map(|var| ("bearer_token_env_var", var.as_str())),
+            );
+            env_names.extend(
+                env_http_headers
+                    .iter()
+                    .flatten()
+                    .map(|(_, var)| ("env_http_headers", var.as_str())),
+            );
+            env_names.extend(oauth_client_credentials.iter().map(|oauth| {
+                (
+                    "oauth_client_credentials.client_secret_env_var",
+                    oauth.client_secret_env_var.as_str(),
+                )
+            }));
+        }
+    }
+    for (field, var) in env_names {
+        if var.trim().is_empty() || var.contains(['=', '\0']) {
+            problems.push(format!(
+                "{field} has invalid environment variable name '{var}'"
+            ));
+        }
+    }
+
+    problems
+}
+
+async fn run_login(config_overrides: &CliConfigOverrides, login_args: LoginArgs) -> Result<()> {
+    let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+    let config = Config::load_with_cli_overrides(overrides, ConfigOverrides::default())
+        .await
+        .context("failed to load configuration")?;
+
+    if !config.features.enabled(Feature::RmcpClient) {
+        bail!(
+            "OAuth login is only supported when experimental_use_rmcp_client is true in config.toml."
+        );
+    }
+
+    let LoginArgs { name } = login_args;
+
+    let Some(server) = config.mcp_servers.get(&name) else {
+        bail!("No MCP server named '{name}' found.");
+    };
+
+    let (url, http_headers, env_http_headers) = match &server.transport {
+        McpServerTransportConfig::StreamableHttp {
+            url,
+            url_env_var,
+            http_headers,
+            env_http_headers,
+            env_http_header_defaults,
+            ..
+        } => (
+            resolve_streamable_http_url(&name, url, url_env_var.as_deref())?,
+            resolve_http_headers(
+                http_headers.as_ref(),
+                env_http_headers.as_ref(),
+                env_http_header_defaults.as_ref(),
+            ),
+            env_http_headers.clone(),
+        ),
+        _ => bail!("OAuth login is only supported for streamable HTTP servers."),
+    };
+
+    perform_oauth_login(
+        &name,
+        &url,
+        config.mcp_oauth_credentials_store_mode,
+        http_headers,
+        env_http_headers,
+    )
+    .await?;
+    println!("Successfully logged in to MCP server '{name}'.");
+    Ok(())
+}
+
+async fn run_logout(config_overrides: &CliConfigOverrides, logout_args: LogoutArgs) -> Result<()> {
+    let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+    let config = Config::load_with_cli_overrides(overrides, ConfigOverrides::default())
+        .await
+        .context("failed to load configuration")?;
+
+    let LogoutArgs { name } = logout_args;
+
+    let server = config
+        .mcp_servers
+        .get(&name)
+        .ok_or_else(|| anyhow!("No MCP server named '{name}' found in configuration."))?;
+
+    let url = match &server.transport {
+        McpServerTransportConfig::StreamableHttp {
+            url, url_env_var, ..
+        } => resolve_streamable_http_url(&name, url, url_env_var.as_deref())?,
+        _ => bail!("OAuth logout is only supported for streamable_http transports."),
+    };
+
+    match delete_oauth_tokens(&name, &url, config.mcp_oauth_credentials_store_mode) {
+        Ok(true) => println!("Removed OAuth credentials for '{name}'."),
+        Ok(false) => println!("No OAuth credentials stored for '{name}'."),
+        Err(err) => return Err(anyhow!("failed to delete OAuth credentials: {err}")),
+    }
+
+    Ok(())
+}
+
+/// Resolve MCP servers the way a session would, without loading (and
+/// validating) the rest of the configuration.
+async fn load_mcp_servers(
+    config_overrides: &CliConfigOverrides,
+) -> Result<(PathBuf, McpServersConfig)> {
+    let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+    let codex_home = find_codex_home().context("failed to resolve CODEX_HOME")?;
+    let mcp = load_mcp_servers_with_cli_overrides(&codex_home, overrides)
+        .await
+        .context("failed to load MCP server configuration")?;
+    Ok((codex_home, mcp))
+}
+
+/// `mcp.servers` with the top-level `mcp_default_*_timeout_sec` values
+/// applied, as a session would launch them.
+fn effective_servers(mcp: &McpServersConfig) -> BTreeMap<String, McpServerConfig> {
+    mcp.servers
+        .iter()
+        .map(|(name, server)| {
+            let server = server.with_defaults(&mcp.server_defaults);
+            (name.clone(), server)
+        })
+        .collect()
+}
+
+/// The `mcp_env_mask_patterns` (or default) patterns whose values `list`,
+/// `get`, and `env` hide.
+fn env_mask(mcp: &McpServersConfig) -> EnvMaskPatterns {
+    EnvMaskPatterns::new(&mcp.env_mask_patterns)
+}
+
+/// Environment entries `list` and `get` show per server before collapsing
+/// the rest into `(+N more)`; `--show-all-env` lifts the limit.
+const MAX_ENV_ENTRIES_DISPLAYED: usize = 5;
+
+async fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) -> Result<()> {
+    if list_args.watch {
+        return watch_list(config_overrides, &list_args).await;
+    }
+    print_list(config_overrides, &list_args).await
+}
+
+/// How long `list --watch` lets an editor finish saving before re-reading
+/// the config, and how long it waits between failed reads.
+const LIST_WATCH_SETTLE: Duration = Duration::from_millis(100);
+/// Reads `list --watch` attempts before showing the error instead of the
+/// table. A save caught halfway can fail to parse once or twice.
+const LIST_WATCH_READ_ATTEMPTS: u32 = 5;
+
+async fn watch_list(config_overrides: &CliConfigOverrides, list_args: &ListArgs) -> Result<()> {
+    let codex_home = find_codex_home().context("failed to resolve CODEX_HOME")?;
+    let config_path = codex_home.join(CONFIG_TOML_FILE);
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event {
+            let _ = tx.send(event);
+        }
+    })
+    .context("failed to start watching for config changes")?;
+    // Watch the directory rather than the file: many editors save by
+    // replacing the file, which would end a watch on the original.
+    watcher
+        .watch(&codex_home, RecursiveMode::NonRecursive)
+        .with_context(|| format!("failed to watch {}", codex_home.display()))?;
+
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    loop {
+        print!("\x1b[2J\x1b[H");
+        println!(
+            "Watching {} for changes. Press Ctrl-C to exit.\n",
+            config_path.display()
+        );
+        let mut attempts = 1;
+        while let Err(err) = print_list(config_overrides, list_args).await {
+            if attempts >= LIST_WATCH_READ_ATTEMPTS {
+                eprintln!("{err:#}");
+                break;
+            }
+            attempts += 1;
+            tokio::time::sleep(LIST_WATCH_SETTLE).await;
+        }
+        std::io::stdout().flush()?;
+
+        loop {
+            tokio::select! {
+                _ = &mut ctrl_c => return Ok(()),
+                event = rx.recv() => match event {
+                    Some(event) if touches_file(&event, &config_path) => break,
+                    Some(_) => {}
+                    None => return Ok(()),
+                },
+            }
+        }
+        // Coalesce the burst of events a single save produces.
+        tokio::time::sleep(LIST_WATCH_SETTLE).await;
+        while rx.try_recv().is_ok() {}
+    }
+}
+
+/// Whether `event` concerns `path`, including a rename onto or away from it.
+/// Only names are compared because the watch covers just the parent
+/// directory, and some platforms report canonicalized paths.
+fn touches_file(event: &notify::Event, path: &Path) -> bool {
+    event
+        .paths
+        .iter()
+        .any(|changed| changed.file_name() == path.file_name())
+}
+
+async fn print_list(config_overrides: &CliConfigOverrides, list_args: &ListArgs) -> Result<()> {
+    let (codex_home, mcp) = load_mcp_servers(config_overrides).await?;
+    let servers = effective_servers(&mcp);
+
+    let mut entries: Vec<_> = servers.iter().collect();
+    if let Some(pattern) = &list_args.glob {
+        let glob = server_name_glob(pattern);
+        entries.retain(|(name, _)| glob.matches(name));
+    }
+    if let Some(tag) = &list_args.tag {
+        entries.retain(|(_, cfg)| cfg.tags.contains(tag));
+    }
+    sort_list_entries(&mut entries, list_args.sort, list_args.reverse);
+    let health = if list_args.health {
+        let timeout = parse_timeout("--health-timeout", list_args.health_timeout)?
+            .unwrap_or(DEFAULT_HEALTH_TIMEOUT);
+        let health = probe_health(&entries, mcp.oauth_credentials_store_mode, timeout).await;
+        let healthy = health
+            .iter()
+            .filter(|(_, health)| matches!(health, ServerHealth::Healthy(_)))
+            .map(|(name, _)| name.as_str());
+        if let Err(err) = record_connected(&codex_home, healthy, Utc::now()) {
+            eprintln!("warning: failed to record connection times: {err}");
+        }
+        Some(health)
+    } else {
+        None
+    };
+    let last_connected = load_last_connected(&codex_home);
+    let now = Utc::now();
+    let auth_statuses =
+        compute_auth_statuses(servers.iter(), mcp.oauth_credentials_store_mode).await;
+
+    if list_args.json {
+        let json_entries: Vec<_> = entries
+            .into_iter()
+            .map(|(name, cfg)| {
+                let auth_status = auth_statuses
+                    .get(name.as_str())
+                    .copied()
+                    .unwrap_or(McpAuthStatus::Unsupported);
+                // Like `get --json`, report the configured values so the
+                // output round-trips through `add --from-json`; the resolved
+                // ones are under `effective`.
+                let cfg = mcp.servers.get(name.as_str()).unwrap_or(cfg);
+                let transport = transport_json(&cfg.transport);
+
+                let mut value = serde_json::json!({
+                    "name": name,
+                    "description": cfg.description,
+                    "tags": cfg.tags,
+                    "enabled": cfg.enabled,
+                    "enabled_when": cfg.enabled_when,
+                    "effective_enabled": cfg.is_effectively_enabled(),
+                    "transport": transport,
+                    "startup_timeout_sec": cfg
+                        .startup_timeout_sec
+                        .map(|timeout| timeout.as_secs_f64()),
+                    "tool_timeout_sec": cfg
+                        .tool_timeout_sec
+                        .map(|timeout| timeout.as_secs_f64()),
+                    "tool_timeouts": tool_timeouts_json(cfg),
+                    "allowed_tools": cfg.allowed_tools,
+                    "denied_tools": cfg.denied_tools,
+                    "protocol_version": cfg.protocol_version,
+                    "startup_priority": cfg.startup_priority,
+                    "client_name": cfg.client_name,
+                    "client_version": cfg.client_version,
+                    "auth_status": auth_status,
+                    "last_connected": last_connected
+                        .get(name.as_str())
+                        .map(DateTime::to_rfc3339),
+                });
+                if list_args.include_effective {
+                    value["effective"] = effective_json(cfg, &mcp.server_defaults);
+                }
+                if let Some(health) = health.as_ref().and_then(|health| health.get(name)) {
+                    value["health"] = health.to_json();
+                }
+                value
+            })
+            .collect();
+        if list_args.compact {
+            for entry in &json_entries {
+                println!("{}", serde_json::to_string(entry)?);
+            }
+        } else {
+            let output = serde_json::to_string_pretty(&json_entries)?;
+            println!("{output}");
+        }
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        match (&list_args.glob, &list_args.tag) {
+            (Some(pattern), _) if !servers.is_empty() => {
+                println!("No MCP servers match '{pattern}'.");
+            }
+            (None, Some(tag)) if !servers.is_empty() => {
+                println!("No MCP servers are tagged '{tag}'.");
+            }
+            _ => println!(
+                "No MCP servers configured yet. Try `codex mcp add my-tool -- my-command`."
+            ),
+        }
+        return Ok(());
+    }
+
+    if let Some(health) = &health {
+        print_health_sections(&entries, health);
+        return Ok(());
+    }
+
+    let env_limit = (!list_args.show_all_env).then_some(MAX_ENV_ENTRIES_DISPLAYED);
+    let mask = env_mask(&mcp);
+    // Descriptions go in a trailing column that only appears when at least
+    // one listed server has one.
+    let show_description = entries.iter().any(|(_, cfg)| cfg.description.is_some());
+    let description_cell =
+        |cfg: &McpServerConfig| cfg.description.clone().unwrap_or_else(|| "-".to_string());
+    // Likewise, the resolved startup priority is only shown when at least one
+    // listed server sets `startup_priority`.
+    let show_priority = entries
+        .iter()
+        .any(|(_, cfg)| cfg.startup_priority.is_some());
+    let mut stdio_rows: Vec<[String; 10]> = Vec::new();
+    let mut http_rows: Vec<[String; 8]> = Vec::new();
+
+    for (name, cfg) in entries {
+        match &cfg.transport {
+            McpServerTransportConfig::Stdio {
+                command,
+                args,
+                env,
+                env_vars,
                 cwd,
+                ..
             } => {
                 let args_display = if args.is_empty() {
                     "-".to_string()
                 } else {
                     args.join(" ")
                 };
-                let env_display = format_env_display(env.as_ref(), env_vars);
+                let env_display =
+                    format_env_display_masked(env.as_ref(), env_vars, env_limit, &mask);
                 let cwd_display = cwd
                     .as_ref()
                     .map(|path| path.display().to_string())
                     .filter(|value| !value.is_empty())
                     .unwrap_or_else(|| "-".to_string());
-                let status = if cfg.enabled {
-                    "enabled".to_string()
-                } else {
-                    "disabled".to_string()
-                };
+                let status = status_display(cfg);
                 let auth_status = auth_statuses
                     .get(name.as_str())
                     .copied()
@@ -498,18 +2316,20 @@ async fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) ->
                     cwd_display,
                     status,
                     auth_status,
+                    format_last_connected(last_connected.get(name.as_str()).copied(), now),
+                    cfg.resolved_startup_priority().to_string(),
+                    description_cell(cfg),
                 ]);
             }
             McpServerTransportConfig::StreamableHttp {
                 url,
+                url_env_var,
                 bearer_token_env_var,
+                bearer_token_file,
+                oauth_client_credentials,
                 ..
             } => {
-                let status = if cfg.enabled {
-                    "enabled".to_string()
-                } else {
-                    "disabled".to_string()
-                };
+                let status = status_display(cfg);
                 let auth_status = auth_statuses
                     .get(name.as_str())
                     .copied()
@@ -517,14 +2337,46 @@ async fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) ->
                     .to_string();
                 http_rows.push([
                     name.clone(),
-                    url.clone(),
-                    bearer_token_env_var.clone().unwrap_or("-".to_string()),
+                    url_display(url, url_env_var.as_deref()),
+                    match (oauth_client_credentials, bearer_token_file) {
+                        (Some(oauth), _) => format!("oauth:{}", oauth.client_id),
+                        (None, Some(path)) => format!("file:{}", path.display()),
+                        (None, None) => bearer_token_env_var.clone().unwrap_or("-".to_string()),
+                    },
                     status,
                     auth_status,
+                    format_last_connected(last_connected.get(name.as_str()).copied(), now),
+                    cfg.resolved_startup_priority().to_string(),
+                    description_cell(cfg),
                 ]);
             }
         }
     }
+    let description_header = if show_description {
+        "  Description"
+    } else {
+        ""
+    };
+    let description_suffix = |cell: &str| {
+        if show_description {
+            format!("  {cell}")
+        } else {
+            String::new()
+        }
+    };
+    let priority_column = |cell: &str, width: usize| {
+        if show_priority {
+            format!("  {cell:<width$}")
+        } else {
+            String::new()
+        }
+    };
+    let color = list_args.color.enabled();
+    let header_style = if color {
+        Style::new().bold()
+    } else {
+        Style::new()
+    };
 
     if !stdio_rows.is_empty() {
         let mut widths = [
@@ -535,6 +2387,9 @@ async fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) ->
             "Cwd".len(),
             "Status".len(),
             "Auth".len(),
+            "Last Connected".len(),
+            "Priority".len(),
+            "Description".len(),
         ];
         for row in &stdio_rows {
             for (i, cell) in row.iter().enumerate() {
@@ -542,8 +2397,8 @@ async fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) ->
             }
         }
 
-        println!(
-            "{name:<name_w$}  {command:<cmd_w$}  {args:<args_w$}  {env:<env_w$}  {cwd:<cwd_w$}  {status:<status_w$}  {auth:<auth_w$}",
+        let header = format!(
+            "{name:<name_w$}  {command:<cmd_w$}  {args:<args_w$}  {env:<env_w$}  {cwd:<cwd_w$}  {status:<status_w$}  {auth:<auth_w$}  {last:<last_w$}{priority}{description_header}",
             name = "Name",
             command = "Command",
             args = "Args",
@@ -551,6 +2406,8 @@ async fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) ->
             cwd = "Cwd",
             status = "Status",
             auth = "Auth",
+            last = "Last Connected",
+            priority = priority_column("Priority", widths[8]),
             name_w = widths[0],
             cmd_w = widths[1],
             args_w = widths[2],
@@ -558,25 +2415,30 @@ async fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) ->
             cwd_w = widths[4],
             status_w = widths[5],
             auth_w = widths[6],
+            last_w = widths[7],
         );
+        println!("{}", header.style(header_style));
 
         for row in &stdio_rows {
             println!(
-                "{name:<name_w$}  {command:<cmd_w$}  {args:<args_w$}  {env:<env_w$}  {cwd:<cwd_w$}  {status:<status_w$}  {auth:<auth_w$}",
+                "{name:<name_w$}  {command:<cmd_w$}  {args:<args_w$}  {env:<env_w$}  {cwd:<cwd_w$}  {status}  {auth:<auth_w$}  {last:<last_w$}{priority}{description}",
                 name = row[0].as_str(),
                 command = row[1].as_str(),
                 args = row[2].as_str(),
                 env = row[3].as_str(),
                 cwd = row[4].as_str(),
-                status = row[5].as_str(),
+                status = status_cell(&row[5], widths[5], color),
                 auth = row[6].as_str(),
+                last = row[7].as_str(),
+                priority = priority_column(&row[8], widths[8]),
+                description = description_suffix(&row[9]),
                 name_w = widths[0],
                 cmd_w = widths[1],
                 args_w = widths[2],
                 env_w = widths[3],
                 cwd_w = widths[4],
-                status_w = widths[5],
                 auth_w = widths[6],
+                last_w = widths[7],
             );
         }
     }
@@ -592,6 +2454,9 @@ async fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) ->
             "Bearer Token Env Var".len(),
             "Status".len(),
             "Auth".len(),
+            "Last Connected".len(),
+            "Priority".len(),
+            "Description".len(),
         ];
         for row in &http_rows {
             for (i, cell) in row.iter().enumerate() {
@@ -599,33 +2464,40 @@ async fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) ->
             }
         }
 
-        println!(
-            "{name:<name_w$}  {url:<url_w$}  {token:<token_w$}  {status:<status_w$}  {auth:<auth_w$}",
+        let header = format!(
+            "{name:<name_w$}  {url:<url_w$}  {token:<token_w$}  {status:<status_w$}  {auth:<auth_w$}  {last:<last_w$}{priority}{description_header}",
             name = "Name",
             url = "Url",
             token = "Bearer Token Env Var",
             status = "Status",
             auth = "Auth",
+            last = "Last Connected",
+            priority = priority_column("Priority", widths[6]),
             name_w = widths[0],
             url_w = widths[1],
             token_w = widths[2],
             status_w = widths[3],
             auth_w = widths[4],
+            last_w = widths[5],
         );
+        println!("{}", header.style(header_style));
 
         for row in &http_rows {
             println!(
-                "{name:<name_w$}  {url:<url_w$}  {token:<token_w$}  {status:<status_w$}  {auth:<auth_w$}",
+                "{name:<name_w$}  {url:<url_w$}  {token:<token_w$}  {status}  {auth:<auth_w$}  {last:<last_w$}{priority}{description}",
                 name = row[0].as_str(),
                 url = row[1].as_str(),
                 token = row[2].as_str(),
-                status = row[3].as_str(),
+                status = status_cell(&row[3], widths[3], color),
                 auth = row[4].as_str(),
+                last = row[5].as_str(),
+                priority = priority_column(&row[6], widths[6]),
+                description = description_suffix(&row[7]),
                 name_w = widths[0],
                 url_w = widths[1],
                 token_w = widths[2],
-                status_w = widths[3],
                 auth_w = widths[4],
+                last_w = widths[5],
             );
         }
     }
@@ -633,48 +2505,771 @@ async fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) ->
     Ok(())
 }
 
-async fn run_get(config_overrides: &CliConfigOverrides, get_args: GetArgs) -> Result<()> {
-    let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
-    let config = Config::load_with_cli_overrides(overrides, ConfigOverrides::default())
-        .await
-        .context("failed to load configuration")?;
+/// `codex mcp ping` exit status when the server could not be reached.
+const PING_EXIT_UNHEALTHY: i32 = 1;
+/// `codex mcp ping` exit status when no server with the name is configured.
+const PING_EXIT_NOT_FOUND: i32 = 2;
 
-    let Some(server) = config.mcp_servers.get(&get_args.name) else {
-        bail!("No MCP server named '{name}' found.", name = get_args.name);
-    };
+/// Last successful connection per server. An unreadable state file only
+/// hides this informational data, so it is a warning rather than an error.
+fn load_last_connected(codex_home: &Path) -> HashMap<String, DateTime<Utc>> {
+    match load_server_states(codex_home) {
+        Ok(states) => states
+            .into_iter()
+            .filter_map(|(name, state)| Some((name, state.last_connected?)))
+            .collect(),
+        Err(err) => {
+            eprintln!("warning: failed to read MCP server state: {err}");
+            HashMap::new()
+        }
+    }
+}
 
-    if get_args.json {
-        let transport = match &server.transport {
-            McpServerTransportConfig::Stdio {
-                command,
-                args,
-                env,
-                env_vars,
-                cwd,
-            } => serde_json::json!({
-                "type": "stdio",
-                "command": command,
-                "args": args,
-                "env": env,
-                "env_vars": env_vars,
-                "cwd": cwd,
+/// How long `list --health` waits for servers when --health-timeout is not
+/// given.
+const DEFAULT_HEALTH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Servers `list --health` connects to at once.
+const HEALTH_PROBE_CONCURRENCY: usize = 8;
+
+/// Outcome of probing one server for `list --health` or `which`.
+#[derive(Debug)]
+enum ServerHealth {
+    /// Completed the handshake and answered `tools/list`.
+    Healthy(McpServerProbe),
+    Unreachable(String),
+    /// Not probed because the server is disabled (or its `enabled_when`
+    /// condition does not hold) on this machine.
+    Skipped,
+}
+
+impl ServerHealth {
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            ServerHealth::Healthy(probe) => serde_json::json!({
+                "status": "healthy",
+                "latency_ms": probe.latency.as_secs_f64() * 1000.0,
             }),
-            McpServerTransportConfig::StreamableHttp {
-                url,
-                bearer_token_env_var,
-                http_headers,
-                env_http_headers,
-            } => serde_json::json!({
-                "type": "streamable_http",
-                "url": url,
-                "bearer_token_env_var": bearer_token_env_var,
-                "http_headers": http_headers,
-                "env_http_headers": env_http_headers,
+            ServerHealth::Unreachable(error) => serde_json::json!({
+                "status": "unreachable",
+                "error": error,
             }),
+            ServerHealth::Skipped => serde_json::json!({ "status": "skipped" }),
+        }
+    }
+}
+
+/// Probe every effectively enabled server in `entries`, at most
+/// [`HEALTH_PROBE_CONCURRENCY`] at a time. Servers that have not answered
+/// when `timeout` elapses are reported as unreachable.
+async fn probe_health(
+    entries: &[(&String, &McpServerConfig)],
+    store_mode: OAuthCredentialsStoreMode,
+    timeout: Duration,
+) -> HashMap<String, ServerHealth> {
+    let deadline = tokio::time::Instant::now() + timeout;
+    stream::iter(entries.iter().copied())
+        .map(|(name, cfg)| async move {
+            if !cfg.is_effectively_enabled() {
+                return (name.clone(), ServerHealth::Skipped);
+            }
+            let probe = tokio::time::timeout_at(deadline, probe_server(name, cfg, store_mode));
+            let health = match probe.await {
+                Ok(Ok(probe)) => ServerHealth::Healthy(probe),
+                Ok(Err(err)) => ServerHealth::Unreachable(format!("{err:#}")),
+                Err(_) => ServerHealth::Unreachable(format!(
+                    "no answer within {}s",
+                    timeout.as_secs_f64()
+                )),
+            };
+            (name.clone(), health)
+        })
+        .buffer_unordered(HEALTH_PROBE_CONCURRENCY)
+        .collect()
+        .await
+}
+
+/// Print `list --health` output: one section per outcome, keeping the list
+/// order within each section and omitting empty sections.
+fn print_health_sections(
+    entries: &[(&String, &McpServerConfig)],
+    health: &HashMap<String, ServerHealth>,
+) {
+    let name_width = entries
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or_default();
+    let sections: [(&str, fn(&ServerHealth) -> Option<String>); 3] = [
+        ("Healthy", |health| match health {
+            ServerHealth::Healthy(probe) => Some(format!("{} ms", probe.latency.as_millis())),
+            _ => None,
+        }),
+        ("Unreachable", |health| match health {
+            ServerHealth::Unreachable(error) => Some(error.clone()),
+            _ => None,
+        }),
+        ("Skipped", |health| match health {
+            ServerHealth::Skipped => Some("disabled".to_string()),
+            _ => None,
+        }),
+    ];
+
+    let mut first = true;
+    for (title, detail) in sections {
+        let rows: Vec<(&String, String)> = entries
+            .iter()
+            .filter_map(|(name, _)| Some((*name, detail(health.get(*name)?)?)))
+            .collect();
+        if rows.is_empty() {
+            continue;
+        }
+        if !first {
+            println!();
+        }
+        first = false;
+        println!("{title} ({}):", rows.len());
+        for (name, detail) in rows {
+            println!("  {name:<name_width$}  {detail}");
+        }
+    }
+}
+
+/// Names of the servers in `health` that advertise `tool`, sorted.
+fn servers_providing_tool<'a>(
+    health: &'a HashMap<String, ServerHealth>,
+    tool: &str,
+) -> Vec<&'a str> {
+    let mut servers: Vec<&str> = health
+        .iter()
+        .filter_map(|(name, health)| match health {
+            ServerHealth::Healthy(probe)
+                if probe.tools.iter().any(|advertised| advertised == tool) =>
+            {
+                Some(name.as_str())
+            }
+            _ => None,
+        })
+        .collect();
+    servers.sort_unstable();
+    servers
+}
+
+fn remember_connection(codex_home: &Path, name: &str) {
+    if let Err(err) = record_connected(codex_home, [name], Utc::now()) {
+        eprintln!("warning: failed to record connection time for '{name}': {err}");
+    }
+}
+
+/// Render a last-connected time relative to `now`, e.g. `2h ago`.
+fn format_last_connected(at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> String {
+    let Some(at) = at else {
+        return "never".to_string();
+    };
+    let secs = (now - at).num_seconds();
+    match secs {
+        ..60 => "just now".to_string(),
+        60..3_600 => format!("{}m ago", secs / 60),
+        3_600..86_400 => format!("{}h ago", secs / 3_600),
+        _ => format!("{}d ago", secs / 86_400),
+    }
+}
+
+async fn run_ping(config_overrides: &CliConfigOverrides, ping_args: PingArgs) -> Result<()> {
+    let (codex_home, mcp) = load_mcp_servers(config_overrides).await?;
+    let PingArgs { name, json } = ping_args;
+
+    let (probe, exit_code) = match mcp.servers.get(&name) {
+        Some(server) => {
+            let server = server.with_defaults(&mcp.server_defaults);
+            let probe = probe_server(&name, &server, mcp.oauth_credentials_store_mode).await;
+            let exit_code = if probe.is_ok() {
+                remember_connection(&codex_home, &name);
+                0
+            } else {
+                PING_EXIT_UNHEALTHY
+            };
+            (probe, exit_code)
+        }
+        None => (
+            Err(anyhow!("No MCP server named '{name}' found.")),
+            PING_EXIT_NOT_FOUND,
+        ),
+    };
+
+    if json {
+        let value = serde_json::json!({
+            "name": name,
+            "ok": probe.is_ok(),
+            "startup_ms": probe
+                .as_ref()
+                .ok()
+                .map(|probe| probe.startup.as_secs_f64() * 1000.0),
+            "latency_ms": probe
+                .as_ref()
+                .ok()
+                .map(|probe| probe.latency.as_secs_f64() * 1000.0),
+            "error": probe.as_ref().err().map(|err| format!("{err:#}")),
+        });
+        println!("{}", serde_json::to_string(&value)?);
+    } else {
+        match &probe {
+            Ok(probe) => println!(
+                "{name}: ok (startup {} ms, tools/list {} ms)",
+                probe.startup.as_millis(),
+                probe.latency.as_millis()
+            ),
+            Err(err) => eprintln!("{name}: {err:#}"),
+        }
+    }
+
+    if exit_code != 0 {
+        std::process::exit(exit_code);
+    }
+    Ok(())
+}
+
+/// Extra time `restart` allows past the longest startup timeout before
+/// giving up on a session that has not answered.
+const RESTART_RESULT_GRACE: Duration = Duration::from_secs(5);
+const RESTART_RESULT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Restarts happen in every running session, reached through the MCP control
+/// channel each one registers under `$CODEX_HOME/mcp_control`.
+async fn run_restart(
+    config_overrides: &CliConfigOverrides,
+    restart_args: RestartArgs,
+) -> Result<()> {
+    let RestartArgs {
+        mut names,
+        all,
+        timeout,
+    } = restart_args;
+    let timeout = parse_timeout("--timeout", timeout)?;
+
+    let (codex_home, mcp) = load_mcp_servers(config_overrides).await?;
+    let servers = effective_servers(&mcp);
+    if let Some(name) = names.iter().find(|name| !servers.contains_key(*name)) {
+        bail!("No MCP server named '{name}' found.");
+    }
+    names.sort();
+    names.dedup();
+    // A disabled server cannot be running, so `--all` does not wait for it.
+    let targets: Vec<String> = if all {
+        servers
+            .iter()
+            .filter(|(_, server)| server.is_effectively_enabled())
+            .map(|(name, _)| name.clone())
+            .collect()
+    } else {
+        names.clone()
+    };
+    if targets.is_empty() {
+        println!("No enabled MCP servers configured.");
+        return Ok(());
+    }
+
+    let sessions = running_sessions(&codex_home).with_context(|| {
+        format!(
+            "failed to find running Codex sessions in {}",
+            codex_home.join(MCP_CONTROL_DIR).display()
+        )
+    })?;
+    if sessions.is_empty() {
+        for name in &targets {
+            eprintln!("{name}: not currently active (no running Codex session)");
+        }
+        std::process::exit(1);
+    }
+
+    let request = McpControlRequest::Restart { servers: names };
+    let mut pending = Vec::with_capacity(sessions.len());
+    for session in sessions {
+        let request_id = session
+            .send(&request)
+            .with_context(|| format!("failed to send the restart to session {}", session.id))?;
+        pending.push((session, request_id));
+    }
+
+    let timeout = timeout.unwrap_or_else(|| {
+        targets
+            .iter()
+            .filter_map(|name| servers[name].startup_timeout_sec)
+            .fold(DEFAULT_STARTUP_TIMEOUT, Duration::max)
+            + RESTART_RESULT_GRACE
+    });
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut responses: Vec<Option<McpControlResponse>> = vec![None; pending.len()];
+    loop {
+        for ((session, request_id), response) in pending.iter().zip(responses.iter_mut()) {
+            if response.is_none() {
+                *response = session.take_response(request_id).with_context(|| {
+                    format!("failed to read the answer from session {}", session.id)
+                })?;
+            }
+        }
+        if responses.iter().all(Option::is_some) || tokio::time::Instant::now() >= deadline {
+            break;
+        }
+        tokio::time::sleep(RESTART_RESULT_POLL_INTERVAL).await;
+    }
+
+    // With several sessions, each line names the session it is about.
+    let several_sessions = pending.len() > 1;
+    let mut all_restarted = true;
+    let mut all_answered = true;
+    let mut running_somewhere: HashSet<&str> = HashSet::new();
+    for ((session, request_id), response) in pending.iter().zip(responses) {
+        let prefix = if several_sessions {
+            format!("session {}: ", session.id)
+        } else {
+            String::new()
+        };
+        let response = match response {
+            Some(response) => response,
+            None => {
+                all_restarted = false;
+                all_answered = false;
+                if let Err(err) = session.withdraw(request_id) {
+                    eprintln!("warning: failed to withdraw the restart request: {err}");
+                }
+                eprintln!(
+                    "{prefix}the session did not answer within {}s",
+                    timeout.as_secs_f64()
+                );
+                continue;
+            }
+        };
+        if let Some(error) = response.error {
+            all_restarted = false;
+            eprintln!("{prefix}{error}");
+            continue;
+        }
+        for name in &targets {
+            match response.servers.get(name) {
+                Some(McpRestartOutcome::Restarted { tool_count }) => {
+                    running_somewhere.insert(name);
+                    let noun = if *tool_count == 1 { "tool" } else { "tools" };
+                    println!("{prefix}{name}: restarted ({tool_count} {noun})");
+                }
+                Some(McpRestartOutcome::Failed { error }) => {
+                    running_somewhere.insert(name);
+                    all_restarted = false;
+                    eprintln!("{prefix}{name}: failed to restart: {error}");
+                }
+                Some(McpRestartOutcome::Inactive) | None => {}
+            }
+        }
+    }
+    // A server only one of several sessions runs is not an error; one that no
+    // session runs is. A session that did not answer may be running it.
+    if all_answered {
+        for name in targets
+            .iter()
+            .filter(|name| !running_somewhere.contains(name.as_str()))
+        {
+            all_restarted = false;
+            eprintln!("{name}: not currently active");
+        }
+    }
+    if !all_restarted {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+async fn run_which(config_overrides: &CliConfigOverrides, which_args: WhichArgs) -> Result<()> {
+    let WhichArgs {
+        tool,
+        json,
+        timeout,
+    } = which_args;
+    let timeout = parse_timeout("--timeout", timeout)?.unwrap_or(DEFAULT_HEALTH_TIMEOUT);
+
+    let (codex_home, mcp) = load_mcp_servers(config_overrides).await?;
+    let servers = effective_servers(&mcp);
+    let entries: Vec<(&String, &McpServerConfig)> = servers.iter().collect();
+    let health = probe_health(&entries, mcp.oauth_credentials_store_mode, timeout).await;
+    let healthy = health
+        .iter()
+        .filter(|(_, health)| matches!(health, ServerHealth::Healthy(_)))
+        .map(|(name, _)| name.as_str());
+    if let Err(err) = record_connected(&codex_home, healthy, Utc::now()) {
+        eprintln!("warning: failed to record connection times: {err}");
+    }
+
+    let providers = servers_providing_tool(&health, &tool);
+    let unreachable: BTreeMap<&str, &str> = health
+        .iter()
+        .filter_map(|(name, health)| match health {
+            ServerHealth::Unreachable(error) => Some((name.as_str(), error.as_str())),
+            _ => None,
+        })
+        .collect();
+
+    if json {
+        let value = serde_json::json!({
+            "tool": tool,
+            "servers": providers,
+            "conflict": providers.len() > 1,
+            "unreachable": unreachable,
+        });
+        println!("{}", serde_json::to_string_pretty(&value)?);
+    } else {
+        for (name, error) in &unreachable {
+            eprintln!("warning: could not check '{name}': {error}");
+        }
+        match providers.as_slice() {
+            [] => eprintln!("No reachable MCP server provides '{tool}'."),
+            [server] => println!("{tool}: {server}"),
+            _ => println!(
+                "{tool}: {} (conflict: {} servers expose this tool)",
+                providers.join(", "),
+                providers.len()
+            ),
+        }
+    }
+
+    if providers.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+async fn run_test_tool(
+    config_overrides: &CliConfigOverrides,
+    test_tool_args: TestToolArgs,
+) -> Result<()> {
+    let TestToolArgs {
+        name,
+        tool,
+        args_json,
+        json,
+    } = test_tool_args;
+    let arguments = args_json
+        .map(|raw| parse_tool_arguments(&raw))
+        .transpose()?;
+
+    let (codex_home, mcp) = load_mcp_servers(config_overrides).await?;
+    let Some(server) = mcp.servers.get(&name) else {
+        bail!("No MCP server named '{name}' found.");
+    };
+    let server = server.with_defaults(&mcp.server_defaults);
+
+    let call = call_server_tool(
+        &name,
+        &server,
+        mcp.oauth_credentials_store_mode,
+        &tool,
+        arguments,
+    )
+    .await?;
+    remember_connection(&codex_home, &name);
+    let result = call.result;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else {
+        for block in &result.content {
+            match block {
+                ContentBlock::TextContent(text) => println!("{}", text.text),
+                other => println!("{}", serde_json::to_string(other)?),
+            }
+        }
+        if let Some(structured) = &result.structured_content {
+            println!("{}", serde_json::to_string_pretty(structured)?);
+        }
+    }
+
+    // Timings go to stderr so `--json` output stays machine-readable.
+    eprintln!(
+        "startup {} ms, call {} ms",
+        call.startup.as_millis(),
+        call.duration.as_millis()
+    );
+
+    if result.is_error == Some(true) {
+        eprintln!("Tool '{tool}' on MCP server '{name}' reported an error.");
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Parse `--args-json`, which must be a JSON object.
+fn parse_tool_arguments(raw: &str) -> Result<serde_json::Value> {
+    let value: serde_json::Value =
+        serde_json::from_str(raw).context("--args-json is not valid JSON")?;
+    if !value.is_object() {
+        bail!("--args-json must be a JSON object");
+    }
+    Ok(value)
+}
+
+/// How often `codex mcp logs --follow` checks the log for new output.
+const LOG_FOLLOW_INTERVAL: Duration = Duration::from_millis(250);
+
+async fn run_logs(config_overrides: &CliConfigOverrides, logs_args: LogsArgs) -> Result<()> {
+    let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+    let config = Config::load_with_cli_overrides(overrides, ConfigOverrides::default())
+        .await
+        .context("failed to load configuration")?;
+    let LogsArgs {
+        name,
+        follow,
+        lines,
+    } = logs_args;
+
+    validate_server_name(&name)?;
+    let log_dir = log_dir(&config).context("failed to resolve log directory")?;
+    let path = server_log_path(&log_dir, &name);
+
+    let contents = match std::fs::read(&path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == ErrorKind::NotFound => bail!(
+            "No log for MCP server '{name}' at {}. The server has not been started yet.",
+            path.display()
+        ),
+        Err(err) => {
+            return Err(err).with_context(|| format!("failed to read {}", path.display()));
+        }
+    };
+    print!("{}", tail_lines(&String::from_utf8_lossy(&contents), lines));
+
+    if !follow {
+        return Ok(());
+    }
+
+    let mut offset = contents.len() as u64;
+    loop {
+        tokio::time::sleep(LOG_FOLLOW_INTERVAL).await;
+        let len = match std::fs::metadata(&path) {
+            Ok(metadata) => metadata.len(),
+            Err(err) if err.kind() == ErrorKind::NotFound => continue,
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to read {}", path.display()));
+            }
+        };
+        // The log only grows; a shorter file means it was replaced.
+        if len < offset {
+            offset = 0;
+        }
+        if len == offset {
+            continue;
+        }
+        let mut file = std::fs::File::open(&path)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut appended = Vec::new();
+        file.read_to_end(&mut appended)?;
+        offset += appended.len() as u64;
+        print!("{}", String::from_utf8_lossy(&appended));
+        std::io::stdout().flush()?;
+    }
+}
+
+/// The last `lines` lines of `contents` (all of it when `None`).
+fn tail_lines(contents: &str, lines: Option<usize>) -> &str {
+    let Some(lines) = lines else {
+        return contents;
+    };
+    if lines == 0 {
+        return "";
+    }
+    // Skip the final newline so it does not count as an empty last line.
+    let body = contents.strip_suffix('\n').unwrap_or(contents);
+    match body.rmatch_indices('\n').nth(lines - 1) {
+        Some((idx, _)) => &contents[idx + 1..],
+        None => contents,
+    }
+}
+
+/// Order `entries` by `key`, breaking ties (and placing servers the column
+/// does not apply to) by name.
+fn sort_list_entries(entries: &mut [(&String, &McpServerConfig)], key: ListSortKey, reverse: bool) {
+    fn command(cfg: &McpServerConfig) -> Option<&str> {
+        match &cfg.transport {
+            McpServerTransportConfig::Stdio { command, .. } => Some(command),
+            McpServerTransportConfig::StreamableHttp { .. } => None,
+        }
+    }
+    fn url(cfg: &McpServerConfig) -> Option<String> {
+        match &cfg.transport {
+            McpServerTransportConfig::StreamableHttp {
+                url, url_env_var, ..
+            } => Some(url_display(url, url_env_var.as_deref())),
+            McpServerTransportConfig::Stdio { .. } => None,
+        }
+    }
+
+    entries.sort_by(|(a_name, a), (b_name, b)| {
+        let ordering = match key {
+            ListSortKey::Name => Ordering::Equal,
+            ListSortKey::Command => command(a).cmp(&command(b)),
+            ListSortKey::Url => url(a).cmp(&url(b)),
+            ListSortKey::Enabled => a.enabled.cmp(&b.enabled),
         };
-        let output = serde_json::to_string_pretty(&serde_json::json!({
+        ordering.then_with(|| a_name.cmp(b_name))
+    });
+    if reverse {
+        entries.reverse();
+    }
+}
+
+async fn run_env(config_overrides: &CliConfigOverrides, env_args: EnvArgs) -> Result<()> {
+    let (_, mcp) = load_mcp_servers(config_overrides).await?;
+    let EnvArgs {
+        name,
+        json,
+        show_secrets,
+        only_secrets,
+        only_plain,
+    } = env_args;
+
+    let Some(server) = mcp.servers.get(&name) else {
+        bail!("No MCP server named '{name}' found.");
+    };
+    let effective = server.with_defaults(&mcp.server_defaults);
+    let Some(server_env) = resolve_stdio_server_env(&name, &effective.transport)? else {
+        bail!("MCP server '{name}' uses streamable HTTP and is not launched as a process.");
+    };
+
+    let mask = env_mask(&mcp);
+    let mut server_env: BTreeMap<String, String> = server_env
+        .into_iter()
+        .filter(|(key, _)| match (only_secrets, only_plain) {
+            (true, _) => mask.is_masked(key),
+            (_, true) => !mask.is_masked(key),
+            _ => true,
+        })
+        .collect();
+    if !show_secrets {
+        for (key, value) in &mut server_env {
+            if !value.is_empty() && mask.is_masked(key) {
+                *value = MASKED_ENV_VALUE.to_string();
+            }
+        }
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&server_env)?);
+        return Ok(());
+    }
+    for (key, value) in &server_env {
+        println!("{key}={value}");
+    }
+    Ok(())
+}
+
+/// Counts `codex mcp stats` reports.
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct McpStats {
+    total: usize,
+    enabled: usize,
+    disabled: usize,
+    stdio: usize,
+    streamable_http: usize,
+    bearer_token_env_var: usize,
+    bearer_token_file: usize,
+    custom_timeouts: usize,
+}
+
+impl McpStats {
+    fn from_servers<'a>(servers: impl IntoIterator<Item = &'a McpServerConfig>) -> Self {
+        let mut stats = McpStats::default();
+        for server in servers {
+            stats.total += 1;
+            if server.is_effectively_enabled() {
+                stats.enabled += 1;
+            } else {
+                stats.disabled += 1;
+            }
+            match &server.transport {
+                McpServerTransportConfig::Stdio { .. } => stats.stdio += 1,
+                McpServerTransportConfig::StreamableHttp {
+                    bearer_token_env_var,
+                    bearer_token_file,
+                    ..
+                } => {
+                    stats.streamable_http += 1;
+                    if bearer_token_env_var.is_some() {
+                        stats.bearer_token_env_var += 1;
+                    }
+                    if bearer_token_file.is_some() {
+                        stats.bearer_token_file += 1;
+                    }
+                }
+            }
+            if server.startup_timeout_sec.is_some()
+                || server.tool_timeout_sec.is_some()
+                || server.tool_timeouts.is_some()
+            {
+                stats.custom_timeouts += 1;
+            }
+        }
+        stats
+    }
+}
+
+async fn run_stats(config_overrides: &CliConfigOverrides, stats_args: StatsArgs) -> Result<()> {
+    let (_, mcp) = load_mcp_servers(config_overrides).await?;
+    let stats = McpStats::from_servers(mcp.servers.values());
+
+    if stats_args.json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+    let McpStats {
+        total,
+        enabled,
+        disabled,
+        stdio,
+        streamable_http,
+        bearer_token_env_var,
+        bearer_token_file,
+        custom_timeouts,
+    } = stats;
+    println!("Servers: {total} ({enabled} enabled, {disabled} disabled)");
+    println!("Transports: {stdio} stdio, {streamable_http} streamable HTTP");
+    println!("Bearer tokens: {bearer_token_env_var} from env var, {bearer_token_file} from file");
+    println!("Custom timeouts: {custom_timeouts}");
+    Ok(())
+}
+
+async fn run_get(config_overrides: &CliConfigOverrides, get_args: GetArgs) -> Result<()> {
+    let (codex_home, mcp) = load_mcp_servers(config_overrides).await?;
+
+    let Some(server) = mcp.servers.get(&get_args.name) else {
+        bail!("No MCP server named '{name}' found.", name = get_args.name);
+    };
+    let source = mcp
+        .sources
+        .get(&get_args.name)
+        .copied()
+        .unwrap_or(McpServerSource::UserConfig);
+
+    let effective = server.with_defaults(&mcp.server_defaults);
+
+    let probe = if get_args.probe {
+        Some(probe_server(&get_args.name, &effective, mcp.oauth_credentials_store_mode).await)
+    } else {
+        None
+    };
+    if let Some(Ok(_)) = &probe {
+        remember_connection(&codex_home, &get_args.name);
+    }
+    let last_connected = load_last_connected(&codex_home)
+        .get(get_args.name.as_str())
+        .copied();
+
+    if get_args.json {
+        let transport = transport_json(&server.transport);
+        let mut value = serde_json::json!({
             "name": get_args.name,
+            "description": server.description,
+            "tags": server.tags,
             "enabled": server.enabled,
+            "enabled_when": server.enabled_when,
+            "effective_enabled": server.is_effectively_enabled(),
             "transport": transport,
             "startup_timeout_sec": server
                 .startup_timeout_sec
@@ -682,13 +3277,64 @@ async fn run_get(config_overrides: &CliConfigOverrides, get_args: GetArgs) -> Re
             "tool_timeout_sec": server
                 .tool_timeout_sec
                 .map(|timeout| timeout.as_secs_f64()),
-        }))?;
+            "tool_timeouts": tool_timeouts_json(server),
+            "allowed_tools": server.allowed_tools,
+            "denied_tools": server.denied_tools,
+            "protocol_version": server.protocol_version,
+            "startup_priority": server.startup_priority,
+            "client_name": server.client_name,
+            "client_version": server.client_version,
+            "last_connected": last_connected.as_ref().map(DateTime::to_rfc3339),
+            "source": source,
+        });
+        if get_args.include_effective {
+            value["effective"] = effective_json(server, &mcp.server_defaults);
+        }
+        if let Some(probe) = &probe {
+            value["diagnostics"] = probe_json(probe);
+        }
+        let output = if get_args.compact {
+            serde_json::to_string(&value)?
+        } else {
+            serde_json::to_string_pretty(&value)?
+        };
         println!("{output}");
         return Ok(());
     }
 
-    println!("{}", get_args.name);
-    println!("  enabled: {}", server.enabled);
+    let color = get_args.color.enabled();
+    if color {
+        println!("{}", get_args.name.bold());
+    } else {
+        println!("{}", get_args.name);
+    }
+    if let Some(description) = &server.description {
+        println!("  description: {description}");
+    }
+    if !server.tags.is_empty() {
+        println!("  tags: {}", server.tags.join(", "));
+    }
+    let enabled = server.enabled.to_string();
+    if color {
+        println!(
+            "  enabled: {}",
+            enabled.style(enabled_style(server.enabled))
+        );
+    } else {
+        println!("  enabled: {enabled}");
+    }
+    if let Some(condition) = &server.enabled_when {
+        let met = if condition.is_satisfied() {
+            "met"
+        } else {
+            "not met"
+        };
+        println!(
+            "  enabled_when: {} ({met} on this machine)",
+            enabled_when_display(condition)
+        );
+    }
+    println!("  source: {}", source_display(source));
     match &server.transport {
         McpServerTransportConfig::Stdio {
             command,
@@ -696,6 +3342,10 @@ async fn run_get(config_overrides: &CliConfigOverrides, get_args: GetArgs) -> Re
             env,
             env_vars,
             cwd,
+            expand_env,
+            expand_env_strict,
+            shutdown_timeout_sec,
+            inherit_env,
         } => {
             println!("  transport: stdio");
             println!("  command: {command}");
@@ -711,19 +3361,59 @@ async fn run_get(config_overrides: &CliConfigOverrides, get_args: GetArgs) -> Re
                 .filter(|value| !value.is_empty())
                 .unwrap_or_else(|| "-".to_string());
             println!("  cwd: {cwd_display}");
-            let env_display = format_env_display(env.as_ref(), env_vars);
+            let env_limit = (!get_args.show_all_env).then_some(MAX_ENV_ENTRIES_DISPLAYED);
+            let mask = env_mask(&mcp);
+            let env_display = format_env_display_masked(env.as_ref(), env_vars, env_limit, &mask);
             println!("  env: {env_display}");
+            let expand_env_display = match (expand_env, expand_env_strict) {
+                (false, _) => "off",
+                (true, false) => "on (unset variables expand to empty)",
+                (true, true) => "strict (unset variables are an error)",
+            };
+            println!("  expand_env: {expand_env_display}");
+            match (inherit_env, mcp.server_defaults.inherit_env) {
+                (Some(mode), _) => println!("  inherit_env: {}", mode.as_str()),
+                (None, Some(mode)) => println!(
+                    "  inherit_env: {} (from mcp_default_inherit_env)",
+                    mode.as_str()
+                ),
+                (None, None) => println!(
+                    "  inherit_env: {} (default)",
+                    McpInheritEnv::default().as_str()
+                ),
+            }
+            if let Some(timeout) = shutdown_timeout_sec {
+                println!("  shutdown_timeout_sec: {}", timeout.as_secs_f64());
+            }
         }
         McpServerTransportConfig::StreamableHttp {
             url,
+            url_env_var,
             bearer_token_env_var,
+            bearer_token_file,
             http_headers,
             env_http_headers,
+            env_http_header_defaults,
+            oauth_client_credentials,
+            keep_alive,
+            max_idle_connections,
+            connect_timeout_sec,
+            max_requests_per_sec,
+            client_cert_path,
+            client_key_path,
+            ca_cert_path,
+            health_check_interval_sec,
         } => {
             println!("  transport: streamable_http");
-            println!("  url: {url}");
+            match url_env_var {
+                Some(env_var) => println!("  url_env_var: {env_var}"),
+                None => println!("  url: {url}"),
+            }
             let env_var = bearer_token_env_var.as_deref().unwrap_or("-");
             println!("  bearer_token_env_var: {env_var}");
+            if let Some(path) = bearer_token_file {
+                println!("  bearer_token_file: {}", path.display());
+            }
             let headers_display = match http_headers {
                 Some(map) if !map.is_empty() => {
                     let mut pairs: Vec<_> = map.iter().collect();
@@ -743,41 +3433,1070 @@ async fn run_get(config_overrides: &CliConfigOverrides, get_args: GetArgs) -> Re
                     pairs.sort_by(|(a, _), (b, _)| a.cmp(b));
                     pairs
                         .into_iter()
-                        .map(|(k, v)| format!("{k}={v}"))
+                        .map(|(k, v)| {
+                            match env_http_header_defaults
+                                .as_ref()
+                                .and_then(|defaults| defaults.get(k))
+                            {
+                                Some(default) => format!("{k}={v} (default: {default})"),
+                                None => format!("{k}={v}"),
+                            }
+                        })
                         .collect::<Vec<_>>()
                         .join(", ")
                 }
                 _ => "-".to_string(),
             };
             println!("  env_http_headers: {env_headers_display}");
+            if let Some(oauth) = oauth_client_credentials {
+                println!("  oauth_token_url: {}", oauth.token_url);
+                println!("  oauth_client_id: {}", oauth.client_id);
+                println!(
+                    "  oauth_client_secret_env_var: {}",
+                    oauth.client_secret_env_var
+                );
+                let scopes_display = if oauth.scopes.is_empty() {
+                    "-".to_string()
+                } else {
+                    oauth.scopes.join(" ")
+                };
+                println!("  oauth_scopes: {scopes_display}");
+            }
+            let keep_alive_display = if *keep_alive { "on" } else { "off" };
+            println!("  keep_alive: {keep_alive_display}");
+            let max_idle_display = max_idle_connections
+                .map(|max| max.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            println!("  max_idle_connections: {max_idle_display}");
+            if let Some(timeout) = connect_timeout_sec {
+                println!("  connect_timeout_sec: {}", timeout.as_secs_f64());
+            }
+            if let Some(interval) = health_check_interval_sec {
+                println!("  health_check_interval_sec: {}", interval.as_secs_f64());
+            }
+            if let Some(rate) = max_requests_per_sec {
+                println!("  max_requests_per_sec: {rate}");
+            }
+            for (field, path) in [
+                ("client_cert_path", client_cert_path),
+                ("client_key_path", client_key_path),
+                ("ca_cert_path", ca_cert_path),
+            ] {
+                if let Some(path) = path {
+                    println!("  {field}: {}", path.display());
+                }
+            }
+        }
+    }
+    for (field, own, effective) in [
+        (
+            "startup_timeout_sec",
+            server.startup_timeout_sec,
+            effective.startup_timeout_sec,
+        ),
+        (
+            "tool_timeout_sec",
+            server.tool_timeout_sec,
+            effective.tool_timeout_sec,
+        ),
+    ] {
+        match (own, effective) {
+            (Some(timeout), _) => println!("  {field}: {}", timeout.as_secs_f64()),
+            (None, Some(timeout)) => println!(
+                "  {field}: {} (from mcp_default_{field})",
+                timeout.as_secs_f64()
+            ),
+            (None, None) => {}
         }
     }
-    if let Some(timeout) = server.startup_timeout_sec {
-        println!("  startup_timeout_sec: {}", timeout.as_secs_f64());
+    if let Some(timeouts) = tool_timeouts_json(server) {
+        let display = timeouts
+            .into_iter()
+            .map(|(tool, secs)| format!("{tool}={secs}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!("  tool_timeouts: {display}");
+    }
+    if let Some(tools) = &server.allowed_tools {
+        println!("  allowed_tools: {}", tools.join(", "));
+    }
+    if let Some(tools) = &server.denied_tools {
+        println!("  denied_tools: {}", tools.join(", "));
     }
-    if let Some(timeout) = server.tool_timeout_sec {
-        println!("  tool_timeout_sec: {}", timeout.as_secs_f64());
+    if let Some(version) = &server.protocol_version {
+        println!("  protocol_version: {version}");
     }
+    println!("  startup_priority: {}", server.resolved_startup_priority());
+    if let Some(client_name) = &server.client_name {
+        println!("  client_name: {client_name}");
+    }
+    if let Some(client_version) = &server.client_version {
+        println!("  client_version: {client_version}");
+    }
+    println!(
+        "  last_connected: {}",
+        format_last_connected(last_connected, Utc::now())
+    );
     println!("  remove: codex mcp remove {}", get_args.name);
 
-    Ok(())
-}
+    if get_args.include_effective {
+        println!("  effective:");
+        for setting in effective_settings(server, &mcp.server_defaults) {
+            println!(
+                "    {}: {} ({})",
+                setting.field,
+                setting.display,
+                setting.source.label()
+            );
+        }
+    }
 
+    if let Some(probe) = probe {
+        println!("  diagnostics:");
+        match probe {
+            Ok(probe) => {
+                println!("    protocol_version: {}", probe.protocol_version);
+                println!("    server: {} {}", probe.server_name, probe.server_version);
+                println!("    startup_ms: {}", probe.startup.as_millis());
+                println!("    latency_ms: {}", probe.latency.as_millis());
+                let tools_display = if probe.tools.is_empty() {
+                    "-".to_string()
+                } else {
+                    probe.tools.join(", ")
+                };
+                println!("    tools: {tools_display}");
+            }
+            Err(err) => println!("    error: {err:#}"),
+        }
+    }
+
+    Ok(())
+}
+
+fn source_display(source: McpServerSource) -> &'static str {
+    match source {
+        McpServerSource::UserConfig => "config.toml",
+        McpServerSource::CliOverride => "command-line override (-c)",
+        McpServerSource::ManagedConfig => "managed_config.toml",
+        McpServerSource::ManagedPreferences => "managed preferences",
+    }
+}
+
+/// Persist `servers`, turning permission failures into an actionable message.
+fn write_servers(codex_home: &Path, servers: &BTreeMap<String, McpServerConfig>) -> Result<()> {
+    write_global_mcp_servers(codex_home, servers)
+        .map_err(|err| {
+            if err.kind() == ErrorKind::PermissionDenied {
+                let hint = permission_hint(codex_home);
+                anyhow::Error::new(err).context(hint)
+            } else {
+                anyhow::Error::new(err)
+            }
+        })
+        .with_context(|| format!("failed to write MCP servers to {}", codex_home.display()))
+}
+
+fn permission_hint(codex_home: &Path) -> String {
+    let config_path = codex_home.join(CONFIG_TOML_FILE);
+    // Name the config file when it exists; otherwise the directory is the
+    // problem because the temp file could not be created next to it.
+    let target = if config_path.exists() {
+        config_path
+    } else {
+        codex_home.to_path_buf()
+    };
+    let permissions = std::fs::metadata(&target)
+        .map(|metadata| describe_permissions(&metadata))
+        .unwrap_or_else(|err| format!("unknown: {err}"));
+    let path = target.display();
+    format!(
+        "{path} is not writable (permissions: {permissions}); \
+         run `chmod u+w {path}` or `chown $(whoami) {path}`, \
+         or update the tool that provisions it"
+    )
+}
+
+#[cfg(unix)]
+fn describe_permissions(metadata: &std::fs::Metadata) -> String {
+    use std::os::unix::fs::PermissionsExt;
+    format!("{:o}", metadata.permissions().mode() & 0o777)
+}
+
+#[cfg(not(unix))]
+fn describe_permissions(metadata: &std::fs::Metadata) -> String {
+    if metadata.permissions().readonly() {
+        "read-only".to_string()
+    } else {
+        "writable".to_string()
+    }
+}
+
+/// Split `--command-line` into the command and its arguments.
+fn split_command_line(line: &str) -> Result<Vec<String>> {
+    let Some(words) = shlex::split(line) else {
+        bail!("--command-line has unbalanced quotes or a trailing backslash: {line}");
+    };
+    if words.is_empty() {
+        bail!("--command-line must name a command");
+    }
+    Ok(words)
+}
+
+/// Prefix `command` with the `--npx`/`--uvx` launcher. Its first word is the
+/// package spec; the rest are the server's own arguments.
+fn package_launcher_command(npx: bool, uvx: bool, command: Vec<String>) -> Result<Vec<String>> {
+    let (flag, launcher): (&str, &[&str]) = match (npx, uvx) {
+        (true, _) => ("--npx", &["npx", "-y"]),
+        (false, true) => ("--uvx", &["uvx"]),
+        (false, false) => return Ok(command),
+    };
+    if command.first().is_none_or(|spec| spec.trim().is_empty()) {
+        bail!("{flag} needs a non-empty package spec");
+    }
+    Ok(launcher
+        .iter()
+        .map(ToString::to_string)
+        .chain(command)
+        .collect())
+}
+
+fn new_server_config(transport: McpServerTransportConfig) -> McpServerConfig {
+    McpServerConfig {
+        transport,
+        enabled: true,
+        startup_timeout_sec: None,
+        tool_timeout_sec: None,
+        allowed_tools: None,
+        denied_tools: None,
+        protocol_version: None,
+        enabled_when: None,
+        description: None,
+        startup_priority: None,
+        client_name: None,
+        client_version: None,
+        tool_timeouts: None,
+        tags: Vec::new(),
+    }
+}
+
+/// JSON rendering of a transport shared by `list --json` and `get --json`.
+/// [`McpServerJson`] parses the same shape back.
+fn transport_json(transport: &McpServerTransportConfig) -> serde_json::Value {
+    match transport {
+        McpServerTransportConfig::Stdio {
+            command,
+            args,
+            env,
+            env_vars,
+            cwd,
+            expand_env,
+            expand_env_strict,
+            shutdown_timeout_sec,
+            inherit_env,
+        } => serde_json::json!({
+            "type": "stdio",
+            "command": command,
+            "args": args,
+            "env": env,
+            "env_vars": env_vars,
+            "cwd": cwd,
+            "expand_env": expand_env,
+            "expand_env_strict": expand_env_strict,
+            "shutdown_timeout_sec": shutdown_timeout_sec.map(|timeout| timeout.as_secs_f64()),
+            "inherit_env": inherit_env,
+        }),
+        McpServerTransportConfig::StreamableHttp {
+            url,
+            url_env_var,
+            bearer_token_env_var,
+            bearer_token_file,
+            http_headers,
+            env_http_headers,
+            env_http_header_defaults,
+            oauth_client_credentials,
+            keep_alive,
+            max_idle_connections,
+            connect_timeout_sec,
+            max_requests_per_sec,
+            client_cert_path,
+            client_key_path,
+            ca_cert_path,
+            health_check_interval_sec,
+        } => serde_json::json!({
+            "type": "streamable_http",
+            "url": (!url.is_empty()).then_some(url),
+            "url_env_var": url_env_var,
+            "bearer_token_env_var": bearer_token_env_var,
+            // The path is shown, never the token it holds.
+            "bearer_token_file": bearer_token_file,
+            "http_headers": http_headers,
+            "env_http_headers": env_http_headers,
+            "env_http_header_defaults": env_http_header_defaults,
+            // Only the secret's variable name is stored, never its value.
+            "oauth_client_credentials": oauth_client_credentials,
+            "keep_alive": keep_alive,
+            "max_idle_connections": max_idle_connections,
+            "connect_timeout_sec": connect_timeout_sec.map(|timeout| timeout.as_secs_f64()),
+            "max_requests_per_sec": max_requests_per_sec,
+            "client_cert_path": client_cert_path,
+            "client_key_path": client_key_path,
+            "ca_cert_path": ca_cert_path,
+            "health_check_interval_sec": health_check_interval_sec
+                .map(|interval| interval.as_secs_f64()),
+        }),
+    }
+}
+
+fn probe_json(probe: &Result<McpServerProbe>) -> serde_json::Value {
+    match probe {
+        Ok(probe) => serde_json::json!({
+            "protocol_version": probe.protocol_version,
+            "server_name": probe.server_name,
+            "server_version": probe.server_version,
+            "tools": probe.tools,
+            "startup_ms": probe.startup.as_secs_f64() * 1000.0,
+            "latency_ms": probe.latency.as_secs_f64() * 1000.0,
+        }),
+        Err(err) => serde_json::json!({
+            "error": format!("{err:#}"),
+        }),
+    }
+}
+
+/// Server definition in the shape printed by `codex mcp get --json`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct McpServerJson {
+    /// Accepted for round-tripping `get --json`; the NAME argument decides
+    /// where the entry is stored.
+    #[serde(default, rename = "name")]
+    _name: Option<IgnoredAny>,
+    #[serde(default = "default_enabled")]
+    enabled: bool,
+    #[serde(default)]
+    enabled_when: Option<McpEnabledWhen>,
+    #[serde(default)]
+    description: Option<String>,
+    /// Evaluated on the machine that ran `get --json`; not part of the
+    /// definition.
+    #[serde(default, rename = "effective_enabled")]
+    _effective_enabled: Option<IgnoredAny>,
+    transport: McpServerTransportJson,
+    #[serde(default)]
+    startup_timeout_sec: Option<f64>,
+    #[serde(default)]
+    tool_timeout_sec: Option<f64>,
+    #[serde(default)]
+    tool_timeouts: Option<HashMap<String, f64>>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    allowed_tools: Option<Vec<String>>,
+    #[serde(default)]
+    denied_tools: Option<Vec<String>>,
+    #[serde(default)]
+    protocol_version: Option<String>,
+    #[serde(default)]
+    startup_priority: Option<i32>,
+    #[serde(default)]
+    client_name: Option<String>,
+    #[serde(default)]
+    client_version: Option<String>,
+    /// Runtime state reported by `get --json`; not part of the definition.
+    #[serde(default, rename = "last_connected")]
+    _last_connected: Option<IgnoredAny>,
+    /// Provenance reported by `get --json`; imports always go to config.toml.
+    #[serde(default, rename = "source")]
+    _source: Option<IgnoredAny>,
+    /// Resolved settings from `get --json --include-effective`; recomputed
+    /// on the importing machine.
+    #[serde(default, rename = "effective")]
+    _effective: Option<IgnoredAny>,
+    /// Connection results from `get --json --probe`.
+    #[serde(default, rename = "diagnostics")]
+    _diagnostics: Option<IgnoredAny>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum McpServerTransportJson {
+    Stdio {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        env: Option<HashMap<String, String>>,
+        #[serde(default)]
+        env_vars: Vec<String>,
+        #[serde(default)]
+        cwd: Option<PathBuf>,
+        #[serde(default)]
+        expand_env: bool,
+        #[serde(default)]
+        expand_env_strict: bool,
+        #[serde(default)]
+        shutdown_timeout_sec: Option<f64>,
+        #[serde(default)]
+        inherit_env: Option<McpInheritEnv>,
+    },
+    StreamableHttp {
+        #[serde(default)]
+        url: Option<String>,
+        #[serde(default)]
+        url_env_var: Option<String>,
+        #[serde(default)]
+        bearer_token_env_var: Option<String>,
+        #[serde(default)]
+        bearer_token_file: Option<PathBuf>,
+        #[serde(default)]
+        http_headers: Option<HashMap<String, String>>,
+        #[serde(default)]
+        env_http_headers: Option<HashMap<String, String>>,
+        #[serde(default)]
+        env_http_header_defaults: Option<HashMap<String, String>>,
+        #[serde(default)]
+        oauth_client_credentials: Option<McpOAuthClientCredentials>,
+        #[serde(default = "default_keep_alive")]
+        keep_alive: bool,
+        #[serde(default)]
+        max_idle_connections: Option<usize>,
+        #[serde(default)]
+        connect_timeout_sec: Option<f64>,
+        #[serde(default)]
+        max_requests_per_sec: Option<f64>,
+        #[serde(default)]
+        client_cert_path: Option<PathBuf>,
+        #[serde(default)]
+        client_key_path: Option<PathBuf>,
+        #[serde(default)]
+        ca_cert_path: Option<PathBuf>,
+        #[serde(default)]
+        health_check_interval_sec: Option<f64>,
+    },
+}
+
+const fn default_enabled() -> bool {
+    true
+}
+
+const fn default_keep_alive() -> bool {
+    true
+}
+
+impl TryFrom<McpServerJson> for McpServerConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(spec: McpServerJson) -> Result<Self> {
+        let transport = match spec.transport {
+            McpServerTransportJson::Stdio {
+                command,
+                args,
+                env,
+                env_vars,
+                cwd,
+                expand_env,
+                expand_env_strict,
+                shutdown_timeout_sec,
+                inherit_env,
+            } => {
+                if expand_env_strict && !expand_env {
+                    bail!("expand_env_strict requires expand_env to be true");
+                }
+                McpServerTransportConfig::Stdio {
+                    command,
+                    args,
+                    env,
+                    env_vars,
+                    cwd,
+                    expand_env,
+                    expand_env_strict,
+                    shutdown_timeout_sec: parse_timeout(
+                        "shutdown_timeout_sec",
+                        shutdown_timeout_sec,
+                    )?,
+                    inherit_env,
+                }
+            }
+            McpServerTransportJson::StreamableHttp {
+                url,
+                url_env_var,
+                bearer_token_env_var,
+                bearer_token_file,
+                http_headers,
+                env_http_headers,
+                env_http_header_defaults,
+                oauth_client_credentials,
+                keep_alive,
+                max_idle_connections,
+                connect_timeout_sec,
+                max_requests_per_sec,
+                client_cert_path,
+                client_key_path,
+                ca_cert_path,
+                health_check_interval_sec,
+            } => McpServerTransportConfig::StreamableHttp {
+                url: match (url, &url_env_var) {
+                    (Some(_), Some(_)) => bail!("url and url_env_var cannot both be set"),
+                    (Some(url), None) => url,
+                    (None, Some(_)) => String::new(),
+                    (None, None) => bail!("streamable_http transport requires url or url_env_var"),
+                },
+                url_env_var,
+                bearer_token_env_var,
+                bearer_token_file,
+                http_headers,
+                env_http_headers,
+                env_http_header_defaults,
+                oauth_client_credentials,
+                keep_alive,
+                max_idle_connections,
+                connect_timeout_sec: parse_timeout("connect_timeout_sec", connect_timeout_sec)?,
+                max_requests_per_sec,
+                client_cert_path,
+                client_key_path,
+                ca_cert_path,
+                health_check_interval_sec: parse_timeout(
+                    "health_check_interval_sec",
+                    health_check_interval_sec,
+                )?,
+            },
+        };
+        validate_transport(&transport)?;
+        if let Some(condition) = &spec.enabled_when {
+            condition.validate().map_err(|err| anyhow!(err))?;
+        }
+        if let Some(version) = &spec.protocol_version
+            && !MCP_PROTOCOL_VERSIONS.contains(&version.as_str())
+        {
+            bail!(
+                "unsupported protocol_version '{version}'; expected one of: {}",
+                MCP_PROTOCOL_VERSIONS.join(", ")
+            );
+        }
+        validate_client_info(spec.client_name.as_deref(), spec.client_version.as_deref())?;
+
+        Ok(McpServerConfig {
+            transport,
+            enabled: spec.enabled,
+            startup_timeout_sec: parse_timeout("startup_timeout_sec", spec.startup_timeout_sec)?,
+            tool_timeout_sec: parse_timeout("tool_timeout_sec", spec.tool_timeout_sec)?,
+            allowed_tools: spec.allowed_tools,
+            denied_tools: spec.denied_tools,
+            protocol_version: spec.protocol_version,
+            enabled_when: spec.enabled_when,
+            description: spec.description,
+            startup_priority: spec.startup_priority,
+            client_name: spec.client_name,
+            client_version: spec.client_version,
+            tool_timeouts: parse_tool_timeouts(spec.tool_timeouts)?,
+            tags: spec.tags,
+        })
+    }
+}
+
+/// Reject a blank `client_name` or `client_version`; leave them unset to
+/// send Codex's own identity.
+fn validate_client_info(client_name: Option<&str>, client_version: Option<&str>) -> Result<()> {
+    for (field, value) in [
+        ("client_name", client_name),
+        ("client_version", client_version),
+    ] {
+        if value.is_some_and(|value| value.trim().is_empty()) {
+            bail!("{field} must not be empty");
+        }
+    }
+    Ok(())
+}
+
+fn parse_timeout(field: &str, secs: Option<f64>) -> Result<Option<Duration>> {
+    secs.map(|secs| {
+        Duration::try_from_secs_f64(secs).map_err(|err| anyhow!("invalid {field} {secs}: {err}"))
+    })
+    .transpose()
+}
+
+/// `tool_timeouts` from server JSON. Every timeout must be positive.
+fn parse_tool_timeouts(
+    timeouts: Option<HashMap<String, f64>>,
+) -> Result<Option<HashMap<String, Duration>>> {
+    timeouts
+        .map(|timeouts| {
+            timeouts
+                .into_iter()
+                .map(|(tool, secs)| {
+                    if !(secs.is_finite() && secs > 0.0) {
+                        bail!("tool_timeouts.{tool} must be a positive number of seconds");
+                    }
+                    let timeout = Duration::try_from_secs_f64(secs)
+                        .map_err(|err| anyhow!("invalid tool_timeouts.{tool}: {err}"))?;
+                    Ok((tool, timeout))
+                })
+                .collect()
+        })
+        .transpose()
+}
+
+/// `tool_timeouts` in seconds, ordered by tool name for stable output.
+fn tool_timeouts_json(server: &McpServerConfig) -> Option<BTreeMap<&str, f64>> {
+    server.tool_timeouts.as_ref().map(|timeouts| {
+        timeouts
+            .iter()
+            .map(|(tool, timeout)| (tool.as_str(), timeout.as_secs_f64()))
+            .collect()
+    })
+}
+
+/// Where the effective value of a setting shown by `get --include-effective`
+/// came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum EffectiveSource {
+    /// The server sets the value itself.
+    Configured,
+    /// The top-level `mcp_default_*` key of this name.
+    GlobalDefault(&'static str),
+    /// Codex's built-in default.
+    BuiltIn,
+    /// The server is enabled and its `enabled_when` condition decides.
+    EnabledWhen { met: bool },
+}
+
+impl EffectiveSource {
+    /// Value of the `source` field in `--json` output.
+    fn key(&self) -> &'static str {
+        match self {
+            EffectiveSource::Configured => "configured",
+            EffectiveSource::GlobalDefault(key) => key,
+            EffectiveSource::BuiltIn => "built_in_default",
+            EffectiveSource::EnabledWhen { .. } => "enabled_when",
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            EffectiveSource::Configured => "configured".to_string(),
+            EffectiveSource::GlobalDefault(key) => format!("from {key}"),
+            EffectiveSource::BuiltIn => "built-in default".to_string(),
+            EffectiveSource::EnabledWhen { met: true } => "enabled_when met".to_string(),
+            EffectiveSource::EnabledWhen { met: false } => "enabled_when not met".to_string(),
+        }
+    }
+}
+
+struct EffectiveSetting {
+    field: &'static str,
+    value: serde_json::Value,
+    /// `value` as `get` prints it in text output.
+    display: String,
+    source: EffectiveSource,
+}
+
+/// The settings whose effective value can differ from what the server
+/// configures: whether it starts, its timeouts, its startup priority, and
+/// (for stdio servers) the environment it inherits.
+fn effective_settings(
+    server: &McpServerConfig,
+    defaults: &McpServerDefaults,
+) -> Vec<EffectiveSetting> {
+    let enabled_source = match &server.enabled_when {
+        Some(condition) if server.enabled => EffectiveSource::EnabledWhen {
+            met: condition.is_satisfied(),
+        },
+        _ => EffectiveSource::Configured,
+    };
+    let mut settings = vec![EffectiveSetting {
+        field: "enabled",
+        value: server.is_effectively_enabled().into(),
+        display: server.is_effectively_enabled().to_string(),
+        source: enabled_source,
+    }];
+
+    for (field, own, default, default_key, built_in) in [
+        (
+            "startup_timeout_sec",
+            server.startup_timeout_sec,
+            defaults.startup_timeout_sec,
+            "mcp_default_startup_timeout_sec",
+            DEFAULT_STARTUP_TIMEOUT,
+        ),
+        (
+            "tool_timeout_sec",
+            server.tool_timeout_sec,
+            defaults.tool_timeout_sec,
+            "mcp_default_tool_timeout_sec",
+            DEFAULT_TOOL_TIMEOUT,
+        ),
+    ] {
+        let (timeout, source) = match (own, default) {
+            (Some(timeout), _) => (timeout, EffectiveSource::Configured),
+            (None, Some(timeout)) => (timeout, EffectiveSource::GlobalDefault(default_key)),
+            (None, None) => (built_in, EffectiveSource::BuiltIn),
+        };
+        settings.push(EffectiveSetting {
+            field,
+            value: timeout.as_secs_f64().into(),
+            display: timeout.as_secs_f64().to_string(),
+            source,
+        });
+    }
+
+    let priority = server.resolved_startup_priority();
+    settings.push(EffectiveSetting {
+        field: "startup_priority",
+        value: priority.into(),
+        display: priority.to_string(),
+        source: if server.startup_priority.is_some() {
+            EffectiveSource::Configured
+        } else {
+            EffectiveSource::BuiltIn
+        },
+    });
+
+    if let McpServerTransportConfig::Stdio { inherit_env, .. } = &server.transport {
+        let (mode, source) = match (inherit_env, defaults.inherit_env) {
+            (Some(mode), _) => (*mode, EffectiveSource::Configured),
+            (None, Some(mode)) => (
+                mode,
+                EffectiveSource::GlobalDefault("mcp_default_inherit_env"),
+            ),
+            (None, None) => (McpInheritEnv::default(), EffectiveSource::BuiltIn),
+        };
+        settings.push(EffectiveSetting {
+            field: "inherit_env",
+            value: mode.as_str().into(),
+            display: mode.as_str().to_string(),
+            source,
+        });
+    }
+
+    settings
+}
+
+/// [`effective_settings`] as the `effective` object of `--json` output:
+/// `{"value": ..., "source": ...}` per setting.
+fn effective_json(server: &McpServerConfig, defaults: &McpServerDefaults) -> serde_json::Value {
+    effective_settings(server, defaults)
+        .into_iter()
+        .map(|setting| {
+            let json = serde_json::json!({
+                "value": setting.value,
+                "source": setting.source.key(),
+            });
+            (setting.field, json)
+        })
+        .collect()
+}
+
+fn read_server_json(path: &Path) -> Result<McpServerConfig> {
+    let raw = if path == Path::new("-") {
+        let mut raw = String::new();
+        std::io::stdin()
+            .read_to_string(&mut raw)
+            .context("failed to read server JSON from stdin")?;
+        raw
+    } else {
+        std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read server JSON from {}", path.display()))?
+    };
+
+    let spec: McpServerJson = serde_json::from_str(&raw)
+        .with_context(|| format!("invalid MCP server JSON in {}", path.display()))?;
+    spec.try_into()
+}
+
+/// Absolute form of a token, certificate, or key path passed to `add`, so the saved
+/// config does not depend on the directory `add` ran in. A file that does not
+/// exist yet is only warned about.
+fn file_path_arg(flag: &str, path: Option<PathBuf>) -> Result<Option<PathBuf>> {
+    let Some(path) = path else {
+        return Ok(None);
+    };
+    let path = std::path::absolute(&path)
+        .with_context(|| format!("failed to resolve {flag} path {}", path.display()))?;
+    if !path.exists() {
+        eprintln!("warning: {flag} file {} does not exist", path.display());
+    }
+    Ok(Some(path))
+}
+
+fn validate_rate_limit(rate: f64) -> Result<()> {
+    if !(rate.is_finite() && rate > 0.0) {
+        bail!("max_requests_per_sec must be a positive number");
+    }
+    if rate < MIN_MAX_REQUESTS_PER_SEC {
+        bail!("max_requests_per_sec must be at least one request per hour (1/3600)");
+    }
+    Ok(())
+}
+
+fn validate_transport(transport: &McpServerTransportConfig) -> Result<()> {
+    match transport {
+        McpServerTransportConfig::Stdio { command, .. } => {
+            if command.trim().is_empty() {
+                bail!("stdio transport requires a non-empty command");
+            }
+        }
+        McpServerTransportConfig::StreamableHttp {
+            url,
+            url_env_var,
+            bearer_token_env_var,
+            bearer_token_file,
+            env_http_headers,
+            env_http_header_defaults,
+            oauth_client_credentials,
+            keep_alive,
+            max_idle_connections,
+            connect_timeout_sec,
+            max_requests_per_sec,
+            client_cert_path,
+            client_key_path,
+            health_check_interval_sec,
+            ..
+        } => {
+            match url_env_var {
+                Some(env_var) if env_var.trim().is_empty() => {
+                    bail!("url_env_var must not be empty");
+                }
+                Some(_) => {}
+                None => validate_http_url("server URL", url)?,
+            }
+            if !keep_alive && max_idle_connections.is_some() {
+                bail!("max_idle_connections requires keep_alive = true");
+            }
+            if *connect_timeout_sec == Some(Duration::ZERO) {
+                bail!("connect_timeout_sec must be positive");
+            }
+            if *health_check_interval_sec == Some(Duration::ZERO) {
+                bail!("health_check_interval_sec must be positive");
+            }
+            if let Some(rate) = max_requests_per_sec {
+                validate_rate_limit(*rate)?;
+            }
+            if client_cert_path.is_some() != client_key_path.is_some() {
+                bail!("client_cert_path and client_key_path must be set together");
+            }
+            if bearer_token_env_var.is_some() && bearer_token_file.is_some() {
+                bail!("bearer_token_env_var and bearer_token_file cannot both be set");
+            }
+            if let Some(header) = undeclared_env_header_default(
+                env_http_headers.as_ref(),
+                env_http_header_defaults.as_ref(),
+            ) {
+                bail!(
+                    "env_http_header_defaults has a default for '{header}', which is not in env_http_headers"
+                );
+            }
+            if let Some(oauth) = oauth_client_credentials {
+                if bearer_token_env_var.is_some() {
+                    bail!("bearer_token_env_var and oauth_client_credentials cannot both be set");
+                }
+                if bearer_token_file.is_some() {
+                    bail!("bearer_token_file and oauth_client_credentials cannot both be set");
+                }
+                validate_http_url("OAuth token URL", &oauth.token_url)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// A header that has an `env_http_header_defaults` entry but no
+/// `env_http_headers` entry, if any.
+fn undeclared_env_header_default<'a>(
+    env_http_headers: Option<&HashMap<String, String>>,
+    env_http_header_defaults: Option<&'a HashMap<String, String>>,
+) -> Option<&'a str> {
+    env_http_header_defaults
+        .into_iter()
+        .flat_map(HashMap::keys)
+        .find(|header| !env_http_headers.is_some_and(|headers| headers.contains_key(*header)))
+        .map(String::as_str)
+}
+
+/// The status column of `list`. A server whose `enabled_when` condition does
+/// not hold here is reported as inactive rather than enabled.
+fn status_display(server: &McpServerConfig) -> String {
+    if !server.enabled {
+        return "disabled".to_string();
+    }
+    match &server.enabled_when {
+        Some(condition) if !condition.is_satisfied() => {
+            format!("inactive ({})", enabled_when_display(condition))
+        }
+        _ => "enabled".to_string(),
+    }
+}
+
+/// Pads a `Status` cell to `width` and colors it. The escape codes wrap only
+/// the text, not the padding, so they do not count towards the column width.
+fn status_cell(status: &str, width: usize, color: bool) -> String {
+    let padding = " ".repeat(width.saturating_sub(status.len()));
+    if !color {
+        return format!("{status}{padding}");
+    }
+    let style = match status {
+        "enabled" => enabled_style(true),
+        "disabled" => enabled_style(false),
+        _ => Style::new().yellow(),
+    };
+    format!("{}{padding}", status.style(style))
+}
+
+fn enabled_style(enabled: bool) -> Style {
+    if enabled {
+        Style::new().green()
+    } else {
+        Style::new().dimmed()
+    }
+}
+
+fn enabled_when_display(condition: &McpEnabledWhen) -> String {
+    let mut parts = Vec::new();
+    if let Some(os) = &condition.os {
+        parts.push(format!("os={os}"));
+    }
+    if let Some(env) = &condition.env {
+        parts.push(format!("env={env}"));
+    }
+    parts.join(", ")
+}
+
+/// The URL column for a streamable HTTP server: the URL itself, or `$VAR`
+/// when it is read from the environment at connect time.
+fn url_display(url: &str, url_env_var: Option<&str>) -> String {
+    match url_env_var {
+        Some(env_var) => format!("${env_var}"),
+        None => url.to_string(),
+    }
+}
+
+fn validate_http_url(label: &str, url: &str) -> Result<()> {
+    let parsed = url::Url::parse(url).with_context(|| format!("invalid {label} '{url}'"))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        bail!("{label} '{url}' must use http or https");
+    }
+    Ok(())
+}
+
+fn validate_tool_filters(config: &McpServerConfig) -> Result<()> {
+    if let (Some(allowed), Some(denied)) = (&config.allowed_tools, &config.denied_tools)
+        && let Some(tool) = allowed.iter().find(|tool| denied.contains(tool))
+    {
+        bail!("tool '{tool}' cannot be both allowed and denied");
+    }
+    Ok(())
+}
+
+/// Parse a `HEADER=VALUE` flag value. The value may be empty.
+fn parse_header_pair(raw: &str) -> Result<(String, String), String> {
+    match raw.split_once('=') {
+        Some((header, value)) if !header.trim().is_empty() => {
+            Ok((header.trim().to_string(), value.to_string()))
+        }
+        _ => Err("header entries must be in HEADER=VALUE form".to_string()),
+    }
+}
+
+/// Parse a `--env KEY=VALUE` entry.
+///
+/// The key ends at the first `=` not written as `\=` and is trimmed. The
+/// value is everything after it, taken literally unless it is wrapped in
+/// quotes: single quotes preserve the contents exactly, and double quotes
+/// additionally accept `\n`, `\t`, `\"`, and `\\` escapes.
 fn parse_env_pair(raw: &str) -> Result<(String, String), String> {
-    let mut parts = raw.splitn(2, '=');
-    let key = parts
-        .next()
-        .map(str::trim)
-        .filter(|s| !s.is_empty())
-        .ok_or_else(|| "environment entries must be in KEY=VALUE form".to_string())?;
-    let value = parts
-        .next()
-        .map(str::to_string)
-        .ok_or_else(|| "environment entries must be in KEY=VALUE form".to_string())?;
+    const FORM_ERROR: &str = "environment entries must be in KEY=VALUE form";
+
+    let mut key = String::new();
+    let mut chars = raw.char_indices();
+    let value_start = loop {
+        match chars.next() {
+            Some((_, '\\')) if chars.as_str().starts_with('=') => {
+                chars.next();
+                key.push('=');
+            }
+            Some((idx, '=')) => break idx + 1,
+            Some((_, c)) => key.push(c),
+            None => return Err(FORM_ERROR.to_string()),
+        }
+    };
+    let key = key.trim();
+    if key.is_empty() {
+        return Err(FORM_ERROR.to_string());
+    }
 
+    let value = unquote_env_value(&raw[value_start..])
+        .map_err(|err| format!("invalid value for environment variable '{key}': {err}"))?;
     Ok((key.to_string(), value))
 }
 
+fn unquote_env_value(raw: &str) -> Result<String, String> {
+    let Some(quote) = raw.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+        return Ok(raw.to_string());
+    };
+    let inner = raw
+        .get(1..)
+        .and_then(|rest| rest.strip_suffix(quote))
+        .ok_or_else(|| format!("missing closing {quote}"))?;
+    if quote == '\'' {
+        return Ok(inner.to_string());
+    }
+
+    let mut value = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('t') => value.push('\t'),
+            Some(escaped @ ('"' | '\\')) => value.push(escaped),
+            Some(other) => return Err(format!("unsupported escape \\{other}")),
+            None => return Err("trailing backslash".to_string()),
+        }
+    }
+    Ok(value)
+}
+
+/// Returns the existing server name that matches `name` case-insensitively
+/// without being identical to it. Re-adding under the exact same name is an
+/// intentional overwrite and is not reported.
+fn find_case_collision<'a>(
+    servers: &'a BTreeMap<String, McpServerConfig>,
+    name: &str,
+) -> Option<&'a str> {
+    servers
+        .keys()
+        .find(|existing| existing.as_str() != name && existing.eq_ignore_ascii_case(name))
+        .map(String::as_str)
+}
+
+/// Returns the first existing stdio server, other than `name` itself, that
+/// launches exactly the same command and arguments as `transport`.
+fn find_duplicate_command<'a>(
+    servers: &'a BTreeMap<String, McpServerConfig>,
+    name: &str,
+    transport: &McpServerTransportConfig,
+) -> Option<&'a str> {
+    let McpServerTransportConfig::Stdio { command, args, .. } = transport else {
+        return None;
+    };
+    servers
+        .iter()
+        .find(|(existing, config)| {
+            existing.as_str() != name
+                && matches!(
+                    &config.transport,
+                    McpServerTransportConfig::Stdio {
+                        command: existing_command,
+                        args: existing_args,
+                        ..
+                    } if existing_command == command && existing_args == args
+                )
+        })
+        .map(|(existing, _)| existing.as_str())
+}
+
 fn validate_server_name(name: &str) -> Result<()> {
     let is_valid = !name.is_empty()
         && name
@@ -790,3 +4509,395 @@ fn validate_server_name(name: &str) -> Result<()> {
         bail!("invalid server name '{name}' (use letters, numbers, '-', '_')");
     }
 }
+
+/// Names (compared case-insensitively) that Codex uses for its own tool
+/// namespaces.
+const RESERVED_SERVER_NAMES: &[&str] = &["codex", "builtin"];
+
+/// Prefix reserved for internal use.
+const RESERVED_SERVER_NAME_PREFIX: &str = "_";
+
+/// Explain why `name` is reserved, or `None` when it is free to use.
+fn reserved_server_name_reason(name: &str) -> Option<String> {
+    if let Some(reserved) = RESERVED_SERVER_NAMES
+        .iter()
+        .find(|reserved| name.eq_ignore_ascii_case(reserved))
+    {
+        return Some(format!(
+            "'{reserved}' is used by Codex's built-in tools and a server with this name could shadow them"
+        ));
+    }
+    if name.starts_with(RESERVED_SERVER_NAME_PREFIX) {
+        return Some(format!(
+            "names starting with '{RESERVED_SERVER_NAME_PREFIX}' are reserved for internal use and blur the '__' separator in qualified tool names"
+        ));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_server_list_reports_every_bad_line() {
+        let contents = r#"
+# provisioned servers
+docs=docs-server --root "/srv/my docs"
+search = search-server
+no-separator
+bad name=server
+docs=other-server
+empty=
+codex=server
+quote=server "unterminated
+"#;
+        assert_eq!(
+            parse_server_list(contents),
+            Err(vec![
+                "line 5: expected NAME=COMMAND".to_string(),
+                "line 6: invalid server name 'bad name' (use letters, numbers, '-', '_')"
+                    .to_string(),
+                "line 7: server 'docs' is already listed on line 3".to_string(),
+                "line 8: command is required".to_string(),
+                format!(
+                    "line 9: server name 'codex' is reserved: {}",
+                    reserved_server_name_reason("codex").unwrap_or_default()
+                ),
+                "line 10: unbalanced quotes in command".to_string(),
+            ])
+        );
+
+        assert_eq!(
+            parse_server_list("docs=docs-server --root \"/srv/my docs\"\nsearch = search-server\n"),
+            Ok(vec![
+                ServerListEntry {
+                    line: 1,
+                    name: "docs".to_string(),
+                    command: "docs-server".to_string(),
+                    args: vec!["--root".to_string(), "/srv/my docs".to_string()],
+                },
+                ServerListEntry {
+                    line: 2,
+                    name: "search".to_string(),
+                    command: "search-server".to_string(),
+                    args: Vec::new(),
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn reserved_server_names_are_detected() {
+        assert!(reserved_server_name_reason("codex").is_some());
+        assert!(reserved_server_name_reason("Builtin").is_some());
+        assert!(reserved_server_name_reason("_internal").is_some());
+        assert_eq!(reserved_server_name_reason("codex-docs"), None);
+        assert_eq!(reserved_server_name_reason("my_server"), None);
+    }
+
+    fn pair(key: &str, value: &str) -> Result<(String, String), String> {
+        Ok((key.to_string(), value.to_string()))
+    }
+
+    #[test]
+    fn format_last_connected_uses_largest_whole_unit() {
+        let now = DateTime::parse_from_rfc3339("2025-06-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let ago =
+            |secs: i64| format_last_connected(Some(now - chrono::Duration::seconds(secs)), now);
+
+        assert_eq!(format_last_connected(None, now), "never");
+        assert_eq!(ago(-5), "just now");
+        assert_eq!(ago(59), "just now");
+        assert_eq!(ago(125), "2m ago");
+        assert_eq!(ago(2 * 3_600 + 59 * 60), "2h ago");
+        assert_eq!(ago(3 * 86_400), "3d ago");
+    }
+
+    #[test]
+    fn sort_list_entries_orders_by_column_with_name_fallback() {
+        let stdio = |command: &str, enabled: bool| McpServerConfig {
+            enabled,
+            ..new_server_config(McpServerTransportConfig::Stdio {
+                command: command.to_string(),
+                args: Vec::new(),
+                env: None,
+                env_vars: Vec::new(),
+                cwd: None,
+                expand_env: false,
+                expand_env_strict: false,
+                shutdown_timeout_sec: None,
+                inherit_env: None,
+            })
+        };
+        let http = |url: &str| {
+            new_server_config(McpServerTransportConfig::StreamableHttp {
+                url: url.to_string(),
+                url_env_var: None,
+                bearer_token_env_var: None,
+                bearer_token_file: None,
+                http_headers: None,
+                env_http_headers: None,
+                env_http_header_defaults: None,
+                oauth_client_credentials: None,
+                keep_alive: true,
+                max_idle_connections: None,
+                connect_timeout_sec: None,
+                max_requests_per_sec: None,
+                client_cert_path: None,
+                client_key_path: None,
+                ca_cert_path: None,
+                health_check_interval_sec: None,
+            })
+        };
+        let servers = [
+            ("alpha".to_string(), stdio("zeta-server", true)),
+            ("beta".to_string(), http("https://b.example.com/mcp")),
+            ("gamma".to_string(), stdio("alpha-server", false)),
+            ("delta".to_string(), http("https://a.example.com/mcp")),
+        ];
+        let sorted = |key: ListSortKey, reverse: bool| {
+            let mut entries: Vec<_> = servers.iter().map(|(name, cfg)| (name, cfg)).collect();
+            sort_list_entries(&mut entries, key, reverse);
+            entries
+                .into_iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            sorted(ListSortKey::Name, false),
+            vec!["alpha", "beta", "delta", "gamma"]
+        );
+        assert_eq!(
+            sorted(ListSortKey::Command, false),
+            vec!["beta", "delta", "gamma", "alpha"]
+        );
+        assert_eq!(
+            sorted(ListSortKey::Url, false),
+            vec!["alpha", "gamma", "delta", "beta"]
+        );
+        assert_eq!(
+            sorted(ListSortKey::Enabled, true),
+            vec!["delta", "beta", "alpha", "gamma"]
+        );
+    }
+
+    #[test]
+    fn touches_file_matches_events_for_the_config_file_only() {
+        let config = Path::new("/home/me/.codex/config.toml");
+        let event =
+            |path: &str| notify::Event::new(notify::EventKind::Any).add_path(PathBuf::from(path));
+
+        assert!(touches_file(&event("/home/me/.codex/config.toml"), config));
+        assert!(touches_file(
+            &event("/private/home/me/.codex/config.toml"),
+            config
+        ));
+        assert!(!touches_file(
+            &event("/home/me/.codex/config.toml.swp"),
+            config
+        ));
+        assert!(!touches_file(
+            &event("/home/me/.codex/history.jsonl"),
+            config
+        ));
+    }
+
+    #[test]
+    fn diff_servers_reports_added_removed_and_changed_fields() {
+        let installed = parse_mcp_servers_toml(
+            r#"
+[mcp_servers.docs]
+command = "docs-server"
+args = ["--port", "4000"]
+tool_timeout_sec = 30
+
+[mcp_servers.old]
+command = "old-server"
+
+[mcp_servers.same]
+url = "https://example.com/mcp"
+"#,
+        )
+        .expect("installed servers");
+        let incoming = parse_mcp_servers_toml(
+            r#"
+[mcp_servers.docs]
+command = "docs-server"
+args = ["--port", "5000"]
+description = "Handbook search"
+
+[mcp_servers.new]
+command = "new-server"
+
+[mcp_servers.same]
+url = "https://example.com/mcp"
+"#,
+        )
+        .expect("incoming servers");
+
+        let diff = diff_servers(&installed, &incoming);
+
+        assert_eq!(diff.added.keys().collect::<Vec<_>>(), vec!["new"]);
+        assert_eq!(
+            diff.added["new"].get("command"),
+            Some(&serde_json::json!("new-server"))
+        );
+        assert_eq!(diff.removed.keys().collect::<Vec<_>>(), vec!["old"]);
+        assert_eq!(diff.unchanged, vec!["same".to_string()]);
+        assert_eq!(
+            diff.changed["docs"],
+            vec![
+                FieldChange {
+                    field: "args".to_string(),
+                    old: Some(serde_json::json!(["--port", "4000"])),
+                    new: Some(serde_json::json!(["--port", "5000"])),
+                },
+                FieldChange {
+                    field: "description".to_string(),
+                    old: None,
+                    new: Some(serde_json::json!("Handbook search")),
+                },
+                FieldChange {
+                    field: "tool_timeout_sec".to_string(),
+                    old: Some(serde_json::json!(30.0)),
+                    new: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_env_pair_accepts_plain_form() {
+        assert_eq!(parse_env_pair("FOO=bar"), pair("FOO", "bar"));
+        assert_eq!(parse_env_pair(" FOO =bar "), pair("FOO", "bar "));
+        assert_eq!(parse_env_pair("FOO=a=b"), pair("FOO", "a=b"));
+        assert_eq!(parse_env_pair("FOO="), pair("FOO", ""));
+    }
+
+    #[test]
+    fn parse_env_pair_strips_quotes() {
+        assert_eq!(parse_env_pair(r#"FOO="a b ""#), pair("FOO", "a b "));
+        assert_eq!(parse_env_pair("FOO=''"), pair("FOO", ""));
+        assert_eq!(parse_env_pair(r"FOO='a\nb'"), pair("FOO", r"a\nb"));
+        assert_eq!(
+            parse_env_pair(r#"FOO="line1\nline2 \"quoted\"""#),
+            pair("FOO", "line1\nline2 \"quoted\"")
+        );
+    }
+
+    #[test]
+    fn parse_env_pair_unescapes_equals_in_key() {
+        assert_eq!(parse_env_pair(r"A\=B=c"), pair("A=B", "c"));
+    }
+
+    #[test]
+    fn parse_env_pair_rejects_malformed_entries() {
+        let form_error = Err("environment entries must be in KEY=VALUE form".to_string());
+        assert_eq!(parse_env_pair("FOO"), form_error);
+        assert_eq!(parse_env_pair(" =bar"), form_error);
+        assert_eq!(
+            parse_env_pair(r#"FOO="unterminated"#),
+            Err("invalid value for environment variable 'FOO': missing closing \"".to_string())
+        );
+    }
+
+    fn healthy(tools: &[&str]) -> ServerHealth {
+        ServerHealth::Healthy(McpServerProbe {
+            protocol_version: "2025-06-18".to_string(),
+            server_name: "test".to_string(),
+            server_version: "1.0.0".to_string(),
+            tools: tools.iter().map(ToString::to_string).collect(),
+            startup: Duration::ZERO,
+            latency: Duration::ZERO,
+        })
+    }
+
+    #[test]
+    fn servers_providing_tool_lists_every_healthy_provider() {
+        let health = HashMap::from([
+            ("wiki".to_string(), healthy(&["search", "edit"])),
+            ("docs".to_string(), healthy(&["search"])),
+            ("files".to_string(), healthy(&["read"])),
+            (
+                "broken".to_string(),
+                ServerHealth::Unreachable("boom".to_string()),
+            ),
+            ("off".to_string(), ServerHealth::Skipped),
+        ]);
+
+        assert_eq!(
+            servers_providing_tool(&health, "search"),
+            vec!["docs", "wiki"]
+        );
+        assert_eq!(servers_providing_tool(&health, "read"), vec!["files"]);
+        assert!(servers_providing_tool(&health, "missing").is_empty());
+    }
+
+    #[test]
+    fn status_cell_pads_outside_the_color_codes() {
+        assert_eq!(status_cell("enabled", 10, false), "enabled   ");
+        let colored = status_cell("disabled", 10, true);
+        assert!(colored.starts_with('\x1b'));
+        assert!(colored.ends_with("\x1b[0m  "));
+        assert!(colored.contains("disabled"));
+    }
+
+    #[test]
+    fn effective_settings_label_where_each_value_came_from() {
+        let server = McpServerConfig {
+            tool_timeout_sec: Some(Duration::from_secs(5)),
+            ..new_server_config(McpServerTransportConfig::Stdio {
+                command: "docs-server".to_string(),
+                args: Vec::new(),
+                env: None,
+                env_vars: Vec::new(),
+                cwd: None,
+                expand_env: false,
+                expand_env_strict: false,
+                shutdown_timeout_sec: None,
+                inherit_env: None,
+            })
+        };
+        let defaults = McpServerDefaults {
+            startup_timeout_sec: Some(Duration::from_secs(20)),
+            tool_timeout_sec: Some(Duration::from_secs(90)),
+            inherit_env: None,
+        };
+
+        let settings: Vec<_> = effective_settings(&server, &defaults)
+            .into_iter()
+            .map(|setting| (setting.field, setting.display, setting.source))
+            .collect();
+        assert_eq!(
+            settings,
+            vec![
+                ("enabled", "true".to_string(), EffectiveSource::Configured),
+                (
+                    "startup_timeout_sec",
+                    "20".to_string(),
+                    EffectiveSource::GlobalDefault("mcp_default_startup_timeout_sec"),
+                ),
+                (
+                    "tool_timeout_sec",
+                    "5".to_string(),
+                    EffectiveSource::Configured,
+                ),
+                (
+                    "startup_priority",
+                    "0".to_string(),
+                    EffectiveSource::BuiltIn
+                ),
+                (
+                    "inherit_env",
+                    "minimal".to_string(),
+                    EffectiveSource::BuiltIn,
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use std::path::Path;
+
+use anyhow::Result;
+use codex_core::config::load_global_mcp_servers;
+use codex_core::config_types::McpServerTransportConfig;
+use predicates::prelude::PredicateBooleanExt;
+use predicates::str::contains;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+
+fn codex_command(codex_home: &Path) -> Result<assert_cmd::Command> {
+    let mut cmd = assert_cmd::Command::cargo_bin("codex")?;
+    cmd.env("CODEX_HOME", codex_home);
+    Ok(cmd)
+}
+
+fn stdio_command(transport: &McpServerTransportConfig) -> (String, Vec<String>) {
+    match transport {
+        McpServerTransportConfig::Stdio { command, args, .. } => (command.clone(), args.clone()),
+        other => panic!("unexpected transport: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn add_many_adds_every_listed_server() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut cmd = codex_command(codex_home.path())?;
+    cmd.args(["mcp", "add-many", "-"])
+        .write_stdin(
+            "# team servers\ndocs=docs-server --root \"/srv/my docs\"\n\nsearch=search-server\n",
+        )
+        .assert()
+        .success()
+        .stdout(
+            contains("Added global MCP server 'docs'.")
+                .and(contains("Added global MCP server 'search'.")),
+        );
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    assert_eq!(servers.len(), 2);
+    assert_eq!(
+        stdio_command(&servers["docs"].transport),
+        (
+            "docs-server".to_string(),
+            vec!["--root".to_string(), "/srv/my docs".to_string()]
+        )
+    );
+    assert_eq!(
+        stdio_command(&servers["search"].transport),
+        ("search-server".to_string(), Vec::new())
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_many_handles_existing_servers() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let list = codex_home.path().join("servers.txt");
+    std::fs::write(&list, "docs=new-docs-server\nsearch=search-server\n")?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--",
+            "old-docs-server",
+        ])
+        .assert()
+        .success();
+
+    let mut cmd = codex_command(codex_home.path())?;
+    cmd.args(["mcp", "add-many"])
+        .arg(&list)
+        .assert()
+        .failure()
+        .stderr(
+            contains("docs: server 'docs' already exists")
+                .and(contains("1 server(s) conflict with existing entries")),
+        );
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    assert_eq!(
+        servers.len(),
+        1,
+        "a conflict should leave the config untouched"
+    );
+
+    let mut cmd = codex_command(codex_home.path())?;
+    cmd.args(["mcp", "add-many", "--skip-existing"])
+        .arg(&list)
+        .assert()
+        .success()
+        .stdout(
+            contains("Skipped 'docs': server 'docs' already exists.")
+                .and(contains("Added global MCP server 'search'.")),
+        );
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    assert_eq!(
+        stdio_command(&servers["docs"].transport).0,
+        "old-docs-server"
+    );
+    assert!(servers.contains_key("search"));
+
+    let mut cmd = codex_command(codex_home.path())?;
+    cmd.args(["mcp", "add-many", "--force"])
+        .arg(&list)
+        .assert()
+        .success()
+        .stdout(
+            contains("Replaced global MCP server 'docs'.")
+                .and(contains("Replaced global MCP server 'search'.")),
+        );
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    assert_eq!(
+        stdio_command(&servers["docs"].transport).0,
+        "new-docs-server"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_many_rejects_malformed_lists_without_writing() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut cmd = codex_command(codex_home.path())?;
+    cmd.args(["mcp", "add-many", "-"])
+        .write_stdin("docs=docs-server\nbroken line\n")
+        .assert()
+        .failure()
+        .stderr(
+            contains("line 2: expected NAME=COMMAND")
+                .and(contains("1 problem(s) found in -; nothing was added")),
+        );
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    assert!(servers.is_empty());
+    Ok(())
+}
@@ -1,8 +1,13 @@
+use std::collections::HashMap;
 use std::path::Path;
+use std::time::Duration;
 
 use anyhow::Result;
 use codex_core::config::load_global_mcp_servers;
+use codex_core::config_types::McpOAuthClientCredentials;
+use codex_core::config_types::McpServerConfig;
 use codex_core::config_types::McpServerTransportConfig;
+use predicates::prelude::PredicateBooleanExt;
 use predicates::str::contains;
 use pretty_assertions::assert_eq;
 use tempfile::TempDir;
@@ -19,7 +24,15 @@ async fn add_and_remove_server_updates_global_config() -> Result<()> {
 
     let mut add_cmd = codex_command(codex_home.path())?;
     add_cmd
-        .args(["mcp", "add", "docs", "--", "echo", "hello"])
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--",
+            "echo",
+            "hello",
+        ])
         .assert()
         .success()
         .stdout(contains("Added global MCP server 'docs'."));
@@ -34,12 +47,20 @@ async fn add_and_remove_server_updates_global_config() -> Result<()> {
             env,
             env_vars,
             cwd,
+            expand_env,
+            expand_env_strict,
+            shutdown_timeout_sec,
+            inherit_env,
         } => {
             assert_eq!(command, "echo");
             assert_eq!(args, &vec!["hello".to_string()]);
             assert!(env.is_none());
             assert!(env_vars.is_empty());
             assert!(cwd.is_none());
+            assert!(!expand_env);
+            assert!(!expand_env_strict);
+            assert!(shutdown_timeout_sec.is_none());
+            assert!(inherit_env.is_none());
         }
         other => panic!("unexpected transport: {other:?}"),
     }
@@ -68,6 +89,474 @@ async fn add_and_remove_server_updates_global_config() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn remove_glob_requires_confirmation_and_prefers_exact_name() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    for name in ["team-a-docs", "team-a-search", "team-b-docs"] {
+        let mut add_cmd = codex_command(codex_home.path())?;
+        add_cmd
+            .args([
+                "mcp",
+                "add",
+                name,
+                "--no-enable-check",
+                "--",
+                "echo",
+                "hello",
+            ])
+            .assert()
+            .success();
+    }
+
+    let mut unconfirmed_cmd = codex_command(codex_home.path())?;
+    unconfirmed_cmd
+        .args(["mcp", "remove", "--glob", "team-a-*"])
+        .assert()
+        .failure()
+        .stdout(contains(
+            "Matched 2 MCP server(s): team-a-docs, team-a-search",
+        ))
+        .stderr(contains("pass --yes"));
+    assert_eq!(load_global_mcp_servers(codex_home.path()).await?.len(), 3);
+
+    let mut exact_cmd = codex_command(codex_home.path())?;
+    exact_cmd
+        .args(["mcp", "remove", "team-b-docs", "--glob", "*-docs", "--yes"])
+        .assert()
+        .success()
+        .stdout(contains("Removed global MCP server 'team-b-docs'."));
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    assert_eq!(
+        servers.keys().collect::<Vec<_>>(),
+        vec!["team-a-docs", "team-a-search"]
+    );
+
+    let mut glob_cmd = codex_command(codex_home.path())?;
+    glob_cmd
+        .args(["mcp", "remove", "--glob", "team-a-*", "--yes"])
+        .assert()
+        .success()
+        .stdout(
+            contains("Removed global MCP server 'team-a-docs'.")
+                .and(contains("Removed global MCP server 'team-a-search'.")),
+        );
+    assert!(load_global_mcp_servers(codex_home.path()).await?.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn remove_prune_disabled_removes_only_disabled_servers() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    for (name, disabled) in [("docs", false), ("old-docs", true), ("old-search", true)] {
+        let mut add_cmd = codex_command(codex_home.path())?;
+        add_cmd.args(["mcp", "add", name, "--no-enable-check"]);
+        if disabled {
+            add_cmd.arg("--disabled");
+        }
+        add_cmd.args(["--", "echo"]).assert().success();
+    }
+
+    let mut unconfirmed_cmd = codex_command(codex_home.path())?;
+    unconfirmed_cmd
+        .args(["mcp", "remove", "--prune-disabled"])
+        .assert()
+        .failure()
+        .stdout(contains("Matched 2 MCP server(s): old-docs, old-search"))
+        .stderr(contains("pass --yes"));
+    assert_eq!(load_global_mcp_servers(codex_home.path()).await?.len(), 3);
+
+    let mut prune_cmd = codex_command(codex_home.path())?;
+    prune_cmd
+        .args(["mcp", "remove", "--prune-disabled", "--yes"])
+        .assert()
+        .success()
+        .stdout(contains("Removed 2 disabled MCP server(s)."));
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    assert_eq!(servers.keys().collect::<Vec<_>>(), vec!["docs"]);
+
+    let mut nothing_cmd = codex_command(codex_home.path())?;
+    nothing_cmd
+        .args(["mcp", "remove", "--prune-disabled"])
+        .assert()
+        .success()
+        .stdout(contains("No disabled MCP servers to remove."));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_with_protocol_version_pins_handshake_version() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "legacy",
+            "--no-enable-check",
+            "--protocol-version",
+            "2024-11-05",
+            "--",
+            "legacy-server",
+        ])
+        .assert()
+        .success();
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    assert_eq!(
+        servers["legacy"].protocol_version.as_deref(),
+        Some("2024-11-05")
+    );
+
+    let mut get_cmd = codex_command(codex_home.path())?;
+    get_cmd
+        .args(["mcp", "get", "legacy"])
+        .assert()
+        .success()
+        .stdout(contains("protocol_version: 2024-11-05"));
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "future",
+            "--no-enable-check",
+            "--protocol-version",
+            "2099-01-01",
+            "--",
+            "future-server",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("2099-01-01"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_with_shutdown_timeout_persists_grace_period() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "indexer",
+            "--no-enable-check",
+            "--shutdown-timeout",
+            "5",
+            "--",
+            "indexer-server",
+        ])
+        .assert()
+        .success();
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    match &servers["indexer"].transport {
+        McpServerTransportConfig::Stdio {
+            shutdown_timeout_sec,
+            ..
+        } => assert_eq!(*shutdown_timeout_sec, Some(Duration::from_secs(5))),
+        other => panic!("unexpected transport: {other:?}"),
+    }
+
+    let mut get_cmd = codex_command(codex_home.path())?;
+    get_cmd
+        .args(["mcp", "get", "indexer"])
+        .assert()
+        .success()
+        .stdout(contains("shutdown_timeout_sec: 5"));
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "remote",
+            "--no-enable-check",
+            "--url",
+            "https://example.com/mcp",
+            "--shutdown-timeout",
+            "5",
+        ])
+        .assert()
+        .failure();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_with_command_line_splits_shell_quoted_string() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--shutdown-timeout",
+            "2",
+            "--command-line",
+            r#"docs-server --root "/srv/my docs" 'it''s'"#,
+        ])
+        .assert()
+        .success();
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    match &servers["docs"].transport {
+        McpServerTransportConfig::Stdio {
+            command,
+            args,
+            shutdown_timeout_sec,
+            ..
+        } => {
+            assert_eq!(command, "docs-server");
+            assert_eq!(
+                args,
+                &vec![
+                    "--root".to_string(),
+                    "/srv/my docs".to_string(),
+                    "its".to_string()
+                ]
+            );
+            assert_eq!(*shutdown_timeout_sec, Some(Duration::from_secs(2)));
+        }
+        other => panic!("unexpected transport: {other:?}"),
+    }
+
+    let mut unbalanced_cmd = codex_command(codex_home.path())?;
+    unbalanced_cmd
+        .args([
+            "mcp",
+            "add",
+            "broken",
+            "--no-enable-check",
+            "--command-line",
+            "server 'oops",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("unbalanced quotes"));
+
+    let mut empty_cmd = codex_command(codex_home.path())?;
+    empty_cmd
+        .args([
+            "mcp",
+            "add",
+            "empty",
+            "--no-enable-check",
+            "--command-line",
+            "   ",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("--command-line must name a command"));
+
+    let mut both_cmd = codex_command(codex_home.path())?;
+    both_cmd
+        .args([
+            "mcp",
+            "add",
+            "both",
+            "--no-enable-check",
+            "--command-line",
+            "docs-server",
+            "--",
+            "docs-server",
+        ])
+        .assert()
+        .failure();
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    assert_eq!(servers.keys().collect::<Vec<_>>(), vec!["docs"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_with_npx_and_uvx_expands_launcher() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut npx_cmd = codex_command(codex_home.path())?;
+    npx_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--npx",
+            "@scope/mcp-server",
+            "--port",
+            "4000",
+        ])
+        .assert()
+        .success();
+    let mut uvx_cmd = codex_command(codex_home.path())?;
+    uvx_cmd
+        .args([
+            "mcp",
+            "add",
+            "git",
+            "--no-enable-check",
+            "--uvx",
+            "mcp-server-git",
+        ])
+        .assert()
+        .success();
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    let launch = |name: &str| match &servers[name].transport {
+        McpServerTransportConfig::Stdio { command, args, .. } => (command.clone(), args.clone()),
+        other => panic!("unexpected transport: {other:?}"),
+    };
+    assert_eq!(
+        launch("docs"),
+        (
+            "npx".to_string(),
+            vec![
+                "-y".to_string(),
+                "@scope/mcp-server".to_string(),
+                "--port".to_string(),
+                "4000".to_string(),
+            ]
+        )
+    );
+    assert_eq!(
+        launch("git"),
+        ("uvx".to_string(), vec!["mcp-server-git".to_string()])
+    );
+
+    let mut empty_cmd = codex_command(codex_home.path())?;
+    empty_cmd
+        .args(["mcp", "add", "empty", "--no-enable-check", "--npx", ""])
+        .assert()
+        .failure()
+        .stderr(contains("--npx needs a non-empty package spec"));
+
+    let mut both_cmd = codex_command(codex_home.path())?;
+    both_cmd
+        .args([
+            "mcp",
+            "add",
+            "both",
+            "--no-enable-check",
+            "--npx",
+            "--uvx",
+            "pkg",
+        ])
+        .assert()
+        .failure();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_reports_connection_check_without_failing() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let missing = codex_home.path().join("no-such-server");
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args(["mcp", "add", "broken", "--"])
+        .arg(&missing)
+        .assert()
+        .success()
+        .stdout(contains("Added global MCP server 'broken'."))
+        .stderr(contains("warning: could not connect:"));
+    assert!(
+        load_global_mcp_servers(codex_home.path())
+            .await?
+            .contains_key("broken")
+    );
+
+    let mut unchecked_cmd = codex_command(codex_home.path())?;
+    unchecked_cmd
+        .args(["mcp", "add", "unchecked", "--no-enable-check", "--"])
+        .arg(&missing)
+        .assert()
+        .success()
+        .stderr(contains("could not connect").not());
+
+    let mut quiet_cmd = codex_command(codex_home.path())?;
+    quiet_cmd
+        .args(["mcp", "add", "hushed", "--quiet", "--"])
+        .arg(&missing)
+        .assert()
+        .success()
+        .stdout("")
+        .stderr(contains("warning: could not connect:"));
+
+    let mut disabled_cmd = codex_command(codex_home.path())?;
+    disabled_cmd
+        .args(["mcp", "add", "off", "--disabled", "--"])
+        .arg(&missing)
+        .assert()
+        .success()
+        .stderr(contains("could not connect").not());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn quiet_add_and_remove_print_nothing_on_success() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--quiet",
+            "--",
+            "echo",
+            "hello",
+        ])
+        .assert()
+        .success()
+        .stdout("");
+    assert!(
+        load_global_mcp_servers(codex_home.path())
+            .await?
+            .contains_key("docs")
+    );
+
+    let mut duplicate_cmd = codex_command(codex_home.path())?;
+    duplicate_cmd
+        .args([
+            "mcp",
+            "add",
+            "DOCS",
+            "--no-enable-check",
+            "--quiet",
+            "--",
+            "echo",
+            "hello",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("docs"));
+
+    let mut remove_cmd = codex_command(codex_home.path())?;
+    remove_cmd
+        .args(["mcp", "remove", "docs", "-q"])
+        .assert()
+        .success()
+        .stdout("");
+    assert!(load_global_mcp_servers(codex_home.path()).await?.is_empty());
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn add_with_env_preserves_key_order_and_values() -> Result<()> {
     let codex_home = TempDir::new()?;
@@ -78,6 +567,7 @@ async fn add_with_env_preserves_key_order_and_values() -> Result<()> {
             "mcp",
             "add",
             "envy",
+            "--no-enable-check",
             "--env",
             "FOO=bar",
             "--env",
@@ -110,7 +600,14 @@ async fn add_streamable_http_without_manual_token() -> Result<()> {
 
     let mut add_cmd = codex_command(codex_home.path())?;
     add_cmd
-        .args(["mcp", "add", "github", "--url", "https://example.com/mcp"])
+        .args([
+            "mcp",
+            "add",
+            "github",
+            "--no-enable-check",
+            "--url",
+            "https://example.com/mcp",
+        ])
         .assert()
         .success();
 
@@ -122,6 +619,8 @@ async fn add_streamable_http_without_manual_token() -> Result<()> {
             bearer_token_env_var,
             http_headers,
             env_http_headers,
+            oauth_client_credentials: None,
+            ..
         } => {
             assert_eq!(url, "https://example.com/mcp");
             assert!(bearer_token_env_var.is_none());
@@ -148,6 +647,7 @@ async fn add_streamable_http_with_custom_env_var() -> Result<()> {
             "mcp",
             "add",
             "issues",
+            "--no-enable-check",
             "--url",
             "https://example.com/issues",
             "--bearer-token-env-var",
@@ -164,6 +664,8 @@ async fn add_streamable_http_with_custom_env_var() -> Result<()> {
             bearer_token_env_var,
             http_headers,
             env_http_headers,
+            oauth_client_credentials: None,
+            ..
         } => {
             assert_eq!(url, "https://example.com/issues");
             assert_eq!(bearer_token_env_var.as_deref(), Some("GITHUB_TOKEN"));
@@ -186,6 +688,7 @@ async fn add_streamable_http_rejects_removed_flag() -> Result<()> {
             "mcp",
             "add",
             "github",
+            "--no-enable-check",
             "--url",
             "https://example.com/mcp",
             "--with-bearer-token",
@@ -210,6 +713,7 @@ async fn add_cant_add_command_and_url() -> Result<()> {
             "mcp",
             "add",
             "github",
+            "--no-enable-check",
             "--url",
             "https://example.com/mcp",
             "--command",
@@ -226,3 +730,1557 @@ async fn add_cant_add_command_and_url() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn description_is_shown_and_round_trips_through_json() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--description",
+            "Search the internal handbook",
+            "--",
+            "docs-server",
+        ])
+        .assert()
+        .success();
+
+    let mut get_cmd = codex_command(codex_home.path())?;
+    get_cmd
+        .args(["mcp", "get", "docs"])
+        .assert()
+        .success()
+        .stdout(contains("description: Search the internal handbook"));
+
+    let mut list_cmd = codex_command(codex_home.path())?;
+    list_cmd
+        .args(["mcp", "list"])
+        .assert()
+        .success()
+        .stdout(contains("Description").and(contains("Search the internal handbook")));
+
+    let mut get_json_cmd = codex_command(codex_home.path())?;
+    let output = get_json_cmd
+        .args(["mcp", "get", "docs", "--json"])
+        .output()?;
+    assert!(output.status.success());
+    let mut import_cmd = codex_command(codex_home.path())?;
+    import_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs-copy",
+            "--no-enable-check",
+            "--from-json",
+            "-",
+        ])
+        .write_stdin(output.stdout)
+        .assert()
+        .success();
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    assert_eq!(
+        servers["docs-copy"].description.as_deref(),
+        Some("Search the internal handbook")
+    );
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "blank",
+            "--no-enable-check",
+            "--description",
+            " ",
+            "--",
+            "server",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("--description must not be empty"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_from_json_file_imports_full_definition() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let spec_path = codex_home.path().join("server.json");
+    std::fs::write(
+        &spec_path,
+        r#"{
+            "name": "ignored",
+            "enabled": false,
+            "transport": {
+                "type": "streamable_http",
+                "url": "https://example.com/mcp",
+                "bearer_token_env_var": "EXAMPLE_TOKEN"
+            },
+            "startup_timeout_sec": 2.5,
+            "tool_timeout_sec": null,
+            "last_connected": "2025-01-01T00:00:00+00:00",
+            "effective": {
+                "enabled": {"value": false, "source": "configured"}
+            },
+            "diagnostics": {"error": "connection refused"}
+        }"#,
+    )?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args(["mcp", "add", "github", "--no-enable-check", "--from-json"])
+        .arg(&spec_path)
+        .assert()
+        .success()
+        .stdout(contains("Added global MCP server 'github'."));
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    assert_eq!(
+        servers.get("github"),
+        Some(&McpServerConfig {
+            transport: McpServerTransportConfig::StreamableHttp {
+                url: "https://example.com/mcp".to_string(),
+                url_env_var: None,
+                bearer_token_env_var: Some("EXAMPLE_TOKEN".to_string()),
+                bearer_token_file: None,
+                http_headers: None,
+                env_http_headers: None,
+                env_http_header_defaults: None,
+                oauth_client_credentials: None,
+                keep_alive: true,
+                max_idle_connections: None,
+                connect_timeout_sec: None,
+                max_requests_per_sec: None,
+                client_cert_path: None,
+                client_key_path: None,
+                ca_cert_path: None,
+                health_check_interval_sec: None,
+            },
+            enabled: false,
+            startup_timeout_sec: Some(Duration::from_millis(2500)),
+            tool_timeout_sec: None,
+            allowed_tools: None,
+            denied_tools: None,
+            protocol_version: None,
+            enabled_when: None,
+            description: None,
+            startup_priority: None,
+            client_name: None,
+            client_version: None,
+            tool_timeouts: None,
+            tags: Vec::new(),
+        })
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_from_json_stdin_rejects_unknown_fields() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--from-json",
+            "-",
+        ])
+        .write_stdin(r#"{"transport": {"type": "stdio", "command": "echo", "shell": true}}"#)
+        .assert()
+        .failure()
+        .stderr(contains("unknown field `shell`"));
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    assert!(servers.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_from_json_rejects_tool_timeouts_too_large_for_a_duration() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--from-json",
+            "-",
+        ])
+        .write_stdin(
+            r#"{"transport": {"type": "stdio", "command": "echo"}, "tool_timeouts": {"search": 1e30}}"#,
+        )
+        .assert()
+        .failure()
+        .stderr(contains("invalid tool_timeouts.search"));
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    assert!(servers.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_with_tool_filters_persists_lists() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--allow-tool",
+            "search",
+            "--allow-tool",
+            "fetch",
+            "--deny-tool",
+            "delete",
+            "--",
+            "docs-server",
+        ])
+        .assert()
+        .success();
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    let docs = servers.get("docs").expect("server should exist");
+    assert_eq!(
+        (docs.allowed_tools.clone(), docs.denied_tools.clone()),
+        (
+            Some(vec!["search".to_string(), "fetch".to_string()]),
+            Some(vec!["delete".to_string()])
+        )
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_disabled_server_persists_enabled_false() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "--disabled",
+            "--no-enable-check",
+            "docs",
+            "--",
+            "docs-server",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("Added global MCP server 'docs' (disabled)."));
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    let docs = servers.get("docs").expect("server should exist");
+    assert!(!docs.enabled);
+
+    let mut list_cmd = codex_command(codex_home.path())?;
+    list_cmd
+        .args(["mcp", "list"])
+        .assert()
+        .success()
+        .stdout(contains("disabled"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_rejects_tool_both_allowed_and_denied() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--allow-tool",
+            "search",
+            "--deny-tool",
+            "search",
+            "--",
+            "docs-server",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("tool 'search' cannot be both allowed and denied"));
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    assert!(servers.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_rejects_name_differing_only_by_case() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "Docs",
+            "--no-enable-check",
+            "--",
+            "docs-server",
+        ])
+        .assert()
+        .success();
+
+    let mut collide_cmd = codex_command(codex_home.path())?;
+    collide_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--",
+            "other-server",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "server name 'docs' differs from existing server 'Docs' only by case",
+        ));
+
+    let mut force_cmd = codex_command(codex_home.path())?;
+    force_cmd
+        .args([
+            "mcp",
+            "add",
+            "--force",
+            "--no-enable-check",
+            "docs",
+            "--",
+            "other-server",
+        ])
+        .assert()
+        .success()
+        .stderr(contains("warning: server name 'docs'"));
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    assert_eq!(
+        servers.keys().cloned().collect::<Vec<_>>(),
+        vec!["Docs".to_string(), "docs".to_string()]
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_warns_when_command_duplicates_existing_server() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--",
+            "docs-server",
+            "--port",
+            "1",
+        ])
+        .assert()
+        .success();
+
+    let mut duplicate_cmd = codex_command(codex_home.path())?;
+    duplicate_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs-copy",
+            "--no-enable-check",
+            "--",
+            "docs-server",
+            "--port",
+            "1",
+        ])
+        .assert()
+        .success()
+        .stderr(contains(
+            "warning: server 'docs-copy' launches the same command as existing server 'docs'",
+        ));
+
+    let mut different_args_cmd = codex_command(codex_home.path())?;
+    different_args_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs-two",
+            "--no-enable-check",
+            "--",
+            "docs-server",
+            "--port",
+            "2",
+        ])
+        .assert()
+        .success()
+        .stderr(contains("warning").not());
+
+    let mut allowed_cmd = codex_command(codex_home.path())?;
+    allowed_cmd
+        .args([
+            "mcp",
+            "add",
+            "--allow-duplicate-command",
+            "--no-enable-check",
+            "docs-third",
+            "--",
+            "docs-server",
+            "--port",
+            "1",
+        ])
+        .assert()
+        .success()
+        .stderr(contains("warning").not());
+
+    // Re-adding a server under its own name is an overwrite, not a duplicate.
+    let mut overwrite_cmd = codex_command(codex_home.path())?;
+    overwrite_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs-two",
+            "--no-enable-check",
+            "--",
+            "docs-server",
+            "--port",
+            "2",
+        ])
+        .assert()
+        .success()
+        .stderr(contains("warning").not());
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    assert_eq!(servers.len(), 4);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_rejects_reserved_name_unless_allowed() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut reserved_cmd = codex_command(codex_home.path())?;
+    reserved_cmd
+        .args(["mcp", "add", "codex", "--no-enable-check", "--", "echo"])
+        .assert()
+        .failure()
+        .stderr(contains("server name 'codex' is reserved").and(contains("--allow-reserved")));
+    assert!(load_global_mcp_servers(codex_home.path()).await?.is_empty());
+
+    let mut allowed_cmd = codex_command(codex_home.path())?;
+    allowed_cmd
+        .args([
+            "mcp",
+            "add",
+            "_scratch",
+            "--no-enable-check",
+            "--allow-reserved",
+            "--",
+            "echo",
+        ])
+        .assert()
+        .success();
+    assert!(
+        load_global_mcp_servers(codex_home.path())
+            .await?
+            .contains_key("_scratch")
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_reports_read_only_config_with_hint() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let config_path = codex_home.path().join("config.toml");
+    std::fs::write(&config_path, "")?;
+    let mut permissions = std::fs::metadata(&config_path)?.permissions();
+    permissions.set_readonly(true);
+    std::fs::set_permissions(&config_path, permissions)?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--",
+            "echo",
+            "hello",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("is not writable").and(contains("chmod u+w")));
+
+    assert_eq!(std::fs::read_to_string(&config_path)?, "");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_creates_missing_codex_home() -> Result<()> {
+    let parent = TempDir::new()?;
+    let codex_home = parent.path().join("fresh");
+
+    let mut add_cmd = codex_command(&codex_home)?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--",
+            "echo",
+            "hello",
+        ])
+        .assert()
+        .success();
+
+    let servers = load_global_mcp_servers(&codex_home).await?;
+    assert!(servers.contains_key("docs"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_streamable_http_with_oauth_client_credentials() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "github",
+            "--no-enable-check",
+            "--url",
+            "https://example.com/mcp",
+            "--oauth-token-url",
+            "https://auth.example.com/oauth/token",
+            "--oauth-client-id",
+            "codex",
+            "--oauth-client-secret-env-var",
+            "GITHUB_CLIENT_SECRET",
+            "--oauth-scope",
+            "tools.read",
+        ])
+        .env("GITHUB_CLIENT_SECRET", "super-secret-value")
+        .assert()
+        .success();
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    let github = servers.get("github").expect("github server should exist");
+    assert_eq!(
+        github.transport,
+        McpServerTransportConfig::StreamableHttp {
+            url: "https://example.com/mcp".to_string(),
+            url_env_var: None,
+            bearer_token_env_var: None,
+            bearer_token_file: None,
+            http_headers: None,
+            env_http_headers: None,
+            env_http_header_defaults: None,
+            oauth_client_credentials: Some(McpOAuthClientCredentials {
+                token_url: "https://auth.example.com/oauth/token".to_string(),
+                client_id: "codex".to_string(),
+                client_secret_env_var: "GITHUB_CLIENT_SECRET".to_string(),
+                scopes: vec!["tools.read".to_string()],
+            }),
+            keep_alive: true,
+            max_idle_connections: None,
+            connect_timeout_sec: None,
+            max_requests_per_sec: None,
+            client_cert_path: None,
+            client_key_path: None,
+            ca_cert_path: None,
+            health_check_interval_sec: None,
+        }
+    );
+
+    let mut get_cmd = codex_command(codex_home.path())?;
+    get_cmd
+        .args(["mcp", "get", "github", "--json"])
+        .env("GITHUB_CLIENT_SECRET", "super-secret-value")
+        .assert()
+        .success()
+        .stdout(
+            contains("\"client_secret_env_var\": \"GITHUB_CLIENT_SECRET\"")
+                .and(contains("super-secret-value").not()),
+        );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_streamable_http_with_connection_pool_settings() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--url",
+            "https://example.com/mcp",
+            "--max-idle-connections",
+            "8",
+        ])
+        .assert()
+        .success();
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "search",
+            "--no-enable-check",
+            "--url",
+            "https://example.com/search",
+            "--no-keep-alive",
+        ])
+        .assert()
+        .success();
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    assert!(matches!(
+        servers["docs"].transport,
+        McpServerTransportConfig::StreamableHttp {
+            keep_alive: true,
+            max_idle_connections: Some(8),
+            ..
+        }
+    ));
+    assert!(matches!(
+        servers["search"].transport,
+        McpServerTransportConfig::StreamableHttp {
+            keep_alive: false,
+            max_idle_connections: None,
+            ..
+        }
+    ));
+
+    let mut get_cmd = codex_command(codex_home.path())?;
+    get_cmd
+        .args(["mcp", "get", "search"])
+        .assert()
+        .success()
+        .stdout(contains("keep_alive: off").and(contains("max_idle_connections: -")));
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "other",
+            "--no-enable-check",
+            "--url",
+            "https://example.com/other",
+            "--no-keep-alive",
+            "--max-idle-connections",
+            "2",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("--max-idle-connections"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_streamable_http_with_rate_limit() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--url",
+            "https://example.com/mcp",
+            "--rate-limit",
+            "2.5",
+        ])
+        .assert()
+        .success();
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    match &servers["docs"].transport {
+        McpServerTransportConfig::StreamableHttp {
+            max_requests_per_sec,
+            ..
+        } => assert_eq!(*max_requests_per_sec, Some(2.5)),
+        other => panic!("unexpected transport: {other:?}"),
+    }
+
+    let mut get_cmd = codex_command(codex_home.path())?;
+    get_cmd
+        .args(["mcp", "get", "docs"])
+        .assert()
+        .success()
+        .stdout(contains("max_requests_per_sec: 2.5"));
+
+    let mut list_cmd = codex_command(codex_home.path())?;
+    let output = list_cmd.args(["mcp", "list", "--json"]).output()?;
+    assert!(output.status.success());
+    let listed: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(
+        listed[0]["transport"]["max_requests_per_sec"],
+        serde_json::json!(2.5)
+    );
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "zero",
+            "--no-enable-check",
+            "--url",
+            "https://example.com/mcp",
+            "--rate-limit",
+            "0",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("max_requests_per_sec must be a positive number"));
+
+    let mut tiny_cmd = codex_command(codex_home.path())?;
+    tiny_cmd
+        .args([
+            "mcp",
+            "add",
+            "tiny",
+            "--no-enable-check",
+            "--url",
+            "https://example.com/mcp",
+            "--rate-limit",
+            "0.0001",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "max_requests_per_sec must be at least one request per hour",
+        ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_streamable_http_with_client_certificate() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let certs = TempDir::new()?;
+    let cert_path = certs.path().join("client.pem");
+    let key_path = certs.path().join("client.key");
+    std::fs::write(&cert_path, "-----BEGIN CERTIFICATE-----\n")?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "gateway",
+            "--no-enable-check",
+            "--url",
+            "https://example.com/mcp",
+        ])
+        .arg("--client-cert")
+        .arg(&cert_path)
+        .arg("--client-key")
+        .arg(&key_path)
+        .assert()
+        .success()
+        .stderr(
+            contains(format!(
+                "warning: --client-key file {} does not exist",
+                key_path.display()
+            ))
+            .and(contains("--client-cert file").not()),
+        );
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    match &servers["gateway"].transport {
+        McpServerTransportConfig::StreamableHttp {
+            client_cert_path,
+            client_key_path,
+            ca_cert_path,
+            ..
+        } => {
+            assert_eq!(client_cert_path.as_deref(), Some(cert_path.as_path()));
+            assert_eq!(client_key_path.as_deref(), Some(key_path.as_path()));
+            assert_eq!(*ca_cert_path, None);
+        }
+        other => panic!("unexpected transport: {other:?}"),
+    }
+
+    let mut get_cmd = codex_command(codex_home.path())?;
+    get_cmd
+        .args(["mcp", "get", "gateway"])
+        .assert()
+        .success()
+        .stdout(contains(format!(
+            "client_cert_path: {}",
+            cert_path.display()
+        )));
+
+    let mut list_cmd = codex_command(codex_home.path())?;
+    let output = list_cmd.args(["mcp", "list", "--json"]).output()?;
+    assert!(output.status.success());
+    let listed: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(
+        listed[0]["transport"]["client_key_path"],
+        serde_json::json!(key_path)
+    );
+
+    let mut missing_key_cmd = codex_command(codex_home.path())?;
+    missing_key_cmd
+        .args([
+            "mcp",
+            "add",
+            "half",
+            "--no-enable-check",
+            "--url",
+            "https://example.com/mcp",
+        ])
+        .arg("--client-cert")
+        .arg(&cert_path)
+        .assert()
+        .failure()
+        .stderr(contains("--client-key"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_streamable_http_with_bearer_token_file() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let secrets = TempDir::new()?;
+    let token_path = secrets.path().join("token");
+    std::fs::write(&token_path, "super-secret-token\n")?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "vault",
+            "--url",
+            "https://example.com/mcp",
+            "--no-enable-check",
+        ])
+        .arg("--bearer-token-file")
+        .arg(&token_path)
+        .assert()
+        .success()
+        .stderr(contains("does not exist").not());
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    match &servers["vault"].transport {
+        McpServerTransportConfig::StreamableHttp {
+            bearer_token_env_var,
+            bearer_token_file,
+            ..
+        } => {
+            assert_eq!(*bearer_token_env_var, None);
+            assert_eq!(bearer_token_file.as_deref(), Some(token_path.as_path()));
+        }
+        other => panic!("unexpected transport: {other:?}"),
+    }
+
+    let mut get_cmd = codex_command(codex_home.path())?;
+    get_cmd
+        .args(["mcp", "get", "vault"])
+        .assert()
+        .success()
+        .stdout(
+            contains(format!("bearer_token_file: {}", token_path.display()))
+                .and(contains("super-secret-token").not()),
+        );
+
+    let mut list_cmd = codex_command(codex_home.path())?;
+    let output = list_cmd.args(["mcp", "list", "--json"]).output()?;
+    assert!(output.status.success());
+    assert!(!String::from_utf8(output.stdout.clone())?.contains("super-secret-token"));
+    let listed: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(
+        listed[0]["transport"]["bearer_token_file"],
+        serde_json::json!(token_path)
+    );
+
+    let missing_path = secrets.path().join("missing");
+    let mut missing_cmd = codex_command(codex_home.path())?;
+    missing_cmd
+        .args([
+            "mcp",
+            "add",
+            "later",
+            "--no-enable-check",
+            "--url",
+            "https://example.com/mcp",
+        ])
+        .arg("--bearer-token-file")
+        .arg(&missing_path)
+        .assert()
+        .success()
+        .stderr(contains(format!(
+            "warning: --bearer-token-file file {} does not exist",
+            missing_path.display()
+        )));
+
+    let mut both_cmd = codex_command(codex_home.path())?;
+    both_cmd
+        .args([
+            "mcp",
+            "add",
+            "both",
+            "--no-enable-check",
+            "--url",
+            "https://example.com/mcp",
+        ])
+        .args(["--bearer-token-env-var", "TOKEN", "--bearer-token-file"])
+        .arg(&token_path)
+        .assert()
+        .failure()
+        .stderr(contains("--bearer-token-file"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_streamable_http_with_connect_timeout() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--url",
+            "https://example.com/mcp",
+            "--connect-timeout",
+            "1.5",
+        ])
+        .assert()
+        .success();
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    match &servers["docs"].transport {
+        McpServerTransportConfig::StreamableHttp {
+            connect_timeout_sec,
+            ..
+        } => assert_eq!(*connect_timeout_sec, Some(Duration::from_millis(1500))),
+        other => panic!("unexpected transport: {other:?}"),
+    }
+    assert_eq!(servers["docs"].startup_timeout_sec, None);
+
+    let mut get_cmd = codex_command(codex_home.path())?;
+    get_cmd
+        .args(["mcp", "get", "docs"])
+        .assert()
+        .success()
+        .stdout(contains("connect_timeout_sec: 1.5"));
+
+    let mut list_cmd = codex_command(codex_home.path())?;
+    let output = list_cmd.args(["mcp", "list", "--json"]).output()?;
+    assert!(output.status.success());
+    let listed: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(
+        listed[0]["transport"]["connect_timeout_sec"],
+        serde_json::json!(1.5)
+    );
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "zero",
+            "--no-enable-check",
+            "--url",
+            "https://example.com/mcp",
+            "--connect-timeout",
+            "0",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("connect_timeout_sec must be positive"));
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "stdio",
+            "--no-enable-check",
+            "--connect-timeout",
+            "3",
+            "--",
+            "server",
+        ])
+        .assert()
+        .failure();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_streamable_http_with_health_check_interval() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--url",
+            "https://example.com/mcp",
+            "--health-check-interval",
+            "30",
+        ])
+        .assert()
+        .success();
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    match &servers["docs"].transport {
+        McpServerTransportConfig::StreamableHttp {
+            health_check_interval_sec,
+            ..
+        } => assert_eq!(*health_check_interval_sec, Some(Duration::from_secs(30))),
+        other => panic!("unexpected transport: {other:?}"),
+    }
+
+    let mut get_cmd = codex_command(codex_home.path())?;
+    get_cmd
+        .args(["mcp", "get", "docs"])
+        .assert()
+        .success()
+        .stdout(contains("health_check_interval_sec: 30"));
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "zero",
+            "--no-enable-check",
+            "--url",
+            "https://example.com/mcp",
+            "--health-check-interval",
+            "0",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("health_check_interval_sec must be positive"));
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "stdio",
+            "--no-enable-check",
+            "--health-check-interval",
+            "30",
+            "--",
+            "server",
+        ])
+        .assert()
+        .failure();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_streamable_http_with_env_header_defaults() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--url",
+            "https://example.com/mcp",
+            "--env-header",
+            "X-Tenant=DOCS_TENANT",
+            "--env-header-default",
+            "X-Tenant=public",
+        ])
+        .assert()
+        .success();
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    match &servers["docs"].transport {
+        McpServerTransportConfig::StreamableHttp {
+            env_http_headers,
+            env_http_header_defaults,
+            ..
+        } => {
+            assert_eq!(
+                env_http_headers,
+                &Some(HashMap::from([(
+                    "X-Tenant".to_string(),
+                    "DOCS_TENANT".to_string()
+                )]))
+            );
+            assert_eq!(
+                env_http_header_defaults,
+                &Some(HashMap::from([(
+                    "X-Tenant".to_string(),
+                    "public".to_string()
+                )]))
+            );
+        }
+        other => panic!("unexpected transport: {other:?}"),
+    }
+
+    let mut get_cmd = codex_command(codex_home.path())?;
+    get_cmd
+        .args(["mcp", "get", "docs"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "env_http_headers: X-Tenant=DOCS_TENANT (default: public)",
+        ));
+
+    let mut list_cmd = codex_command(codex_home.path())?;
+    let output = list_cmd.args(["mcp", "list", "--json"]).output()?;
+    assert!(output.status.success());
+    let listed: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(
+        listed[0]["transport"]["env_http_header_defaults"],
+        serde_json::json!({ "X-Tenant": "public" })
+    );
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "orphan",
+            "--no-enable-check",
+            "--url",
+            "https://example.com/mcp",
+            "--env-header",
+            "X-Tenant=DOCS_TENANT",
+            "--env-header-default",
+            "X-Region=us",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "--env-header-default sets 'X-Region', which has no matching --env-header",
+        ));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_streamable_http_with_url_env_var() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--url-env",
+            "DOCS_MCP_URL",
+        ])
+        .env_remove("DOCS_MCP_URL")
+        .assert()
+        .success();
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    assert_eq!(
+        servers["docs"].transport,
+        McpServerTransportConfig::StreamableHttp {
+            url: String::new(),
+            url_env_var: Some("DOCS_MCP_URL".to_string()),
+            bearer_token_env_var: None,
+            bearer_token_file: None,
+            http_headers: None,
+            env_http_headers: None,
+            env_http_header_defaults: None,
+            oauth_client_credentials: None,
+            keep_alive: true,
+            max_idle_connections: None,
+            connect_timeout_sec: None,
+            max_requests_per_sec: None,
+            client_cert_path: None,
+            client_key_path: None,
+            ca_cert_path: None,
+            health_check_interval_sec: None,
+        }
+    );
+
+    let mut get_cmd = codex_command(codex_home.path())?;
+    get_cmd
+        .args(["mcp", "get", "docs"])
+        .assert()
+        .success()
+        .stdout(contains("url_env_var: DOCS_MCP_URL"));
+
+    let mut list_cmd = codex_command(codex_home.path())?;
+    let output = list_cmd.args(["mcp", "list", "--json"]).output()?;
+    assert!(output.status.success());
+    let listed: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(
+        listed[0]["transport"]["url_env_var"],
+        serde_json::json!("DOCS_MCP_URL")
+    );
+    assert_eq!(listed[0]["transport"]["url"], serde_json::Value::Null);
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "other",
+            "--no-enable-check",
+            "--url",
+            "https://example.com/mcp",
+            "--url-env",
+            "DOCS_MCP_URL",
+        ])
+        .assert()
+        .failure();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_oauth_client_credentials_requires_client_id_and_secret() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "github",
+            "--no-enable-check",
+            "--url",
+            "https://example.com/mcp",
+            "--oauth-token-url",
+            "https://auth.example.com/oauth/token",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("--oauth-client-id"));
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    assert!(servers.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_verify_saves_nothing_when_server_fails_to_start() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "broken",
+            "--no-enable-check",
+            "--verify",
+            "--",
+            "/nonexistent/mcp-server",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "failed to verify MCP server 'broken'; nothing was saved",
+        ));
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    assert!(servers.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_replace_transport_keeps_other_settings() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--disabled",
+            "--description",
+            "Internal handbook",
+            "--allow-tool",
+            "search",
+            "--",
+            "docs-server",
+        ])
+        .assert()
+        .success();
+
+    let mut replace_cmd = codex_command(codex_home.path())?;
+    replace_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--replace-transport",
+            "--url",
+            "https://docs.example.com/mcp",
+            "--bearer-token-env-var",
+            "DOCS_TOKEN",
+        ])
+        .assert()
+        .success()
+        .stdout(contains(
+            "Replaced the transport of global MCP server 'docs'.",
+        ));
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    let docs = &servers["docs"];
+    match &docs.transport {
+        McpServerTransportConfig::StreamableHttp {
+            url,
+            bearer_token_env_var,
+            ..
+        } => {
+            assert_eq!(url, "https://docs.example.com/mcp");
+            assert_eq!(bearer_token_env_var.as_deref(), Some("DOCS_TOKEN"));
+        }
+        other => panic!("unexpected transport: {other:?}"),
+    }
+    assert!(!docs.enabled);
+    assert_eq!(docs.description.as_deref(), Some("Internal handbook"));
+    assert_eq!(docs.allowed_tools, Some(vec!["search".to_string()]));
+
+    let mut missing_cmd = codex_command(codex_home.path())?;
+    missing_cmd
+        .args([
+            "mcp",
+            "add",
+            "missing",
+            "--no-enable-check",
+            "--replace-transport",
+            "--",
+            "missing-server",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("No MCP server named 'missing' found."));
+
+    let mut no_transport_cmd = codex_command(codex_home.path())?;
+    no_transport_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--replace-transport",
+        ])
+        .assert()
+        .failure();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn clone_copies_server_and_applies_overrides() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "docs",
+            "--no-enable-check",
+            "--env",
+            "MODE=prod",
+            "--env",
+            "REGION=us",
+            "--deny-tool",
+            "delete",
+            "--",
+            "docs-server",
+            "--yes",
+        ])
+        .assert()
+        .success();
+
+    let mut clone_cmd = codex_command(codex_home.path())?;
+    clone_cmd
+        .args([
+            "mcp",
+            "clone",
+            "docs",
+            "docs-staging",
+            "--env",
+            "MODE=staging",
+        ])
+        .assert()
+        .success()
+        .stdout(contains(
+            "Cloned global MCP server 'docs' to 'docs-staging'.",
+        ));
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    let original = servers.get("docs").expect("original should remain");
+    let copy = servers.get("docs-staging").expect("clone should exist");
+    assert_eq!(copy.denied_tools, original.denied_tools);
+    match &copy.transport {
+        McpServerTransportConfig::Stdio {
+            command, args, env, ..
+        } => {
+            assert_eq!(command, "docs-server");
+            assert_eq!(args, &vec!["--yes".to_string()]);
+            assert_eq!(
+                env,
+                &Some(HashMap::from([
+                    ("MODE".to_string(), "staging".to_string()),
+                    ("REGION".to_string(), "us".to_string()),
+                ]))
+            );
+        }
+        other => panic!("unexpected transport: {other:?}"),
+    }
+    let McpServerTransportConfig::Stdio { env, .. } = &original.transport else {
+        panic!("original transport changed: {:?}", original.transport);
+    };
+    assert_eq!(
+        env.as_ref().and_then(|env| env.get("MODE")),
+        Some(&"prod".to_string())
+    );
+
+    let mut existing_cmd = codex_command(codex_home.path())?;
+    existing_cmd
+        .args(["mcp", "clone", "docs", "docs-staging"])
+        .assert()
+        .failure()
+        .stderr(contains("server 'docs-staging' already exists"));
+
+    let mut missing_cmd = codex_command(codex_home.path())?;
+    missing_cmd
+        .args(["mcp", "clone", "missing", "other"])
+        .assert()
+        .failure()
+        .stderr(contains("No MCP server named 'missing' found."));
+
+    let mut url_cmd = codex_command(codex_home.path())?;
+    url_cmd
+        .args([
+            "mcp",
+            "clone",
+            "docs",
+            "other",
+            "--url",
+            "https://example.com/mcp",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("--url is only valid for streamable HTTP servers"));
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    assert_eq!(servers.len(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn clone_replaces_url_of_streamable_http_server() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add_cmd = codex_command(codex_home.path())?;
+    add_cmd
+        .args([
+            "mcp",
+            "add",
+            "github",
+            "--no-enable-check",
+            "--url-env",
+            "GITHUB_MCP_URL",
+            "--bearer-token-env-var",
+            "GITHUB_TOKEN",
+        ])
+        .assert()
+        .success();
+
+    let mut clone_cmd = codex_command(codex_home.path())?;
+    clone_cmd
+        .args([
+            "mcp",
+            "clone",
+            "github",
+            "github-enterprise",
+            "--url",
+            "https://ghe.example.com/mcp",
+            "--quiet",
+        ])
+        .assert()
+        .success()
+        .stdout("");
+
+    let servers = load_global_mcp_servers(codex_home.path()).await?;
+    let copy = servers
+        .get("github-enterprise")
+        .expect("clone should exist");
+    match &copy.transport {
+        McpServerTransportConfig::StreamableHttp {
+            url,
+            url_env_var,
+            bearer_token_env_var,
+            ..
+        } => {
+            assert_eq!(url, "https://ghe.example.com/mcp");
+            assert_eq!(url_env_var, &None);
+            assert_eq!(bearer_token_env_var.as_deref(), Some("GITHUB_TOKEN"));
+        }
+        other => panic!("unexpected transport: {other:?}"),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use std::path::Path;
+
+use anyhow::Result;
+use codex_core::config::CONFIG_TOML_FILE;
+use predicates::str::contains;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+
+fn codex_command(codex_home: &Path) -> Result<assert_cmd::Command> {
+    let mut cmd = assert_cmd::Command::cargo_bin("codex")?;
+    cmd.env("CODEX_HOME", codex_home);
+    Ok(cmd)
+}
+
+const INSTALLED: &str = r#"
+[mcp_servers.docs]
+command = "docs-server"
+args = ["--port", "4000"]
+
+[mcp_servers.legacy]
+command = "legacy-server"
+"#;
+
+const INCOMING: &str = r#"
+[[mcp_servers]]
+name = "docs"
+command = "docs-server"
+args = ["--port", "5000"]
+
+[[mcp_servers]]
+name = "search"
+url = "https://example.com/mcp"
+"#;
+
+#[test]
+fn diff_prints_per_server_changes_without_modifying_config() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let config_path = codex_home.path().join(CONFIG_TOML_FILE);
+    std::fs::write(&config_path, INSTALLED)?;
+    let file = codex_home.path().join("servers.toml");
+    std::fs::write(&file, INCOMING)?;
+
+    let mut cmd = codex_command(codex_home.path())?;
+    cmd.args(["mcp", "diff"])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(contains("+ search"))
+        .stdout(contains(r#"    url: "https://example.com/mcp""#))
+        .stdout(contains("- legacy"))
+        .stdout(contains("~ docs"))
+        .stdout(contains(
+            r#"    args: ["--port","4000"] -> ["--port","5000"]"#,
+        ))
+        .stdout(contains("1 added, 1 removed, 1 changed, 0 unchanged"));
+
+    let mut json_cmd = codex_command(codex_home.path())?;
+    let output = json_cmd
+        .args(["mcp", "diff", "--json"])
+        .arg(&file)
+        .output()?;
+    assert!(output.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(parsed["added"]["search"]["transport"], "streamable_http");
+    assert_eq!(parsed["removed"]["legacy"]["command"], "legacy-server");
+    assert_eq!(
+        parsed["changed"]["docs"]["args"],
+        serde_json::json!({
+            "old": ["--port", "4000"],
+            "new": ["--port", "5000"],
+        })
+    );
+    assert_eq!(parsed["unchanged"], serde_json::json!([]));
+
+    assert_eq!(std::fs::read_to_string(&config_path)?, INSTALLED);
+    Ok(())
+}
+
+#[test]
+fn diff_rejects_invalid_file() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let file = codex_home.path().join("servers.toml");
+    std::fs::write(&file, "[mcp_servers.broken]\nargs = [\"x\"]\n")?;
+
+    let mut cmd = codex_command(codex_home.path())?;
+    cmd.args(["mcp", "diff"])
+        .arg(&file)
+        .assert()
+        .failure()
+        .stderr(contains("run `codex mcp validate` for details"));
+    Ok(())
+}
@@ -4,6 +4,7 @@ use anyhow::Result;
 use codex_core::config::load_global_mcp_servers;
 use codex_core::config::write_global_mcp_servers;
 use codex_core::config_types::McpServerTransportConfig;
+use codex_core::mcp::state::record_connected;
 use predicates::prelude::PredicateBooleanExt;
 use predicates::str::contains;
 use pretty_assertions::assert_eq;
@@ -39,6 +40,7 @@ async fn list_and_get_render_expected_output() -> Result<()> {
         "mcp",
         "add",
         "docs",
+        "--no-enable-check",
         "--env",
         "TOKEN=secret",
         "--",
@@ -68,7 +70,7 @@ async fn list_and_get_render_expected_output() -> Result<()> {
     assert!(stdout.contains("Name"));
     assert!(stdout.contains("docs"));
     assert!(stdout.contains("docs-server"));
-    assert!(stdout.contains("TOKEN=secret"));
+    assert!(stdout.contains("TOKEN=****"));
     assert!(stdout.contains("APP_TOKEN=$APP_TOKEN"));
     assert!(stdout.contains("WORKSPACE_ID=$WORKSPACE_ID"));
     assert!(stdout.contains("Status"));
@@ -86,7 +88,11 @@ async fn list_and_get_render_expected_output() -> Result<()> {
         json!([
           {
             "name": "docs",
+            "description": null,
+            "tags": [],
             "enabled": true,
+            "enabled_when": null,
+            "effective_enabled": true,
             "transport": {
               "type": "stdio",
               "command": "docs-server",
@@ -101,11 +107,23 @@ async fn list_and_get_render_expected_output() -> Result<()> {
                 "APP_TOKEN",
                 "WORKSPACE_ID"
               ],
-              "cwd": null
+              "cwd": null,
+              "expand_env": false,
+              "expand_env_strict": false,
+              "shutdown_timeout_sec": null,
+              "inherit_env": null
             },
             "startup_timeout_sec": null,
             "tool_timeout_sec": null,
-            "auth_status": "unsupported"
+            "tool_timeouts": null,
+            "allowed_tools": null,
+            "denied_tools": null,
+            "protocol_version": null,
+            "startup_priority": null,
+            "client_name": null,
+            "client_version": null,
+            "auth_status": "unsupported",
+            "last_connected": null
           }
         ]
         )
@@ -119,7 +137,7 @@ async fn list_and_get_render_expected_output() -> Result<()> {
     assert!(stdout.contains("transport: stdio"));
     assert!(stdout.contains("command: docs-server"));
     assert!(stdout.contains("args: --port 4000"));
-    assert!(stdout.contains("env: TOKEN=secret"));
+    assert!(stdout.contains("env: TOKEN=****"));
     assert!(stdout.contains("APP_TOKEN=$APP_TOKEN"));
     assert!(stdout.contains("WORKSPACE_ID=$WORKSPACE_ID"));
     assert!(stdout.contains("enabled: true"));
@@ -134,3 +152,1183 @@ async fn list_and_get_render_expected_output() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn get_reports_source_of_server_definition() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add = codex_command(codex_home.path())?;
+    add.args([
+        "mcp",
+        "add",
+        "docs",
+        "--no-enable-check",
+        "--",
+        "docs-server",
+    ])
+    .assert()
+    .success();
+
+    let mut get_cmd = codex_command(codex_home.path())?;
+    get_cmd
+        .args(["mcp", "get", "docs"])
+        .assert()
+        .success()
+        .stdout(contains("source: config.toml"));
+
+    let mut override_cmd = codex_command(codex_home.path())?;
+    let output = override_cmd
+        .args([
+            "mcp",
+            "-c",
+            "mcp_servers.docs.enabled=false",
+            "get",
+            "docs",
+            "--json",
+        ])
+        .output()?;
+    assert!(output.status.success());
+    let parsed: JsonValue = serde_json::from_str(&String::from_utf8(output.stdout)?)?;
+    assert_eq!(
+        (&parsed["enabled"], &parsed["source"]),
+        (&json!(false), &json!("cli_override"))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn list_and_get_show_enabled_when_state() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let other_os = if cfg!(target_os = "windows") {
+        "linux"
+    } else {
+        "windows"
+    };
+    std::fs::write(
+        codex_home.path().join("config.toml"),
+        format!(
+            r#"
+[mcp_servers.docs]
+command = "docs-server"
+enabled_when = {{ os = "{other_os}" }}
+"#
+        ),
+    )?;
+
+    let mut list_cmd = codex_command(codex_home.path())?;
+    list_cmd
+        .args(["mcp", "list"])
+        .assert()
+        .success()
+        .stdout(contains(format!("inactive (os={other_os})")));
+
+    let mut get_cmd = codex_command(codex_home.path())?;
+    get_cmd
+        .args(["mcp", "get", "docs"])
+        .assert()
+        .success()
+        .stdout(contains(format!(
+            "enabled_when: os={other_os} (not met on this machine)"
+        )));
+
+    let mut get_json_cmd = codex_command(codex_home.path())?;
+    let output = get_json_cmd
+        .args(["mcp", "get", "docs", "--json"])
+        .output()?;
+    assert!(output.status.success());
+    let parsed: JsonValue = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(
+        (
+            &parsed["enabled"],
+            &parsed["enabled_when"],
+            &parsed["effective_enabled"]
+        ),
+        (&json!(true), &json!({ "os": other_os }), &json!(false))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn list_and_get_show_startup_priority() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    std::fs::write(
+        codex_home.path().join("config.toml"),
+        r#"
+[mcp_servers.docs]
+command = "docs-server"
+startup_priority = 10
+
+[mcp_servers.search]
+command = "search-server"
+"#,
+    )?;
+
+    let mut list_cmd = codex_command(codex_home.path())?;
+    let output = list_cmd.args(["mcp", "list"]).output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    let priorities: Vec<&str> = stdout
+        .lines()
+        .filter_map(|line| line.split_whitespace().last())
+        .collect();
+    assert_eq!(priorities, vec!["Priority", "10", "0"]);
+
+    let mut list_json_cmd = codex_command(codex_home.path())?;
+    let output = list_json_cmd.args(["mcp", "list", "--json"]).output()?;
+    assert!(output.status.success());
+    let parsed: JsonValue = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(
+        (
+            &parsed[0]["startup_priority"],
+            &parsed[1]["startup_priority"]
+        ),
+        (&json!(10), &JsonValue::Null)
+    );
+
+    let mut get_cmd = codex_command(codex_home.path())?;
+    get_cmd
+        .args(["mcp", "get", "docs"])
+        .assert()
+        .success()
+        .stdout(contains("startup_priority: 10"));
+
+    // `get` reports the same resolved priority as `list` for a server that
+    // does not set one.
+    let mut get_default_cmd = codex_command(codex_home.path())?;
+    get_default_cmd
+        .args(["mcp", "get", "search"])
+        .assert()
+        .success()
+        .stdout(contains("startup_priority: 0\n"));
+
+    // JSON reports what is configured, so `add --from-json` does not write a
+    // priority the user never set; the resolved one is under `effective`.
+    let mut get_json_cmd = codex_command(codex_home.path())?;
+    let output = get_json_cmd
+        .args(["mcp", "get", "search", "--json", "--include-effective"])
+        .output()?;
+    assert!(output.status.success());
+    let parsed: JsonValue = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(parsed["startup_priority"], JsonValue::Null);
+    assert_eq!(
+        parsed["effective"]["startup_priority"],
+        json!({ "value": 0, "source": "built_in_default" })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn add_client_info_overrides_show_in_get_and_list() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add = codex_command(codex_home.path())?;
+    add.args([
+        "mcp",
+        "add",
+        "docs",
+        "--no-enable-check",
+        "--client-name",
+        "acme-ide",
+        "--client-version",
+        "2.0.1",
+        "--",
+        "docs-server",
+    ])
+    .assert()
+    .success();
+
+    let mut get_cmd = codex_command(codex_home.path())?;
+    get_cmd
+        .args(["mcp", "get", "docs"])
+        .assert()
+        .success()
+        .stdout(contains("client_name: acme-ide").and(contains("client_version: 2.0.1")));
+
+    let mut list_cmd = codex_command(codex_home.path())?;
+    let output = list_cmd.args(["mcp", "list", "--json"]).output()?;
+    assert!(output.status.success());
+    let parsed: JsonValue = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(
+        (&parsed[0]["client_name"], &parsed[0]["client_version"]),
+        (&json!("acme-ide"), &json!("2.0.1"))
+    );
+
+    let mut blank = codex_command(codex_home.path())?;
+    blank
+        .args([
+            "mcp",
+            "add",
+            "blank",
+            "--no-enable-check",
+            "--client-name",
+            " ",
+            "--",
+            "server",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("client_name must not be empty"));
+
+    Ok(())
+}
+
+#[test]
+fn get_shows_effective_inherit_env() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    std::fs::write(
+        codex_home.path().join("config.toml"),
+        r#"
+mcp_default_inherit_env = "minimal"
+
+[mcp_servers.docs]
+command = "docs-server"
+
+[mcp_servers.shell]
+command = "shell-server"
+inherit_env = "all"
+"#,
+    )?;
+
+    let mut docs_cmd = codex_command(codex_home.path())?;
+    docs_cmd
+        .args(["mcp", "get", "docs"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "inherit_env: minimal (from mcp_default_inherit_env)",
+        ));
+
+    let mut shell_cmd = codex_command(codex_home.path())?;
+    shell_cmd
+        .args(["mcp", "get", "shell"])
+        .assert()
+        .success()
+        .stdout(contains("inherit_env: all\n"));
+
+    let mut json_cmd = codex_command(codex_home.path())?;
+    let output = json_cmd.args(["mcp", "get", "shell", "--json"]).output()?;
+    assert!(output.status.success());
+    let parsed: JsonValue = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(parsed["transport"]["inherit_env"], json!("all"));
+
+    Ok(())
+}
+
+#[test]
+fn get_include_effective_labels_defaults() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let other_os = if cfg!(target_os = "windows") {
+        "linux"
+    } else {
+        "windows"
+    };
+    std::fs::write(
+        codex_home.path().join("config.toml"),
+        format!(
+            r#"
+mcp_default_tool_timeout_sec = 45
+
+[mcp_servers.docs]
+command = "docs-server"
+startup_timeout_sec = 15
+enabled_when = {{ os = "{other_os}" }}
+"#
+        ),
+    )?;
+
+    let mut text_cmd = codex_command(codex_home.path())?;
+    text_cmd
+        .args(["mcp", "get", "docs", "--include-effective"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "  effective:\n    enabled: false (enabled_when not met)\n    startup_timeout_sec: 15 (configured)\n    tool_timeout_sec: 45 (from mcp_default_tool_timeout_sec)\n    startup_priority: 0 (built-in default)\n    inherit_env: minimal (built-in default)\n",
+        ));
+
+    let mut json_cmd = codex_command(codex_home.path())?;
+    let output = json_cmd
+        .args(["mcp", "get", "docs", "--json", "--include-effective"])
+        .output()?;
+    assert!(output.status.success());
+    let parsed: JsonValue = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(
+        parsed["effective"],
+        json!({
+            "enabled": { "value": false, "source": "enabled_when" },
+            "startup_timeout_sec": { "value": 15.0, "source": "configured" },
+            "tool_timeout_sec": {
+                "value": 45.0,
+                "source": "mcp_default_tool_timeout_sec",
+            },
+            "startup_priority": { "value": 0, "source": "built_in_default" },
+            "inherit_env": { "value": "minimal", "source": "built_in_default" },
+        })
+    );
+
+    // `list --json` reports the same configured values as `get --json`,
+    // with the resolved ones only under `effective`.
+    let mut list_cmd = codex_command(codex_home.path())?;
+    let output = list_cmd
+        .args(["mcp", "list", "--json", "--include-effective"])
+        .output()?;
+    assert!(output.status.success());
+    let listed: JsonValue = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(
+        (
+            &listed[0]["startup_timeout_sec"],
+            &listed[0]["tool_timeout_sec"],
+            &listed[0]["effective"],
+        ),
+        (
+            &parsed["startup_timeout_sec"],
+            &JsonValue::Null,
+            &parsed["effective"],
+        )
+    );
+
+    let mut plain_cmd = codex_command(codex_home.path())?;
+    plain_cmd
+        .args(["mcp", "get", "docs"])
+        .assert()
+        .success()
+        .stdout(contains("effective:").not());
+
+    Ok(())
+}
+
+#[test]
+fn list_tag_filters_and_tags_are_deduped() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add = codex_command(codex_home.path())?;
+    add.args([
+        "mcp",
+        "add",
+        "docs",
+        "--no-enable-check",
+        "--tag",
+        "team-a",
+        "--tag",
+        "search",
+        "--tag",
+        "team-a",
+        "--",
+        "docs-server",
+    ])
+    .assert()
+    .success();
+    let mut add = codex_command(codex_home.path())?;
+    add.args([
+        "mcp",
+        "add",
+        "logs",
+        "--no-enable-check",
+        "--tag",
+        "ops",
+        "--",
+        "logs-server",
+    ])
+    .assert()
+    .success();
+
+    let mut get_cmd = codex_command(codex_home.path())?;
+    get_cmd
+        .args(["mcp", "get", "docs"])
+        .assert()
+        .success()
+        .stdout(contains("tags: team-a, search\n"));
+
+    let mut list_cmd = codex_command(codex_home.path())?;
+    let output = list_cmd
+        .args(["mcp", "list", "--json", "--tag", "team-a"])
+        .output()?;
+    assert!(output.status.success());
+    let parsed: JsonValue = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(parsed.as_array().map(Vec::len), Some(1));
+    assert_eq!(
+        (&parsed[0]["name"], &parsed[0]["tags"]),
+        (&json!("docs"), &json!(["team-a", "search"]))
+    );
+
+    let mut none_cmd = codex_command(codex_home.path())?;
+    none_cmd
+        .args(["mcp", "list", "--tag", "missing"])
+        .assert()
+        .success()
+        .stdout(contains("No MCP servers are tagged 'missing'."));
+
+    let mut blank = codex_command(codex_home.path())?;
+    blank
+        .args([
+            "mcp",
+            "add",
+            "blank",
+            "--no-enable-check",
+            "--tag",
+            " ",
+            "--",
+            "server",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("--tag must not be empty"));
+
+    Ok(())
+}
+
+#[test]
+fn get_and_list_show_per_tool_timeouts() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    std::fs::write(
+        codex_home.path().join("config.toml"),
+        r#"
+[mcp_servers.search]
+url = "https://example.com/mcp"
+tool_timeout_sec = 30
+
+[mcp_servers.search.tool_timeouts]
+slow_tool = 120
+index = 0.5
+"#,
+    )?;
+
+    let mut get_cmd = codex_command(codex_home.path())?;
+    get_cmd
+        .args(["mcp", "get", "search"])
+        .assert()
+        .success()
+        .stdout(contains("tool_timeouts: index=0.5, slow_tool=120\n"));
+
+    let mut list_cmd = codex_command(codex_home.path())?;
+    let output = list_cmd.args(["mcp", "list", "--json"]).output()?;
+    assert!(output.status.success());
+    let parsed: JsonValue = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(
+        parsed[0]["tool_timeouts"],
+        json!({ "index": 0.5, "slow_tool": 120.0 })
+    );
+
+    std::fs::write(
+        codex_home.path().join("config.toml"),
+        r#"
+[mcp_servers.search]
+url = "https://example.com/mcp"
+tool_timeouts = { slow_tool = -1 }
+"#,
+    )?;
+    let mut invalid_cmd = codex_command(codex_home.path())?;
+    invalid_cmd
+        .args(["mcp", "get", "search"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "tool_timeouts.slow_tool must be a positive number of seconds",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn env_masks_values_matching_configured_patterns() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    std::fs::write(
+        codex_home.path().join("config.toml"),
+        r#"
+mcp_env_mask_patterns = ["*_PAT", "GH_*"]
+
+[mcp_servers.git]
+command = "git-server"
+env = { GITLAB_PAT = "glpat-123", GH_HOST = "github.example.com", GIT_TOKEN = "shown", GIT_MODE = "fast" }
+inherit_env = "none"
+"#,
+    )?;
+
+    let mut env_cmd = codex_command(codex_home.path())?;
+    env_cmd
+        .args(["mcp", "env", "git"])
+        .assert()
+        .success()
+        .stdout("GH_HOST=****\nGITLAB_PAT=****\nGIT_MODE=fast\nGIT_TOKEN=shown\n");
+
+    let mut get_cmd = codex_command(codex_home.path())?;
+    get_cmd
+        .args(["mcp", "get", "git", "--show-all-env"])
+        .assert()
+        .success()
+        .stdout(contains("GH_HOST=****"))
+        .stdout(contains("GITLAB_PAT=****"))
+        .stdout(contains("GIT_TOKEN=shown"))
+        .stdout(contains("glpat-123").not());
+
+    let mut list_cmd = codex_command(codex_home.path())?;
+    list_cmd
+        .args(["mcp", "list", "--show-all-env"])
+        .assert()
+        .success()
+        .stdout(contains("GH_HOST=****"))
+        .stdout(contains("GITLAB_PAT=****"))
+        .stdout(contains("GIT_TOKEN=shown"))
+        .stdout(contains("glpat-123").not());
+
+    let mut secrets_cmd = codex_command(codex_home.path())?;
+    secrets_cmd
+        .args(["mcp", "env", "git", "--only-secrets"])
+        .assert()
+        .success()
+        .stdout("GH_HOST=****\nGITLAB_PAT=****\n");
+
+    let mut plain_cmd = codex_command(codex_home.path())?;
+    plain_cmd
+        .args(["mcp", "env", "git", "--only-plain"])
+        .assert()
+        .success()
+        .stdout("GIT_MODE=fast\nGIT_TOKEN=shown\n");
+
+    let mut both_cmd = codex_command(codex_home.path())?;
+    both_cmd
+        .args(["mcp", "env", "git", "--only-secrets", "--only-plain"])
+        .assert()
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn env_prints_launch_environment_with_secrets_masked() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    std::fs::write(
+        codex_home.path().join("config.toml"),
+        r#"
+[mcp_servers.docs]
+command = "docs-server"
+env = { DOCS_TOKEN = "abc123", DOCS_MODE = "fast" }
+env_vars = ["DOCS_PASSTHROUGH"]
+inherit_env = "none"
+
+[mcp_servers.remote]
+url = "https://example.com/mcp"
+"#,
+    )?;
+
+    let mut env_cmd = codex_command(codex_home.path())?;
+    env_cmd
+        .env("DOCS_PASSTHROUGH", "from-parent")
+        .args(["mcp", "env", "docs"])
+        .assert()
+        .success()
+        .stdout("DOCS_MODE=fast\nDOCS_PASSTHROUGH=from-parent\nDOCS_TOKEN=****\n");
+
+    let mut secrets_cmd = codex_command(codex_home.path())?;
+    let output = secrets_cmd
+        .env("DOCS_PASSTHROUGH", "from-parent")
+        .args(["mcp", "env", "docs", "--json", "--show-secrets"])
+        .output()?;
+    assert!(output.status.success());
+    let parsed: JsonValue = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(
+        parsed,
+        json!({
+            "DOCS_MODE": "fast",
+            "DOCS_PASSTHROUGH": "from-parent",
+            "DOCS_TOKEN": "abc123",
+        })
+    );
+
+    let mut http_cmd = codex_command(codex_home.path())?;
+    http_cmd
+        .args(["mcp", "env", "remote"])
+        .assert()
+        .failure()
+        .stderr(contains("uses streamable HTTP"));
+
+    Ok(())
+}
+
+#[test]
+fn stats_summarizes_configured_servers() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    std::fs::write(
+        codex_home.path().join("config.toml"),
+        r#"
+[mcp_servers.docs]
+command = "docs-server"
+tool_timeout_sec = 30
+
+[mcp_servers.off]
+command = "off-server"
+enabled = false
+
+[mcp_servers.remote]
+url = "https://example.com/mcp"
+bearer_token_env_var = "REMOTE_TOKEN"
+startup_timeout_sec = 5
+
+[mcp_servers.vault]
+url = "https://vault.example.com/mcp"
+bearer_token_file = "/var/run/secrets/mcp/token"
+"#,
+    )?;
+
+    let mut stats_cmd = codex_command(codex_home.path())?;
+    stats_cmd.args(["mcp", "stats"]).assert().success().stdout(
+        "Servers: 4 (3 enabled, 1 disabled)\n\
+         Transports: 2 stdio, 2 streamable HTTP\n\
+         Bearer tokens: 1 from env var, 1 from file\n\
+         Custom timeouts: 2\n",
+    );
+
+    let mut json_cmd = codex_command(codex_home.path())?;
+    let output = json_cmd.args(["mcp", "stats", "--json"]).output()?;
+    assert!(output.status.success());
+    let parsed: JsonValue = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(
+        parsed,
+        json!({
+            "total": 4,
+            "enabled": 3,
+            "disabled": 1,
+            "stdio": 2,
+            "streamable_http": 2,
+            "bearer_token_env_var": 1,
+            "bearer_token_file": 1,
+            "custom_timeouts": 2,
+        })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn list_and_get_collapse_long_env_until_show_all_env() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add = codex_command(codex_home.path())?;
+    add.args(["mcp", "add", "docs", "--no-enable-check"]);
+    for key in ["A", "B", "C", "D", "E", "F", "G"] {
+        add.args(["--env", &format!("{key}=1")]);
+    }
+    add.args(["--", "docs-server"]).assert().success();
+
+    for args in [vec!["mcp", "list"], vec!["mcp", "get", "docs"]] {
+        let mut cmd = codex_command(codex_home.path())?;
+        cmd.args(&args)
+            .assert()
+            .success()
+            .stdout(contains("A=1, B=1, C=1, D=1, E=1 (+2 more)"));
+
+        let mut cmd = codex_command(codex_home.path())?;
+        cmd.args(&args)
+            .arg("--show-all-env")
+            .assert()
+            .success()
+            .stdout(contains("E=1, F=1, G=1").and(contains("more").not()));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn list_glob_filters_by_server_name() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    for name in ["team-a-docs", "team-a-search", "team-b-docs"] {
+        let mut add = codex_command(codex_home.path())?;
+        add.args(["mcp", "add", name, "--no-enable-check", "--", "echo"])
+            .assert()
+            .success();
+    }
+
+    let mut list_cmd = codex_command(codex_home.path())?;
+    let output = list_cmd
+        .args(["mcp", "list", "--json", "--glob", "team-?-docs"])
+        .output()?;
+    assert!(output.status.success());
+    let parsed: JsonValue = serde_json::from_str(&String::from_utf8(output.stdout)?)?;
+    let names: Vec<_> = parsed
+        .as_array()
+        .expect("list --json prints an array")
+        .iter()
+        .map(|entry| entry["name"].clone())
+        .collect();
+    assert_eq!(names, vec![json!("team-a-docs"), json!("team-b-docs")]);
+
+    let mut no_match_cmd = codex_command(codex_home.path())?;
+    no_match_cmd
+        .args(["mcp", "list", "--glob", "other-*"])
+        .assert()
+        .success()
+        .stdout(contains("No MCP servers match 'other-*'."));
+
+    Ok(())
+}
+
+#[test]
+fn list_and_get_show_last_connected_time() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add = codex_command(codex_home.path())?;
+    add.args([
+        "mcp",
+        "add",
+        "docs",
+        "--no-enable-check",
+        "--",
+        "docs-server",
+    ])
+    .assert()
+    .success();
+    let mut add = codex_command(codex_home.path())?;
+    add.args([
+        "mcp",
+        "add",
+        "search",
+        "--no-enable-check",
+        "--",
+        "search-server",
+    ])
+    .assert()
+    .success();
+
+    let connected_at = chrono::Utc::now() - chrono::Duration::minutes(150);
+    record_connected(codex_home.path(), ["docs"], connected_at)?;
+
+    let mut list_cmd = codex_command(codex_home.path())?;
+    list_cmd.args(["mcp", "list"]).assert().success().stdout(
+        contains("Last Connected")
+            .and(contains("2h ago"))
+            .and(contains("never")),
+    );
+
+    let mut list_json_cmd = codex_command(codex_home.path())?;
+    let output = list_json_cmd.args(["mcp", "list", "--json"]).output()?;
+    assert!(output.status.success());
+    let parsed: JsonValue = serde_json::from_str(&String::from_utf8(output.stdout)?)?;
+    assert_eq!(
+        (&parsed[0]["last_connected"], &parsed[1]["last_connected"]),
+        (&json!(connected_at.to_rfc3339()), &JsonValue::Null)
+    );
+
+    let mut get_cmd = codex_command(codex_home.path())?;
+    get_cmd
+        .args(["mcp", "get", "docs"])
+        .assert()
+        .success()
+        .stdout(contains("last_connected: 2h ago"));
+
+    Ok(())
+}
+
+#[test]
+fn get_probe_reports_connection_failure_in_diagnostics() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add = codex_command(codex_home.path())?;
+    add.args([
+        "mcp",
+        "add",
+        "broken",
+        "--no-enable-check",
+        "--",
+        "/nonexistent/mcp-server",
+    ])
+    .assert()
+    .success();
+
+    let mut get_json_cmd = codex_command(codex_home.path())?;
+    let output = get_json_cmd
+        .args(["mcp", "get", "broken", "--json", "--probe"])
+        .output()?;
+    assert!(output.status.success());
+    let parsed: JsonValue = serde_json::from_str(&String::from_utf8(output.stdout)?)?;
+    let error = parsed["diagnostics"]["error"]
+        .as_str()
+        .expect("diagnostics.error should be set");
+    assert!(
+        error.contains("failed to launch MCP server 'broken'"),
+        "unexpected error: {error}"
+    );
+
+    let mut get_cmd = codex_command(codex_home.path())?;
+    get_cmd
+        .args(["mcp", "get", "broken"])
+        .assert()
+        .success()
+        .stdout(contains("diagnostics").not());
+
+    Ok(())
+}
+
+#[test]
+fn list_health_groups_servers_by_probe_result() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add = codex_command(codex_home.path())?;
+    add.args([
+        "mcp",
+        "add",
+        "broken",
+        "--no-enable-check",
+        "--",
+        "/nonexistent/mcp-server",
+    ])
+    .assert()
+    .success();
+    let mut add = codex_command(codex_home.path())?;
+    add.args([
+        "mcp",
+        "add",
+        "parked",
+        "--no-enable-check",
+        "--disabled",
+        "--",
+        "parked-server",
+    ])
+    .assert()
+    .success();
+
+    let mut list_cmd = codex_command(codex_home.path())?;
+    list_cmd
+        .args(["mcp", "list", "--health"])
+        .assert()
+        .success()
+        .stdout(
+            contains("Unreachable (1):")
+                .and(contains("failed to launch MCP server 'broken'"))
+                .and(contains("Skipped (1):"))
+                .and(contains("Healthy").not()),
+        );
+
+    let mut list_json_cmd = codex_command(codex_home.path())?;
+    let output = list_json_cmd
+        .args([
+            "mcp",
+            "list",
+            "--json",
+            "--health",
+            "--health-timeout",
+            "10",
+        ])
+        .output()?;
+    assert!(output.status.success());
+    let parsed: JsonValue = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(parsed[0]["name"], json!("broken"));
+    assert_eq!(parsed[0]["health"]["status"], json!("unreachable"));
+    assert_eq!(parsed[1]["health"], json!({ "status": "skipped" }));
+
+    let mut list_cmd = codex_command(codex_home.path())?;
+    let output = list_cmd.args(["mcp", "list", "--json"]).output()?;
+    let parsed: JsonValue = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(parsed[0].get("health"), None);
+
+    Ok(())
+}
+
+#[test]
+fn ping_exit_status_distinguishes_failure_from_missing_server() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add = codex_command(codex_home.path())?;
+    add.args([
+        "mcp",
+        "add",
+        "broken",
+        "--no-enable-check",
+        "--",
+        "/nonexistent/mcp-server",
+    ])
+    .assert()
+    .success();
+
+    let mut ping_cmd = codex_command(codex_home.path())?;
+    let output = ping_cmd
+        .args(["mcp", "ping", "broken", "--json"])
+        .output()?;
+    assert_eq!(output.status.code(), Some(1));
+    let parsed: JsonValue = serde_json::from_str(&String::from_utf8(output.stdout)?)?;
+    assert_eq!(
+        (&parsed["name"], &parsed["ok"], &parsed["latency_ms"]),
+        (&json!("broken"), &json!(false), &JsonValue::Null)
+    );
+    assert!(parsed["error"].is_string());
+
+    let mut missing_cmd = codex_command(codex_home.path())?;
+    missing_cmd
+        .args(["mcp", "ping", "missing"])
+        .assert()
+        .code(2)
+        .stderr(contains("No MCP server named 'missing' found."));
+
+    Ok(())
+}
+
+#[test]
+fn json_mode_reports_errors_as_json_on_stderr() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut get_cmd = codex_command(codex_home.path())?;
+    let output = get_cmd.args(["mcp", "get", "missing", "--json"]).output()?;
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    let parsed: JsonValue = serde_json::from_slice(&output.stderr)?;
+    assert_eq!(
+        parsed,
+        json!({
+            "error": {
+                "message": "No MCP server named 'missing' found.",
+                "context": [],
+            }
+        })
+    );
+
+    let spec_path = codex_home.path().join("server.json");
+    std::fs::write(&spec_path, "{not json")?;
+    let mut add_cmd = codex_command(codex_home.path())?;
+    let output = add_cmd
+        .args(["mcp", "--json", "add", "docs", "--from-json"])
+        .arg(&spec_path)
+        .output()?;
+    assert_eq!(output.status.code(), Some(1));
+    let parsed: JsonValue = serde_json::from_slice(&output.stderr)?;
+    assert!(
+        parsed["error"]["message"]
+            .as_str()
+            .is_some_and(|message| message.starts_with("invalid MCP server JSON")),
+        "unexpected error: {parsed}"
+    );
+    assert!(
+        parsed["error"]["context"]
+            .as_array()
+            .is_some_and(|context| !context.is_empty())
+    );
+
+    // Without --json the error stays plain text.
+    let mut text_cmd = codex_command(codex_home.path())?;
+    text_cmd
+        .args(["mcp", "get", "missing"])
+        .assert()
+        .failure()
+        .stderr(contains("No MCP server named 'missing' found.").and(contains("\"error\"").not()));
+
+    Ok(())
+}
+
+#[test]
+fn test_tool_reports_bad_arguments_missing_servers_and_launch_failures() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add = codex_command(codex_home.path())?;
+    add.args([
+        "mcp",
+        "add",
+        "broken",
+        "--no-enable-check",
+        "--",
+        "/nonexistent/mcp-server",
+    ])
+    .assert()
+    .success();
+
+    let mut bad_args_cmd = codex_command(codex_home.path())?;
+    bad_args_cmd
+        .args(["mcp", "test-tool", "broken", "echo", "--args-json", "[1]"])
+        .assert()
+        .failure()
+        .stderr(contains("--args-json must be a JSON object"));
+
+    let mut missing_cmd = codex_command(codex_home.path())?;
+    missing_cmd
+        .args(["mcp", "test-tool", "missing", "echo"])
+        .assert()
+        .failure()
+        .stderr(contains("No MCP server named 'missing' found."));
+
+    let mut broken_cmd = codex_command(codex_home.path())?;
+    broken_cmd
+        .args([
+            "mcp",
+            "test-tool",
+            "broken",
+            "echo",
+            "--args-json",
+            r#"{"text": "hi"}"#,
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("failed to launch MCP server 'broken'"));
+
+    Ok(())
+}
+
+#[test]
+fn which_reports_unreachable_servers_and_missing_tool() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add = codex_command(codex_home.path())?;
+    add.args([
+        "mcp",
+        "add",
+        "broken",
+        "--no-enable-check",
+        "--",
+        "/nonexistent/mcp-server",
+    ])
+    .assert()
+    .success();
+
+    let mut which_cmd = codex_command(codex_home.path())?;
+    which_cmd
+        .args(["mcp", "which", "echo"])
+        .assert()
+        .code(1)
+        .stderr(contains("warning: could not check 'broken'"))
+        .stderr(contains("No reachable MCP server provides 'echo'."));
+
+    let mut json_cmd = codex_command(codex_home.path())?;
+    let output = json_cmd.args(["mcp", "which", "echo", "--json"]).output()?;
+    assert_eq!(output.status.code(), Some(1));
+    let parsed: JsonValue = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(
+        (&parsed["tool"], &parsed["servers"], &parsed["conflict"]),
+        (&json!("echo"), &json!([]), &json!(false))
+    );
+    assert!(parsed["unreachable"]["broken"].is_string());
+
+    Ok(())
+}
+
+#[test]
+fn logs_prints_tail_and_reports_missing_log() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut missing_cmd = codex_command(codex_home.path())?;
+    missing_cmd
+        .args(["mcp", "logs", "docs"])
+        .assert()
+        .failure()
+        .stderr(contains("No log for MCP server 'docs'"));
+
+    let log_dir = codex_home.path().join("log").join("mcp");
+    std::fs::create_dir_all(&log_dir)?;
+    std::fs::write(log_dir.join("docs.log"), "one\ntwo\nthree\n")?;
+
+    let mut logs_cmd = codex_command(codex_home.path())?;
+    let output = logs_cmd
+        .args(["mcp", "logs", "docs", "--lines", "2"])
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout)?, "two\nthree\n");
+
+    Ok(())
+}
+
+#[test]
+fn list_and_get_compact_emit_single_line_json() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    for name in ["alpha", "beta"] {
+        let mut add = codex_command(codex_home.path())?;
+        add.args(["mcp", "add", name, "--no-enable-check", "--", "echo", name])
+            .assert()
+            .success();
+    }
+
+    let mut list_cmd = codex_command(codex_home.path())?;
+    let output = list_cmd
+        .args(["mcp", "list", "--json", "--compact"])
+        .output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    let names = stdout
+        .lines()
+        .map(|line| {
+            let value: JsonValue = serde_json::from_str(line)?;
+            Ok(value["name"].clone())
+        })
+        .collect::<Result<Vec<_>>>()?;
+    assert_eq!(names, vec![json!("alpha"), json!("beta")]);
+
+    let mut get_cmd = codex_command(codex_home.path())?;
+    let output = get_cmd
+        .args(["mcp", "get", "alpha", "--json", "--compact"])
+        .output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert_eq!(stdout.lines().count(), 1);
+    let parsed: JsonValue = serde_json::from_str(&stdout)?;
+    assert_eq!(parsed["name"], json!("alpha"));
+
+    let mut bad_cmd = codex_command(codex_home.path())?;
+    bad_cmd
+        .args(["mcp", "list", "--compact"])
+        .assert()
+        .failure()
+        .stderr(contains("--json"));
+
+    Ok(())
+}
+
+#[test]
+fn list_color_always_keeps_columns_aligned() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut add = codex_command(codex_home.path())?;
+    add.args([
+        "mcp",
+        "add",
+        "docs",
+        "--no-enable-check",
+        "--",
+        "docs-server",
+    ])
+    .assert()
+    .success();
+    let mut add = codex_command(codex_home.path())?;
+    add.args([
+        "mcp",
+        "add",
+        "search",
+        "--no-enable-check",
+        "--disabled",
+        "--",
+        "search-server",
+    ])
+    .assert()
+    .success();
+
+    let list = |color: &str| -> Result<String> {
+        let mut cmd = codex_command(codex_home.path())?;
+        let output = cmd
+            .args(["mcp", "list", "--color", color])
+            .env("NO_COLOR", "1")
+            .output()?;
+        assert!(output.status.success());
+        Ok(String::from_utf8(output.stdout)?)
+    };
+    let plain = list("never")?;
+    let colored = list("always")?;
+
+    assert!(!plain.contains('\x1b'));
+    assert!(colored.contains('\x1b'));
+    // Removing the escape codes must give back exactly the plain table.
+    let mut stripped = String::new();
+    let mut chars = colored.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\x1b' {
+            chars.by_ref().find(|&ch| ch == 'm');
+        } else {
+            stripped.push(ch);
+        }
+    }
+    assert_eq!(stripped, plain);
+
+    let mut get = codex_command(codex_home.path())?;
+    get.args(["mcp", "get", "search"])
+        .assert()
+        .success()
+        .stdout(contains("enabled: false").and(contains("\x1b").not()));
+
+    Ok(())
+}
@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::Result;
+use codex_core::config::CONFIG_TOML_FILE;
+use codex_core::mcp::control::MCP_CONTROL_DIR;
+use codex_core::mcp::control::McpControlChannel;
+use codex_core::mcp::control::McpControlRequest;
+use codex_core::mcp::control::McpControlResponse;
+use codex_core::mcp::control::McpRestartOutcome;
+use predicates::prelude::PredicateBooleanExt;
+use predicates::str::contains;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+
+fn codex_command(codex_home: &Path) -> Result<assert_cmd::Command> {
+    let mut cmd = assert_cmd::Command::cargo_bin("codex")?;
+    cmd.env("CODEX_HOME", codex_home);
+    Ok(cmd)
+}
+
+const SERVERS: &str = r#"
+[mcp_servers.docs]
+command = "docs-server"
+
+[mcp_servers.search]
+command = "search-server"
+"#;
+
+/// Stand in for a running session: wait for one request on `channel` and
+/// answer it with `outcomes`. Returns the request.
+fn answer_one_request(
+    channel: McpControlChannel,
+    outcomes: BTreeMap<String, McpRestartOutcome>,
+) -> std::thread::JoinHandle<Result<McpControlRequest>> {
+    std::thread::spawn(move || {
+        let deadline = Instant::now() + Duration::from_secs(30);
+        loop {
+            if let Some((id, request)) = channel.take_requests()?.pop() {
+                channel.respond(
+                    &id,
+                    &McpControlResponse {
+                        servers: outcomes,
+                        error: None,
+                    },
+                )?;
+                return Ok(request);
+            }
+            anyhow::ensure!(Instant::now() < deadline, "no restart request arrived");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    })
+}
+
+#[test]
+fn restart_reports_servers_as_inactive_without_a_session() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    std::fs::write(codex_home.path().join(CONFIG_TOML_FILE), SERVERS)?;
+
+    let mut cmd = codex_command(codex_home.path())?;
+    cmd.args(["mcp", "restart", "docs"])
+        .assert()
+        .code(1)
+        .stderr(
+            contains("docs: not currently active (no running Codex session)")
+                .and(contains("search").not()),
+        );
+    Ok(())
+}
+
+#[test]
+fn restart_rejects_unknown_servers() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    std::fs::write(codex_home.path().join(CONFIG_TOML_FILE), SERVERS)?;
+
+    let mut cmd = codex_command(codex_home.path())?;
+    cmd.args(["mcp", "restart", "nope"])
+        .assert()
+        .failure()
+        .stderr(contains("No MCP server named 'nope' found."));
+
+    assert!(!codex_home.path().join(MCP_CONTROL_DIR).exists());
+    Ok(())
+}
+
+#[test]
+fn restart_prints_the_outcomes_the_session_reports() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    std::fs::write(codex_home.path().join(CONFIG_TOML_FILE), SERVERS)?;
+
+    let channel = McpControlChannel::register(codex_home.path(), "session-a")?;
+    let session = answer_one_request(
+        channel,
+        BTreeMap::from([
+            (
+                "docs".to_string(),
+                McpRestartOutcome::Restarted { tool_count: 3 },
+            ),
+            (
+                "search".to_string(),
+                McpRestartOutcome::Failed {
+                    error: "exited during initialize".to_string(),
+                },
+            ),
+        ]),
+    );
+
+    let mut cmd = codex_command(codex_home.path())?;
+    cmd.args(["mcp", "restart", "--all"])
+        .assert()
+        .code(1)
+        .stdout(contains("docs: restarted (3 tools)").and(contains("session").not()))
+        .stderr(contains(
+            "search: failed to restart: exited during initialize",
+        ));
+
+    let request = session.join().expect("session thread panicked")?;
+    assert_eq!(request, McpControlRequest::Restart { servers: vec![] });
+    Ok(())
+}
+
+#[test]
+fn restart_reaches_every_running_session() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    std::fs::write(codex_home.path().join(CONFIG_TOML_FILE), SERVERS)?;
+
+    // Only the second session runs `docs`; that is not an error.
+    let first = answer_one_request(
+        McpControlChannel::register(codex_home.path(), "session-a")?,
+        BTreeMap::from([("docs".to_string(), McpRestartOutcome::Inactive)]),
+    );
+    let second = answer_one_request(
+        McpControlChannel::register(codex_home.path(), "session-b")?,
+        BTreeMap::from([(
+            "docs".to_string(),
+            McpRestartOutcome::Restarted { tool_count: 1 },
+        )]),
+    );
+
+    let mut cmd = codex_command(codex_home.path())?;
+    cmd.args(["mcp", "restart", "docs"])
+        .assert()
+        .success()
+        .stdout(contains("session session-b: docs: restarted (1 tool)"))
+        .stderr(contains("not currently active").not());
+
+    let expected = McpControlRequest::Restart {
+        servers: vec!["docs".to_string()],
+    };
+    assert_eq!(first.join().expect("session thread panicked")?, expected);
+    assert_eq!(second.join().expect("session thread panicked")?, expected);
+    Ok(())
+}
+
+#[test]
+fn restart_gives_up_on_a_session_that_does_not_answer() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    std::fs::write(codex_home.path().join(CONFIG_TOML_FILE), SERVERS)?;
+    let _channel = McpControlChannel::register(codex_home.path(), "session-a")?;
+
+    let mut cmd = codex_command(codex_home.path())?;
+    cmd.args(["mcp", "restart", "docs", "--timeout", "0.2"])
+        .assert()
+        .code(1)
+        .stderr(contains("the session did not answer within 0.2s"));
+
+    // The unanswered request is withdrawn.
+    let requests = codex_home
+        .path()
+        .join(MCP_CONTROL_DIR)
+        .join("session-a")
+        .join("requests");
+    assert_eq!(std::fs::read_dir(requests)?.count(), 0);
+    Ok(())
+}
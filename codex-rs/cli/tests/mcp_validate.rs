@@ -0,0 +1,108 @@
+use std::path::Path;
+
+use anyhow::Result;
+use codex_core::config::CONFIG_TOML_FILE;
+use predicates::prelude::PredicateBooleanExt;
+use predicates::str::contains;
+use tempfile::TempDir;
+
+fn codex_command(codex_home: &Path) -> Result<assert_cmd::Command> {
+    let mut cmd = assert_cmd::Command::cargo_bin("codex")?;
+    cmd.env("CODEX_HOME", codex_home);
+    Ok(cmd)
+}
+
+#[test]
+fn validate_accepts_well_formed_file() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let file = codex_home.path().join("servers.toml");
+    std::fs::write(
+        &file,
+        r#"
+[[mcp_servers]]
+name = "docs"
+command = "docs-server"
+startup_timeout_sec = 5
+
+[[mcp_servers]]
+name = "search"
+url = "https://example.com/mcp"
+bearer_token_env_var = "SEARCH_TOKEN"
+"#,
+    )?;
+
+    let mut cmd = codex_command(codex_home.path())?;
+    cmd.args(["mcp", "validate"])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout(contains("2 MCP server(s) OK"));
+
+    assert!(!codex_home.path().join(CONFIG_TOML_FILE).exists());
+    Ok(())
+}
+
+#[test]
+fn validate_reports_every_problem() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let file = codex_home.path().join("servers.toml");
+    std::fs::write(
+        &file,
+        r#"
+[mcp_servers."bad name"]
+command = "server"
+
+[mcp_servers.ftp]
+url = "ftp://example.com/mcp"
+
+[mcp_servers.slow]
+command = "server"
+tool_timeout_sec = 0
+
+[mcp_servers.mixed]
+url = "https://example.com/mcp"
+command = "server"
+
+[mcp_servers.envy]
+command = "server"
+env_vars = ["OK_VAR", "BAD=VAR"]
+"#,
+    )?;
+
+    let mut cmd = codex_command(codex_home.path())?;
+    cmd.args(["mcp", "validate"])
+        .arg(&file)
+        .assert()
+        .failure()
+        .stderr(
+            contains("bad name: invalid server name 'bad name'")
+                .and(contains(
+                    "ftp: server URL 'ftp://example.com/mcp' must use http or https",
+                ))
+                .and(contains("slow: tool_timeout_sec must be positive"))
+                .and(contains("mixed: "))
+                .and(contains(
+                    "envy: env_vars has invalid environment variable name 'BAD=VAR'",
+                ))
+                .and(contains("5 problem(s) found")),
+        );
+
+    assert!(!codex_home.path().join(CONFIG_TOML_FILE).exists());
+    Ok(())
+}
+
+#[test]
+fn validate_rejects_unreadable_toml() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let file = codex_home.path().join("servers.toml");
+    std::fs::write(&file, "[mcp_servers.docs\ncommand = ")?;
+
+    let mut cmd = codex_command(codex_home.path())?;
+    cmd.args(["mcp", "validate"])
+        .arg(&file)
+        .assert()
+        .failure()
+        .stderr(contains("invalid MCP server file"));
+
+    Ok(())
+}
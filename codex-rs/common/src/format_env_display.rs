@@ -1,26 +1,63 @@
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 
+use codex_core::mcp::env_mask::EnvMaskPatterns;
+use codex_core::mcp::env_mask::MASKED_ENV_VALUE;
+
 pub fn format_env_display(env: Option<&HashMap<String, String>>, env_vars: &[String]) -> String {
+    format_env_display_with_limit(env, env_vars, None)
+}
+
+/// Like [`format_env_display`], but shows at most `max_entries` entries
+/// (`KEY=value` pairs first, then `$VAR` passthroughs, each sorted) and
+/// summarizes the rest as `(+K more)`.
+pub fn format_env_display_with_limit(
+    env: Option<&HashMap<String, String>>,
+    env_vars: &[String],
+    max_entries: Option<usize>,
+) -> String {
+    format_env_display_masked(env, env_vars, max_entries, &EnvMaskPatterns::default())
+}
+
+/// Like [`format_env_display_with_limit`], but shows `****` for the
+/// non-empty value of every `KEY=value` pair whose name matches `mask`.
+/// `$VAR` passthroughs carry no value and are shown as-is.
+pub fn format_env_display_masked(
+    env: Option<&HashMap<String, String>>,
+    env_vars: &[String],
+    max_entries: Option<usize>,
+    mask: &EnvMaskPatterns,
+) -> String {
     let mut parts: Vec<String> = Vec::new();
 
     if let Some(map) = env {
         let mut pairs: Vec<_> = map.iter().collect();
         pairs.sort_by(|(a, _), (b, _)| a.cmp(b));
-        parts.extend(
-            pairs
-                .into_iter()
-                .map(|(key, value)| format!("{key}={value}")),
-        );
+        parts.extend(pairs.into_iter().map(|(key, value)| {
+            if !value.is_empty() && mask.is_masked(key) {
+                format!("{key}={MASKED_ENV_VALUE}")
+            } else {
+                format!("{key}={value}")
+            }
+        }));
     }
 
-    if !env_vars.is_empty() {
-        parts.extend(env_vars.iter().map(|var| format!("{var}=${var}")));
+    let env_vars: BTreeSet<&String> = env_vars.iter().collect();
+    parts.extend(env_vars.into_iter().map(|var| format!("{var}=${var}")));
+
+    if parts.is_empty() {
+        return "-".to_string();
     }
 
+    let hidden = max_entries.map_or(0, |max| parts.len().saturating_sub(max));
+    if hidden == 0 {
+        return parts.join(", ");
+    }
+    parts.truncate(parts.len() - hidden);
     if parts.is_empty() {
-        "-".to_string()
+        format!("(+{hidden} more)")
     } else {
-        parts.join(", ")
+        format!("{} (+{hidden} more)", parts.join(", "))
     }
 }
 
@@ -49,7 +86,64 @@ mod tests {
     fn formats_env_vars_with_dollar_prefix() {
         let vars = vec!["TOKEN".to_string(), "PATH".to_string()];
 
-        assert_eq!(format_env_display(None, &vars), "TOKEN=$TOKEN, PATH=$PATH");
+        assert_eq!(format_env_display(None, &vars), "PATH=$PATH, TOKEN=$TOKEN");
+    }
+
+    #[test]
+    fn dedupes_env_vars_case_sensitively() {
+        let vars = vec![
+            "TOKEN".to_string(),
+            "token".to_string(),
+            "TOKEN".to_string(),
+        ];
+
+        assert_eq!(
+            format_env_display(None, &vars),
+            "TOKEN=$TOKEN, token=$token"
+        );
+    }
+
+    #[test]
+    fn limits_entries_with_more_suffix() {
+        let mut env = HashMap::new();
+        env.insert("B".to_string(), "two".to_string());
+        env.insert("A".to_string(), "one".to_string());
+        let vars = vec!["TOKEN".to_string(), "PATH".to_string()];
+
+        assert_eq!(
+            format_env_display_with_limit(Some(&env), &vars, Some(3)),
+            "A=one, B=two, PATH=$PATH (+1 more)"
+        );
+        assert_eq!(
+            format_env_display_with_limit(Some(&env), &vars, Some(0)),
+            "(+4 more)"
+        );
+        assert_eq!(
+            format_env_display_with_limit(Some(&env), &vars, Some(4)),
+            "A=one, B=two, PATH=$PATH, TOKEN=$TOKEN"
+        );
+        assert_eq!(format_env_display_with_limit(None, &[], Some(1)), "-");
+    }
+
+    #[test]
+    fn masks_values_matching_patterns() {
+        let env = HashMap::from([
+            ("GITLAB_PAT".to_string(), "glpat-123".to_string()),
+            ("GH_HOST".to_string(), "github.example.com".to_string()),
+            ("EMPTY_PAT".to_string(), String::new()),
+            ("MODE".to_string(), "fast".to_string()),
+        ]);
+        let vars = vec!["GH_TOKEN".to_string()];
+        let mask = EnvMaskPatterns::new(&["*_pat", "GH_*"]);
+
+        assert_eq!(
+            format_env_display_masked(Some(&env), &vars, None, &mask),
+            "EMPTY_PAT=, GH_HOST=****, GITLAB_PAT=****, MODE=fast, GH_TOKEN=$GH_TOKEN"
+        );
+        assert_eq!(
+            format_env_display_masked(Some(&env), &[], Some(1), &mask),
+            "EMPTY_PAT= (+3 more)"
+        );
     }
 
     #[test]
@@ -1,8 +1,10 @@
 use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::AtomicU64;
+use std::time::Duration;
 
 use crate::AuthManager;
 use crate::client_common::REVIEW_PROMPT;
@@ -66,6 +68,10 @@ use crate::executor::Executor;
 use crate::executor::ExecutorConfig;
 use crate::executor::normalize_exec_result;
 use crate::mcp::auth::compute_auth_statuses;
+use crate::mcp::control::McpControlChannel;
+use crate::mcp::control::McpControlRequest;
+use crate::mcp::control::McpControlResponse;
+use crate::mcp::control::McpRestartOutcome;
 use crate::mcp_connection_manager::McpConnectionManager;
 use crate::model_family::find_family_for_model;
 use crate::openai_model_info::get_model_info;
@@ -154,6 +160,11 @@ pub struct CodexSpawnOk {
 
 pub(crate) const INITIAL_SUBMIT_ID: &str = "";
 pub(crate) const SUBMISSION_CHANNEL_CAPACITY: usize = 64;
+/// Event id for the output of restarts requested through the session's
+/// [`McpControlChannel`] rather than a submission.
+const MCP_CONTROL_SUB_ID: &str = "mcp_control";
+/// How often a session checks its [`McpControlChannel`] for requests.
+const MCP_CONTROL_POLL_INTERVAL: Duration = Duration::from_millis(250);
 
 impl Codex {
     /// Spawn a new [`Codex`] and initialize the session.
@@ -461,6 +472,8 @@ impl Session {
                 .features
                 .enabled(crate::features::Feature::RmcpClient),
             config.mcp_oauth_credentials_store_mode,
+            crate::config::log_dir(&config).ok(),
+            config.mcp_startup_concurrency,
         );
         let default_shell_fut = shell::default_user_shell();
         let history_meta_fut = crate::message_history::history_metadata(&config);
@@ -504,6 +517,17 @@ impl Session {
             }
         };
 
+        if let Err(err) = crate::mcp::state::record_connected(
+            &config.codex_home,
+            mcp_connection_manager
+                .server_names()
+                .iter()
+                .map(String::as_str),
+            chrono::Utc::now(),
+        ) {
+            warn!("failed to record MCP server connection times: {err}");
+        }
+
         // Surface individual client start-up failures to the user.
         if !failed_clients.is_empty() {
             for (server_name, err) in failed_clients {
@@ -1292,6 +1316,19 @@ impl Session {
 
 async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiver<Submission>) {
     let mut previous_context: Option<Arc<TurnContext>> = None;
+    // `codex mcp restart` reaches the session through this channel.
+    let mcp_control =
+        match McpControlChannel::register(&config.codex_home, &sess.conversation_id.to_string()) {
+            Ok(channel) => Some(tokio::spawn(serve_mcp_control(
+                Arc::clone(&sess),
+                Arc::clone(&config),
+                channel,
+            ))),
+            Err(err) => {
+                warn!("failed to register the MCP control channel: {err}");
+                None
+            }
+        };
     // To break out of this loop, send Op::Shutdown.
     while let Ok(sub) = rx_sub.recv().await {
         debug!(?sub, "Submission");
@@ -1435,9 +1472,10 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
 
                 // This is a cheap lookup from the connection manager's cache.
                 let tools = sess.services.mcp_connection_manager.list_all_tools();
+                let mcp_servers = sess.services.mcp_connection_manager.server_configs();
                 let (auth_statuses, resources, resource_templates) = tokio::join!(
                     compute_auth_statuses(
-                        config.mcp_servers.iter(),
+                        mcp_servers.iter(),
                         config.mcp_oauth_credentials_store_mode,
                     ),
                     sess.services.mcp_connection_manager.list_all_resources(),
@@ -1458,6 +1496,22 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
                 };
                 sess.send_event(event).await;
             }
+            Op::ReloadMcpServers => {
+                let sess = sess.clone();
+                let config = config.clone();
+                let sub_id = sub.id.clone();
+                tokio::spawn(async move {
+                    reload_mcp_servers(&sess, &config, &sub_id).await;
+                });
+            }
+            Op::RestartMcpServers { servers } => {
+                let sess = sess.clone();
+                let config = config.clone();
+                let sub_id = sub.id.clone();
+                tokio::spawn(async move {
+                    restart_mcp_servers(&sess, &config, &sub_id, &servers).await;
+                });
+            }
             Op::ListCustomPrompts => {
                 let sub_id = sub.id.clone();
 
@@ -1492,6 +1546,7 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
             Op::Shutdown => {
                 sess.abort_all_tasks(TurnAbortReason::Interrupted).await;
                 info!("Shutting down Codex instance");
+                sess.services.mcp_connection_manager.shutdown().await;
 
                 // Gracefully flush and shutdown rollout recorder on session end so tests
                 // that inspect the rollout file do not race with the background writer.
@@ -1562,6 +1617,9 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
             }
         }
     }
+    if let Some(mcp_control) = mcp_control {
+        mcp_control.abort();
+    }
     debug!("Agent loop exited");
 }
 
@@ -1986,6 +2044,199 @@ fn parse_review_output_event(text: &str) -> ReviewOutputEvent {
     }
 }
 
+/// Handle [`Op::ReloadMcpServers`]. The `-c` overrides the session was
+/// started with are applied again on top of the re-read files.
+async fn reload_mcp_servers(sess: &Session, config: &Config, sub_id: &str) {
+    let loaded = match crate::config::load_mcp_servers_with_cli_overrides(
+        &config.codex_home,
+        config.cli_overrides.clone(),
+    )
+    .await
+    {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            sess.send_event(Event {
+                id: sub_id.to_string(),
+                msg: EventMsg::Error(ErrorEvent {
+                    message: format!("Failed to reload MCP servers: {err}"),
+                }),
+            })
+            .await;
+            return;
+        }
+    };
+    let mcp_servers = loaded
+        .servers
+        .iter()
+        .map(|(name, server)| (name.clone(), server.with_defaults(&loaded.server_defaults)))
+        .collect();
+
+    let (summary, failed_clients) = match sess
+        .services
+        .mcp_connection_manager
+        .reload(
+            mcp_servers,
+            config
+                .features
+                .enabled(crate::features::Feature::RmcpClient),
+            loaded.oauth_credentials_store_mode,
+            crate::config::log_dir(config).ok(),
+            config.mcp_startup_concurrency,
+        )
+        .await
+    {
+        Ok(result) => result,
+        Err(err) => {
+            sess.send_event(Event {
+                id: sub_id.to_string(),
+                msg: EventMsg::Error(ErrorEvent {
+                    message: format!("Failed to reload MCP servers: {err:#}"),
+                }),
+            })
+            .await;
+            return;
+        }
+    };
+
+    let running = sess.services.mcp_connection_manager.server_names();
+    let started = running
+        .iter()
+        .filter(|name| summary.added.contains(*name) || summary.restarted.contains(*name))
+        .map(String::as_str);
+    if let Err(err) =
+        crate::mcp::state::record_connected(&config.codex_home, started, chrono::Utc::now())
+    {
+        warn!("failed to record MCP server connection times: {err}");
+    }
+    for (server_name, err) in failed_clients {
+        let message = format!("MCP client for `{server_name}` failed to start: {err:#}");
+        error!("{message}");
+        sess.send_event(Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::Error(ErrorEvent { message }),
+        })
+        .await;
+    }
+    sess.notify_background_event(sub_id, summary.to_string())
+        .await;
+}
+
+/// Handle [`Op::RestartMcpServers`], or a restart requested through the
+/// session's [`McpControlChannel`]. Servers restart with the definitions they
+/// were running with; use [`Op::ReloadMcpServers`] to pick up edits. Returns
+/// each server's outcome: every configured server when `servers` is empty.
+async fn restart_mcp_servers(
+    sess: &Session,
+    config: &Config,
+    sub_id: &str,
+    servers: &[String],
+) -> McpControlResponse {
+    let (summary, failed_clients) = match sess
+        .services
+        .mcp_connection_manager
+        .restart(
+            servers,
+            config
+                .features
+                .enabled(crate::features::Feature::RmcpClient),
+            config.mcp_oauth_credentials_store_mode,
+            crate::config::log_dir(config).ok(),
+            config.mcp_startup_concurrency,
+        )
+        .await
+    {
+        Ok(result) => result,
+        Err(err) => {
+            let message = format!("Failed to restart MCP servers: {err:#}");
+            sess.send_event(Event {
+                id: sub_id.to_string(),
+                msg: EventMsg::Error(ErrorEvent {
+                    message: message.clone(),
+                }),
+            })
+            .await;
+            return McpControlResponse {
+                error: Some(message),
+                ..Default::default()
+            };
+        }
+    };
+
+    if let Err(err) = crate::mcp::state::record_connected(
+        &config.codex_home,
+        summary.restarted.iter().map(|(name, _)| name.as_str()),
+        chrono::Utc::now(),
+    ) {
+        warn!("failed to record MCP server connection times: {err}");
+    }
+
+    let not_running: Vec<String> = if servers.is_empty() {
+        sess.services
+            .mcp_connection_manager
+            .server_configs()
+            .into_keys()
+            .collect()
+    } else {
+        summary.inactive.clone()
+    };
+    let mut outcomes: BTreeMap<String, McpRestartOutcome> = not_running
+        .into_iter()
+        .map(|name| (name, McpRestartOutcome::Inactive))
+        .collect();
+    outcomes.extend(summary.restarted.iter().map(|(name, tool_count)| {
+        (
+            name.clone(),
+            McpRestartOutcome::Restarted {
+                tool_count: *tool_count,
+            },
+        )
+    }));
+    for (server_name, err) in failed_clients {
+        let message = format!("MCP client for `{server_name}` failed to restart: {err:#}");
+        error!("{message}");
+        sess.send_event(Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::Error(ErrorEvent { message }),
+        })
+        .await;
+        outcomes.insert(
+            server_name,
+            McpRestartOutcome::Failed {
+                error: format!("{err:#}"),
+            },
+        );
+    }
+    sess.notify_background_event(sub_id, summary.to_string())
+        .await;
+    McpControlResponse {
+        servers: outcomes,
+        error: None,
+    }
+}
+
+/// Answer requests sent through the session's [`McpControlChannel`]. Runs
+/// until the submission loop aborts it at shutdown, which drops the channel
+/// and removes its directory.
+async fn serve_mcp_control(sess: Arc<Session>, config: Arc<Config>, channel: McpControlChannel) {
+    loop {
+        let requests = channel.take_requests().unwrap_or_else(|err| {
+            warn!("failed to read MCP control requests: {err}");
+            Vec::new()
+        });
+        for (id, request) in requests {
+            let response = match request {
+                McpControlRequest::Restart { servers } => {
+                    restart_mcp_servers(&sess, &config, MCP_CONTROL_SUB_ID, &servers).await
+                }
+            };
+            if let Err(err) = channel.respond(&id, &response) {
+                warn!("failed to answer MCP control request {id}: {err}");
+            }
+        }
+        tokio::time::sleep(MCP_CONTROL_POLL_INTERVAL).await;
+    }
+}
+
 async fn run_turn(
     sess: Arc<Session>,
     turn_context: Arc<TurnContext>,
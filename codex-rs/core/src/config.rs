@@ -5,7 +5,9 @@ use crate::config_loader::merge_toml_values;
 use crate::config_profile::ConfigProfile;
 use crate::config_types::DEFAULT_OTEL_ENVIRONMENT;
 use crate::config_types::History;
+use crate::config_types::McpInheritEnv;
 use crate::config_types::McpServerConfig;
+use crate::config_types::McpServerDefaults;
 use crate::config_types::McpServerTransportConfig;
 use crate::config_types::Notice;
 use crate::config_types::Notifications;
@@ -23,6 +25,7 @@ use crate::features::FeatureOverrides;
 use crate::features::Features;
 use crate::features::FeaturesToml;
 use crate::git_info::resolve_root_git_project_for_trust;
+use crate::mcp::env_mask::default_env_mask_patterns;
 use crate::model_family::ModelFamily;
 use crate::model_family::derive_default_model_family;
 use crate::model_family::find_family_for_model;
@@ -44,12 +47,16 @@ use codex_rmcp_client::OAuthCredentialsStoreMode;
 use dirs::home_dir;
 use dunce::canonicalize;
 use serde::Deserialize;
+use serde::Serialize;
 use similar::DiffableStr;
 use std::collections::BTreeMap;
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::io::ErrorKind;
+use std::num::NonZeroUsize;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use tempfile::NamedTempFile;
 use toml::Value as TomlValue;
@@ -70,7 +77,7 @@ pub const GPT_5_CODEX_MEDIUM_MODEL: &str = "gpt-5-codex";
 /// the context window.
 pub(crate) const PROJECT_DOC_MAX_BYTES: usize = 32 * 1024; // 32 KiB
 
-pub(crate) const CONFIG_TOML_FILE: &str = "config.toml";
+pub const CONFIG_TOML_FILE: &str = "config.toml";
 
 /// Application configuration loaded from disk and merged with overrides.
 #[derive(Debug, Clone, PartialEq)]
@@ -167,6 +174,18 @@ pub struct Config {
     /// auto (default): keyring if available, otherwise file.
     pub mcp_oauth_credentials_store_mode: OAuthCredentialsStoreMode,
 
+    /// Glob patterns naming environment variables whose values MCP displays
+    /// mask. Defaults to [`crate::mcp::env_mask::DEFAULT_ENV_MASK_PATTERNS`].
+    pub mcp_env_mask_patterns: Vec<String>,
+
+    /// Most MCP servers initialized at once when a session starts; `None`
+    /// starts them all together.
+    pub mcp_startup_concurrency: Option<NonZeroUsize>,
+
+    /// The `-c key=value` overrides this config was loaded with, applied
+    /// again when a session reloads its MCP servers.
+    pub cli_overrides: Vec<(String, TomlValue)>,
+
     /// Combined provider map (defaults merged with user-defined overrides).
     pub model_providers: HashMap<String, ModelProviderInfo>,
 
@@ -265,7 +284,7 @@ impl Config {
 
         let root_value = load_resolved_config(
             &codex_home,
-            cli_overrides,
+            cli_overrides.clone(),
             crate::config_loader::LoaderOverrides::default(),
         )
         .await?;
@@ -275,7 +294,9 @@ impl Config {
             std::io::Error::new(std::io::ErrorKind::InvalidData, e)
         })?;
 
-        Self::load_from_base_config_with_overrides(cfg, overrides, codex_home)
+        let mut config = Self::load_from_base_config_with_overrides(cfg, overrides, codex_home)?;
+        config.cli_overrides = cli_overrides;
+        Ok(config)
     }
 }
 
@@ -344,6 +365,252 @@ pub async fn load_global_mcp_servers(
         .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
 }
 
+/// Parse a standalone file of MCP server definitions. Both TOML shapes are
+/// accepted and normalized to the canonical map:
+///
+/// * `[mcp_servers.<name>]` tables, as written to `config.toml`;
+/// * an `[[mcp_servers]]` array of tables, each with a `name` key.
+///
+/// Duplicate names in the array form are an error.
+pub fn parse_mcp_servers_toml(
+    contents: &str,
+) -> std::io::Result<BTreeMap<String, McpServerConfig>> {
+    let servers_value = mcp_servers_toml_table(contents)?;
+    ensure_no_inline_bearer_tokens(&servers_value)?;
+    servers_value
+        .try_into()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+/// Like [`parse_mcp_servers_toml`], but deserializes each server on its own
+/// so one malformed entry does not hide problems in the others. Problems with
+/// the file as a whole (invalid TOML, unnamed or duplicate array entries) are
+/// still returned as the outer error.
+pub fn parse_mcp_servers_toml_per_server(
+    contents: &str,
+) -> std::io::Result<BTreeMap<String, std::io::Result<McpServerConfig>>> {
+    let TomlValue::Table(servers) = mcp_servers_toml_table(contents)? else {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidData,
+            "mcp_servers must be a table or an array of tables",
+        ));
+    };
+    Ok(servers
+        .into_iter()
+        .map(|(name, value)| {
+            let single =
+                TomlValue::Table(toml::map::Map::from_iter([(name.clone(), value.clone())]));
+            let server = ensure_no_inline_bearer_tokens(&single).and_then(|()| {
+                value
+                    .try_into()
+                    .map_err(|e| std::io::Error::new(ErrorKind::InvalidData, e))
+            });
+            (name, server)
+        })
+        .collect())
+}
+
+/// The `mcp_servers` value of a standalone file, with the array-of-tables
+/// form converted to a table keyed by name.
+fn mcp_servers_toml_table(contents: &str) -> std::io::Result<TomlValue> {
+    let invalid = |message: String| std::io::Error::new(ErrorKind::InvalidData, message);
+
+    let root: TomlValue = toml::from_str(contents)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    match root.get("mcp_servers") {
+        None => Ok(TomlValue::Table(toml::map::Map::new())),
+        Some(TomlValue::Array(entries)) => {
+            let mut servers = toml::map::Map::new();
+            for (index, entry) in entries.iter().enumerate() {
+                let Some(mut entry) = entry.as_table().cloned() else {
+                    return Err(invalid(format!("mcp_servers[{index}] must be a table")));
+                };
+                let Some(TomlValue::String(name)) = entry.remove("name") else {
+                    return Err(invalid(format!(
+                        "mcp_servers[{index}] must have a string `name`"
+                    )));
+                };
+                if servers.contains_key(&name) {
+                    return Err(invalid(format!(
+                        "duplicate MCP server name '{name}' in mcp_servers[{index}]"
+                    )));
+                }
+                servers.insert(name, TomlValue::Table(entry));
+            }
+            Ok(TomlValue::Table(servers))
+        }
+        Some(servers_value) => Ok(servers_value.clone()),
+    }
+}
+
+/// Config layer that last defined or changed an MCP server, in increasing
+/// order of precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum McpServerSource {
+    /// `$CODEX_HOME/config.toml`.
+    UserConfig,
+    /// A `-c`/`--config` command-line override.
+    CliOverride,
+    /// The system-wide `managed_config.toml`.
+    ManagedConfig,
+    /// Managed device preferences (macOS only).
+    ManagedPreferences,
+}
+
+/// MCP settings resolved exactly as [`Config`] resolves them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct McpServersConfig {
+    pub servers: BTreeMap<String, McpServerConfig>,
+    /// Layer each entry of `servers` came from.
+    pub sources: BTreeMap<String, McpServerSource>,
+    pub oauth_credentials_store_mode: OAuthCredentialsStoreMode,
+    /// Top-level `mcp_default_*` settings for servers that do not set their
+    /// own. `servers` holds the definitions as written; see
+    /// [`McpServerConfig::with_defaults`].
+    pub server_defaults: McpServerDefaults,
+    /// `mcp_env_mask_patterns`, or the default list when unset.
+    pub env_mask_patterns: Vec<String>,
+}
+
+/// Load the merged MCP server map, with provenance, without building a full
+/// [`Config`]. This is the supported entry point for tools that only need
+/// MCP server settings: it reads the same layers and applies `cli_overrides`
+/// the same way, but skips validation of unrelated settings.
+pub async fn load_mcp_servers_with_cli_overrides(
+    codex_home: &Path,
+    cli_overrides: Vec<(String, TomlValue)>,
+) -> std::io::Result<McpServersConfig> {
+    load_mcp_servers_with_loader_overrides(
+        codex_home,
+        cli_overrides,
+        crate::config_loader::LoaderOverrides::default(),
+    )
+    .await
+}
+
+async fn load_mcp_servers_with_loader_overrides(
+    codex_home: &Path,
+    cli_overrides: Vec<(String, TomlValue)>,
+    overrides: crate::config_loader::LoaderOverrides,
+) -> std::io::Result<McpServersConfig> {
+    let LoadedConfigLayers {
+        mut base,
+        managed_config,
+        managed_preferences,
+    } = load_config_layers_with_overrides(codex_home, overrides).await?;
+
+    // Mirrors `apply_overlays`, noting which layer touched each server.
+    let mut sources = BTreeMap::new();
+    record_mcp_server_sources(&mut sources, None, &base, McpServerSource::UserConfig);
+    let before = base.get("mcp_servers").cloned();
+    for (path, value) in cli_overrides.into_iter() {
+        apply_toml_override(&mut base, &path, value);
+    }
+    record_mcp_server_sources(
+        &mut sources,
+        before.as_ref(),
+        &base,
+        McpServerSource::CliOverride,
+    );
+    for (overlay, source) in [
+        (managed_config, McpServerSource::ManagedConfig),
+        (managed_preferences, McpServerSource::ManagedPreferences),
+    ] {
+        if let Some(overlay) = overlay {
+            let before = base.get("mcp_servers").cloned();
+            merge_toml_values(&mut base, &overlay);
+            record_mcp_server_sources(&mut sources, before.as_ref(), &base, source);
+        }
+    }
+
+    let servers: BTreeMap<String, McpServerConfig> = match base.get("mcp_servers") {
+        Some(servers_value) => {
+            ensure_no_inline_bearer_tokens(servers_value)?;
+            servers_value
+                .clone()
+                .try_into()
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+        }
+        None => BTreeMap::new(),
+    };
+    sources.retain(|name, _| servers.contains_key(name));
+
+    let oauth_credentials_store_mode = base
+        .get("mcp_oauth_credentials_store")
+        .cloned()
+        .map(TomlValue::try_into)
+        .transpose()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+        .unwrap_or_default();
+
+    let server_defaults = McpServerDefaults {
+        startup_timeout_sec: mcp_default_timeout(&base, "mcp_default_startup_timeout_sec")?,
+        tool_timeout_sec: mcp_default_timeout(&base, "mcp_default_tool_timeout_sec")?,
+        inherit_env: base
+            .get("mcp_default_inherit_env")
+            .cloned()
+            .map(TomlValue::try_into)
+            .transpose()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?,
+    };
+
+    let env_mask_patterns = base
+        .get("mcp_env_mask_patterns")
+        .cloned()
+        .map(TomlValue::try_into)
+        .transpose()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+        .unwrap_or_else(default_env_mask_patterns);
+
+    Ok(McpServersConfig {
+        servers,
+        sources,
+        oauth_credentials_store_mode,
+        server_defaults,
+        env_mask_patterns,
+    })
+}
+
+/// Read a top-level `mcp_default_*_timeout_sec` key, in seconds.
+fn mcp_default_timeout(root: &TomlValue, key: &str) -> std::io::Result<Option<Duration>> {
+    let Some(value) = root.get(key) else {
+        return Ok(None);
+    };
+    let invalid = || {
+        std::io::Error::new(
+            ErrorKind::InvalidData,
+            format!("{key} must be a non-negative number of seconds"),
+        )
+    };
+    let secs = match value {
+        TomlValue::Float(secs) => *secs,
+        TomlValue::Integer(secs) => *secs as f64,
+        _ => return Err(invalid()),
+    };
+    Duration::try_from_secs_f64(secs)
+        .map(Some)
+        .map_err(|_| invalid())
+}
+
+/// Attribute to `source` every server whose definition in `root` differs
+/// from the `mcp_servers` table it had before the layer was applied.
+fn record_mcp_server_sources(
+    sources: &mut BTreeMap<String, McpServerSource>,
+    before: Option<&TomlValue>,
+    root: &TomlValue,
+    source: McpServerSource,
+) {
+    let Some(servers) = root.get("mcp_servers").and_then(TomlValue::as_table) else {
+        return;
+    };
+    for (name, value) in servers {
+        if before.and_then(|before| before.get(name)) != Some(value) {
+            sources.insert(name.clone(), source);
+        }
+    }
+}
+
 /// We briefly allowed plain text bearer_token fields in MCP server configs.
 /// We want to warn people who recently added these fields but can remove this after a few months.
 fn ensure_no_inline_bearer_tokens(value: &TomlValue) -> std::io::Result<()> {
@@ -356,7 +623,7 @@ fn ensure_no_inline_bearer_tokens(value: &TomlValue) -> std::io::Result<()> {
             && server_table.contains_key("bearer_token")
         {
             let message = format!(
-                "mcp_servers.{server_name} uses unsupported `bearer_token`; set `bearer_token_env_var`."
+                "mcp_servers.{server_name} uses unsupported `bearer_token`; set `bearer_token_env_var` or `bearer_token_file`."
             );
             return Err(std::io::Error::new(ErrorKind::InvalidData, message));
         }
@@ -378,6 +645,17 @@ pub fn write_global_mcp_servers(
         Err(e) => return Err(e),
     };
 
+    // The temp-file rename below would silently replace a read-only file, so
+    // refuse up front instead of clobbering a provisioned config.
+    if let Ok(metadata) = std::fs::metadata(&config_path)
+        && metadata.permissions().readonly()
+    {
+        return Err(std::io::Error::new(
+            ErrorKind::PermissionDenied,
+            format!("{} is read-only", config_path.display()),
+        ));
+    }
+
     doc.as_table_mut().remove("mcp_servers");
 
     if !servers.is_empty() {
@@ -395,6 +673,10 @@ pub fn write_global_mcp_servers(
                     env,
                     env_vars,
                     cwd,
+                    expand_env,
+                    expand_env_strict,
+                    shutdown_timeout_sec,
+                    inherit_env,
                 } => {
                     entry["command"] = toml_edit::value(command.clone());
 
@@ -419,25 +701,62 @@ pub fn write_global_mcp_servers(
                         entry["env"] = TomlItem::Table(env_table);
                     }
 
+                    // Hand edits and merges can leave duplicates or an
+                    // arbitrary order; write a canonical list so repeated
+                    // writes produce identical files.
                     if !env_vars.is_empty() {
+                        let env_vars: BTreeSet<&String> = env_vars.iter().collect();
                         entry["env_vars"] =
-                            TomlItem::Value(env_vars.iter().collect::<TomlArray>().into());
+                            TomlItem::Value(env_vars.into_iter().collect::<TomlArray>().into());
                     }
 
                     if let Some(cwd) = cwd {
                         entry["cwd"] = toml_edit::value(cwd.to_string_lossy().to_string());
                     }
+
+                    if *expand_env {
+                        entry["expand_env"] = toml_edit::value(true);
+                    }
+                    if *expand_env_strict {
+                        entry["expand_env_strict"] = toml_edit::value(true);
+                    }
+                    if let Some(timeout) = shutdown_timeout_sec {
+                        entry["shutdown_timeout_sec"] = toml_edit::value(timeout.as_secs_f64());
+                    }
+                    if let Some(inherit_env) = inherit_env {
+                        entry["inherit_env"] = toml_edit::value(inherit_env.as_str());
+                    }
                 }
                 McpServerTransportConfig::StreamableHttp {
                     url,
+                    url_env_var,
                     bearer_token_env_var,
+                    bearer_token_file,
                     http_headers,
                     env_http_headers,
+                    env_http_header_defaults,
+                    oauth_client_credentials,
+                    keep_alive,
+                    max_idle_connections,
+                    connect_timeout_sec,
+                    max_requests_per_sec,
+                    client_cert_path,
+                    client_key_path,
+                    ca_cert_path,
+                    health_check_interval_sec,
                 } => {
-                    entry["url"] = toml_edit::value(url.clone());
+                    if let Some(env_var) = url_env_var {
+                        entry["url_env_var"] = toml_edit::value(env_var.clone());
+                    } else {
+                        entry["url"] = toml_edit::value(url.clone());
+                    }
                     if let Some(env_var) = bearer_token_env_var {
                         entry["bearer_token_env_var"] = toml_edit::value(env_var.clone());
                     }
+                    if let Some(path) = bearer_token_file {
+                        entry["bearer_token_file"] =
+                            toml_edit::value(path.to_string_lossy().to_string());
+                    }
                     if let Some(headers) = http_headers
                         && !headers.is_empty()
                     {
@@ -462,6 +781,60 @@ pub fn write_global_mcp_servers(
                         }
                         entry["env_http_headers"] = TomlItem::Table(table);
                     }
+                    if let Some(defaults) = env_http_header_defaults
+                        && !defaults.is_empty()
+                    {
+                        let mut table = TomlTable::new();
+                        table.set_implicit(false);
+                        let mut pairs: Vec<_> = defaults.iter().collect();
+                        pairs.sort_by(|(a, _), (b, _)| a.cmp(b));
+                        for (key, value) in pairs {
+                            table.insert(key, toml_edit::value(value.clone()));
+                        }
+                        entry["env_http_header_defaults"] = TomlItem::Table(table);
+                    }
+                    if !*keep_alive {
+                        entry["keep_alive"] = toml_edit::value(false);
+                    }
+                    if let Some(max) = max_idle_connections {
+                        entry["max_idle_connections"] = toml_edit::value(*max as i64);
+                    }
+                    if let Some(timeout) = connect_timeout_sec {
+                        entry["connect_timeout_sec"] = toml_edit::value(timeout.as_secs_f64());
+                    }
+                    if let Some(interval) = health_check_interval_sec {
+                        entry["health_check_interval_sec"] =
+                            toml_edit::value(interval.as_secs_f64());
+                    }
+                    if let Some(rate) = max_requests_per_sec {
+                        entry["max_requests_per_sec"] = toml_edit::value(*rate);
+                    }
+                    for (key, path) in [
+                        ("client_cert_path", client_cert_path),
+                        ("client_key_path", client_key_path),
+                        ("ca_cert_path", ca_cert_path),
+                    ] {
+                        if let Some(path) = path {
+                            entry[key] = toml_edit::value(path.to_string_lossy().to_string());
+                        }
+                    }
+                    if let Some(oauth) = oauth_client_credentials {
+                        let mut table = TomlTable::new();
+                        table.set_implicit(false);
+                        table.insert("token_url", toml_edit::value(oauth.token_url.clone()));
+                        table.insert("client_id", toml_edit::value(oauth.client_id.clone()));
+                        table.insert(
+                            "client_secret_env_var",
+                            toml_edit::value(oauth.client_secret_env_var.clone()),
+                        );
+                        if !oauth.scopes.is_empty() {
+                            table.insert(
+                                "scopes",
+                                TomlItem::Value(oauth.scopes.iter().collect::<TomlArray>().into()),
+                            );
+                        }
+                        entry["oauth_client_credentials"] = TomlItem::Table(table);
+                    }
                 }
             }
 
@@ -469,6 +842,32 @@ pub fn write_global_mcp_servers(
                 entry["enabled"] = toml_edit::value(false);
             }
 
+            if let Some(description) = &config.description {
+                entry["description"] = toml_edit::value(description.clone());
+            }
+
+            if !config.tags.is_empty() {
+                let mut seen = BTreeSet::new();
+                let tags: TomlArray = config
+                    .tags
+                    .iter()
+                    .filter(|tag| seen.insert(tag.as_str()))
+                    .collect();
+                entry["tags"] = TomlItem::Value(tags.into());
+            }
+
+            if let Some(condition) = &config.enabled_when {
+                let mut table = TomlTable::new();
+                table.set_implicit(false);
+                if let Some(os) = &condition.os {
+                    table.insert("os", toml_edit::value(os.clone()));
+                }
+                if let Some(env) = &condition.env {
+                    table.insert("env", toml_edit::value(env.clone()));
+                }
+                entry["enabled_when"] = TomlItem::Table(table);
+            }
+
             if let Some(timeout) = config.startup_timeout_sec {
                 entry["startup_timeout_sec"] = toml_edit::value(timeout.as_secs_f64());
             }
@@ -477,6 +876,43 @@ pub fn write_global_mcp_servers(
                 entry["tool_timeout_sec"] = toml_edit::value(timeout.as_secs_f64());
             }
 
+            if let Some(timeouts) = &config.tool_timeouts {
+                let mut table = TomlTable::new();
+                table.set_implicit(false);
+                let mut pairs: Vec<_> = timeouts.iter().collect();
+                pairs.sort_by(|(a, _), (b, _)| a.cmp(b));
+                for (tool, timeout) in pairs {
+                    table.insert(tool, toml_edit::value(timeout.as_secs_f64()));
+                }
+                entry["tool_timeouts"] = TomlItem::Table(table);
+            }
+
+            if let Some(allowed_tools) = &config.allowed_tools {
+                entry["allowed_tools"] =
+                    TomlItem::Value(allowed_tools.iter().collect::<TomlArray>().into());
+            }
+
+            if let Some(denied_tools) = &config.denied_tools {
+                entry["denied_tools"] =
+                    TomlItem::Value(denied_tools.iter().collect::<TomlArray>().into());
+            }
+
+            if let Some(version) = &config.protocol_version {
+                entry["protocol_version"] = toml_edit::value(version.clone());
+            }
+
+            if let Some(priority) = config.startup_priority {
+                entry["startup_priority"] = toml_edit::value(i64::from(priority));
+            }
+
+            if let Some(client_name) = &config.client_name {
+                entry["client_name"] = toml_edit::value(client_name.clone());
+            }
+
+            if let Some(client_version) = &config.client_version {
+                entry["client_version"] = toml_edit::value(client_version.clone());
+            }
+
             doc["mcp_servers"][name.as_str()] = TomlItem::Table(entry);
         }
     }
@@ -856,6 +1292,31 @@ pub struct ConfigToml {
     #[serde(default)]
     pub mcp_oauth_credentials_store: Option<OAuthCredentialsStoreMode>,
 
+    /// Startup timeout, in seconds, for MCP servers that do not set their own
+    /// `startup_timeout_sec`.
+    #[serde(default, with = "crate::config_types::option_duration_secs")]
+    pub mcp_default_startup_timeout_sec: Option<Duration>,
+
+    /// Tool call timeout, in seconds, for MCP servers that do not set their
+    /// own `tool_timeout_sec`.
+    #[serde(default, with = "crate::config_types::option_duration_secs")]
+    pub mcp_default_tool_timeout_sec: Option<Duration>,
+
+    /// Which of Codex's environment variables stdio MCP servers that do not
+    /// set their own `inherit_env` receive.
+    #[serde(default)]
+    pub mcp_default_inherit_env: Option<McpInheritEnv>,
+
+    /// Glob patterns (case-insensitive) naming environment variables whose
+    /// values `codex mcp env` and `/mcp` mask. Replaces the default list.
+    #[serde(default)]
+    pub mcp_env_mask_patterns: Option<Vec<String>>,
+
+    /// Most MCP servers to initialize at once at session start. Servers
+    /// wait their turn in `startup_priority` order. Unset means no limit.
+    #[serde(default)]
+    pub mcp_startup_concurrency: Option<NonZeroUsize>,
+
     /// User-defined provider entries that extend/override the built-in list.
     #[serde(default)]
     pub model_providers: HashMap<String, ModelProviderInfo>,
@@ -1242,6 +1703,12 @@ impl Config {
 
         let history = cfg.history.unwrap_or_default();
 
+        let mcp_server_defaults = McpServerDefaults {
+            startup_timeout_sec: cfg.mcp_default_startup_timeout_sec,
+            tool_timeout_sec: cfg.mcp_default_tool_timeout_sec,
+            inherit_env: cfg.mcp_default_inherit_env,
+        };
+
         let include_plan_tool_flag = features.enabled(Feature::PlanTool);
         let include_apply_patch_tool_flag = features.enabled(Feature::ApplyPatchFreeform);
         let include_view_image_tool_flag = features.enabled(Feature::ViewImageTool);
@@ -1317,10 +1784,19 @@ impl Config {
             notify: cfg.notify,
             user_instructions,
             base_instructions,
-            mcp_servers: cfg.mcp_servers,
+            mcp_servers: cfg
+                .mcp_servers
+                .into_iter()
+                .map(|(name, server)| (name, server.with_defaults(&mcp_server_defaults)))
+                .collect(),
             // The config.toml omits "_mode" because it's a config file. However, "_mode"
             // is important in code to differentiate the mode from the store implementation.
             mcp_oauth_credentials_store_mode: cfg.mcp_oauth_credentials_store.unwrap_or_default(),
+            mcp_env_mask_patterns: cfg
+                .mcp_env_mask_patterns
+                .unwrap_or_else(default_env_mask_patterns),
+            mcp_startup_concurrency: cfg.mcp_startup_concurrency,
+            cli_overrides: Vec::new(),
             model_providers,
             project_doc_max_bytes: cfg.project_doc_max_bytes.unwrap_or(PROJECT_DOC_MAX_BYTES),
             project_doc_fallback_filenames: cfg
@@ -1486,6 +1962,26 @@ pub fn find_codex_home() -> std::io::Result<PathBuf> {
     Ok(p)
 }
 
+/// Like [`find_codex_home`], but creates the directory when it does not exist
+/// yet so commands that write configuration work on a fresh install.
+pub fn find_or_create_codex_home() -> std::io::Result<PathBuf> {
+    match find_codex_home() {
+        Ok(codex_home) => {
+            std::fs::create_dir_all(&codex_home)?;
+            Ok(codex_home)
+        }
+        // `CODEX_HOME` is canonicalized, which fails until the directory exists.
+        Err(err) if err.kind() == ErrorKind::NotFound => match std::env::var("CODEX_HOME") {
+            Ok(val) if !val.is_empty() => {
+                std::fs::create_dir_all(&val)?;
+                PathBuf::from(val).canonicalize()
+            }
+            _ => Err(err),
+        },
+        Err(err) => Err(err),
+    }
+}
+
 /// Returns the path to the folder where Codex logs are stored. Does not verify
 /// that the directory exists.
 pub fn log_dir(cfg: &Config) -> std::io::Result<PathBuf> {
@@ -1497,6 +1993,7 @@ pub fn log_dir(cfg: &Config) -> std::io::Result<PathBuf> {
 #[cfg(test)]
 mod tests {
     use crate::config_types::HistoryPersistence;
+    use crate::config_types::McpEnabledWhen;
     use crate::config_types::Notifications;
     use crate::features::Feature;
 
@@ -1864,6 +2361,256 @@ approve_all = true
         Ok(())
     }
 
+    #[tokio::test]
+    async fn load_mcp_servers_reports_source_of_each_server() -> anyhow::Result<()> {
+        let codex_home = TempDir::new()?;
+        let managed_path = codex_home.path().join("managed_config.toml");
+        std::fs::write(
+            codex_home.path().join(CONFIG_TOML_FILE),
+            r#"
+[mcp_servers.docs]
+command = "docs-server"
+
+[mcp_servers.search]
+command = "search-server"
+"#,
+        )?;
+        std::fs::write(
+            &managed_path,
+            r#"
+mcp_oauth_credentials_store = "file"
+
+[mcp_servers.audit]
+url = "https://audit.example.com/mcp"
+"#,
+        )?;
+        let overrides = crate::config_loader::LoaderOverrides {
+            managed_config_path: Some(managed_path),
+            #[cfg(target_os = "macos")]
+            managed_preferences_base64: None,
+        };
+
+        let loaded = load_mcp_servers_with_loader_overrides(
+            codex_home.path(),
+            vec![(
+                "mcp_servers.search.enabled".to_string(),
+                TomlValue::Boolean(false),
+            )],
+            overrides,
+        )
+        .await?;
+
+        assert_eq!(
+            loaded.sources,
+            BTreeMap::from([
+                ("audit".to_string(), McpServerSource::ManagedConfig),
+                ("docs".to_string(), McpServerSource::UserConfig),
+                ("search".to_string(), McpServerSource::CliOverride),
+            ])
+        );
+        assert!(!loaded.servers["search"].enabled);
+        assert_eq!(
+            loaded.oauth_credentials_store_mode,
+            OAuthCredentialsStoreMode::File
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn mcp_default_timeouts_fill_unset_server_timeouts() -> anyhow::Result<()> {
+        let codex_home = TempDir::new()?;
+        let contents = r#"
+mcp_default_startup_timeout_sec = 20
+mcp_default_tool_timeout_sec = 90.5
+
+[mcp_servers.docs]
+command = "docs-server"
+
+[mcp_servers.search]
+command = "search-server"
+startup_timeout_sec = 5
+"#;
+        std::fs::write(codex_home.path().join(CONFIG_TOML_FILE), contents)?;
+
+        let loaded = load_mcp_servers_with_cli_overrides(codex_home.path(), Vec::new()).await?;
+        assert_eq!(
+            loaded.server_defaults,
+            McpServerDefaults {
+                startup_timeout_sec: Some(Duration::from_secs(20)),
+                tool_timeout_sec: Some(Duration::from_secs_f64(90.5)),
+                inherit_env: None,
+            }
+        );
+        // Definitions are reported as written.
+        assert_eq!(loaded.servers["docs"].startup_timeout_sec, None);
+
+        let config = Config::load_from_base_config_with_overrides(
+            toml::from_str::<ConfigToml>(contents)?,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )?;
+        let docs = &config.mcp_servers["docs"];
+        assert_eq!(docs.startup_timeout_sec, Some(Duration::from_secs(20)));
+        assert_eq!(docs.tool_timeout_sec, Some(Duration::from_secs_f64(90.5)));
+        let search = &config.mcp_servers["search"];
+        assert_eq!(search.startup_timeout_sec, Some(Duration::from_secs(5)));
+        assert_eq!(search.tool_timeout_sec, Some(Duration::from_secs_f64(90.5)));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn mcp_default_inherit_env_fills_unset_stdio_servers() -> anyhow::Result<()> {
+        let codex_home = TempDir::new()?;
+        let contents = r#"
+mcp_default_inherit_env = "minimal"
+
+[mcp_servers.docs]
+command = "docs-server"
+
+[mcp_servers.shell]
+command = "shell-server"
+inherit_env = "all"
+"#;
+        std::fs::write(codex_home.path().join(CONFIG_TOML_FILE), contents)?;
+
+        let loaded = load_mcp_servers_with_cli_overrides(codex_home.path(), Vec::new()).await?;
+        assert_eq!(
+            loaded.server_defaults.inherit_env,
+            Some(McpInheritEnv::Minimal)
+        );
+
+        let config = Config::load_from_base_config_with_overrides(
+            toml::from_str::<ConfigToml>(contents)?,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )?;
+        let inherit_env = |name: &str| match &config.mcp_servers[name].transport {
+            McpServerTransportConfig::Stdio { inherit_env, .. } => *inherit_env,
+            other => panic!("unexpected transport {other:?}"),
+        };
+        assert_eq!(inherit_env("docs"), Some(McpInheritEnv::Minimal));
+        assert_eq!(inherit_env("shell"), Some(McpInheritEnv::All));
+
+        Ok(())
+    }
+
+    #[test]
+    fn mcp_startup_concurrency_must_be_positive() -> anyhow::Result<()> {
+        let cfg = toml::from_str::<ConfigToml>("mcp_startup_concurrency = 2")?;
+        assert_eq!(cfg.mcp_startup_concurrency, NonZeroUsize::new(2));
+
+        let err = toml::from_str::<ConfigToml>("mcp_startup_concurrency = 0")
+            .expect_err("zero should be rejected");
+        assert!(
+            err.to_string().contains("nonzero"),
+            "unexpected error: {err}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_mcp_servers_toml_accepts_map_and_array_shapes() -> anyhow::Result<()> {
+        let map_form = parse_mcp_servers_toml(
+            r#"
+[mcp_servers.docs]
+command = "docs-server"
+args = ["--port", "4000"]
+
+[mcp_servers.search]
+url = "https://search.example.com/mcp"
+enabled = false
+"#,
+        )?;
+        let array_form = parse_mcp_servers_toml(
+            r#"
+[[mcp_servers]]
+name = "search"
+url = "https://search.example.com/mcp"
+enabled = false
+
+[[mcp_servers]]
+name = "docs"
+command = "docs-server"
+args = ["--port", "4000"]
+"#,
+        )?;
+
+        assert_eq!(array_form, map_form);
+        assert_eq!(map_form.keys().collect::<Vec<_>>(), vec!["docs", "search"]);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_mcp_servers_toml_rejects_duplicate_and_unnamed_entries() {
+        let duplicate = parse_mcp_servers_toml(
+            r#"
+[[mcp_servers]]
+name = "docs"
+command = "a"
+
+[[mcp_servers]]
+name = "docs"
+command = "b"
+"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            duplicate.to_string(),
+            "duplicate MCP server name 'docs' in mcp_servers[1]"
+        );
+
+        let unnamed = parse_mcp_servers_toml(
+            r#"
+[[mcp_servers]]
+command = "a"
+"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            unnamed.to_string(),
+            "mcp_servers[0] must have a string `name`"
+        );
+    }
+
+    #[test]
+    fn parse_mcp_servers_toml_per_server_isolates_bad_entries() -> anyhow::Result<()> {
+        let servers = parse_mcp_servers_toml_per_server(
+            r#"
+[mcp_servers.docs]
+command = "docs-server"
+
+[mcp_servers.broken]
+url = "https://example.com/mcp"
+env = { "FOO" = "BAR" }
+
+[mcp_servers.leaky]
+url = "https://example.com/mcp"
+bearer_token = "secret"
+"#,
+        )?;
+
+        assert_eq!(
+            servers.keys().map(String::as_str).collect::<Vec<_>>(),
+            vec!["broken", "docs", "leaky"]
+        );
+        assert!(servers["docs"].is_ok());
+        let broken = servers["broken"].as_ref().unwrap_err().to_string();
+        assert!(
+            broken.contains("env is not supported for streamable_http"),
+            "unexpected error: {broken}"
+        );
+        let leaky = servers["leaky"].as_ref().unwrap_err().to_string();
+        assert!(
+            leaky.contains("bearer_token_env_var"),
+            "unexpected error: {leaky}"
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn load_global_mcp_servers_returns_empty_if_missing() -> anyhow::Result<()> {
         let codex_home = TempDir::new()?;
@@ -1888,10 +2635,24 @@ approve_all = true
                     env: None,
                     env_vars: Vec::new(),
                     cwd: None,
+                    expand_env: false,
+                    expand_env_strict: false,
+                    shutdown_timeout_sec: None,
+                    inherit_env: None,
                 },
                 enabled: true,
                 startup_timeout_sec: Some(Duration::from_secs(3)),
                 tool_timeout_sec: Some(Duration::from_secs(5)),
+                allowed_tools: None,
+                denied_tools: None,
+                protocol_version: None,
+                enabled_when: None,
+                description: None,
+                startup_priority: None,
+                client_name: None,
+                client_version: None,
+                tool_timeouts: None,
+                tags: Vec::new(),
             },
         );
 
@@ -1907,6 +2668,7 @@ approve_all = true
                 env,
                 env_vars,
                 cwd,
+                ..
             } => {
                 assert_eq!(command, "echo");
                 assert_eq!(args, &vec!["hello".to_string()]);
@@ -2024,10 +2786,24 @@ bearer_token = "secret"
                     ])),
                     env_vars: Vec::new(),
                     cwd: None,
+                    expand_env: false,
+                    expand_env_strict: false,
+                    shutdown_timeout_sec: None,
+                    inherit_env: None,
                 },
                 enabled: true,
                 startup_timeout_sec: None,
                 tool_timeout_sec: None,
+                allowed_tools: None,
+                denied_tools: None,
+                protocol_version: None,
+                enabled_when: None,
+                description: None,
+                startup_priority: None,
+                client_name: None,
+                client_version: None,
+                tool_timeouts: None,
+                tags: Vec::new(),
             },
         )]);
 
@@ -2056,6 +2832,7 @@ ZIG_VAR = "3"
                 env,
                 env_vars,
                 cwd,
+                ..
             } => {
                 assert_eq!(command, "docs-server");
                 assert_eq!(args, &vec!["--verbose".to_string()]);
@@ -2086,10 +2863,24 @@ ZIG_VAR = "3"
                     env: None,
                     env_vars: vec!["ALPHA".to_string(), "BETA".to_string()],
                     cwd: None,
+                    expand_env: false,
+                    expand_env_strict: false,
+                    shutdown_timeout_sec: None,
+                    inherit_env: None,
                 },
                 enabled: true,
                 startup_timeout_sec: None,
                 tool_timeout_sec: None,
+                allowed_tools: None,
+                denied_tools: None,
+                protocol_version: None,
+                enabled_when: None,
+                description: None,
+                startup_priority: None,
+                client_name: None,
+                client_version: None,
+                tool_timeouts: None,
+                tags: Vec::new(),
             },
         )]);
 
@@ -2114,6 +2905,64 @@ ZIG_VAR = "3"
         Ok(())
     }
 
+    #[tokio::test]
+    async fn write_global_mcp_servers_is_deterministic_for_env_vars() -> anyhow::Result<()> {
+        let codex_home = TempDir::new()?;
+        let config_path = codex_home.path().join(CONFIG_TOML_FILE);
+
+        let server = |env_vars: &[&str]| McpServerConfig {
+            transport: McpServerTransportConfig::Stdio {
+                command: "docs-server".to_string(),
+                args: Vec::new(),
+                env: Some(HashMap::from([
+                    ("B".to_string(), "2".to_string()),
+                    ("A".to_string(), "1".to_string()),
+                ])),
+                env_vars: env_vars.iter().map(ToString::to_string).collect(),
+                cwd: None,
+                expand_env: false,
+                expand_env_strict: false,
+                shutdown_timeout_sec: None,
+                inherit_env: None,
+            },
+            enabled: true,
+            startup_timeout_sec: None,
+            tool_timeout_sec: None,
+            allowed_tools: None,
+            denied_tools: None,
+            protocol_version: None,
+            enabled_when: None,
+            description: None,
+            startup_priority: None,
+            client_name: None,
+            client_version: None,
+            tool_timeouts: None,
+            tags: Vec::new(),
+        };
+
+        let servers = BTreeMap::from([(
+            "docs".to_string(),
+            server(&["TOKEN", "HOME", "TOKEN", "home"]),
+        )]);
+        write_global_mcp_servers(codex_home.path(), &servers)?;
+        let first = std::fs::read(&config_path)?;
+        assert!(
+            String::from_utf8_lossy(&first).contains(r#"env_vars = ["HOME", "TOKEN", "home"]"#),
+            "env_vars should be sorted and deduped:\n{}",
+            String::from_utf8_lossy(&first)
+        );
+
+        let reordered = BTreeMap::from([("docs".to_string(), server(&["home", "TOKEN", "HOME"]))]);
+        write_global_mcp_servers(codex_home.path(), &reordered)?;
+        assert_eq!(std::fs::read(&config_path)?, first);
+
+        let reloaded = load_global_mcp_servers(codex_home.path()).await?;
+        write_global_mcp_servers(codex_home.path(), &reloaded)?;
+        assert_eq!(std::fs::read(&config_path)?, first);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn write_global_mcp_servers_serializes_cwd() -> anyhow::Result<()> {
         let codex_home = TempDir::new()?;
@@ -2128,10 +2977,24 @@ ZIG_VAR = "3"
                     env: None,
                     env_vars: Vec::new(),
                     cwd: Some(cwd_path.clone()),
+                    expand_env: false,
+                    expand_env_strict: false,
+                    shutdown_timeout_sec: None,
+                    inherit_env: None,
                 },
                 enabled: true,
                 startup_timeout_sec: None,
                 tool_timeout_sec: None,
+                allowed_tools: None,
+                denied_tools: None,
+                protocol_version: None,
+                enabled_when: None,
+                description: None,
+                startup_priority: None,
+                client_name: None,
+                client_version: None,
+                tool_timeouts: None,
+                tags: Vec::new(),
             },
         )]);
 
@@ -2156,6 +3019,55 @@ ZIG_VAR = "3"
         Ok(())
     }
 
+    #[tokio::test]
+    async fn write_global_mcp_servers_serializes_shutdown_timeout() -> anyhow::Result<()> {
+        let codex_home = TempDir::new()?;
+
+        let servers = BTreeMap::from([(
+            "docs".to_string(),
+            McpServerConfig {
+                transport: McpServerTransportConfig::Stdio {
+                    command: "docs-server".to_string(),
+                    args: Vec::new(),
+                    env: None,
+                    env_vars: Vec::new(),
+                    cwd: None,
+                    expand_env: false,
+                    expand_env_strict: false,
+                    shutdown_timeout_sec: Some(Duration::from_secs(5)),
+                    inherit_env: None,
+                },
+                enabled: true,
+                startup_timeout_sec: None,
+                tool_timeout_sec: None,
+                allowed_tools: None,
+                denied_tools: None,
+                protocol_version: None,
+                enabled_when: None,
+                description: None,
+                startup_priority: None,
+                client_name: None,
+                client_version: None,
+                tool_timeouts: None,
+                tags: Vec::new(),
+            },
+        )]);
+
+        write_global_mcp_servers(codex_home.path(), &servers)?;
+
+        let config_path = codex_home.path().join(CONFIG_TOML_FILE);
+        let serialized = std::fs::read_to_string(&config_path)?;
+        assert!(
+            serialized.contains("shutdown_timeout_sec = 5.0"),
+            "serialized config missing shutdown_timeout_sec:\n{serialized}"
+        );
+
+        let loaded = load_global_mcp_servers(codex_home.path()).await?;
+        assert_eq!(loaded, servers);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn write_global_mcp_servers_streamable_http_serializes_bearer_token() -> anyhow::Result<()>
     {
@@ -2166,13 +3078,35 @@ ZIG_VAR = "3"
             McpServerConfig {
                 transport: McpServerTransportConfig::StreamableHttp {
                     url: "https://example.com/mcp".to_string(),
+                    url_env_var: None,
                     bearer_token_env_var: Some("MCP_TOKEN".to_string()),
+                    bearer_token_file: None,
                     http_headers: None,
                     env_http_headers: None,
+                    env_http_header_defaults: None,
+                    oauth_client_credentials: None,
+                    keep_alive: true,
+                    max_idle_connections: None,
+                    connect_timeout_sec: None,
+                    max_requests_per_sec: None,
+                    client_cert_path: None,
+                    client_key_path: None,
+                    ca_cert_path: None,
+                    health_check_interval_sec: None,
                 },
                 enabled: true,
                 startup_timeout_sec: Some(Duration::from_secs(2)),
                 tool_timeout_sec: None,
+                allowed_tools: None,
+                denied_tools: None,
+                protocol_version: None,
+                enabled_when: None,
+                description: None,
+                startup_priority: None,
+                client_name: None,
+                client_version: None,
+                tool_timeouts: None,
+                tags: Vec::new(),
             },
         )]);
 
@@ -2197,6 +3131,8 @@ startup_timeout_sec = 2.0
                 bearer_token_env_var,
                 http_headers,
                 env_http_headers,
+                oauth_client_credentials: None,
+                ..
             } => {
                 assert_eq!(url, "https://example.com/mcp");
                 assert_eq!(bearer_token_env_var.as_deref(), Some("MCP_TOKEN"));
@@ -2220,16 +3156,41 @@ startup_timeout_sec = 2.0
             McpServerConfig {
                 transport: McpServerTransportConfig::StreamableHttp {
                     url: "https://example.com/mcp".to_string(),
+                    url_env_var: None,
                     bearer_token_env_var: Some("MCP_TOKEN".to_string()),
+                    bearer_token_file: None,
                     http_headers: Some(HashMap::from([("X-Doc".to_string(), "42".to_string())])),
                     env_http_headers: Some(HashMap::from([(
                         "X-Auth".to_string(),
                         "DOCS_AUTH".to_string(),
                     )])),
+                    env_http_header_defaults: Some(HashMap::from([(
+                        "X-Auth".to_string(),
+                        "anonymous".to_string(),
+                    )])),
+                    oauth_client_credentials: None,
+                    keep_alive: true,
+                    max_idle_connections: None,
+                    connect_timeout_sec: None,
+                    max_requests_per_sec: None,
+                    client_cert_path: None,
+                    client_key_path: None,
+                    ca_cert_path: None,
+                    health_check_interval_sec: None,
                 },
                 enabled: true,
                 startup_timeout_sec: Some(Duration::from_secs(2)),
                 tool_timeout_sec: None,
+                allowed_tools: None,
+                denied_tools: None,
+                protocol_version: None,
+                enabled_when: None,
+                description: None,
+                startup_priority: None,
+                client_name: None,
+                client_version: None,
+                tool_timeouts: None,
+                tags: Vec::new(),
             },
         )]);
         write_global_mcp_servers(codex_home.path(), &servers)?;
@@ -2248,6 +3209,9 @@ X-Doc = "42"
 
 [mcp_servers.docs.env_http_headers]
 X-Auth = "DOCS_AUTH"
+
+[mcp_servers.docs.env_http_header_defaults]
+X-Auth = "anonymous"
 "#
         );
 
@@ -2257,6 +3221,7 @@ X-Auth = "DOCS_AUTH"
             McpServerTransportConfig::StreamableHttp {
                 http_headers,
                 env_http_headers,
+                env_http_header_defaults,
                 ..
             } => {
                 assert_eq!(
@@ -2270,6 +3235,13 @@ X-Auth = "DOCS_AUTH"
                         "DOCS_AUTH".to_string()
                     )]))
                 );
+                assert_eq!(
+                    env_http_header_defaults,
+                    &Some(HashMap::from([(
+                        "X-Auth".to_string(),
+                        "anonymous".to_string()
+                    )]))
+                );
             }
             other => panic!("unexpected transport {other:?}"),
         }
@@ -2277,6 +3249,181 @@ X-Auth = "DOCS_AUTH"
         Ok(())
     }
 
+    #[tokio::test]
+    async fn write_global_mcp_servers_streamable_http_url_env_var() -> anyhow::Result<()> {
+        let codex_home = TempDir::new()?;
+
+        let servers = BTreeMap::from([(
+            "docs".to_string(),
+            McpServerConfig {
+                transport: McpServerTransportConfig::StreamableHttp {
+                    url: String::new(),
+                    url_env_var: Some("DOCS_MCP_URL".to_string()),
+                    bearer_token_env_var: None,
+                    bearer_token_file: None,
+                    http_headers: None,
+                    env_http_headers: None,
+                    env_http_header_defaults: None,
+                    oauth_client_credentials: None,
+                    keep_alive: true,
+                    max_idle_connections: None,
+                    connect_timeout_sec: None,
+                    max_requests_per_sec: None,
+                    client_cert_path: None,
+                    client_key_path: None,
+                    ca_cert_path: None,
+                    health_check_interval_sec: None,
+                },
+                enabled: true,
+                startup_timeout_sec: None,
+                tool_timeout_sec: None,
+                allowed_tools: None,
+                denied_tools: None,
+                protocol_version: None,
+                enabled_when: None,
+                description: None,
+                startup_priority: None,
+                client_name: None,
+                client_version: None,
+                tool_timeouts: None,
+                tags: Vec::new(),
+            },
+        )]);
+
+        write_global_mcp_servers(codex_home.path(), &servers)?;
+
+        let serialized = std::fs::read_to_string(codex_home.path().join(CONFIG_TOML_FILE))?;
+        assert_eq!(
+            serialized,
+            r#"[mcp_servers.docs]
+url_env_var = "DOCS_MCP_URL"
+"#
+        );
+
+        let loaded = load_global_mcp_servers(codex_home.path()).await?;
+        assert_eq!(loaded, servers);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn write_global_mcp_servers_serializes_enabled_when_and_description() -> anyhow::Result<()>
+    {
+        let codex_home = TempDir::new()?;
+
+        let servers = BTreeMap::from([(
+            "docs".to_string(),
+            McpServerConfig {
+                transport: McpServerTransportConfig::Stdio {
+                    command: "docs-server".to_string(),
+                    args: Vec::new(),
+                    env: None,
+                    env_vars: Vec::new(),
+                    cwd: None,
+                    expand_env: false,
+                    expand_env_strict: false,
+                    shutdown_timeout_sec: None,
+                    inherit_env: None,
+                },
+                enabled: true,
+                startup_timeout_sec: None,
+                tool_timeout_sec: None,
+                allowed_tools: None,
+                denied_tools: None,
+                protocol_version: None,
+                enabled_when: Some(McpEnabledWhen {
+                    os: Some("macos".to_string()),
+                    env: Some("DOCS_TOKEN".to_string()),
+                }),
+                description: Some("Internal docs search".to_string()),
+                startup_priority: None,
+                client_name: None,
+                client_version: None,
+                tool_timeouts: None,
+                tags: Vec::new(),
+            },
+        )]);
+
+        write_global_mcp_servers(codex_home.path(), &servers)?;
+
+        let serialized = std::fs::read_to_string(codex_home.path().join(CONFIG_TOML_FILE))?;
+        assert_eq!(
+            serialized,
+            r#"[mcp_servers.docs]
+command = "docs-server"
+description = "Internal docs search"
+
+[mcp_servers.docs.enabled_when]
+os = "macos"
+env = "DOCS_TOKEN"
+"#
+        );
+
+        let loaded = load_global_mcp_servers(codex_home.path()).await?;
+        assert_eq!(loaded, servers);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn write_global_mcp_servers_streamable_http_serializes_connect_timeout()
+    -> anyhow::Result<()> {
+        let codex_home = TempDir::new()?;
+
+        let servers = BTreeMap::from([(
+            "docs".to_string(),
+            McpServerConfig {
+                transport: McpServerTransportConfig::StreamableHttp {
+                    url: "https://example.com/mcp".to_string(),
+                    url_env_var: None,
+                    bearer_token_env_var: None,
+                    bearer_token_file: None,
+                    http_headers: None,
+                    env_http_headers: None,
+                    env_http_header_defaults: None,
+                    oauth_client_credentials: None,
+                    keep_alive: true,
+                    max_idle_connections: None,
+                    connect_timeout_sec: Some(Duration::from_millis(1500)),
+                    max_requests_per_sec: None,
+                    client_cert_path: None,
+                    client_key_path: None,
+                    ca_cert_path: None,
+                    health_check_interval_sec: None,
+                },
+                enabled: true,
+                startup_timeout_sec: None,
+                tool_timeout_sec: None,
+                allowed_tools: None,
+                denied_tools: None,
+                protocol_version: None,
+                enabled_when: None,
+                description: None,
+                startup_priority: None,
+                client_name: None,
+                client_version: None,
+                tool_timeouts: None,
+                tags: Vec::new(),
+            },
+        )]);
+
+        write_global_mcp_servers(codex_home.path(), &servers)?;
+
+        let serialized = std::fs::read_to_string(codex_home.path().join(CONFIG_TOML_FILE))?;
+        assert_eq!(
+            serialized,
+            r#"[mcp_servers.docs]
+url = "https://example.com/mcp"
+connect_timeout_sec = 1.5
+"#
+        );
+
+        let loaded = load_global_mcp_servers(codex_home.path()).await?;
+        assert_eq!(loaded, servers);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn write_global_mcp_servers_streamable_http_removes_optional_sections()
     -> anyhow::Result<()> {
@@ -2289,16 +3436,38 @@ X-Auth = "DOCS_AUTH"
             McpServerConfig {
                 transport: McpServerTransportConfig::StreamableHttp {
                     url: "https://example.com/mcp".to_string(),
+                    url_env_var: None,
                     bearer_token_env_var: Some("MCP_TOKEN".to_string()),
+                    bearer_token_file: None,
                     http_headers: Some(HashMap::from([("X-Doc".to_string(), "42".to_string())])),
                     env_http_headers: Some(HashMap::from([(
                         "X-Auth".to_string(),
                         "DOCS_AUTH".to_string(),
                     )])),
+                    env_http_header_defaults: None,
+                    oauth_client_credentials: None,
+                    keep_alive: false,
+                    max_idle_connections: None,
+                    connect_timeout_sec: None,
+                    max_requests_per_sec: None,
+                    client_cert_path: None,
+                    client_key_path: None,
+                    ca_cert_path: None,
+                    health_check_interval_sec: None,
                 },
                 enabled: true,
                 startup_timeout_sec: Some(Duration::from_secs(2)),
                 tool_timeout_sec: None,
+                allowed_tools: None,
+                denied_tools: None,
+                protocol_version: None,
+                enabled_when: None,
+                description: None,
+                startup_priority: None,
+                client_name: None,
+                client_version: None,
+                tool_timeouts: None,
+                tags: Vec::new(),
             },
         )]);
 
@@ -2307,19 +3476,42 @@ X-Auth = "DOCS_AUTH"
         assert!(serialized_with_optional.contains("bearer_token_env_var = \"MCP_TOKEN\""));
         assert!(serialized_with_optional.contains("[mcp_servers.docs.http_headers]"));
         assert!(serialized_with_optional.contains("[mcp_servers.docs.env_http_headers]"));
+        assert!(serialized_with_optional.contains("keep_alive = false"));
 
         servers.insert(
             "docs".to_string(),
             McpServerConfig {
                 transport: McpServerTransportConfig::StreamableHttp {
                     url: "https://example.com/mcp".to_string(),
+                    url_env_var: None,
                     bearer_token_env_var: None,
+                    bearer_token_file: None,
                     http_headers: None,
                     env_http_headers: None,
+                    env_http_header_defaults: None,
+                    oauth_client_credentials: None,
+                    keep_alive: true,
+                    max_idle_connections: None,
+                    connect_timeout_sec: None,
+                    max_requests_per_sec: None,
+                    client_cert_path: None,
+                    client_key_path: None,
+                    ca_cert_path: None,
+                    health_check_interval_sec: None,
                 },
                 enabled: true,
                 startup_timeout_sec: None,
                 tool_timeout_sec: None,
+                allowed_tools: None,
+                denied_tools: None,
+                protocol_version: None,
+                enabled_when: None,
+                description: None,
+                startup_priority: None,
+                client_name: None,
+                client_version: None,
+                tool_timeouts: None,
+                tags: Vec::new(),
             },
         );
         write_global_mcp_servers(codex_home.path(), &servers)?;
@@ -2340,6 +3532,8 @@ url = "https://example.com/mcp"
                 bearer_token_env_var,
                 http_headers,
                 env_http_headers,
+                oauth_client_credentials: None,
+                ..
             } => {
                 assert_eq!(url, "https://example.com/mcp");
                 assert!(bearer_token_env_var.is_none());
@@ -2366,7 +3560,9 @@ url = "https://example.com/mcp"
                 McpServerConfig {
                     transport: McpServerTransportConfig::StreamableHttp {
                         url: "https://example.com/mcp".to_string(),
+                        url_env_var: None,
                         bearer_token_env_var: Some("MCP_TOKEN".to_string()),
+                        bearer_token_file: None,
                         http_headers: Some(HashMap::from([(
                             "X-Doc".to_string(),
                             "42".to_string(),
@@ -2375,10 +3571,30 @@ url = "https://example.com/mcp"
                             "X-Auth".to_string(),
                             "DOCS_AUTH".to_string(),
                         )])),
+                        env_http_header_defaults: None,
+                        oauth_client_credentials: None,
+                        keep_alive: true,
+                        max_idle_connections: None,
+                        connect_timeout_sec: None,
+                        max_requests_per_sec: None,
+                        client_cert_path: None,
+                        client_key_path: None,
+                        ca_cert_path: None,
+                        health_check_interval_sec: None,
                     },
                     enabled: true,
                     startup_timeout_sec: Some(Duration::from_secs(2)),
                     tool_timeout_sec: None,
+                    allowed_tools: None,
+                    denied_tools: None,
+                    protocol_version: None,
+                    enabled_when: None,
+                    description: None,
+                    startup_priority: None,
+                    client_name: None,
+                    client_version: None,
+                    tool_timeouts: None,
+                    tags: Vec::new(),
                 },
             ),
             (
@@ -2390,10 +3606,24 @@ url = "https://example.com/mcp"
                         env: None,
                         env_vars: Vec::new(),
                         cwd: None,
+                        expand_env: false,
+                        expand_env_strict: false,
+                        shutdown_timeout_sec: None,
+                        inherit_env: None,
                     },
                     enabled: true,
                     startup_timeout_sec: None,
                     tool_timeout_sec: None,
+                    allowed_tools: None,
+                    denied_tools: None,
+                    protocol_version: None,
+                    enabled_when: None,
+                    description: None,
+                    startup_priority: None,
+                    client_name: None,
+                    client_version: None,
+                    tool_timeouts: None,
+                    tags: Vec::new(),
                 },
             ),
         ]);
@@ -2464,10 +3694,24 @@ url = "https://example.com/mcp"
                     env: None,
                     env_vars: Vec::new(),
                     cwd: None,
+                    expand_env: false,
+                    expand_env_strict: false,
+                    shutdown_timeout_sec: None,
+                    inherit_env: None,
                 },
                 enabled: false,
                 startup_timeout_sec: None,
                 tool_timeout_sec: None,
+                allowed_tools: None,
+                denied_tools: None,
+                protocol_version: None,
+                enabled_when: None,
+                description: None,
+                startup_priority: None,
+                client_name: None,
+                client_version: None,
+                tool_timeouts: None,
+                tags: Vec::new(),
             },
         )]);
 
@@ -2487,6 +3731,80 @@ url = "https://example.com/mcp"
         Ok(())
     }
 
+    #[test]
+    fn write_global_mcp_servers_refuses_read_only_config() -> anyhow::Result<()> {
+        let codex_home = TempDir::new()?;
+        let config_path = codex_home.path().join(CONFIG_TOML_FILE);
+        std::fs::write(&config_path, "model = \"gpt-5\"\n")?;
+        let mut permissions = std::fs::metadata(&config_path)?.permissions();
+        permissions.set_readonly(true);
+        std::fs::set_permissions(&config_path, permissions)?;
+
+        let err = write_global_mcp_servers(codex_home.path(), &BTreeMap::new())
+            .expect_err("read-only config should not be replaced");
+
+        assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        assert_eq!(
+            std::fs::read_to_string(&config_path)?,
+            "model = \"gpt-5\"\n"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn write_global_mcp_servers_serializes_tool_filters() -> anyhow::Result<()> {
+        let codex_home = TempDir::new()?;
+
+        let servers = BTreeMap::from([(
+            "docs".to_string(),
+            McpServerConfig {
+                transport: McpServerTransportConfig::Stdio {
+                    command: "docs-server".to_string(),
+                    args: Vec::new(),
+                    env: None,
+                    env_vars: Vec::new(),
+                    cwd: None,
+                    expand_env: false,
+                    expand_env_strict: false,
+                    shutdown_timeout_sec: None,
+                    inherit_env: None,
+                },
+                enabled: true,
+                startup_timeout_sec: None,
+                tool_timeout_sec: None,
+                allowed_tools: Some(vec!["search".to_string(), "fetch".to_string()]),
+                denied_tools: Some(vec!["delete".to_string()]),
+                protocol_version: None,
+                enabled_when: None,
+                description: None,
+                startup_priority: None,
+                client_name: None,
+                client_version: None,
+                tool_timeouts: None,
+                tags: Vec::new(),
+            },
+        )]);
+
+        write_global_mcp_servers(codex_home.path(), &servers)?;
+
+        let config_path = codex_home.path().join(CONFIG_TOML_FILE);
+        let serialized = std::fs::read_to_string(&config_path)?;
+        assert_eq!(
+            serialized,
+            r#"[mcp_servers.docs]
+command = "docs-server"
+allowed_tools = ["search", "fetch"]
+denied_tools = ["delete"]
+"#
+        );
+
+        let loaded = load_global_mcp_servers(codex_home.path()).await?;
+        assert_eq!(loaded.get("docs"), servers.get("docs"));
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn persist_model_selection_updates_defaults() -> anyhow::Result<()> {
         let codex_home = TempDir::new()?;
@@ -2786,6 +4104,9 @@ model_verbosity = "high"
                 cwd: fixture.cwd(),
                 mcp_servers: HashMap::new(),
                 mcp_oauth_credentials_store_mode: Default::default(),
+                mcp_env_mask_patterns: default_env_mask_patterns(),
+                mcp_startup_concurrency: None,
+                cli_overrides: Vec::new(),
                 model_providers: fixture.model_provider_map.clone(),
                 project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
                 project_doc_fallback_filenames: Vec::new(),
@@ -2853,6 +4174,9 @@ model_verbosity = "high"
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
             mcp_oauth_credentials_store_mode: Default::default(),
+            mcp_env_mask_patterns: default_env_mask_patterns(),
+            mcp_startup_concurrency: None,
+            cli_overrides: Vec::new(),
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             project_doc_fallback_filenames: Vec::new(),
@@ -2935,6 +4259,9 @@ model_verbosity = "high"
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
             mcp_oauth_credentials_store_mode: Default::default(),
+            mcp_env_mask_patterns: default_env_mask_patterns(),
+            mcp_startup_concurrency: None,
+            cli_overrides: Vec::new(),
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             project_doc_fallback_filenames: Vec::new(),
@@ -3003,6 +4330,9 @@ model_verbosity = "high"
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
             mcp_oauth_credentials_store_mode: Default::default(),
+            mcp_env_mask_patterns: default_env_mask_patterns(),
+            mcp_startup_concurrency: None,
+            cli_overrides: Vec::new(),
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             project_doc_fallback_filenames: Vec::new(),
@@ -4,6 +4,7 @@
 // definitions that do not contain business logic.
 
 use serde::Deserializer;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::time::Duration;
@@ -15,6 +16,29 @@ use serde::de::Error as SerdeError;
 
 pub const DEFAULT_OTEL_ENVIRONMENT: &str = "dev";
 
+/// MCP protocol versions a server's `protocol_version` may pin, oldest first.
+pub const MCP_PROTOCOL_VERSIONS: &[&str] = &["2024-11-05", "2025-03-26", "2025-06-18"];
+
+/// Smallest accepted `max_requests_per_sec`: one request per hour. Slower
+/// rates would park requests for hours behind the limiter.
+pub const MIN_MAX_REQUESTS_PER_SEC: f64 = 1.0 / 3600.0;
+
+/// Operating systems an `enabled_when.os` condition may name, as reported by
+/// [`std::env::consts::OS`].
+pub const MCP_ENABLED_WHEN_OS_VALUES: &[&str] = &[
+    "linux",
+    "macos",
+    "windows",
+    "freebsd",
+    "netbsd",
+    "openbsd",
+    "dragonfly",
+    "solaris",
+    "illumos",
+    "android",
+    "ios",
+];
+
 #[derive(Serialize, Debug, Clone, PartialEq)]
 pub struct McpServerConfig {
     #[serde(flatten)]
@@ -24,6 +48,20 @@ pub struct McpServerConfig {
     #[serde(default = "default_enabled")]
     pub enabled: bool,
 
+    /// When set, the server is only initialized if this condition holds at
+    /// session start. Has no effect when `enabled` is `false`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled_when: Option<McpEnabledWhen>,
+
+    /// Free-form note on why the server is configured. Informational only.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Labels for grouping servers, e.g. with `codex mcp list --tag`.
+    /// Informational only.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
     /// Startup timeout in seconds for initializing MCP server & initially listing tools.
     #[serde(
         default,
@@ -35,8 +73,50 @@ pub struct McpServerConfig {
     /// Default timeout for MCP tool calls initiated via this server.
     #[serde(default, with = "option_duration_secs")]
     pub tool_timeout_sec: Option<Duration>,
+
+    /// Per-tool call timeouts, in seconds, keyed by the tool's name on the
+    /// server. Tools not listed use `tool_timeout_sec`.
+    #[serde(
+        default,
+        serialize_with = "serialize_tool_timeouts",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub tool_timeouts: Option<HashMap<String, Duration>>,
+
+    /// When set, only these tools advertised by the server are exposed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_tools: Option<Vec<String>>,
+
+    /// Tools advertised by the server that are never exposed. Takes
+    /// precedence over `allowed_tools`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub denied_tools: Option<Vec<String>>,
+
+    /// MCP protocol version to request in the `initialize` handshake instead
+    /// of the latest one Codex supports. One of [`MCP_PROTOCOL_VERSIONS`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol_version: Option<String>,
+
+    /// Servers with a higher value start first when `mcp_startup_concurrency`
+    /// limits how many initialize at once. Unset means
+    /// [`DEFAULT_STARTUP_PRIORITY`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub startup_priority: Option<i32>,
+
+    /// `clientInfo.name` to send in the `initialize` handshake instead of
+    /// Codex's own, for servers that allow-list or special-case clients.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_name: Option<String>,
+
+    /// `clientInfo.version` to send in the `initialize` handshake instead of
+    /// the Codex version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_version: Option<String>,
 }
 
+/// Startup priority of a server that does not set `startup_priority`.
+pub const DEFAULT_STARTUP_PRIORITY: i32 = 0;
+
 impl<'de> Deserialize<'de> for McpServerConfig {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -54,14 +134,45 @@ impl<'de> Deserialize<'de> for McpServerConfig {
             env_vars: Option<Vec<String>>,
             #[serde(default)]
             cwd: Option<PathBuf>,
+            #[serde(default)]
+            expand_env: Option<bool>,
+            #[serde(default)]
+            expand_env_strict: Option<bool>,
+            #[serde(default, with = "option_duration_secs")]
+            shutdown_timeout_sec: Option<Duration>,
+            #[serde(default)]
+            inherit_env: Option<McpInheritEnv>,
             http_headers: Option<HashMap<String, String>>,
             #[serde(default)]
             env_http_headers: Option<HashMap<String, String>>,
+            #[serde(default)]
+            env_http_header_defaults: Option<HashMap<String, String>>,
 
             // streamable_http
             url: Option<String>,
+            url_env_var: Option<String>,
             bearer_token: Option<String>,
             bearer_token_env_var: Option<String>,
+            #[serde(default)]
+            bearer_token_file: Option<PathBuf>,
+            #[serde(default)]
+            oauth_client_credentials: Option<McpOAuthClientCredentials>,
+            #[serde(default)]
+            keep_alive: Option<bool>,
+            #[serde(default)]
+            max_idle_connections: Option<usize>,
+            #[serde(default, with = "option_duration_secs")]
+            connect_timeout_sec: Option<Duration>,
+            #[serde(default)]
+            max_requests_per_sec: Option<f64>,
+            #[serde(default)]
+            client_cert_path: Option<PathBuf>,
+            #[serde(default)]
+            client_key_path: Option<PathBuf>,
+            #[serde(default)]
+            ca_cert_path: Option<PathBuf>,
+            #[serde(default, with = "option_duration_secs")]
+            health_check_interval_sec: Option<Duration>,
 
             // shared
             #[serde(default)]
@@ -71,7 +182,27 @@ impl<'de> Deserialize<'de> for McpServerConfig {
             #[serde(default, with = "option_duration_secs")]
             tool_timeout_sec: Option<Duration>,
             #[serde(default)]
+            tool_timeouts: Option<HashMap<String, f64>>,
+            #[serde(default)]
             enabled: Option<bool>,
+            #[serde(default)]
+            enabled_when: Option<McpEnabledWhen>,
+            #[serde(default)]
+            description: Option<String>,
+            #[serde(default)]
+            tags: Option<Vec<String>>,
+            #[serde(default)]
+            allowed_tools: Option<Vec<String>>,
+            #[serde(default)]
+            denied_tools: Option<Vec<String>>,
+            #[serde(default)]
+            protocol_version: Option<String>,
+            #[serde(default)]
+            startup_priority: Option<i32>,
+            #[serde(default)]
+            client_name: Option<String>,
+            #[serde(default)]
+            client_version: Option<String>,
         }
 
         let raw = RawMcpServerConfig::deserialize(deserializer)?;
@@ -104,73 +235,394 @@ impl<'de> Deserialize<'de> for McpServerConfig {
                 env,
                 env_vars,
                 cwd,
+                expand_env,
+                expand_env_strict,
+                shutdown_timeout_sec,
+                inherit_env,
                 url,
+                url_env_var,
                 bearer_token_env_var,
+                bearer_token_file,
                 http_headers,
                 env_http_headers,
+                env_http_header_defaults,
+                oauth_client_credentials,
+                keep_alive,
+                max_idle_connections,
+                connect_timeout_sec,
+                max_requests_per_sec,
+                client_cert_path,
+                client_key_path,
+                ca_cert_path,
+                health_check_interval_sec,
                 ..
             } => {
                 throw_if_set("stdio", "url", url.as_ref())?;
+                throw_if_set("stdio", "url_env_var", url_env_var.as_ref())?;
+                throw_if_set(
+                    "stdio",
+                    "oauth_client_credentials",
+                    oauth_client_credentials.as_ref(),
+                )?;
                 throw_if_set(
                     "stdio",
                     "bearer_token_env_var",
                     bearer_token_env_var.as_ref(),
                 )?;
+                throw_if_set("stdio", "bearer_token_file", bearer_token_file.as_ref())?;
                 throw_if_set("stdio", "http_headers", http_headers.as_ref())?;
                 throw_if_set("stdio", "env_http_headers", env_http_headers.as_ref())?;
+                throw_if_set(
+                    "stdio",
+                    "env_http_header_defaults",
+                    env_http_header_defaults.as_ref(),
+                )?;
+                throw_if_set("stdio", "keep_alive", keep_alive.as_ref())?;
+                throw_if_set(
+                    "stdio",
+                    "max_idle_connections",
+                    max_idle_connections.as_ref(),
+                )?;
+                throw_if_set("stdio", "connect_timeout_sec", connect_timeout_sec.as_ref())?;
+                throw_if_set(
+                    "stdio",
+                    "max_requests_per_sec",
+                    max_requests_per_sec.as_ref(),
+                )?;
+                throw_if_set("stdio", "client_cert_path", client_cert_path.as_ref())?;
+                throw_if_set("stdio", "client_key_path", client_key_path.as_ref())?;
+                throw_if_set("stdio", "ca_cert_path", ca_cert_path.as_ref())?;
+                throw_if_set(
+                    "stdio",
+                    "health_check_interval_sec",
+                    health_check_interval_sec.as_ref(),
+                )?;
+                let expand_env = expand_env.unwrap_or(false);
+                let expand_env_strict = expand_env_strict.unwrap_or(false);
+                if expand_env_strict && !expand_env {
+                    return Err(SerdeError::custom(
+                        "expand_env_strict requires expand_env = true",
+                    ));
+                }
                 McpServerTransportConfig::Stdio {
                     command,
                     args: args.unwrap_or_default(),
                     env,
                     env_vars: env_vars.unwrap_or_default(),
                     cwd,
+                    expand_env,
+                    expand_env_strict,
+                    shutdown_timeout_sec,
+                    inherit_env,
                 }
             }
             RawMcpServerConfig {
-                url: Some(url),
+                url,
+                url_env_var,
                 bearer_token,
                 bearer_token_env_var,
+                bearer_token_file,
                 command,
                 args,
                 env,
                 env_vars,
                 cwd,
+                expand_env,
+                expand_env_strict,
+                shutdown_timeout_sec,
+                inherit_env,
                 http_headers,
                 env_http_headers,
+                env_http_header_defaults,
+                oauth_client_credentials,
+                keep_alive,
+                max_idle_connections,
+                connect_timeout_sec,
+                max_requests_per_sec,
+                client_cert_path,
+                client_key_path,
+                ca_cert_path,
+                health_check_interval_sec,
                 startup_timeout_sec: _,
                 tool_timeout_sec: _,
+                tool_timeouts: _,
                 startup_timeout_ms: _,
                 enabled: _,
-            } => {
+                enabled_when: _,
+                description: _,
+                tags: _,
+                allowed_tools: _,
+                denied_tools: _,
+                protocol_version: _,
+                startup_priority: _,
+                client_name: _,
+                client_version: _,
+            } if url.is_some() || url_env_var.is_some() => {
                 throw_if_set("streamable_http", "command", command.as_ref())?;
                 throw_if_set("streamable_http", "args", args.as_ref())?;
                 throw_if_set("streamable_http", "env", env.as_ref())?;
                 throw_if_set("streamable_http", "env_vars", env_vars.as_ref())?;
                 throw_if_set("streamable_http", "cwd", cwd.as_ref())?;
+                throw_if_set("streamable_http", "expand_env", expand_env.as_ref())?;
+                throw_if_set(
+                    "streamable_http",
+                    "expand_env_strict",
+                    expand_env_strict.as_ref(),
+                )?;
+                throw_if_set(
+                    "streamable_http",
+                    "shutdown_timeout_sec",
+                    shutdown_timeout_sec.as_ref(),
+                )?;
+                throw_if_set("streamable_http", "inherit_env", inherit_env.as_ref())?;
                 throw_if_set("streamable_http", "bearer_token", bearer_token.as_ref())?;
+                if bearer_token_env_var.is_some() && bearer_token_file.is_some() {
+                    return Err(SerdeError::custom(
+                        "bearer_token_env_var and bearer_token_file cannot both be set",
+                    ));
+                }
+                if bearer_token_env_var.is_some() && oauth_client_credentials.is_some() {
+                    return Err(SerdeError::custom(
+                        "bearer_token_env_var and oauth_client_credentials cannot both be set",
+                    ));
+                }
+                if bearer_token_file.is_some() && oauth_client_credentials.is_some() {
+                    return Err(SerdeError::custom(
+                        "bearer_token_file and oauth_client_credentials cannot both be set",
+                    ));
+                }
+                let url = match (url, &url_env_var) {
+                    (Some(_), Some(_)) => {
+                        return Err(SerdeError::custom("url and url_env_var cannot both be set"));
+                    }
+                    (Some(url), None) => url,
+                    (None, _) => String::new(),
+                };
+                let keep_alive = keep_alive.unwrap_or_else(default_keep_alive);
+                if !keep_alive && max_idle_connections.is_some() {
+                    return Err(SerdeError::custom(
+                        "max_idle_connections requires keep_alive = true",
+                    ));
+                }
+                if connect_timeout_sec == Some(Duration::ZERO) {
+                    return Err(SerdeError::custom("connect_timeout_sec must be positive"));
+                }
+                if health_check_interval_sec == Some(Duration::ZERO) {
+                    return Err(SerdeError::custom(
+                        "health_check_interval_sec must be positive",
+                    ));
+                }
+                if max_requests_per_sec.is_some_and(|rate| !(rate.is_finite() && rate > 0.0)) {
+                    return Err(SerdeError::custom(
+                        "max_requests_per_sec must be a positive number",
+                    ));
+                }
+                if max_requests_per_sec.is_some_and(|rate| rate < MIN_MAX_REQUESTS_PER_SEC) {
+                    return Err(SerdeError::custom(
+                        "max_requests_per_sec must be at least one request per hour (1/3600)",
+                    ));
+                }
+                if client_cert_path.is_some() != client_key_path.is_some() {
+                    return Err(SerdeError::custom(
+                        "client_cert_path and client_key_path must be set together",
+                    ));
+                }
+                if let Some(header) = env_http_header_defaults
+                    .iter()
+                    .flat_map(HashMap::keys)
+                    .find(|header| {
+                        !env_http_headers
+                            .as_ref()
+                            .is_some_and(|headers| headers.contains_key(*header))
+                    })
+                {
+                    return Err(SerdeError::custom(format!(
+                        "env_http_header_defaults has a default for `{header}`, which is not in env_http_headers"
+                    )));
+                }
                 McpServerTransportConfig::StreamableHttp {
                     url,
+                    url_env_var,
                     bearer_token_env_var,
+                    bearer_token_file,
                     http_headers,
                     env_http_headers,
+                    env_http_header_defaults,
+                    oauth_client_credentials,
+                    keep_alive,
+                    max_idle_connections,
+                    connect_timeout_sec,
+                    max_requests_per_sec,
+                    client_cert_path,
+                    client_key_path,
+                    ca_cert_path,
+                    health_check_interval_sec,
                 }
             }
             _ => return Err(SerdeError::custom("invalid transport")),
         };
 
+        if let (Some(allowed), Some(denied)) = (&raw.allowed_tools, &raw.denied_tools)
+            && let Some(tool) = allowed.iter().find(|tool| denied.contains(tool))
+        {
+            return Err(SerdeError::custom(format!(
+                "tool `{tool}` is listed in both allowed_tools and denied_tools"
+            )));
+        }
+
+        if let Some(version) = &raw.protocol_version
+            && !MCP_PROTOCOL_VERSIONS.contains(&version.as_str())
+        {
+            return Err(SerdeError::custom(format!(
+                "unsupported protocol_version `{version}`; expected one of: {}",
+                MCP_PROTOCOL_VERSIONS.join(", ")
+            )));
+        }
+
+        if let Some(condition) = &raw.enabled_when {
+            condition.validate().map_err(SerdeError::custom)?;
+        }
+
+        for (field, value) in [
+            ("client_name", &raw.client_name),
+            ("client_version", &raw.client_version),
+        ] {
+            if value.as_ref().is_some_and(|value| value.trim().is_empty()) {
+                return Err(SerdeError::custom(format!("{field} must not be empty")));
+            }
+        }
+
+        let tool_timeouts = raw
+            .tool_timeouts
+            .map(|timeouts| {
+                timeouts
+                    .into_iter()
+                    .map(|(tool, secs)| {
+                        if !(secs.is_finite() && secs > 0.0) {
+                            return Err(SerdeError::custom(format!(
+                                "tool_timeouts.{tool} must be a positive number of seconds"
+                            )));
+                        }
+                        let timeout = Duration::try_from_secs_f64(secs).map_err(|err| {
+                            SerdeError::custom(format!("invalid tool_timeouts.{tool}: {err}"))
+                        })?;
+                        Ok((tool, timeout))
+                    })
+                    .collect::<Result<HashMap<_, _>, _>>()
+            })
+            .transpose()?;
+
         Ok(Self {
             transport,
             startup_timeout_sec,
             tool_timeout_sec: raw.tool_timeout_sec,
+            tool_timeouts,
             enabled: raw.enabled.unwrap_or_else(default_enabled),
+            enabled_when: raw.enabled_when,
+            description: raw.description,
+            tags: raw.tags.unwrap_or_default(),
+            allowed_tools: raw.allowed_tools,
+            denied_tools: raw.denied_tools,
+            protocol_version: raw.protocol_version,
+            startup_priority: raw.startup_priority,
+            client_name: raw.client_name,
+            client_version: raw.client_version,
         })
     }
 }
 
+impl McpServerConfig {
+    /// Whether a session should start this server: it is `enabled` and its
+    /// `enabled_when` condition, if any, holds on this machine.
+    pub fn is_effectively_enabled(&self) -> bool {
+        self.enabled
+            && self
+                .enabled_when
+                .as_ref()
+                .is_none_or(McpEnabledWhen::is_satisfied)
+    }
+
+    /// Timeout for calls to `tool`: its `tool_timeouts` entry, or
+    /// `tool_timeout_sec` when it has none.
+    pub fn tool_call_timeout(&self, tool: &str) -> Option<Duration> {
+        self.tool_timeouts
+            .as_ref()
+            .and_then(|timeouts| timeouts.get(tool).copied())
+            .or(self.tool_timeout_sec)
+    }
+
+    /// `startup_priority`, or [`DEFAULT_STARTUP_PRIORITY`] when unset.
+    pub fn resolved_startup_priority(&self) -> i32 {
+        self.startup_priority.unwrap_or(DEFAULT_STARTUP_PRIORITY)
+    }
+
+    /// This server with any unset `startup_timeout_sec`/`tool_timeout_sec`
+    /// (and, for stdio servers, `inherit_env`) taken from `defaults`.
+    /// Settings the server sets itself are kept.
+    pub fn with_defaults(&self, defaults: &McpServerDefaults) -> McpServerConfig {
+        let mut transport = self.transport.clone();
+        if let McpServerTransportConfig::Stdio { inherit_env, .. } = &mut transport {
+            *inherit_env = inherit_env.or(defaults.inherit_env);
+        }
+        McpServerConfig {
+            transport,
+            startup_timeout_sec: self.startup_timeout_sec.or(defaults.startup_timeout_sec),
+            tool_timeout_sec: self.tool_timeout_sec.or(defaults.tool_timeout_sec),
+            ..self.clone()
+        }
+    }
+}
+
+/// Settings from the top-level `mcp_default_*` keys, applied to servers that
+/// do not set their own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct McpServerDefaults {
+    pub startup_timeout_sec: Option<Duration>,
+    pub tool_timeout_sec: Option<Duration>,
+    pub inherit_env: Option<McpInheritEnv>,
+}
+
+/// Which of Codex's own environment variables a stdio MCP server inherits.
+/// `env_vars` and `env` are added on top in every mode.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum McpInheritEnv {
+    /// Codex's entire environment.
+    All,
+    /// Only PATH, HOME, and LANG (PATH, PATHEXT, SYSTEMROOT, and
+    /// USERPROFILE on Windows).
+    #[default]
+    Minimal,
+    /// Nothing.
+    None,
+}
+
+impl McpInheritEnv {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            McpInheritEnv::All => "all",
+            McpInheritEnv::Minimal => "minimal",
+            McpInheritEnv::None => "none",
+        }
+    }
+}
+
 const fn default_enabled() -> bool {
     true
 }
 
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
+const fn default_keep_alive() -> bool {
+    true
+}
+
+fn is_true(value: &bool) -> bool {
+    *value
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(untagged, deny_unknown_fields, rename_all = "snake_case")]
 pub enum McpServerTransportConfig {
@@ -185,25 +637,197 @@ pub enum McpServerTransportConfig {
         env_vars: Vec<String>,
         #[serde(default, skip_serializing_if = "Option::is_none")]
         cwd: Option<PathBuf>,
+        /// Expand `$VAR` and `${VAR}` references in `args`, `cwd`, and `env`
+        /// values from Codex's environment when launching the server.
+        #[serde(default, skip_serializing_if = "is_false")]
+        expand_env: bool,
+        /// With `expand_env`, refuse to launch the server when a referenced
+        /// variable is unset instead of substituting an empty string.
+        #[serde(default, skip_serializing_if = "is_false")]
+        expand_env_strict: bool,
+        /// How long to wait for the server to exit after its stdin is closed
+        /// at shutdown before killing it.
+        #[serde(
+            default,
+            with = "option_duration_secs",
+            skip_serializing_if = "Option::is_none"
+        )]
+        shutdown_timeout_sec: Option<Duration>,
+        /// Which of Codex's environment variables the server inherits. Unset
+        /// means `mcp_default_inherit_env`, or `minimal` when that is unset
+        /// too.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        inherit_env: Option<McpInheritEnv>,
     },
     /// https://modelcontextprotocol.io/specification/2025-06-18/basic/transports#streamable-http
     StreamableHttp {
+        /// Empty when the URL comes from `url_env_var`.
+        #[serde(default, skip_serializing_if = "String::is_empty")]
         url: String,
+        /// Name of the environment variable to read the server URL from when
+        /// connecting. Mutually exclusive with `url`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        url_env_var: Option<String>,
         /// Name of the environment variable to read for an HTTP bearer token.
         /// When set, requests will include the token via `Authorization: Bearer <token>`.
         /// The actual secret value must be provided via the environment.
         #[serde(default, skip_serializing_if = "Option::is_none")]
         bearer_token_env_var: Option<String>,
+        /// File to read the HTTP bearer token from each time Codex connects,
+        /// for secret managers that mount tokens as files. Trailing
+        /// whitespace is trimmed. Mutually exclusive with
+        /// `bearer_token_env_var`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        bearer_token_file: Option<PathBuf>,
         /// Additional HTTP headers to include in requests to this server.
         #[serde(default, skip_serializing_if = "Option::is_none")]
         http_headers: Option<HashMap<String, String>>,
         /// HTTP headers where the value is sourced from an environment variable.
         #[serde(default, skip_serializing_if = "Option::is_none")]
         env_http_headers: Option<HashMap<String, String>>,
+        /// Literal values for `env_http_headers` entries whose environment
+        /// variable is unset or empty. Keys must appear in `env_http_headers`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        env_http_header_defaults: Option<HashMap<String, String>>,
+        /// Obtain bearer tokens with the OAuth client-credentials grant.
+        /// Mutually exclusive with `bearer_token_env_var` and
+        /// `bearer_token_file`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        oauth_client_credentials: Option<McpOAuthClientCredentials>,
+        /// Reuse idle connections to the server across requests. When
+        /// `false`, every request opens a new connection.
+        #[serde(default = "default_keep_alive", skip_serializing_if = "is_true")]
+        keep_alive: bool,
+        /// Upper bound on idle connections kept open to the server. Unset
+        /// uses the HTTP client's default.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_idle_connections: Option<usize>,
+        /// Limit on establishing the TCP/TLS connection.
+        /// `startup_timeout_sec` still bounds the whole initialize handshake.
+        #[serde(
+            default,
+            with = "option_duration_secs",
+            skip_serializing_if = "Option::is_none"
+        )]
+        connect_timeout_sec: Option<Duration>,
+        /// Most requests sent to the server per second, with bursts of up
+        /// to that many. Requests over the limit wait rather than fail.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max_requests_per_sec: Option<f64>,
+        /// PEM certificate presented to the server for mutual TLS. Requires
+        /// `client_key_path`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        client_cert_path: Option<PathBuf>,
+        /// PEM private key for `client_cert_path`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        client_key_path: Option<PathBuf>,
+        /// PEM bundle of certificate authorities trusted for this server, in
+        /// addition to the system roots.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ca_cert_path: Option<PathBuf>,
+        /// How often to ping the server during a session. A failed ping
+        /// replaces the connection with a new one. Unset disables the check.
+        #[serde(
+            default,
+            with = "option_duration_secs",
+            skip_serializing_if = "Option::is_none"
+        )]
+        health_check_interval_sec: Option<Duration>,
     },
 }
 
-mod option_duration_secs {
+impl McpServerTransportConfig {
+    /// Short transport name (`stdio` or `streamable_http`) for logs and
+    /// diagnostics.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            McpServerTransportConfig::Stdio { .. } => "stdio",
+            McpServerTransportConfig::StreamableHttp { .. } => "streamable_http",
+        }
+    }
+}
+
+/// OAuth 2.0 client-credentials settings for a streamable HTTP server. Only
+/// the name of the variable holding the client secret is stored.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct McpOAuthClientCredentials {
+    pub token_url: String,
+    pub client_id: String,
+    /// Name of the environment variable that holds the client secret.
+    pub client_secret_env_var: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub scopes: Vec<String>,
+}
+
+/// Condition under which an MCP server is started, so one shared config can
+/// serve different machines. Every field that is set must hold.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct McpEnabledWhen {
+    /// Operating system the session runs on, one of
+    /// [`MCP_ENABLED_WHEN_OS_VALUES`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub os: Option<String>,
+    /// Environment variable that must be set to a non-empty value.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<String>,
+}
+
+impl McpEnabledWhen {
+    /// Evaluate the condition against the current process.
+    pub fn is_satisfied(&self) -> bool {
+        self.holds(std::env::consts::OS, |var| {
+            std::env::var_os(var).is_some_and(|value| !value.is_empty())
+        })
+    }
+
+    fn holds(&self, os: &str, env_is_set: impl Fn(&str) -> bool) -> bool {
+        self.os.as_deref().is_none_or(|expected| expected == os)
+            && self.env.as_deref().is_none_or(env_is_set)
+    }
+
+    /// Reject conditions that are empty or could never hold.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.os.is_none() && self.env.is_none() {
+            return Err("enabled_when must set `os` or `env`".to_string());
+        }
+        if let Some(os) = &self.os
+            && !MCP_ENABLED_WHEN_OS_VALUES.contains(&os.as_str())
+        {
+            return Err(format!(
+                "unsupported enabled_when.os `{os}`; expected one of: {}",
+                MCP_ENABLED_WHEN_OS_VALUES.join(", ")
+            ));
+        }
+        if let Some(env) = &self.env
+            && (env.trim().is_empty() || env.contains(['=', '\0']))
+        {
+            return Err(format!(
+                "invalid enabled_when.env `{env}`; expected an environment variable name"
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn serialize_tool_timeouts<S>(
+    value: &Option<HashMap<String, Duration>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    let secs = value.as_ref().map(|timeouts| {
+        timeouts
+            .iter()
+            .map(|(tool, timeout)| (tool.as_str(), timeout.as_secs_f64()))
+            .collect::<BTreeMap<_, _>>()
+    });
+    secs.serialize(serializer)
+}
+
+pub(crate) mod option_duration_secs {
     use serde::Deserialize;
     use serde::Deserializer;
     use serde::Serializer;
@@ -506,6 +1130,7 @@ pub enum ReasoningSummaryFormat {
 mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
+    use std::path::Path;
 
     #[test]
     fn deserialize_stdio_command_server_config() {
@@ -524,6 +1149,10 @@ mod tests {
                 env: None,
                 env_vars: Vec::new(),
                 cwd: None,
+                expand_env: false,
+                expand_env_strict: false,
+                shutdown_timeout_sec: None,
+                inherit_env: None,
             }
         );
         assert!(cfg.enabled);
@@ -547,6 +1176,10 @@ mod tests {
                 env: None,
                 env_vars: Vec::new(),
                 cwd: None,
+                expand_env: false,
+                expand_env_strict: false,
+                shutdown_timeout_sec: None,
+                inherit_env: None,
             }
         );
         assert!(cfg.enabled);
@@ -571,6 +1204,10 @@ mod tests {
                 env: Some(HashMap::from([("FOO".to_string(), "BAR".to_string())])),
                 env_vars: Vec::new(),
                 cwd: None,
+                expand_env: false,
+                expand_env_strict: false,
+                shutdown_timeout_sec: None,
+                inherit_env: None,
             }
         );
         assert!(cfg.enabled);
@@ -594,6 +1231,10 @@ mod tests {
                 env: None,
                 env_vars: vec!["FOO".to_string(), "BAR".to_string()],
                 cwd: None,
+                expand_env: false,
+                expand_env_strict: false,
+                shutdown_timeout_sec: None,
+                inherit_env: None,
             }
         );
     }
@@ -616,7 +1257,131 @@ mod tests {
                 env: None,
                 env_vars: Vec::new(),
                 cwd: Some(PathBuf::from("/tmp")),
+                expand_env: false,
+                expand_env_strict: false,
+                shutdown_timeout_sec: None,
+                inherit_env: None,
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_stdio_server_config_with_expand_env() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            command = "my-server"
+            args = ["--config", "$HOME/.myserver.toml"]
+            expand_env = true
+            expand_env_strict = true
+        "#,
+        )
+        .expect("should deserialize expand_env");
+
+        assert_eq!(
+            cfg.transport,
+            McpServerTransportConfig::Stdio {
+                command: "my-server".to_string(),
+                args: vec!["--config".to_string(), "$HOME/.myserver.toml".to_string()],
+                env: None,
+                env_vars: Vec::new(),
+                cwd: None,
+                expand_env: true,
+                expand_env_strict: true,
+                shutdown_timeout_sec: None,
+                inherit_env: None,
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_stdio_server_config_with_shutdown_timeout() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            command = "my-server"
+            shutdown_timeout_sec = 5.5
+        "#,
+        )
+        .expect("should deserialize shutdown_timeout_sec");
+
+        assert_eq!(
+            cfg.transport,
+            McpServerTransportConfig::Stdio {
+                command: "my-server".to_string(),
+                args: Vec::new(),
+                env: None,
+                env_vars: Vec::new(),
+                cwd: None,
+                expand_env: false,
+                expand_env_strict: false,
+                shutdown_timeout_sec: Some(Duration::from_millis(5500)),
+                inherit_env: None,
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_rejects_shutdown_timeout_for_http_transport() {
+        let err = toml::from_str::<McpServerConfig>(
+            r#"
+            url = "https://example.com"
+            shutdown_timeout_sec = 5
+        "#,
+        )
+        .expect_err("should reject shutdown_timeout_sec for http transport");
+
+        assert!(
+            err.to_string()
+                .contains("shutdown_timeout_sec is not supported for streamable_http"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn deserialize_stdio_server_config_with_inherit_env() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            command = "my-server"
+            inherit_env = "none"
+        "#,
+        )
+        .expect("should deserialize inherit_env");
+
+        assert!(matches!(
+            cfg.transport,
+            McpServerTransportConfig::Stdio {
+                inherit_env: Some(McpInheritEnv::None),
+                ..
             }
+        ));
+
+        let err = toml::from_str::<McpServerConfig>(
+            r#"
+            url = "https://example.com"
+            inherit_env = "minimal"
+        "#,
+        )
+        .expect_err("should reject inherit_env for http transport");
+        assert!(
+            err.to_string()
+                .contains("inherit_env is not supported for streamable_http"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn deserialize_rejects_expand_env_strict_without_expand_env() {
+        let err = toml::from_str::<McpServerConfig>(
+            r#"
+            command = "my-server"
+            expand_env_strict = true
+        "#,
+        )
+        .expect_err("should reject expand_env_strict alone");
+
+        assert!(
+            err.to_string()
+                .contains("expand_env_strict requires expand_env = true"),
+            "unexpected error: {err}"
         );
     }
 
@@ -633,6 +1398,26 @@ mod tests {
         assert!(!cfg.enabled);
     }
 
+    #[test]
+    fn deserialize_server_config_with_tool_filters() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            command = "echo"
+            allowed_tools = ["search", "fetch"]
+            denied_tools = ["delete"]
+        "#,
+        )
+        .expect("should deserialize tool filters");
+
+        assert_eq!(
+            (cfg.allowed_tools, cfg.denied_tools),
+            (
+                Some(vec!["search".to_string(), "fetch".to_string()]),
+                Some(vec!["delete".to_string()])
+            )
+        );
+    }
+
     #[test]
     fn deserialize_streamable_http_server_config() {
         let cfg: McpServerConfig = toml::from_str(
@@ -646,9 +1431,21 @@ mod tests {
             cfg.transport,
             McpServerTransportConfig::StreamableHttp {
                 url: "https://example.com/mcp".to_string(),
+                url_env_var: None,
                 bearer_token_env_var: None,
+                bearer_token_file: None,
                 http_headers: None,
                 env_http_headers: None,
+                env_http_header_defaults: None,
+                oauth_client_credentials: None,
+                keep_alive: true,
+                max_idle_connections: None,
+                connect_timeout_sec: None,
+                max_requests_per_sec: None,
+                client_cert_path: None,
+                client_key_path: None,
+                ca_cert_path: None,
+                health_check_interval_sec: None,
             }
         );
         assert!(cfg.enabled);
@@ -668,9 +1465,21 @@ mod tests {
             cfg.transport,
             McpServerTransportConfig::StreamableHttp {
                 url: "https://example.com/mcp".to_string(),
+                url_env_var: None,
                 bearer_token_env_var: Some("GITHUB_TOKEN".to_string()),
+                bearer_token_file: None,
                 http_headers: None,
                 env_http_headers: None,
+                env_http_header_defaults: None,
+                oauth_client_credentials: None,
+                keep_alive: true,
+                max_idle_connections: None,
+                connect_timeout_sec: None,
+                max_requests_per_sec: None,
+                client_cert_path: None,
+                client_key_path: None,
+                ca_cert_path: None,
+                health_check_interval_sec: None,
             }
         );
         assert!(cfg.enabled);
@@ -691,30 +1500,618 @@ mod tests {
             cfg.transport,
             McpServerTransportConfig::StreamableHttp {
                 url: "https://example.com/mcp".to_string(),
+                url_env_var: None,
                 bearer_token_env_var: None,
+                bearer_token_file: None,
                 http_headers: Some(HashMap::from([("X-Foo".to_string(), "bar".to_string())])),
                 env_http_headers: Some(HashMap::from([(
                     "X-Token".to_string(),
                     "TOKEN_ENV".to_string()
                 )])),
+                env_http_header_defaults: None,
+                oauth_client_credentials: None,
+                keep_alive: true,
+                max_idle_connections: None,
+                connect_timeout_sec: None,
+                max_requests_per_sec: None,
+                client_cert_path: None,
+                client_key_path: None,
+                ca_cert_path: None,
+                health_check_interval_sec: None,
             }
         );
     }
 
     #[test]
-    fn deserialize_rejects_command_and_url() {
-        toml::from_str::<McpServerConfig>(
+    fn deserialize_streamable_http_server_config_with_oauth_client_credentials() {
+        let cfg: McpServerConfig = toml::from_str(
             r#"
-            command = "echo"
-            url = "https://example.com"
+            url = "https://example.com/mcp"
+
+            [oauth_client_credentials]
+            token_url = "https://auth.example.com/oauth/token"
+            client_id = "codex"
+            client_secret_env_var = "EXAMPLE_CLIENT_SECRET"
+            scopes = ["tools.read"]
         "#,
         )
-        .expect_err("should reject command+url");
+        .expect("should deserialize oauth client credentials");
+
+        assert_eq!(
+            cfg.transport,
+            McpServerTransportConfig::StreamableHttp {
+                url: "https://example.com/mcp".to_string(),
+                url_env_var: None,
+                bearer_token_env_var: None,
+                bearer_token_file: None,
+                http_headers: None,
+                env_http_headers: None,
+                env_http_header_defaults: None,
+                oauth_client_credentials: Some(McpOAuthClientCredentials {
+                    token_url: "https://auth.example.com/oauth/token".to_string(),
+                    client_id: "codex".to_string(),
+                    client_secret_env_var: "EXAMPLE_CLIENT_SECRET".to_string(),
+                    scopes: vec!["tools.read".to_string()],
+                }),
+                keep_alive: true,
+                max_idle_connections: None,
+                connect_timeout_sec: None,
+                max_requests_per_sec: None,
+                client_cert_path: None,
+                client_key_path: None,
+                ca_cert_path: None,
+                health_check_interval_sec: None,
+            }
+        );
     }
 
     #[test]
-    fn deserialize_rejects_env_for_http_transport() {
-        toml::from_str::<McpServerConfig>(
+    fn deserialize_streamable_http_server_config_with_connection_pool() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            url = "https://example.com/mcp"
+            max_idle_connections = 4
+        "#,
+        )
+        .expect("should deserialize http config with pool settings");
+
+        let McpServerTransportConfig::StreamableHttp {
+            keep_alive,
+            max_idle_connections,
+            ..
+        } = cfg.transport
+        else {
+            panic!("expected streamable http transport");
+        };
+        assert!(keep_alive);
+        assert_eq!(max_idle_connections, Some(4));
+
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            url = "https://example.com/mcp"
+            keep_alive = false
+        "#,
+        )
+        .expect("should deserialize http config without keep-alive");
+        assert!(matches!(
+            cfg.transport,
+            McpServerTransportConfig::StreamableHttp {
+                keep_alive: false,
+                max_idle_connections: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn deserialize_streamable_http_server_config_with_url_env_var() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            url_env_var = "DOCS_MCP_URL"
+        "#,
+        )
+        .expect("should deserialize http config with url_env_var");
+
+        assert_eq!(
+            cfg.transport,
+            McpServerTransportConfig::StreamableHttp {
+                url: String::new(),
+                url_env_var: Some("DOCS_MCP_URL".to_string()),
+                bearer_token_env_var: None,
+                bearer_token_file: None,
+                http_headers: None,
+                env_http_headers: None,
+                env_http_header_defaults: None,
+                oauth_client_credentials: None,
+                keep_alive: true,
+                max_idle_connections: None,
+                connect_timeout_sec: None,
+                max_requests_per_sec: None,
+                client_cert_path: None,
+                client_key_path: None,
+                ca_cert_path: None,
+                health_check_interval_sec: None,
+            }
+        );
+
+        let err = toml::from_str::<McpServerConfig>(
+            r#"
+            url = "https://example.com/mcp"
+            url_env_var = "DOCS_MCP_URL"
+        "#,
+        )
+        .expect_err("should reject url combined with url_env_var");
+        assert!(
+            err.to_string()
+                .contains("url and url_env_var cannot both be set"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn deserialize_rejects_max_idle_connections_without_keep_alive() {
+        let err = toml::from_str::<McpServerConfig>(
+            r#"
+            url = "https://example.com/mcp"
+            keep_alive = false
+            max_idle_connections = 2
+        "#,
+        )
+        .expect_err("should reject idle connections without keep-alive");
+
+        assert!(
+            err.to_string()
+                .contains("max_idle_connections requires keep_alive = true"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn deserialize_streamable_http_connect_timeout() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            url = "https://example.com/mcp"
+            connect_timeout_sec = 2.5
+        "#,
+        )
+        .expect("should deserialize http config with connect timeout");
+        assert!(matches!(
+            cfg.transport,
+            McpServerTransportConfig::StreamableHttp {
+                connect_timeout_sec: Some(timeout),
+                ..
+            } if timeout == Duration::from_millis(2500)
+        ));
+
+        let err = toml::from_str::<McpServerConfig>(
+            r#"
+            url = "https://example.com/mcp"
+            connect_timeout_sec = 0
+        "#,
+        )
+        .expect_err("should reject a zero connect timeout");
+        assert!(
+            err.to_string()
+                .contains("connect_timeout_sec must be positive"),
+            "unexpected error: {err}"
+        );
+
+        let err = toml::from_str::<McpServerConfig>(
+            r#"
+            command = "echo"
+            connect_timeout_sec = 5
+        "#,
+        )
+        .expect_err("should reject connect timeout for stdio");
+        assert!(
+            err.to_string().contains("connect_timeout_sec"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn deserialize_streamable_http_health_check_interval() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            url = "https://example.com/mcp"
+            health_check_interval_sec = 30
+        "#,
+        )
+        .expect("should deserialize http config with health check interval");
+        assert!(matches!(
+            cfg.transport,
+            McpServerTransportConfig::StreamableHttp {
+                health_check_interval_sec: Some(interval),
+                ..
+            } if interval == Duration::from_secs(30)
+        ));
+
+        let err = toml::from_str::<McpServerConfig>(
+            r#"
+            url = "https://example.com/mcp"
+            health_check_interval_sec = 0
+        "#,
+        )
+        .expect_err("should reject a zero interval");
+        assert!(
+            err.to_string()
+                .contains("health_check_interval_sec must be positive"),
+            "unexpected error: {err}"
+        );
+
+        let err = toml::from_str::<McpServerConfig>(
+            r#"
+            command = "echo"
+            health_check_interval_sec = 30
+        "#,
+        )
+        .expect_err("should reject health check interval for stdio");
+        assert!(
+            err.to_string().contains("health_check_interval_sec"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn deserialize_streamable_http_max_requests_per_sec() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            url = "https://example.com/mcp"
+            max_requests_per_sec = 5
+        "#,
+        )
+        .expect("should deserialize http config with a rate limit");
+        assert!(matches!(
+            cfg.transport,
+            McpServerTransportConfig::StreamableHttp {
+                max_requests_per_sec: Some(rate),
+                ..
+            } if rate == 5.0
+        ));
+
+        for rate in ["0", "-1.5"] {
+            let err = toml::from_str::<McpServerConfig>(&format!(
+                "url = \"https://example.com/mcp\"\nmax_requests_per_sec = {rate}\n"
+            ))
+            .expect_err("should reject a non-positive rate limit");
+            assert!(
+                err.to_string()
+                    .contains("max_requests_per_sec must be a positive number"),
+                "unexpected error: {err}"
+            );
+        }
+
+        for rate in ["0.0001", "1e-300"] {
+            let err = toml::from_str::<McpServerConfig>(&format!(
+                "url = \"https://example.com/mcp\"\nmax_requests_per_sec = {rate}\n"
+            ))
+            .expect_err("should reject a rate under one request per hour");
+            assert!(
+                err.to_string()
+                    .contains("max_requests_per_sec must be at least one request per hour"),
+                "unexpected error: {err}"
+            );
+        }
+        let cfg: McpServerConfig = toml::from_str(&format!(
+            "url = \"https://example.com/mcp\"\nmax_requests_per_sec = {MIN_MAX_REQUESTS_PER_SEC}\n"
+        ))
+        .expect("should accept one request per hour");
+        assert!(matches!(
+            cfg.transport,
+            McpServerTransportConfig::StreamableHttp {
+                max_requests_per_sec: Some(rate),
+                ..
+            } if rate == MIN_MAX_REQUESTS_PER_SEC
+        ));
+
+        let err = toml::from_str::<McpServerConfig>(
+            r#"
+            command = "echo"
+            max_requests_per_sec = 5
+        "#,
+        )
+        .expect_err("should reject a rate limit for stdio");
+        assert!(
+            err.to_string()
+                .contains("max_requests_per_sec is not supported for stdio"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn deserialize_streamable_http_tls_paths() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            url = "https://example.com/mcp"
+            client_cert_path = "/etc/codex/client.pem"
+            client_key_path = "/etc/codex/client.key"
+            ca_cert_path = "/etc/codex/ca.pem"
+        "#,
+        )
+        .expect("should deserialize http config with TLS paths");
+        assert!(matches!(
+            cfg.transport,
+            McpServerTransportConfig::StreamableHttp {
+                client_cert_path: Some(cert),
+                client_key_path: Some(key),
+                ca_cert_path: Some(ca),
+                ..
+            } if cert == Path::new("/etc/codex/client.pem")
+                && key == Path::new("/etc/codex/client.key")
+                && ca == Path::new("/etc/codex/ca.pem")
+        ));
+
+        let err = toml::from_str::<McpServerConfig>(
+            r#"
+            url = "https://example.com/mcp"
+            client_cert_path = "/etc/codex/client.pem"
+        "#,
+        )
+        .expect_err("should reject a certificate without a key");
+        assert!(
+            err.to_string()
+                .contains("client_cert_path and client_key_path must be set together"),
+            "unexpected error: {err}"
+        );
+
+        let err = toml::from_str::<McpServerConfig>(
+            r#"
+            command = "echo"
+            ca_cert_path = "/etc/codex/ca.pem"
+        "#,
+        )
+        .expect_err("should reject TLS paths for stdio");
+        assert!(
+            err.to_string()
+                .contains("ca_cert_path is not supported for stdio"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn deserialize_streamable_http_bearer_token_file() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            url = "https://example.com/mcp"
+            bearer_token_file = "/var/run/secrets/mcp/token"
+        "#,
+        )
+        .expect("should deserialize http config with bearer_token_file");
+        assert!(matches!(
+            cfg.transport,
+            McpServerTransportConfig::StreamableHttp {
+                bearer_token_file: Some(path),
+                bearer_token_env_var: None,
+                ..
+            } if path == Path::new("/var/run/secrets/mcp/token")
+        ));
+
+        let err = toml::from_str::<McpServerConfig>(
+            r#"
+            url = "https://example.com/mcp"
+            bearer_token_env_var = "TOKEN"
+            bearer_token_file = "/var/run/secrets/mcp/token"
+        "#,
+        )
+        .expect_err("should reject both bearer token sources");
+        assert!(
+            err.to_string()
+                .contains("bearer_token_env_var and bearer_token_file cannot both be set"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn deserialize_enabled_when() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            command = "echo"
+            enabled_when = { os = "macos", env = "DOCS_TOKEN" }
+        "#,
+        )
+        .expect("should deserialize enabled_when");
+        assert_eq!(
+            cfg.enabled_when,
+            Some(McpEnabledWhen {
+                os: Some("macos".to_string()),
+                env: Some("DOCS_TOKEN".to_string()),
+            })
+        );
+
+        for (snippet, expected) in [
+            ("enabled_when = {}", "enabled_when must set `os` or `env`"),
+            (
+                r#"enabled_when = { os = "darwin" }"#,
+                "unsupported enabled_when.os `darwin`",
+            ),
+            (
+                r#"enabled_when = { env = "A=B" }"#,
+                "invalid enabled_when.env `A=B`",
+            ),
+            (
+                r#"enabled_when = { arch = "x86_64" }"#,
+                "unknown field `arch`",
+            ),
+        ] {
+            let err = toml::from_str::<McpServerConfig>(&format!("command = \"echo\"\n{snippet}"))
+                .expect_err("should reject invalid enabled_when");
+            assert!(
+                err.to_string().contains(expected),
+                "unexpected error for {snippet}: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn enabled_when_requires_every_set_field() {
+        let condition = McpEnabledWhen {
+            os: Some("macos".to_string()),
+            env: Some("DOCS_TOKEN".to_string()),
+        };
+        assert!(condition.holds("macos", |var| var == "DOCS_TOKEN"));
+        assert!(!condition.holds("linux", |var| var == "DOCS_TOKEN"));
+        assert!(!condition.holds("macos", |_| false));
+
+        let os_only = McpEnabledWhen {
+            os: Some("linux".to_string()),
+            env: None,
+        };
+        assert!(os_only.holds("linux", |_| false));
+    }
+
+    #[test]
+    fn deserialize_startup_priority() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            url = "https://example.com/mcp"
+            startup_priority = 10
+        "#,
+        )
+        .expect("should deserialize startup_priority");
+        assert_eq!(cfg.startup_priority, Some(10));
+        assert_eq!(cfg.resolved_startup_priority(), 10);
+
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            command = "echo"
+        "#,
+        )
+        .expect("should deserialize without startup_priority");
+        assert_eq!(cfg.resolved_startup_priority(), DEFAULT_STARTUP_PRIORITY);
+    }
+
+    #[test]
+    fn deserialize_client_info_overrides() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            command = "echo"
+            client_name = "acme-ide"
+            client_version = "1.2.3"
+        "#,
+        )
+        .expect("should deserialize client info overrides");
+        assert_eq!(cfg.client_name.as_deref(), Some("acme-ide"));
+        assert_eq!(cfg.client_version.as_deref(), Some("1.2.3"));
+
+        let err = toml::from_str::<McpServerConfig>(
+            r#"
+            url = "https://example.com/mcp"
+            client_name = " "
+        "#,
+        )
+        .expect_err("should reject an empty client_name");
+        assert!(
+            err.to_string().contains("client_name must not be empty"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn deserialize_tool_timeouts() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            url = "https://example.com/mcp"
+            tool_timeout_sec = 30
+            tool_timeouts = { slow_tool = 120, quick_tool = 0.5 }
+        "#,
+        )
+        .expect("should deserialize per-tool timeouts");
+        assert_eq!(
+            cfg.tool_call_timeout("slow_tool"),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            cfg.tool_call_timeout("quick_tool"),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(
+            cfg.tool_call_timeout("other_tool"),
+            Some(Duration::from_secs(30))
+        );
+
+        let err = toml::from_str::<McpServerConfig>(
+            r#"
+            command = "echo"
+            tool_timeouts = { slow_tool = 0 }
+        "#,
+        )
+        .expect_err("should reject a zero timeout");
+        assert!(
+            err.to_string()
+                .contains("tool_timeouts.slow_tool must be a positive number of seconds"),
+            "unexpected error: {err}"
+        );
+
+        let err = toml::from_str::<McpServerConfig>(
+            r#"
+            command = "echo"
+            tool_timeouts = { slow_tool = 1e30 }
+        "#,
+        )
+        .expect_err("should reject a timeout too large for a Duration");
+        assert!(
+            err.to_string().contains("invalid tool_timeouts.slow_tool"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn deserialize_protocol_version() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            command = "echo"
+            protocol_version = "2025-03-26"
+        "#,
+        )
+        .expect("should deserialize a known protocol version");
+        assert_eq!(cfg.protocol_version.as_deref(), Some("2025-03-26"));
+
+        let err = toml::from_str::<McpServerConfig>(
+            r#"
+            command = "echo"
+            protocol_version = "2023-01-01"
+        "#,
+        )
+        .expect_err("should reject an unknown protocol version");
+        assert!(
+            err.to_string()
+                .contains("unsupported protocol_version `2023-01-01`"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn deserialize_rejects_bearer_token_with_oauth_client_credentials() {
+        let err = toml::from_str::<McpServerConfig>(
+            r#"
+            url = "https://example.com/mcp"
+            bearer_token_env_var = "TOKEN"
+            oauth_client_credentials = { token_url = "https://auth.example.com/token", client_id = "codex", client_secret_env_var = "SECRET" }
+        "#,
+        )
+        .expect_err("should reject bearer token combined with oauth");
+
+        assert!(
+            err.to_string()
+                .contains("bearer_token_env_var and oauth_client_credentials cannot both be set"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn deserialize_rejects_command_and_url() {
+        toml::from_str::<McpServerConfig>(
+            r#"
+            command = "echo"
+            url = "https://example.com"
+        "#,
+        )
+        .expect_err("should reject command+url");
+    }
+
+    #[test]
+    fn deserialize_rejects_env_for_http_transport() {
+        toml::from_str::<McpServerConfig>(
             r#"
             url = "https://example.com"
             env = { "FOO" = "BAR" }
@@ -740,6 +2137,54 @@ mod tests {
         "#,
         )
         .expect_err("should reject env_http_headers for stdio transport");
+
+        toml::from_str::<McpServerConfig>(
+            r#"
+            command = "echo"
+            env_http_header_defaults = { "X-Foo" = "bar" }
+        "#,
+        )
+        .expect_err("should reject env_http_header_defaults for stdio transport");
+    }
+
+    #[test]
+    fn deserialize_env_http_header_defaults() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            url = "https://example.com/mcp"
+            env_http_headers = { "X-Tenant" = "TENANT_ENV" }
+            env_http_header_defaults = { "X-Tenant" = "public" }
+        "#,
+        )
+        .expect("should deserialize env_http_header_defaults");
+        let McpServerTransportConfig::StreamableHttp {
+            env_http_header_defaults,
+            ..
+        } = cfg.transport
+        else {
+            panic!("expected streamable_http transport");
+        };
+        assert_eq!(
+            env_http_header_defaults,
+            Some(HashMap::from([(
+                "X-Tenant".to_string(),
+                "public".to_string()
+            )]))
+        );
+
+        let err = toml::from_str::<McpServerConfig>(
+            r#"
+            url = "https://example.com/mcp"
+            env_http_headers = { "X-Tenant" = "TENANT_ENV" }
+            env_http_header_defaults = { "X-Region" = "us" }
+        "#,
+        )
+        .expect_err("should reject a default for an undeclared header");
+        assert!(
+            err.to_string()
+                .contains("env_http_header_defaults has a default for `X-Region`"),
+            "unexpected error: {err}"
+        );
     }
 
     #[test]
@@ -757,4 +2202,22 @@ mod tests {
             "unexpected error: {err}"
         );
     }
+
+    #[test]
+    fn deserialize_rejects_tool_in_both_filters() {
+        let err = toml::from_str::<McpServerConfig>(
+            r#"
+            command = "echo"
+            allowed_tools = ["search"]
+            denied_tools = ["search"]
+        "#,
+        )
+        .expect_err("should reject overlapping tool filters");
+
+        assert!(
+            err.to_string()
+                .contains("tool `search` is listed in both allowed_tools and denied_tools"),
+            "unexpected error: {err}"
+        );
+    }
 }
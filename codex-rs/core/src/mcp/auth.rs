@@ -9,6 +9,8 @@ use tracing::warn;
 
 use crate::config_types::McpServerConfig;
 use crate::config_types::McpServerTransportConfig;
+use crate::mcp_connection_manager::resolve_http_headers;
+use crate::mcp_connection_manager::resolve_streamable_http_url;
 
 pub async fn compute_auth_statuses<'a, I>(
     servers: I,
@@ -42,17 +44,34 @@ async fn compute_auth_status(
 ) -> Result<McpAuthStatus> {
     match &config.transport {
         McpServerTransportConfig::Stdio { .. } => Ok(McpAuthStatus::Unsupported),
+        McpServerTransportConfig::StreamableHttp {
+            oauth_client_credentials: Some(_),
+            ..
+        } => Ok(McpAuthStatus::OAuth),
+        McpServerTransportConfig::StreamableHttp {
+            bearer_token_file: Some(_),
+            ..
+        } => Ok(McpAuthStatus::BearerToken),
         McpServerTransportConfig::StreamableHttp {
             url,
+            url_env_var,
             bearer_token_env_var,
             http_headers,
             env_http_headers,
+            env_http_header_defaults,
+            oauth_client_credentials: None,
+            ..
         } => {
+            let url = resolve_streamable_http_url(server_name, url, url_env_var.as_deref())?;
             determine_streamable_http_auth_status(
                 server_name,
-                url,
+                &url,
                 bearer_token_env_var.as_deref(),
-                http_headers.clone(),
+                resolve_http_headers(
+                    http_headers.as_ref(),
+                    env_http_headers.as_ref(),
+                    env_http_header_defaults.as_ref(),
+                ),
                 env_http_headers.clone(),
                 store_mode,
             )
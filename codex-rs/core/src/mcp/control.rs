@@ -0,0 +1,339 @@
+//! A control channel into each running session, so `codex mcp restart` can
+//! reach a session from another process whichever frontend started it.
+//!
+//! Every session owns `$CODEX_HOME/mcp_control/<conversation id>/`, holding
+//! `session.json` (the id of the process running it) and the `requests/` and
+//! `responses/` directories. A client writes `requests/<request id>.json`;
+//! the session takes the file, handles it, and answers in
+//! `responses/<request id>.json`. Both sides write to a temporary file and
+//! rename it, so neither reads a partial file. A session removes its
+//! directory when it shuts down, and clients prune directories left behind
+//! by a process that is gone.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::io::ErrorKind;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tempfile::NamedTempFile;
+use tracing::warn;
+use uuid::Uuid;
+
+pub const MCP_CONTROL_DIR: &str = "mcp_control";
+const SESSION_FILE: &str = "session.json";
+const REQUESTS_DIR: &str = "requests";
+const RESPONSES_DIR: &str = "responses";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum McpControlRequest {
+    /// Stop `servers` and start them again with the definitions the session
+    /// is running with. An empty list restarts every running server.
+    Restart { servers: Vec<String> },
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct McpControlResponse {
+    /// How the request ended for each server it named (every configured
+    /// server when it named none).
+    #[serde(default)]
+    pub servers: BTreeMap<String, McpRestartOutcome>,
+    /// Set when the session could not handle the request at all.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum McpRestartOutcome {
+    /// The server is running again and offers `tool_count` tools.
+    Restarted { tool_count: usize },
+    /// The server failed to start again and is stopped.
+    Failed { error: String },
+    /// The session was not running the server, so nothing was restarted.
+    Inactive,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SessionFile {
+    pid: u32,
+}
+
+/// The session's end of the channel. Dropping it removes the session's
+/// directory, so clients stop sending to it.
+#[derive(Debug)]
+pub struct McpControlChannel {
+    dir: PathBuf,
+}
+
+impl McpControlChannel {
+    /// Create the directory for `session_id` and record this process as the
+    /// one serving it.
+    pub fn register(codex_home: &Path, session_id: &str) -> io::Result<Self> {
+        let dir = codex_home.join(MCP_CONTROL_DIR).join(session_id);
+        fs::create_dir_all(dir.join(REQUESTS_DIR))?;
+        fs::create_dir_all(dir.join(RESPONSES_DIR))?;
+        let channel = Self { dir };
+        write_json(
+            &channel.dir,
+            SESSION_FILE,
+            &SessionFile {
+                pid: std::process::id(),
+            },
+        )?;
+        Ok(channel)
+    }
+
+    /// Take every waiting request, keyed by request id. A request that does
+    /// not parse is answered with an error instead of being returned.
+    pub fn take_requests(&self) -> io::Result<Vec<(String, McpControlRequest)>> {
+        let mut requests = Vec::new();
+        for entry in fs::read_dir(self.dir.join(REQUESTS_DIR))? {
+            let path = entry?.path();
+            let Some(id) = json_file_stem(&path) else {
+                continue;
+            };
+            let contents = match fs::read(&path) {
+                Ok(contents) => contents,
+                // Withdrawn by the client in the meantime.
+                Err(err) if err.kind() == ErrorKind::NotFound => continue,
+                Err(err) => return Err(err),
+            };
+            remove_if_present(&path)?;
+            match serde_json::from_slice(&contents) {
+                Ok(request) => requests.push((id, request)),
+                Err(err) => self.respond(
+                    &id,
+                    &McpControlResponse {
+                        error: Some(format!("invalid request: {err}")),
+                        ..Default::default()
+                    },
+                )?,
+            }
+        }
+        Ok(requests)
+    }
+
+    /// Answer request `id`.
+    pub fn respond(&self, id: &str, response: &McpControlResponse) -> io::Result<()> {
+        write_json(
+            &self.dir.join(RESPONSES_DIR),
+            &format!("{id}.json"),
+            response,
+        )
+    }
+}
+
+impl Drop for McpControlChannel {
+    fn drop(&mut self) {
+        if let Err(err) = fs::remove_dir_all(&self.dir)
+            && err.kind() != ErrorKind::NotFound
+        {
+            warn!("failed to remove {}: {err}", self.dir.display());
+        }
+    }
+}
+
+/// A running session, as a client sees it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct McpControlSession {
+    /// The session's conversation id.
+    pub id: String,
+    dir: PathBuf,
+}
+
+/// Every session with a control channel under `codex_home`, sorted by id.
+/// Directories whose process is no longer running are removed.
+pub fn running_sessions(codex_home: &Path) -> io::Result<Vec<McpControlSession>> {
+    let entries = match fs::read_dir(codex_home.join(MCP_CONTROL_DIR)) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+    let mut sessions = Vec::new();
+    for entry in entries {
+        let dir = entry?.path();
+        let Some(id) = dir.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        // A session that is still registering has not written the file yet.
+        let Some(session) = fs::read(dir.join(SESSION_FILE))
+            .ok()
+            .and_then(|contents| serde_json::from_slice::<SessionFile>(&contents).ok())
+        else {
+            continue;
+        };
+        if !process_is_running(session.pid) {
+            if let Err(err) = fs::remove_dir_all(&dir)
+                && err.kind() != ErrorKind::NotFound
+            {
+                warn!("failed to remove stale {}: {err}", dir.display());
+            }
+            continue;
+        }
+        sessions.push(McpControlSession {
+            id: id.to_string(),
+            dir,
+        });
+    }
+    sessions.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(sessions)
+}
+
+impl McpControlSession {
+    /// Hand `request` to the session and return the id to wait on.
+    pub fn send(&self, request: &McpControlRequest) -> io::Result<String> {
+        let id = Uuid::new_v4().to_string();
+        write_json(&self.dir.join(REQUESTS_DIR), &format!("{id}.json"), request)?;
+        Ok(id)
+    }
+
+    /// The session's answer to request `id`, removed once read. `None` while
+    /// the session has not answered.
+    pub fn take_response(&self, id: &str) -> io::Result<Option<McpControlResponse>> {
+        let path = self.dir.join(RESPONSES_DIR).join(format!("{id}.json"));
+        let contents = match fs::read(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        remove_if_present(&path)?;
+        Ok(Some(serde_json::from_slice(&contents)?))
+    }
+
+    /// Withdraw request `id` so the session does not act on it later.
+    /// Returns `false` when the session already took it.
+    pub fn withdraw(&self, id: &str) -> io::Result<bool> {
+        match fs::remove_file(self.dir.join(REQUESTS_DIR).join(format!("{id}.json"))) {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Write `value` to `dir/file_name` through a temporary file in `dir`. The
+/// temporary file has no `.json` extension, so readers skip it.
+fn write_json(dir: &Path, file_name: &str, value: &impl Serialize) -> io::Result<()> {
+    let mut tmp_file = NamedTempFile::new_in(dir)?;
+    serde_json::to_writer(&mut tmp_file, value)?;
+    tmp_file.write_all(b"\n")?;
+    tmp_file
+        .persist(dir.join(file_name))
+        .map_err(|err| err.error)?;
+    Ok(())
+}
+
+fn json_file_stem(path: &Path) -> Option<String> {
+    if path.extension()? != "json" {
+        return None;
+    }
+    path.file_stem()?.to_str().map(str::to_string)
+}
+
+fn remove_if_present(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(unix)]
+fn process_is_running(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // Signal 0 only checks that the process exists; EPERM means it does but
+    // belongs to another user.
+    let result = unsafe { libc::kill(pid, 0) };
+    result == 0 || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Without a cheap liveness check, every registered session counts as
+/// running; one that never answers is reported when the client times out.
+#[cfg(not(unix))]
+fn process_is_running(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    #[test]
+    fn request_and_response_round_trip() {
+        let codex_home = TempDir::new().unwrap();
+        let channel = McpControlChannel::register(codex_home.path(), "session-a").unwrap();
+        let sessions = running_sessions(codex_home.path()).unwrap();
+        let ids: Vec<&str> = sessions.iter().map(|session| session.id.as_str()).collect();
+        assert_eq!(ids, ["session-a"]);
+
+        let session = &sessions[0];
+        let request = McpControlRequest::Restart {
+            servers: vec!["docs".to_string()],
+        };
+        let id = session.send(&request).unwrap();
+        assert_eq!(session.take_response(&id).unwrap(), None);
+        assert_eq!(channel.take_requests().unwrap(), [(id.clone(), request)]);
+        assert_eq!(channel.take_requests().unwrap(), []);
+        // Already taken, so there is nothing to withdraw.
+        assert!(!session.withdraw(&id).unwrap());
+
+        let response = McpControlResponse {
+            servers: BTreeMap::from([(
+                "docs".to_string(),
+                McpRestartOutcome::Restarted { tool_count: 3 },
+            )]),
+            error: None,
+        };
+        channel.respond(&id, &response).unwrap();
+        assert_eq!(session.take_response(&id).unwrap(), Some(response));
+        assert_eq!(session.take_response(&id).unwrap(), None);
+
+        drop(channel);
+        assert_eq!(running_sessions(codex_home.path()).unwrap(), []);
+    }
+
+    #[test]
+    fn unparsable_requests_are_answered_with_an_error() {
+        let codex_home = TempDir::new().unwrap();
+        let channel = McpControlChannel::register(codex_home.path(), "session-a").unwrap();
+        let requests = codex_home
+            .path()
+            .join(MCP_CONTROL_DIR)
+            .join("session-a")
+            .join(REQUESTS_DIR);
+        fs::write(requests.join("bad.json"), r#"{"type": "reboot"}"#).unwrap();
+
+        assert_eq!(channel.take_requests().unwrap(), []);
+        let session = &running_sessions(codex_home.path()).unwrap()[0];
+        let response = session.take_response("bad").unwrap().unwrap();
+        assert!(
+            response
+                .error
+                .is_some_and(|error| error.starts_with("invalid request:"))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn sessions_of_exited_processes_are_pruned() {
+        let codex_home = TempDir::new().unwrap();
+        let stale = codex_home.path().join(MCP_CONTROL_DIR).join("stale");
+        fs::create_dir_all(&stale).unwrap();
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        write_json(&stale, SESSION_FILE, &SessionFile { pid }).unwrap();
+
+        assert_eq!(running_sessions(codex_home.path()).unwrap(), []);
+        assert!(!stale.exists());
+    }
+}
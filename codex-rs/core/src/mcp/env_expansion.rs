@@ -0,0 +1,197 @@
+//! Shell-style `$VAR` / `${VAR}` expansion for stdio MCP server launch
+//! settings, enabled per server with `expand_env`.
+//!
+//! `$$` produces a literal `$`; a `$` that does not start a variable name is
+//! kept as is.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use anyhow::anyhow;
+
+/// The parts of a stdio server definition that are subject to expansion.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct StdioLaunch {
+    pub(crate) args: Vec<String>,
+    pub(crate) env: Option<HashMap<String, String>>,
+    pub(crate) cwd: Option<PathBuf>,
+}
+
+impl StdioLaunch {
+    /// Expand variable references against Codex's own environment. Unset
+    /// variables expand to an empty string unless `strict` is set, in which
+    /// case they are an error.
+    pub(crate) fn expand(self, strict: bool) -> Result<Self> {
+        self.expand_with(strict, |name| std::env::var(name).ok())
+    }
+
+    fn expand_with(self, strict: bool, lookup: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let expand = |value: &str, field: &str| {
+            expand_value(value, strict, &lookup).map_err(|name| {
+                anyhow!("environment variable `{name}` referenced in {field} is not set")
+            })
+        };
+
+        let args = self
+            .args
+            .iter()
+            .map(|arg| expand(arg, "args"))
+            .collect::<Result<Vec<_>>>()?;
+        let env = self
+            .env
+            .map(|env| {
+                env.into_iter()
+                    .map(|(key, value)| Ok((key, expand(&value, "env")?)))
+                    .collect::<Result<HashMap<_, _>>>()
+            })
+            .transpose()?;
+        // Non-UTF-8 paths cannot contain a reference we could expand.
+        let cwd = match self.cwd {
+            Some(cwd) => match cwd.to_str() {
+                Some(raw) => Some(PathBuf::from(expand(raw, "cwd")?)),
+                None => Some(cwd),
+            },
+            None => None,
+        };
+
+        Ok(Self { args, env, cwd })
+    }
+}
+
+/// Expand references in `value`. Returns the name of the first unset
+/// variable when `strict` is set.
+fn expand_value(
+    value: &str,
+    strict: bool,
+    lookup: &impl Fn(&str) -> Option<String>,
+) -> Result<String, String> {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(pos) = rest.find('$') {
+        expanded.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+
+        if let Some(tail) = after.strip_prefix('$') {
+            expanded.push('$');
+            rest = tail;
+            continue;
+        }
+
+        let reference = match after.strip_prefix('{') {
+            Some(braced) => braced
+                .find('}')
+                .map(|end| (&braced[..end], end + 2))
+                .filter(|(name, _)| is_variable_name(name)),
+            None => {
+                let len = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                Some((&after[..len], len)).filter(|(name, _)| is_variable_name(name))
+            }
+        };
+        let Some((name, consumed)) = reference else {
+            expanded.push('$');
+            rest = after;
+            continue;
+        };
+
+        match lookup(name) {
+            Some(resolved) => expanded.push_str(&resolved),
+            None if strict => return Err(name.to_string()),
+            None => {}
+        }
+        rest = &after[consumed..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+fn is_variable_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "HOME" => Some("/home/dev".to_string()),
+            "PORT" => Some("4000".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn expands_bare_and_braced_references() {
+        assert_eq!(
+            expand_value("$HOME/.myserver.toml:${PORT}x", false, &lookup),
+            Ok("/home/dev/.myserver.toml:4000x".to_string())
+        );
+    }
+
+    #[test]
+    fn keeps_literal_dollars() {
+        assert_eq!(
+            expand_value("cost: $$5, $1, ${}, $", false, &lookup),
+            Ok("cost: $5, $1, ${}, $".to_string())
+        );
+    }
+
+    #[test]
+    fn unset_variables_are_empty_unless_strict() {
+        assert_eq!(
+            expand_value("a${MISSING}b", false, &lookup),
+            Ok("ab".to_string())
+        );
+        assert_eq!(
+            expand_value("a${MISSING}b", true, &lookup),
+            Err("MISSING".to_string())
+        );
+    }
+
+    #[test]
+    fn expands_args_env_and_cwd() {
+        let launch = StdioLaunch {
+            args: vec!["--config".to_string(), "$HOME/.myserver.toml".to_string()],
+            env: Some(HashMap::from([(
+                "SERVER_PORT".to_string(),
+                "${PORT}".to_string(),
+            )])),
+            cwd: Some(PathBuf::from("$HOME/work")),
+        };
+
+        assert_eq!(
+            launch.expand_with(true, lookup).unwrap(),
+            StdioLaunch {
+                args: vec![
+                    "--config".to_string(),
+                    "/home/dev/.myserver.toml".to_string()
+                ],
+                env: Some(HashMap::from([(
+                    "SERVER_PORT".to_string(),
+                    "4000".to_string(),
+                )])),
+                cwd: Some(PathBuf::from("/home/dev/work")),
+            }
+        );
+    }
+
+    #[test]
+    fn strict_error_names_variable_and_field() {
+        let launch = StdioLaunch {
+            args: Vec::new(),
+            env: None,
+            cwd: Some(PathBuf::from("$WORKSPACE")),
+        };
+
+        let err = launch.expand_with(true, lookup).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "environment variable `WORKSPACE` referenced in cwd is not set"
+        );
+    }
+}
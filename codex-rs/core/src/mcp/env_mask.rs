@@ -0,0 +1,85 @@
+//! Which environment variable values MCP displays hide.
+
+use wildmatch::WildMatch;
+
+/// Patterns used when `mcp_env_mask_patterns` is not set: names containing a
+/// common secret marker.
+pub const DEFAULT_ENV_MASK_PATTERNS: &[&str] = &[
+    "*TOKEN*",
+    "*SECRET*",
+    "*PASSWORD*",
+    "*PASSWD*",
+    "*CREDENTIAL*",
+    "*AUTH*",
+    "*KEY*",
+];
+
+/// Shown in place of a masked value.
+pub const MASKED_ENV_VALUE: &str = "****";
+
+/// [`DEFAULT_ENV_MASK_PATTERNS`] as owned strings, the shape config holds.
+pub fn default_env_mask_patterns() -> Vec<String> {
+    DEFAULT_ENV_MASK_PATTERNS
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Compiled `mcp_env_mask_patterns`. Each pattern is a glob (`*` and `?`)
+/// matched against the whole variable name, ignoring case. No patterns
+/// means nothing is masked.
+#[derive(Debug, Clone, Default)]
+pub struct EnvMaskPatterns {
+    patterns: Vec<WildMatch>,
+}
+
+impl EnvMaskPatterns {
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Self {
+        Self {
+            patterns: patterns
+                .iter()
+                .map(|pattern| WildMatch::new(&pattern.as_ref().to_ascii_uppercase()))
+                .collect(),
+        }
+    }
+
+    /// Whether the value of the variable `name` should be hidden.
+    pub fn is_masked(&self, name: &str) -> bool {
+        let name = name.to_ascii_uppercase();
+        self.patterns.iter().any(|pattern| pattern.matches(&name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_patterns_detect_secrets_case_insensitively() {
+        let mask = EnvMaskPatterns::new(DEFAULT_ENV_MASK_PATTERNS);
+        for name in [
+            "GITHUB_TOKEN",
+            "api_key",
+            "DB_PASSWORD",
+            "ClientSecret",
+            "AUTH_HEADER",
+        ] {
+            assert!(mask.is_masked(name), "{name} should be masked");
+        }
+        for name in ["PATH", "HOME", "LANG", "DOCS_MODE"] {
+            assert!(!mask.is_masked(name), "{name} should be shown");
+        }
+    }
+
+    #[test]
+    fn custom_patterns_replace_the_defaults() {
+        let mask = EnvMaskPatterns::new(&["*_PAT", "gh_*"]);
+        for name in ["GITLAB_PAT", "GH_ENTERPRISE_HOST", "gh_user"] {
+            assert!(mask.is_masked(name), "{name} should be masked");
+        }
+        for name in ["GITHUB_TOKEN", "PATH", "PAT_COUNT", "MY_GH_HOST"] {
+            assert!(!mask.is_masked(name), "{name} should be shown");
+        }
+        assert!(!EnvMaskPatterns::default().is_masked("GITHUB_TOKEN"));
+    }
+}
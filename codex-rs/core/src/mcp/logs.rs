@@ -0,0 +1,22 @@
+//! Per-server stderr logs for stdio MCP servers, read by `codex mcp logs`.
+
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Location of `server_name`'s stderr log under Codex's log directory
+/// (see [`crate::config::log_dir`]).
+pub fn server_log_path(log_dir: &Path, server_name: &str) -> PathBuf {
+    log_dir.join("mcp").join(format!("{server_name}.log"))
+}
+
+/// Open `server_name`'s log for appending, creating it if needed.
+pub(crate) fn open_server_log(log_dir: &Path, server_name: &str) -> io::Result<File> {
+    let path = server_log_path(log_dir, server_name);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    OpenOptions::new().create(true).append(true).open(path)
+}
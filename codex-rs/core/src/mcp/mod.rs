@@ -1 +1,13 @@
 pub mod auth;
+pub mod control;
+pub(crate) mod env_expansion;
+pub mod env_mask;
+pub mod logs;
+pub mod probe;
+pub mod state;
+
+pub use crate::mcp_connection_manager::DEFAULT_STARTUP_TIMEOUT;
+pub use crate::mcp_connection_manager::DEFAULT_TOOL_TIMEOUT;
+pub use crate::mcp_connection_manager::resolve_http_headers;
+pub use crate::mcp_connection_manager::resolve_stdio_server_env;
+pub use crate::mcp_connection_manager::resolve_streamable_http_url;
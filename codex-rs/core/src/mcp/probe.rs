@@ -0,0 +1,264 @@
+//! One-shot connections to a configured MCP server, used by
+//! `codex mcp get --probe` and `codex mcp test-tool`.
+
+use std::ffi::OsString;
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::bail;
+use codex_rmcp_client::HttpConnectionOptions;
+use codex_rmcp_client::OAuthCredentialsStoreMode;
+use codex_rmcp_client::RmcpClient;
+use mcp_types::CallToolResult;
+use mcp_types::InitializeResult;
+
+use crate::config_types::McpServerConfig;
+use crate::config_types::McpServerTransportConfig;
+use crate::mcp::env_expansion::StdioLaunch;
+use crate::mcp_connection_manager::DEFAULT_STARTUP_TIMEOUT;
+use crate::mcp_connection_manager::DEFAULT_TOOL_TIMEOUT;
+use crate::mcp_connection_manager::client_initialize_params;
+use crate::mcp_connection_manager::resolve_bearer_token;
+use crate::mcp_connection_manager::resolve_client_credentials;
+use crate::mcp_connection_manager::resolve_http_headers;
+use crate::mcp_connection_manager::resolve_stdio_launch;
+use crate::mcp_connection_manager::resolve_streamable_http_url;
+use crate::mcp_connection_manager::stdio_server_env;
+
+/// What a server reported during a successful probe.
+#[derive(Debug, Clone, PartialEq)]
+pub struct McpServerProbe {
+    /// Protocol version the server agreed to during `initialize`.
+    pub protocol_version: String,
+    /// `serverInfo.name` / `serverInfo.version` from `initialize`.
+    pub server_name: String,
+    pub server_version: String,
+    /// Names of every tool the server advertises, sorted. Tool filters from
+    /// the config are not applied so the full surface is visible.
+    pub tools: Vec<String>,
+    /// Time spent launching or connecting and completing `initialize`.
+    pub startup: Duration,
+    /// Round-trip time of the `tools/list` request made after the handshake.
+    pub latency: Duration,
+}
+
+/// Outcome of a one-shot tool call made by [`call_server_tool`].
+#[derive(Debug, Clone)]
+pub struct McpToolCall {
+    pub result: CallToolResult,
+    /// Time spent launching or connecting and completing `initialize`.
+    pub startup: Duration,
+    /// Round-trip time of the `tools/call` request.
+    pub duration: Duration,
+}
+
+/// Connect to `config`, perform the `initialize` handshake, and list tools.
+/// The connection is dropped (and any stdio child killed) before returning.
+pub async fn probe_server(
+    server_name: &str,
+    config: &McpServerConfig,
+    store_mode: OAuthCredentialsStoreMode,
+) -> Result<McpServerProbe> {
+    let timeout = config
+        .startup_timeout_sec
+        .unwrap_or(DEFAULT_STARTUP_TIMEOUT);
+    let started = Instant::now();
+    let (client, initialize) = connect(server_name, config, store_mode).await?;
+    let startup = started.elapsed();
+
+    let started = Instant::now();
+    let tools = list_tool_names(server_name, &client, timeout).await?;
+    let latency = started.elapsed();
+
+    Ok(McpServerProbe {
+        protocol_version: initialize.protocol_version,
+        server_name: initialize.server_info.name,
+        server_version: initialize.server_info.version,
+        tools,
+        startup,
+        latency,
+    })
+}
+
+/// Connect to `config` and call `tool_name` once with `arguments`, waiting
+/// up to the tool's `tool_timeouts` entry or the server's `tool_timeout_sec`.
+/// Fails without calling anything if the server does not advertise the tool.
+/// Tool filters from the config are not applied.
+pub async fn call_server_tool(
+    server_name: &str,
+    config: &McpServerConfig,
+    store_mode: OAuthCredentialsStoreMode,
+    tool_name: &str,
+    arguments: Option<serde_json::Value>,
+) -> Result<McpToolCall> {
+    let startup_timeout = config
+        .startup_timeout_sec
+        .unwrap_or(DEFAULT_STARTUP_TIMEOUT);
+    let started = Instant::now();
+    let (client, _) = connect(server_name, config, store_mode).await?;
+    let startup = started.elapsed();
+
+    let tools = list_tool_names(server_name, &client, startup_timeout).await?;
+    if !tools.iter().any(|tool| tool == tool_name) {
+        let available = if tools.is_empty() {
+            "none".to_string()
+        } else {
+            tools.join(", ")
+        };
+        bail!(
+            "MCP server '{server_name}' does not advertise a tool named '{tool_name}' (available: {available})"
+        );
+    }
+
+    let started = Instant::now();
+    let result = client
+        .call_tool(
+            tool_name.to_string(),
+            arguments,
+            Some(
+                config
+                    .tool_call_timeout(tool_name)
+                    .unwrap_or(DEFAULT_TOOL_TIMEOUT),
+            ),
+        )
+        .await
+        .with_context(|| {
+            format!("failed to call tool '{tool_name}' on MCP server '{server_name}'")
+        })?;
+    Ok(McpToolCall {
+        result,
+        startup,
+        duration: started.elapsed(),
+    })
+}
+
+/// Names of every tool `client` advertises, sorted.
+async fn list_tool_names(
+    server_name: &str,
+    client: &RmcpClient,
+    timeout: Duration,
+) -> Result<Vec<String>> {
+    let list = client
+        .list_tools(None, Some(timeout))
+        .await
+        .with_context(|| format!("failed to list tools for MCP server '{server_name}'"))?;
+    let mut tools: Vec<String> = list.tools.into_iter().map(|tool| tool.name).collect();
+    tools.sort();
+    Ok(tools)
+}
+
+/// Launch or connect to `config` and perform the `initialize` handshake.
+async fn connect(
+    server_name: &str,
+    config: &McpServerConfig,
+    store_mode: OAuthCredentialsStoreMode,
+) -> Result<(RmcpClient, InitializeResult)> {
+    let timeout = config
+        .startup_timeout_sec
+        .unwrap_or(DEFAULT_STARTUP_TIMEOUT);
+
+    let client = match &config.transport {
+        McpServerTransportConfig::Stdio {
+            command,
+            args,
+            env,
+            env_vars,
+            cwd,
+            expand_env,
+            expand_env_strict,
+            shutdown_timeout_sec: _,
+            inherit_env,
+        } => {
+            let StdioLaunch { args, env, cwd } = resolve_stdio_launch(
+                server_name,
+                StdioLaunch {
+                    args: args.clone(),
+                    env: env.clone(),
+                    cwd: cwd.clone(),
+                },
+                *expand_env,
+                *expand_env_strict,
+            )?;
+            let program = OsString::from(command);
+            let args = args.into_iter().map(OsString::from).collect();
+            let server_env =
+                stdio_server_env(inherit_env.unwrap_or_default(), env.as_ref(), env_vars);
+            RmcpClient::new_stdio_client_with_env(program, args, server_env, cwd, None)
+                .await
+                .with_context(|| format!("failed to launch MCP server '{server_name}'"))?
+        }
+        McpServerTransportConfig::StreamableHttp {
+            url,
+            url_env_var,
+            bearer_token_env_var,
+            bearer_token_file,
+            http_headers,
+            env_http_headers,
+            env_http_header_defaults,
+            oauth_client_credentials,
+            keep_alive,
+            max_idle_connections,
+            connect_timeout_sec,
+            max_requests_per_sec,
+            client_cert_path,
+            client_key_path,
+            ca_cert_path,
+            health_check_interval_sec: _,
+        } => {
+            let url = resolve_streamable_http_url(server_name, url, url_env_var.as_deref())?;
+            let http_headers = resolve_http_headers(
+                http_headers.as_ref(),
+                env_http_headers.as_ref(),
+                env_http_header_defaults.as_ref(),
+            );
+            let connection_options = HttpConnectionOptions {
+                keep_alive: *keep_alive,
+                max_idle_connections: *max_idle_connections,
+                connect_timeout: *connect_timeout_sec,
+                max_requests_per_sec: *max_requests_per_sec,
+                client_cert_path: client_cert_path.clone(),
+                client_key_path: client_key_path.clone(),
+                ca_cert_path: ca_cert_path.clone(),
+            };
+            let client =
+                match resolve_client_credentials(server_name, oauth_client_credentials.as_ref())? {
+                    Some(credentials) => {
+                        RmcpClient::new_streamable_http_client_with_client_credentials(
+                            &url,
+                            credentials,
+                            http_headers,
+                            env_http_headers.clone(),
+                            connection_options,
+                        )
+                        .await
+                    }
+                    None => {
+                        let bearer_token = resolve_bearer_token(
+                            server_name,
+                            bearer_token_env_var.as_deref(),
+                            bearer_token_file.as_deref(),
+                        )?;
+                        RmcpClient::new_streamable_http_client(
+                            server_name,
+                            &url,
+                            bearer_token,
+                            http_headers,
+                            env_http_headers.clone(),
+                            store_mode,
+                            connection_options,
+                        )
+                        .await
+                    }
+                };
+            client.with_context(|| format!("failed to connect to MCP server '{server_name}'"))?
+        }
+    };
+
+    let initialize = client
+        .initialize(client_initialize_params(config), Some(timeout))
+        .await
+        .with_context(|| format!("failed to initialize MCP server '{server_name}'"))?;
+    Ok((client, initialize))
+}
@@ -0,0 +1,127 @@
+//! Facts about MCP servers that Codex records itself, such as when each one
+//! last connected. Kept in `$CODEX_HOME/mcp_state.json` so `config.toml`
+//! stays user-owned.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::io::ErrorKind;
+use std::io::Write;
+use std::path::Path;
+
+use chrono::DateTime;
+use chrono::Utc;
+use serde::Deserialize;
+use serde::Serialize;
+use tempfile::NamedTempFile;
+use tracing::warn;
+
+pub const MCP_STATE_FILE: &str = "mcp_state.json";
+
+/// Recorded state for one server.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct McpServerState {
+    /// When a session or `codex mcp ping` last finished `initialize` with
+    /// the server.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_connected: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct McpStateFile {
+    #[serde(default)]
+    servers: BTreeMap<String, McpServerState>,
+}
+
+/// Load the recorded state for every server. A missing file yields an empty
+/// map.
+pub fn load_server_states(codex_home: &Path) -> io::Result<BTreeMap<String, McpServerState>> {
+    Ok(read_state_file(codex_home)?.servers)
+}
+
+/// Record that each of `server_names` successfully connected at `at`.
+///
+/// An unreadable state file is replaced rather than treated as an error: the
+/// file only holds informational data that the next connection restores.
+pub fn record_connected<'a>(
+    codex_home: &Path,
+    server_names: impl IntoIterator<Item = &'a str>,
+    at: DateTime<Utc>,
+) -> io::Result<()> {
+    let mut names = server_names.into_iter().peekable();
+    if names.peek().is_none() {
+        return Ok(());
+    }
+
+    let mut state = read_state_file(codex_home).unwrap_or_else(|err| {
+        warn!("replacing unreadable {MCP_STATE_FILE}: {err}");
+        McpStateFile::default()
+    });
+    for name in names {
+        state
+            .servers
+            .entry(name.to_string())
+            .or_default()
+            .last_connected = Some(at);
+    }
+
+    std::fs::create_dir_all(codex_home)?;
+    let mut tmp_file = NamedTempFile::new_in(codex_home)?;
+    serde_json::to_writer_pretty(&mut tmp_file, &state)?;
+    tmp_file.write_all(b"\n")?;
+    tmp_file
+        .persist(codex_home.join(MCP_STATE_FILE))
+        .map_err(|err| err.error)?;
+    Ok(())
+}
+
+fn read_state_file(codex_home: &Path) -> io::Result<McpStateFile> {
+    match std::fs::read(codex_home.join(MCP_STATE_FILE)) {
+        Ok(contents) => Ok(serde_json::from_slice(&contents)?),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(McpStateFile::default()),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    #[test]
+    fn missing_state_file_is_empty() {
+        let codex_home = TempDir::new().unwrap();
+        assert_eq!(
+            load_server_states(codex_home.path()).unwrap(),
+            BTreeMap::new()
+        );
+    }
+
+    #[test]
+    fn record_connected_updates_only_named_servers() {
+        let codex_home = TempDir::new().unwrap();
+        let earlier = Utc.with_ymd_and_hms(2025, 1, 1, 8, 0, 0).unwrap();
+        let later = Utc.with_ymd_and_hms(2025, 1, 2, 9, 30, 0).unwrap();
+
+        record_connected(codex_home.path(), ["docs", "search"], earlier).unwrap();
+        record_connected(codex_home.path(), ["docs"], later).unwrap();
+
+        let states = load_server_states(codex_home.path()).unwrap();
+        assert_eq!(states["docs"].last_connected, Some(later));
+        assert_eq!(states["search"].last_connected, Some(earlier));
+    }
+
+    #[test]
+    fn record_connected_replaces_corrupt_file() {
+        let codex_home = TempDir::new().unwrap();
+        std::fs::write(codex_home.path().join(MCP_STATE_FILE), "not json").unwrap();
+        assert!(load_server_states(codex_home.path()).is_err());
+
+        let at = Utc.with_ymd_and_hms(2025, 3, 4, 5, 6, 7).unwrap();
+        record_connected(codex_home.path(), ["docs"], at).unwrap();
+
+        let states = load_server_states(codex_home.path()).unwrap();
+        assert_eq!(states["docs"].last_connected, Some(at));
+    }
+}
@@ -6,20 +6,28 @@
 //! in a single aggregated map using the fully-qualified tool name
 //! `"<server><MCP_TOOL_NAME_DELIMITER><tool>"` as the key.
 
+use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::env;
 use std::ffi::OsString;
+use std::num::NonZeroUsize;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::PoisonError;
 use std::time::Duration;
+use std::time::Instant;
 
 use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
 use codex_mcp_client::McpClient;
+use codex_rmcp_client::HttpConnectionOptions;
+use codex_rmcp_client::OAuthClientCredentials;
 use codex_rmcp_client::OAuthCredentialsStoreMode;
 use codex_rmcp_client::RmcpClient;
+use futures::future::join_all;
 use mcp_types::ClientCapabilities;
 use mcp_types::Implementation;
 use mcp_types::ListResourceTemplatesRequestParams;
@@ -35,12 +43,25 @@ use mcp_types::Tool;
 use serde_json::json;
 use sha1::Digest;
 use sha1::Sha1;
+use tokio::sync::Mutex;
+use tokio::sync::RwLock;
+use tokio::sync::Semaphore;
 use tokio::task::JoinSet;
+use tokio::time::MissedTickBehavior;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
+use tracing::Span;
+use tracing::field;
 use tracing::info;
+use tracing::info_span;
 use tracing::warn;
 
+use crate::config_types::McpInheritEnv;
+use crate::config_types::McpOAuthClientCredentials;
 use crate::config_types::McpServerConfig;
 use crate::config_types::McpServerTransportConfig;
+use crate::mcp::env_expansion::StdioLaunch;
+use crate::mcp::logs::open_server_log;
 
 /// Delimiter used to separate the server name from the tool name in a fully
 /// qualified tool name.
@@ -51,10 +72,14 @@ const MCP_TOOL_NAME_DELIMITER: &str = "__";
 const MAX_TOOL_NAME_LENGTH: usize = 64;
 
 /// Default timeout for initializing MCP server & initially listing tools.
-const DEFAULT_STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+pub const DEFAULT_STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// Default timeout for individual tool calls.
-const DEFAULT_TOOL_TIMEOUT: Duration = Duration::from_secs(60);
+pub const DEFAULT_TOOL_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Default time a stdio server gets to exit after its stdin is closed before
+/// it is killed.
+const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
 
 /// Map that holds a startup error for every MCP server that could **not** be
 /// spawned successfully.
@@ -92,22 +117,171 @@ fn qualify_tools(tools: Vec<ToolInfo>) -> HashMap<String, ToolInfo> {
     qualified_tools
 }
 
+#[derive(Clone)]
 struct ToolInfo {
     server_name: String,
     tool_name: String,
     tool: Tool,
 }
 
+#[derive(Clone)]
 struct ManagedClient {
     client: McpClientAdapter,
+    /// `stdio` or `streamable_http`, recorded on tool call spans.
+    transport: &'static str,
     startup_timeout: Duration,
     tool_timeout: Option<Duration>,
+    /// Per-tool overrides of `tool_timeout` from `tool_timeouts`.
+    tool_timeouts: HashMap<String, Duration>,
+    tool_filter: ToolFilter,
+    shutdown_timeout: Duration,
+}
+
+/// Per-server `allowed_tools` / `denied_tools` settings. A tool listed in
+/// `denied` is hidden even when it also appears in `allowed`.
+#[derive(Clone, Default)]
+struct ToolFilter {
+    allowed: Option<HashSet<String>>,
+    denied: HashSet<String>,
+}
+
+impl ToolFilter {
+    fn from_config(cfg: &McpServerConfig) -> Self {
+        Self {
+            allowed: cfg
+                .allowed_tools
+                .as_ref()
+                .map(|tools| tools.iter().cloned().collect()),
+            denied: cfg.denied_tools.iter().flatten().cloned().collect(),
+        }
+    }
+
+    fn allows(&self, tool_name: &str) -> bool {
+        if self.denied.contains(tool_name) {
+            return false;
+        }
+        self.allowed
+            .as_ref()
+            .is_none_or(|allowed| allowed.contains(tool_name))
+    }
 }
 
 #[derive(Clone)]
 enum McpClientAdapter {
     Legacy(Arc<McpClient>),
     Rmcp(Arc<RmcpClient>),
+    Monitored(Arc<MonitoredRmcpClient>),
+}
+
+/// Everything needed to open a streamable HTTP connection, kept so a
+/// health-checked server can be reconnected with the same settings.
+#[derive(Clone)]
+struct StreamableHttpLaunch {
+    server_name: String,
+    url: String,
+    bearer_token: Option<String>,
+    client_credentials: Option<OAuthClientCredentials>,
+    http_headers: Option<HashMap<String, String>>,
+    env_http_headers: Option<HashMap<String, String>>,
+    params: mcp_types::InitializeRequestParams,
+    startup_timeout: Duration,
+    store_mode: OAuthCredentialsStoreMode,
+    connection_options: HttpConnectionOptions,
+}
+
+impl StreamableHttpLaunch {
+    async fn connect(&self) -> Result<Arc<RmcpClient>> {
+        let client = match self.client_credentials.clone() {
+            Some(credentials) => {
+                RmcpClient::new_streamable_http_client_with_client_credentials(
+                    &self.url,
+                    credentials,
+                    self.http_headers.clone(),
+                    self.env_http_headers.clone(),
+                    self.connection_options.clone(),
+                )
+                .await?
+            }
+            None => {
+                RmcpClient::new_streamable_http_client(
+                    &self.server_name,
+                    &self.url,
+                    self.bearer_token.clone(),
+                    self.http_headers.clone(),
+                    self.env_http_headers.clone(),
+                    self.store_mode,
+                    self.connection_options.clone(),
+                )
+                .await?
+            }
+        };
+        let client = Arc::new(client);
+        client
+            .initialize(self.params.clone(), Some(self.startup_timeout))
+            .await?;
+        Ok(client)
+    }
+}
+
+/// A streamable HTTP client with `health_check_interval_sec` set. A
+/// background task pings the server on that interval and swaps in a fresh
+/// connection whenever a ping fails.
+struct MonitoredRmcpClient {
+    current: RwLock<Arc<RmcpClient>>,
+    cancel: CancellationToken,
+}
+
+impl MonitoredRmcpClient {
+    fn spawn(
+        launch: StreamableHttpLaunch,
+        client: Arc<RmcpClient>,
+        interval: Duration,
+    ) -> Arc<Self> {
+        let monitored = Arc::new(Self {
+            current: RwLock::new(client),
+            cancel: CancellationToken::new(),
+        });
+        let weak = Arc::downgrade(&monitored);
+        let cancel = monitored.cancel.clone();
+        tokio::spawn(async move {
+            let mut ticker =
+                tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    _ = ticker.tick() => {}
+                }
+                let Some(monitored) = weak.upgrade() else {
+                    break;
+                };
+                monitored.check(&launch).await;
+            }
+        });
+        monitored
+    }
+
+    async fn client(&self) -> Arc<RmcpClient> {
+        self.current.read().await.clone()
+    }
+
+    /// Ping the server and reconnect if it does not answer within the
+    /// startup timeout.
+    async fn check(&self, launch: &StreamableHttpLaunch) {
+        let server_name = &launch.server_name;
+        let Err(err) = self.client().await.ping(Some(launch.startup_timeout)).await else {
+            return;
+        };
+        warn!("MCP server '{server_name}' failed its health check: {err:#}; reconnecting");
+        match launch.connect().await {
+            Ok(client) if !self.cancel.is_cancelled() => {
+                *self.current.write().await = client;
+                info!("MCP server '{server_name}' reconnected");
+            }
+            Ok(_) => {}
+            Err(err) => warn!("failed to reconnect MCP server '{server_name}': {err:#}"),
+        }
+    }
 }
 
 impl McpClientAdapter {
@@ -118,47 +292,52 @@ impl McpClientAdapter {
         args: Vec<OsString>,
         env: Option<HashMap<String, String>>,
         env_vars: Vec<String>,
+        inherit_env: McpInheritEnv,
         cwd: Option<PathBuf>,
+        stderr_log: Option<std::fs::File>,
         params: mcp_types::InitializeRequestParams,
         startup_timeout: Duration,
     ) -> Result<Self> {
+        let server_env = stdio_server_env(inherit_env, env.as_ref(), &env_vars);
         if use_rmcp_client {
-            let client =
-                Arc::new(RmcpClient::new_stdio_client(program, args, env, &env_vars, cwd).await?);
+            let client = Arc::new(
+                RmcpClient::new_stdio_client_with_env(program, args, server_env, cwd, stderr_log)
+                    .await?,
+            );
             client.initialize(params, Some(startup_timeout)).await?;
             Ok(McpClientAdapter::Rmcp(client))
         } else {
-            let client =
-                Arc::new(McpClient::new_stdio_client(program, args, env, &env_vars, cwd).await?);
+            let client = Arc::new(
+                McpClient::new_stdio_client_with_env(program, args, server_env, cwd, stderr_log)
+                    .await?,
+            );
             client.initialize(params, Some(startup_timeout)).await?;
             Ok(McpClientAdapter::Legacy(client))
         }
     }
 
-    #[allow(clippy::too_many_arguments)]
     async fn new_streamable_http_client(
-        server_name: String,
-        url: String,
-        bearer_token: Option<String>,
-        http_headers: Option<HashMap<String, String>>,
-        env_http_headers: Option<HashMap<String, String>>,
-        params: mcp_types::InitializeRequestParams,
-        startup_timeout: Duration,
-        store_mode: OAuthCredentialsStoreMode,
+        launch: StreamableHttpLaunch,
+        health_check_interval: Option<Duration>,
     ) -> Result<Self> {
-        let client = Arc::new(
-            RmcpClient::new_streamable_http_client(
-                &server_name,
-                &url,
-                bearer_token,
-                http_headers,
-                env_http_headers,
-                store_mode,
-            )
-            .await?,
-        );
-        client.initialize(params, Some(startup_timeout)).await?;
-        Ok(McpClientAdapter::Rmcp(client))
+        let client = launch.connect().await?;
+        Ok(match health_check_interval {
+            Some(interval) => {
+                McpClientAdapter::Monitored(MonitoredRmcpClient::spawn(launch, client, interval))
+            }
+            None => McpClientAdapter::Rmcp(client),
+        })
+    }
+
+    async fn shutdown(&self, grace: Duration) {
+        match self {
+            McpClientAdapter::Legacy(client) => client.shutdown(grace).await,
+            McpClientAdapter::Rmcp(client) => client.shutdown(grace).await,
+            McpClientAdapter::Monitored(monitored) => {
+                monitored.cancel.cancel();
+                monitored.client().await.shutdown(grace).await
+            }
+        }
     }
 
     async fn list_tools(
@@ -169,6 +348,9 @@ impl McpClientAdapter {
         match self {
             McpClientAdapter::Legacy(client) => client.list_tools(params, timeout).await,
             McpClientAdapter::Rmcp(client) => client.list_tools(params, timeout).await,
+            McpClientAdapter::Monitored(monitored) => {
+                monitored.client().await.list_tools(params, timeout).await
+            }
         }
     }
 
@@ -183,6 +365,13 @@ impl McpClientAdapter {
                 resources: Vec::new(),
             }),
             McpClientAdapter::Rmcp(client) => client.list_resources(params, timeout).await,
+            McpClientAdapter::Monitored(monitored) => {
+                monitored
+                    .client()
+                    .await
+                    .list_resources(params, timeout)
+                    .await
+            }
         }
     }
 
@@ -196,6 +385,13 @@ impl McpClientAdapter {
                 "resources/read is not supported by legacy MCP clients"
             )),
             McpClientAdapter::Rmcp(client) => client.read_resource(params, timeout).await,
+            McpClientAdapter::Monitored(monitored) => {
+                monitored
+                    .client()
+                    .await
+                    .read_resource(params, timeout)
+                    .await
+            }
         }
     }
 
@@ -210,6 +406,13 @@ impl McpClientAdapter {
                 resource_templates: Vec::new(),
             }),
             McpClientAdapter::Rmcp(client) => client.list_resource_templates(params, timeout).await,
+            McpClientAdapter::Monitored(monitored) => {
+                monitored
+                    .client()
+                    .await
+                    .list_resource_templates(params, timeout)
+                    .await
+            }
         }
     }
 
@@ -222,6 +425,13 @@ impl McpClientAdapter {
         match self {
             McpClientAdapter::Legacy(client) => client.call_tool(name, arguments, timeout).await,
             McpClientAdapter::Rmcp(client) => client.call_tool(name, arguments, timeout).await,
+            McpClientAdapter::Monitored(monitored) => {
+                monitored
+                    .client()
+                    .await
+                    .call_tool(name, arguments, timeout)
+                    .await
+            }
         }
     }
 }
@@ -229,6 +439,17 @@ impl McpClientAdapter {
 /// A thin wrapper around a set of running [`McpClient`] instances.
 #[derive(Default)]
 pub(crate) struct McpConnectionManager {
+    /// Replaced as a whole by [`McpConnectionManager::reload`]. Callers take
+    /// a snapshot, so a call in flight keeps the servers it started with.
+    servers: std::sync::RwLock<Arc<McpServers>>,
+
+    /// Held for the duration of a reload so concurrent reloads do not
+    /// interleave.
+    reloading: Mutex<()>,
+}
+
+#[derive(Default)]
+struct McpServers {
     /// Server-name -> client instance.
     ///
     /// The server name originates from the keys of the `mcp_servers` map in
@@ -237,6 +458,114 @@ pub(crate) struct McpConnectionManager {
 
     /// Fully qualified tool name -> tool instance.
     tools: HashMap<String, ToolInfo>,
+
+    /// Definition of every configured server, including ones that are
+    /// disabled or failed to start, compared against on reload.
+    configs: HashMap<String, McpServerConfig>,
+}
+
+/// Servers a reload added, restarted, or stopped, by name.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct McpReloadSummary {
+    /// Newly configured servers.
+    pub added: Vec<String>,
+    /// Servers whose definition changed, so they were stopped and started
+    /// again.
+    pub restarted: Vec<String>,
+    /// Servers no longer configured, which were stopped.
+    pub removed: Vec<String>,
+    /// Number of servers left as they were.
+    pub unchanged: usize,
+}
+
+impl McpReloadSummary {
+    /// Compare the definitions the servers are running with against a
+    /// reloaded configuration.
+    fn plan(
+        current: &HashMap<String, McpServerConfig>,
+        reloaded: &HashMap<String, McpServerConfig>,
+    ) -> Self {
+        let mut summary = Self::default();
+        for (name, cfg) in reloaded {
+            match current.get(name) {
+                None => summary.added.push(name.clone()),
+                Some(running) if running != cfg => summary.restarted.push(name.clone()),
+                Some(_) => summary.unchanged += 1,
+            }
+        }
+        summary.removed = current
+            .keys()
+            .filter(|name| !reloaded.contains_key(*name))
+            .cloned()
+            .collect();
+        summary.added.sort();
+        summary.restarted.sort();
+        summary.removed.sort();
+        summary
+    }
+
+    /// Whether a server that was running before the reload had to stop.
+    fn stops(&self, server_name: &str) -> bool {
+        self.restarted.iter().any(|name| name == server_name)
+            || self.removed.iter().any(|name| name == server_name)
+    }
+}
+
+impl std::fmt::Display for McpReloadSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        for (label, names) in [
+            ("added", &self.added),
+            ("restarted", &self.restarted),
+            ("removed", &self.removed),
+        ] {
+            if !names.is_empty() {
+                parts.push(format!("{label} {}", names.join(", ")));
+            }
+        }
+        if parts.is_empty() {
+            return write!(f, "MCP servers reloaded: no changes");
+        }
+        write!(
+            f,
+            "MCP servers reloaded: {}; {} unchanged",
+            parts.join("; "),
+            self.unchanged
+        )
+    }
+}
+
+/// Servers a restart stopped and started again, by name.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct McpRestartSummary {
+    /// Servers running again, with the number of tools each now offers.
+    pub restarted: Vec<(String, usize)>,
+    /// Requested servers that were not running, so nothing was restarted.
+    pub inactive: Vec<String>,
+}
+
+impl std::fmt::Display for McpRestartSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let restarted = self
+            .restarted
+            .iter()
+            .map(|(name, count)| {
+                let noun = if *count == 1 { "tool" } else { "tools" };
+                format!("{name} ({count} {noun})")
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let inactive = self.inactive.join(", ");
+        match (restarted.is_empty(), inactive.is_empty()) {
+            (true, true) => write!(f, "MCP servers restarted: none"),
+            (false, true) => write!(f, "MCP servers restarted: {restarted}"),
+            (true, false) => write!(f, "MCP servers not currently active: {inactive}"),
+            (false, false) => write!(
+                f,
+                "MCP servers restarted: {restarted}; not currently active: {inactive}"
+            ),
+        }
+    }
 }
 
 impl McpConnectionManager {
@@ -248,21 +577,33 @@ impl McpConnectionManager {
     ///
     /// Servers that fail to start are reported in `ClientStartErrors`: the
     /// user should be informed about these errors.
+    ///
+    /// When `log_dir` is set, each stdio server's stderr is appended to its
+    /// [`crate::mcp::logs::server_log_path`].
+    ///
+    /// With `startup_concurrency`, at most that many servers initialize at
+    /// once and the rest start, highest `startup_priority` first, as earlier
+    /// ones finish. A server's `startup_timeout_sec` counts from its own
+    /// launch, not from the time spent waiting for a slot.
     pub async fn new(
         mcp_servers: HashMap<String, McpServerConfig>,
         use_rmcp_client: bool,
         store_mode: OAuthCredentialsStoreMode,
+        log_dir: Option<PathBuf>,
+        startup_concurrency: Option<NonZeroUsize>,
     ) -> Result<(Self, ClientStartErrors)> {
         // Early exit if no servers are configured.
         if mcp_servers.is_empty() {
             return Ok((Self::default(), ClientStartErrors::default()));
         }
+        let configs = mcp_servers.clone();
 
-        // Launch all configured servers concurrently.
+        // Launch the configured servers concurrently, up to the limit.
         let mut join_set = JoinSet::new();
         let mut errors = ClientStartErrors::new();
+        let startup_slots = startup_concurrency.map(|limit| Arc::new(Semaphore::new(limit.get())));
 
-        for (server_name, cfg) in mcp_servers {
+        for (server_name, cfg) in startup_order(mcp_servers) {
             // Validate server name before spawning
             if !is_valid_mcp_server_name(&server_name) {
                 let error = anyhow::anyhow!(
@@ -272,43 +613,65 @@ impl McpConnectionManager {
                 continue;
             }
 
-            if !cfg.enabled {
+            if !cfg.is_effectively_enabled() {
                 continue;
             }
 
             let startup_timeout = cfg.startup_timeout_sec.unwrap_or(DEFAULT_STARTUP_TIMEOUT);
             let tool_timeout = cfg.tool_timeout_sec.unwrap_or(DEFAULT_TOOL_TIMEOUT);
+            let tool_timeouts = cfg.tool_timeouts.clone().unwrap_or_default();
+            let tool_filter = ToolFilter::from_config(&cfg);
+            let shutdown_timeout = match &cfg.transport {
+                McpServerTransportConfig::Stdio {
+                    shutdown_timeout_sec,
+                    ..
+                } => shutdown_timeout_sec.unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT),
+                McpServerTransportConfig::StreamableHttp { .. } => DEFAULT_SHUTDOWN_TIMEOUT,
+            };
 
-            let resolved_bearer_token = match &cfg.transport {
+            let (resolved_bearer_token, resolved_client_credentials) = match &cfg.transport {
                 McpServerTransportConfig::StreamableHttp {
                     bearer_token_env_var,
+                    bearer_token_file,
+                    oauth_client_credentials,
                     ..
-                } => resolve_bearer_token(&server_name, bearer_token_env_var.as_deref()),
-                _ => Ok(None),
+                } => (
+                    resolve_bearer_token(
+                        &server_name,
+                        bearer_token_env_var.as_deref(),
+                        bearer_token_file.as_deref(),
+                    ),
+                    resolve_client_credentials(&server_name, oauth_client_credentials.as_ref()),
+                ),
+                _ => (Ok(None), Ok(None)),
+            };
+            let log_dir = log_dir.clone();
+            let transport_kind = cfg.transport.kind();
+            let span = info_span!(
+                "mcp_server_startup",
+                server = %server_name,
+                transport = transport_kind,
+                startup_timeout_sec = startup_timeout.as_secs_f64(),
+                duration_ms = field::Empty,
+            );
+
+            // Waiting here, rather than inside the task, keeps launches in
+            // priority order.
+            let startup_slot = match &startup_slots {
+                Some(slots) => Some(
+                    Arc::clone(slots)
+                        .acquire_owned()
+                        .await
+                        .context("MCP startup semaphore closed")?,
+                ),
+                None => None,
             };
 
-            join_set.spawn(async move {
+            let startup = async move {
+                let _startup_slot = startup_slot;
+                let started = Instant::now();
+                let params = client_initialize_params(&cfg);
                 let McpServerConfig { transport, .. } = cfg;
-                let params = mcp_types::InitializeRequestParams {
-                    capabilities: ClientCapabilities {
-                        experimental: None,
-                        roots: None,
-                        sampling: None,
-                        // https://modelcontextprotocol.io/specification/2025-06-18/client/elicitation#capabilities
-                        // indicates this should be an empty object.
-                        elicitation: Some(json!({})),
-                    },
-                    client_info: Implementation {
-                        name: "codex-mcp-client".to_owned(),
-                        version: env!("CARGO_PKG_VERSION").to_owned(),
-                        title: Some("Codex".into()),
-                        // This field is used by Codex when it is an MCP
-                        // server: it should not be used when Codex is
-                        // an MCP client.
-                        user_agent: None,
-                    },
-                    protocol_version: mcp_types::MCP_SCHEMA_VERSION.to_owned(),
-                };
 
                 let client = match transport {
                     McpServerTransportConfig::Stdio {
@@ -317,50 +680,136 @@ impl McpConnectionManager {
                         env,
                         env_vars,
                         cwd,
-                    } => {
-                        let command_os: OsString = command.into();
-                        let args_os: Vec<OsString> = args.into_iter().map(Into::into).collect();
-                        McpClientAdapter::new_stdio_client(
-                            use_rmcp_client,
-                            command_os,
-                            args_os,
-                            env,
-                            env_vars,
-                            cwd,
-                            params,
-                            startup_timeout,
-                        )
-                        .await
-                    }
+                        expand_env,
+                        expand_env_strict,
+                        shutdown_timeout_sec: _,
+                        inherit_env,
+                    } => match resolve_stdio_launch(
+                        &server_name,
+                        StdioLaunch { args, env, cwd },
+                        expand_env,
+                        expand_env_strict,
+                    ) {
+                        Ok(StdioLaunch { args, env, cwd }) => {
+                            let stderr_log = log_dir.as_deref().and_then(|log_dir| {
+                                open_server_log(log_dir, &server_name)
+                                    .inspect_err(|err| {
+                                        warn!(
+                                            "failed to open stderr log for MCP server '{server_name}': {err}"
+                                        );
+                                    })
+                                    .ok()
+                            });
+                            let command_os: OsString = command.into();
+                            let args_os: Vec<OsString> = args.into_iter().map(Into::into).collect();
+                            McpClientAdapter::new_stdio_client(
+                                use_rmcp_client,
+                                command_os,
+                                args_os,
+                                env,
+                                env_vars,
+                                inherit_env.unwrap_or_default(),
+                                cwd,
+                                stderr_log,
+                                params,
+                                startup_timeout,
+                            )
+                            .await
+                        }
+                        Err(err) => Err(err),
+                    },
                     McpServerTransportConfig::StreamableHttp {
                         url,
+                        url_env_var,
                         http_headers,
                         env_http_headers,
+                        env_http_header_defaults,
+                        keep_alive,
+                        max_idle_connections,
+                        connect_timeout_sec,
+                        max_requests_per_sec,
+                        client_cert_path,
+                        client_key_path,
+                        ca_cert_path,
+                        health_check_interval_sec,
                         ..
-                    } => {
-                        McpClientAdapter::new_streamable_http_client(
-                            server_name.clone(),
-                            url,
-                            resolved_bearer_token.unwrap_or_default(),
-                            http_headers,
-                            env_http_headers,
-                            params,
-                            startup_timeout,
-                            store_mode,
-                        )
-                        .await
-                    }
+                    } => match (
+                        resolve_streamable_http_url(&server_name, &url, url_env_var.as_deref()),
+                        resolved_client_credentials,
+                    ) {
+                        (Ok(url), Ok(client_credentials)) => {
+                            let launch = StreamableHttpLaunch {
+                                server_name: server_name.clone(),
+                                url,
+                                bearer_token: resolved_bearer_token.unwrap_or_default(),
+                                client_credentials,
+                                http_headers: resolve_http_headers(
+                                    http_headers.as_ref(),
+                                    env_http_headers.as_ref(),
+                                    env_http_header_defaults.as_ref(),
+                                ),
+                                env_http_headers,
+                                params,
+                                startup_timeout,
+                                store_mode,
+                                connection_options: HttpConnectionOptions {
+                                    keep_alive,
+                                    max_idle_connections,
+                                    connect_timeout: connect_timeout_sec,
+                                    max_requests_per_sec,
+                                    client_cert_path,
+                                    client_key_path,
+                                    ca_cert_path,
+                                },
+                            };
+                            McpClientAdapter::new_streamable_http_client(
+                                launch,
+                                health_check_interval_sec,
+                            )
+                            .await
+                        }
+                        (Err(err), _) | (_, Err(err)) => Err(err),
+                    },
                 }
                 .map(|c| (c, startup_timeout));
 
-                ((server_name, tool_timeout), client)
-            });
+                let duration_ms = started.elapsed().as_secs_f64() * 1000.0;
+                Span::current().record("duration_ms", duration_ms);
+                if client.is_ok() {
+                    info!("MCP server '{server_name}' started in {duration_ms:.0} ms");
+                } else {
+                    info!("MCP server '{server_name}' failed to start after {duration_ms:.0} ms");
+                }
+
+                (
+                    (
+                        server_name,
+                        transport_kind,
+                        tool_timeout,
+                        tool_timeouts,
+                        tool_filter,
+                        shutdown_timeout,
+                    ),
+                    client,
+                )
+            };
+            join_set.spawn(startup.instrument(span));
         }
 
         let mut clients: HashMap<String, ManagedClient> = HashMap::with_capacity(join_set.len());
 
         while let Some(res) = join_set.join_next().await {
-            let ((server_name, tool_timeout), client_res) = match res {
+            let (
+                (
+                    server_name,
+                    transport,
+                    tool_timeout,
+                    tool_timeouts,
+                    tool_filter,
+                    shutdown_timeout,
+                ),
+                client_res,
+            ) = match res {
                 Ok(result) => result,
                 Err(e) => {
                     warn!("Task panic when starting MCP server: {e:#}");
@@ -374,8 +823,12 @@ impl McpConnectionManager {
                         server_name,
                         ManagedClient {
                             client,
+                            transport,
                             startup_timeout,
                             tool_timeout: Some(tool_timeout),
+                            tool_timeouts,
+                            tool_filter,
+                            shutdown_timeout,
                         },
                     );
                 }
@@ -395,13 +848,218 @@ impl McpConnectionManager {
 
         let tools = qualify_tools(all_tools);
 
-        Ok((Self { clients, tools }, errors))
+        let servers = McpServers {
+            clients,
+            tools,
+            configs,
+        };
+        Ok((
+            Self {
+                servers: std::sync::RwLock::new(Arc::new(servers)),
+                reloading: Mutex::default(),
+            },
+            errors,
+        ))
+    }
+
+    /// Apply a reloaded `mcp_servers` map: start servers that are new or
+    /// whose definition changed, stop ones that were removed or changed, and
+    /// leave the rest running. The other arguments are as for [`Self::new`].
+    ///
+    /// Servers that fail to start are reported in `ClientStartErrors`, and a
+    /// changed server that fails to start stays stopped.
+    pub async fn reload(
+        &self,
+        mcp_servers: HashMap<String, McpServerConfig>,
+        use_rmcp_client: bool,
+        store_mode: OAuthCredentialsStoreMode,
+        log_dir: Option<PathBuf>,
+        startup_concurrency: Option<NonZeroUsize>,
+    ) -> Result<(McpReloadSummary, ClientStartErrors)> {
+        let _reloading = self.reloading.lock().await;
+        let summary = McpReloadSummary::plan(&self.snapshot().configs, &mcp_servers);
+        let to_start = mcp_servers
+            .iter()
+            .filter(|(name, _)| summary.added.contains(*name) || summary.restarted.contains(*name))
+            .map(|(name, cfg)| (name.clone(), cfg.clone()))
+            .collect();
+
+        // Stop before starting, so a changed server never runs twice at once.
+        self.stop_servers(|name| summary.stops(name), Some(mcp_servers))
+            .await;
+        let (started, errors) = Self::new(
+            to_start,
+            use_rmcp_client,
+            store_mode,
+            log_dir,
+            startup_concurrency,
+        )
+        .await?;
+        self.add_servers(&started.snapshot());
+        Ok((summary, errors))
+    }
+
+    /// Stop the named running servers and start them again from the
+    /// definitions they are running with, re-running `initialize`. An empty
+    /// `names` restarts every running server. The other arguments are as for
+    /// [`Self::new`].
+    ///
+    /// A server that fails to start again is reported in `ClientStartErrors`
+    /// and stays stopped.
+    pub async fn restart(
+        &self,
+        names: &[String],
+        use_rmcp_client: bool,
+        store_mode: OAuthCredentialsStoreMode,
+        log_dir: Option<PathBuf>,
+        startup_concurrency: Option<NonZeroUsize>,
+    ) -> Result<(McpRestartSummary, ClientStartErrors)> {
+        let _reloading = self.reloading.lock().await;
+        let current = self.snapshot();
+        let mut summary = McpRestartSummary::default();
+        let to_start: HashMap<String, McpServerConfig> = current
+            .configs
+            .iter()
+            .filter(|(name, _)| {
+                current.clients.contains_key(*name) && (names.is_empty() || names.contains(*name))
+            })
+            .map(|(name, cfg)| (name.clone(), cfg.clone()))
+            .collect();
+        summary.inactive = names
+            .iter()
+            .filter(|name| !to_start.contains_key(*name))
+            .cloned()
+            .collect();
+        summary.inactive.sort();
+        summary.inactive.dedup();
+
+        // Stop before starting: a server holding a lock, port, or other
+        // single-instance resource could not start next to its old process.
+        self.stop_servers(|name| to_start.contains_key(name), None)
+            .await;
+        let (started, errors) = Self::new(
+            to_start,
+            use_rmcp_client,
+            store_mode,
+            log_dir,
+            startup_concurrency,
+        )
+        .await?;
+        let started = started.snapshot();
+        summary.restarted = started
+            .clients
+            .keys()
+            .map(|name| {
+                let count = started
+                    .tools
+                    .values()
+                    .filter(|tool| &tool.server_name == name)
+                    .count();
+                (name.clone(), count)
+            })
+            .collect();
+        summary.restarted.sort();
+
+        self.add_servers(&started);
+        Ok((summary, errors))
+    }
+
+    /// Remove the running servers `stops` selects, with their tools, and
+    /// shut them down. `configs` replaces the recorded definitions when set.
+    async fn stop_servers(
+        &self,
+        stops: impl Fn(&str) -> bool,
+        configs: Option<HashMap<String, McpServerConfig>>,
+    ) {
+        let mut stopped = Vec::new();
+        {
+            let mut servers = self.servers.write().unwrap_or_else(PoisonError::into_inner);
+            let mut clients = HashMap::with_capacity(servers.clients.len());
+            for (name, managed) in &servers.clients {
+                if stops(name) {
+                    stopped.push(managed.clone());
+                } else {
+                    clients.insert(name.clone(), managed.clone());
+                }
+            }
+            let tools = servers
+                .tools
+                .values()
+                .filter(|tool| !stops(&tool.server_name))
+                .cloned()
+                .collect();
+            let configs = configs.unwrap_or_else(|| servers.configs.clone());
+            *servers = Arc::new(McpServers {
+                clients,
+                tools: qualify_tools(tools),
+                configs,
+            });
+        }
+
+        join_all(
+            stopped
+                .iter()
+                .map(|managed| managed.client.shutdown(managed.shutdown_timeout)),
+        )
+        .await;
+    }
+
+    /// Add the clients and tools of `started` to the running servers.
+    fn add_servers(&self, started: &McpServers) {
+        let mut servers = self.servers.write().unwrap_or_else(PoisonError::into_inner);
+        let clients = servers
+            .clients
+            .iter()
+            .chain(&started.clients)
+            .map(|(name, managed)| (name.clone(), managed.clone()))
+            .collect();
+        let tools = servers
+            .tools
+            .values()
+            .chain(started.tools.values())
+            .cloned()
+            .collect();
+        *servers = Arc::new(McpServers {
+            clients,
+            tools: qualify_tools(tools),
+            configs: servers.configs.clone(),
+        });
+    }
+
+    fn snapshot(&self) -> Arc<McpServers> {
+        let servers = self.servers.read().unwrap_or_else(PoisonError::into_inner);
+        Arc::clone(&*servers)
+    }
+
+    /// Definitions of the configured servers, as last started or reloaded.
+    pub fn server_configs(&self) -> HashMap<String, McpServerConfig> {
+        self.snapshot().configs.clone()
+    }
+
+    /// Close every server connection. Stdio servers get their
+    /// `shutdown_timeout_sec` to exit after stdin is closed before they are
+    /// killed.
+    pub async fn shutdown(&self) {
+        let servers = self.snapshot();
+        join_all(
+            servers
+                .clients
+                .values()
+                .map(|managed| managed.client.shutdown(managed.shutdown_timeout)),
+        )
+        .await;
+    }
+
+    /// Names of the servers that started successfully.
+    pub fn server_names(&self) -> Vec<String> {
+        self.snapshot().clients.keys().cloned().collect()
     }
 
     /// Returns a single map that contains all tools. Each key is the
     /// fully-qualified name for the tool.
     pub fn list_all_tools(&self) -> HashMap<String, Tool> {
-        self.tools
+        self.snapshot()
+            .tools
             .iter()
             .map(|(name, tool)| (name.clone(), tool.tool.clone()))
             .collect()
@@ -411,8 +1069,9 @@ impl McpConnectionManager {
     /// server name and the value is a vector of resources.
     pub async fn list_all_resources(&self) -> HashMap<String, Vec<Resource>> {
         let mut join_set = JoinSet::new();
+        let servers = self.snapshot();
 
-        for (server_name, managed_client) in &self.clients {
+        for (server_name, managed_client) in &servers.clients {
             let server_name_cloned = server_name.clone();
             let client_clone = managed_client.client.clone();
             let timeout = managed_client.tool_timeout;
@@ -471,8 +1130,9 @@ impl McpConnectionManager {
     /// server name and the value is a vector of resource templates.
     pub async fn list_all_resource_templates(&self) -> HashMap<String, Vec<ResourceTemplate>> {
         let mut join_set = JoinSet::new();
+        let servers = self.snapshot();
 
-        for (server_name, managed_client) in &self.clients {
+        for (server_name, managed_client) in &servers.clients {
             let server_name_cloned = server_name.clone();
             let client_clone = managed_client.client.clone();
             let timeout = managed_client.tool_timeout;
@@ -541,17 +1201,37 @@ impl McpConnectionManager {
         tool: &str,
         arguments: Option<serde_json::Value>,
     ) -> Result<mcp_types::CallToolResult> {
-        let managed = self
+        let servers = self.snapshot();
+        let managed = servers
             .clients
             .get(server)
             .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))?;
         let client = &managed.client;
-        let timeout = managed.tool_timeout;
+        let timeout = managed
+            .tool_timeouts
+            .get(tool)
+            .copied()
+            .or(managed.tool_timeout);
+        let span = info_span!(
+            "mcp_tool_call",
+            server,
+            tool,
+            transport = managed.transport,
+            tool_timeout_sec = timeout.map(|timeout| timeout.as_secs_f64()),
+            duration_ms = field::Empty,
+        );
 
-        client
-            .call_tool(tool.to_string(), arguments, timeout)
-            .await
-            .with_context(|| format!("tool call failed for `{server}/{tool}`"))
+        async {
+            let started = Instant::now();
+            let result = client.call_tool(tool.to_string(), arguments, timeout).await;
+            let duration_ms = started.elapsed().as_secs_f64() * 1000.0;
+            Span::current().record("duration_ms", duration_ms);
+            info!("MCP tool call `{server}/{tool}` finished in {duration_ms:.0} ms");
+            result
+        }
+        .instrument(span)
+        .await
+        .with_context(|| format!("tool call failed for `{server}/{tool}`"))
     }
 
     /// List resources from the specified server.
@@ -560,7 +1240,8 @@ impl McpConnectionManager {
         server: &str,
         params: Option<ListResourcesRequestParams>,
     ) -> Result<ListResourcesResult> {
-        let managed = self
+        let servers = self.snapshot();
+        let managed = servers
             .clients
             .get(server)
             .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))?;
@@ -579,7 +1260,8 @@ impl McpConnectionManager {
         server: &str,
         params: Option<ListResourceTemplatesRequestParams>,
     ) -> Result<ListResourceTemplatesResult> {
-        let managed = self
+        let servers = self.snapshot();
+        let managed = servers
             .clients
             .get(server)
             .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))?;
@@ -598,7 +1280,8 @@ impl McpConnectionManager {
         server: &str,
         params: ReadResourceRequestParams,
     ) -> Result<ReadResourceResult> {
-        let managed = self
+        let servers = self.snapshot();
+        let managed = servers
             .clients
             .get(server)
             .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))?;
@@ -613,20 +1296,220 @@ impl McpConnectionManager {
     }
 
     pub fn parse_tool_name(&self, tool_name: &str) -> Option<(String, String)> {
-        self.tools
+        self.snapshot()
+            .tools
             .get(tool_name)
             .map(|tool| (tool.server_name.clone(), tool.tool_name.clone()))
     }
 }
 
-fn resolve_bearer_token(
+/// `initialize` parameters for `config`: its `protocol_version`,
+/// `client_name`, and `client_version` when set, Codex's own otherwise.
+pub(crate) fn client_initialize_params(
+    config: &McpServerConfig,
+) -> mcp_types::InitializeRequestParams {
+    mcp_types::InitializeRequestParams {
+        capabilities: ClientCapabilities {
+            experimental: None,
+            roots: None,
+            sampling: None,
+            // https://modelcontextprotocol.io/specification/2025-06-18/client/elicitation#capabilities
+            // indicates this should be an empty object.
+            elicitation: Some(json!({})),
+        },
+        client_info: Implementation {
+            name: config
+                .client_name
+                .clone()
+                .unwrap_or_else(|| "codex-mcp-client".to_owned()),
+            version: config
+                .client_version
+                .clone()
+                .unwrap_or_else(|| env!("CARGO_PKG_VERSION").to_owned()),
+            title: Some("Codex".into()),
+            // This field is used by Codex when it is an MCP
+            // server: it should not be used when Codex is
+            // an MCP client.
+            user_agent: None,
+        },
+        protocol_version: config
+            .protocol_version
+            .as_deref()
+            .unwrap_or(mcp_types::MCP_SCHEMA_VERSION)
+            .to_owned(),
+    }
+}
+
+/// Apply `expand_env` to a stdio server's launch settings, if enabled.
+pub(crate) fn resolve_stdio_launch(
     server_name: &str,
-    bearer_token_env_var: Option<&str>,
-) -> Result<Option<String>> {
-    let Some(env_var) = bearer_token_env_var else {
+    launch: StdioLaunch,
+    expand_env: bool,
+    expand_env_strict: bool,
+) -> Result<StdioLaunch> {
+    if !expand_env {
+        return Ok(launch);
+    }
+    launch
+        .expand(expand_env_strict)
+        .with_context(|| format!("failed to expand environment for MCP server '{server_name}'"))
+}
+
+/// Variables a stdio server launched with `inherit_env = "minimal"` receives
+/// from Codex's environment.
+#[cfg(unix)]
+const MINIMAL_ENV_VARS: &[&str] = &["PATH", "HOME", "LANG"];
+
+#[cfg(windows)]
+const MINIMAL_ENV_VARS: &[&str] = &["PATH", "PATHEXT", "SYSTEMROOT", "USERPROFILE"];
+
+/// The complete environment for a stdio server: the variables `inherit_env`
+/// allows from Codex's environment, then `env_vars`, then `extra_env`.
+pub(crate) fn stdio_server_env(
+    inherit_env: McpInheritEnv,
+    extra_env: Option<&HashMap<String, String>>,
+    env_vars: &[String],
+) -> HashMap<String, String> {
+    let mut server_env: HashMap<String, String> = match inherit_env {
+        McpInheritEnv::All => env::vars().collect(),
+        McpInheritEnv::Minimal => MINIMAL_ENV_VARS
+            .iter()
+            .filter_map(|var| env::var(var).ok().map(|value| (var.to_string(), value)))
+            .collect(),
+        McpInheritEnv::None => HashMap::new(),
+    };
+    server_env.extend(
+        env_vars
+            .iter()
+            .filter_map(|var| env::var(var).ok().map(|value| (var.clone(), value))),
+    );
+    server_env.extend(extra_env.cloned().unwrap_or_default());
+    server_env
+}
+
+/// The environment a stdio server is launched with: `env` after `expand_env`,
+/// over the variables `inherit_env` allows from Codex's environment and
+/// `env_vars`. `None` for streamable HTTP servers.
+pub fn resolve_stdio_server_env(
+    server_name: &str,
+    transport: &McpServerTransportConfig,
+) -> Result<Option<HashMap<String, String>>> {
+    let McpServerTransportConfig::Stdio {
+        args,
+        env,
+        env_vars,
+        cwd,
+        expand_env,
+        expand_env_strict,
+        inherit_env,
+        ..
+    } = transport
+    else {
         return Ok(None);
     };
+    let StdioLaunch { env, .. } = resolve_stdio_launch(
+        server_name,
+        StdioLaunch {
+            args: args.clone(),
+            env: env.clone(),
+            cwd: cwd.clone(),
+        },
+        *expand_env,
+        *expand_env_strict,
+    )?;
+    Ok(Some(stdio_server_env(
+        inherit_env.unwrap_or_default(),
+        env.as_ref(),
+        env_vars,
+    )))
+}
+
+/// The URL to connect to for a streamable HTTP server, read from
+/// `url_env_var` when the config names one.
+pub fn resolve_streamable_http_url(
+    server_name: &str,
+    url: &str,
+    url_env_var: Option<&str>,
+) -> Result<String> {
+    match url_env_var {
+        Some(env_var) => resolve_secret_env_var(server_name, env_var),
+        None => Ok(url.to_string()),
+    }
+}
+
+/// The static headers to send to a streamable HTTP server, plus the literal
+/// from `env_http_header_defaults` for every `env_http_headers` entry whose
+/// variable is unset or empty.
+pub fn resolve_http_headers(
+    http_headers: Option<&HashMap<String, String>>,
+    env_http_headers: Option<&HashMap<String, String>>,
+    env_http_header_defaults: Option<&HashMap<String, String>>,
+) -> Option<HashMap<String, String>> {
+    let mut headers = http_headers.cloned();
+    for (header, default) in env_http_header_defaults.into_iter().flatten() {
+        let env_value_set = env_http_headers
+            .and_then(|env_headers| env_headers.get(header))
+            .and_then(|env_var| env::var(env_var).ok())
+            .is_some_and(|value| !value.trim().is_empty());
+        if !env_value_set {
+            headers
+                .get_or_insert_with(HashMap::new)
+                .insert(header.clone(), default.clone());
+        }
+    }
+    headers
+}
+
+pub(crate) fn resolve_bearer_token(
+    server_name: &str,
+    bearer_token_env_var: Option<&str>,
+    bearer_token_file: Option<&Path>,
+) -> Result<Option<String>> {
+    if let Some(path) = bearer_token_file {
+        return read_bearer_token_file(server_name, path).map(Some);
+    }
+    bearer_token_env_var
+        .map(|env_var| resolve_secret_env_var(server_name, env_var))
+        .transpose()
+}
 
+/// Read a bearer token mounted as a file, dropping the trailing newline that
+/// secret managers usually leave behind.
+fn read_bearer_token_file(server_name: &str, path: &Path) -> Result<String> {
+    let contents = std::fs::read_to_string(path).with_context(|| {
+        format!(
+            "failed to read bearer token file {} for MCP server '{server_name}'",
+            path.display()
+        )
+    })?;
+    let token = contents.trim_end();
+    if token.is_empty() {
+        return Err(anyhow!(
+            "Bearer token file {} for MCP server '{server_name}' is empty",
+            path.display()
+        ));
+    }
+    Ok(token.to_string())
+}
+
+/// Read the client secret for the OAuth client-credentials grant.
+pub(crate) fn resolve_client_credentials(
+    server_name: &str,
+    config: Option<&McpOAuthClientCredentials>,
+) -> Result<Option<OAuthClientCredentials>> {
+    config
+        .map(|config| {
+            Ok(OAuthClientCredentials {
+                token_url: config.token_url.clone(),
+                client_id: config.client_id.clone(),
+                client_secret: resolve_secret_env_var(server_name, &config.client_secret_env_var)?,
+                scopes: config.scopes.clone(),
+            })
+        })
+        .transpose()
+}
+
+fn resolve_secret_env_var(server_name: &str, env_var: &str) -> Result<String> {
     match env::var(env_var) {
         Ok(value) => {
             if value.is_empty() {
@@ -634,7 +1517,7 @@ fn resolve_bearer_token(
                     "Environment variable {env_var} for MCP server '{server_name}' is empty"
                 ))
             } else {
-                Ok(Some(value))
+                Ok(value)
             }
         }
         Err(env::VarError::NotPresent) => Err(anyhow!(
@@ -658,16 +1541,17 @@ async fn list_all_tools(clients: &HashMap<String, ManagedClient>) -> Result<Vec<
         let server_name_cloned = server_name.clone();
         let client_clone = managed_client.client.clone();
         let startup_timeout = managed_client.startup_timeout;
+        let tool_filter = managed_client.tool_filter.clone();
         join_set.spawn(async move {
             let res = client_clone.list_tools(None, Some(startup_timeout)).await;
-            (server_name_cloned, res)
+            (server_name_cloned, tool_filter, res)
         });
     }
 
     let mut aggregated: Vec<ToolInfo> = Vec::with_capacity(join_set.len());
 
     while let Some(join_res) = join_set.join_next().await {
-        let (server_name, list_result) = if let Ok(result) = join_res {
+        let (server_name, tool_filter, list_result) = if let Ok(result) = join_res {
             result
         } else {
             warn!("Task panic when listing tools for MCP server: {join_res:#?}");
@@ -682,6 +1566,9 @@ async fn list_all_tools(clients: &HashMap<String, ManagedClient>) -> Result<Vec<
         };
 
         for tool in list_result.tools {
+            if !tool_filter.allows(&tool.name) {
+                continue;
+            }
             let tool_info = ToolInfo {
                 server_name: server_name.clone(),
                 tool_name: tool.name.clone(),
@@ -700,6 +1587,17 @@ async fn list_all_tools(clients: &HashMap<String, ManagedClient>) -> Result<Vec<
     Ok(aggregated)
 }
 
+/// `mcp_servers` in launch order: highest `startup_priority` first, ties
+/// broken by name so the order is stable.
+fn startup_order(mcp_servers: HashMap<String, McpServerConfig>) -> Vec<(String, McpServerConfig)> {
+    let mut ordered: Vec<_> = mcp_servers.into_iter().collect();
+    ordered.sort_by(|(a_name, a_cfg), (b_name, b_cfg)| {
+        (Reverse(a_cfg.resolved_startup_priority()), a_name)
+            .cmp(&(Reverse(b_cfg.resolved_startup_priority()), b_name))
+    });
+    ordered
+}
+
 fn is_valid_mcp_server_name(server_name: &str) -> bool {
     !server_name.is_empty()
         && server_name
@@ -731,6 +1629,263 @@ mod tests {
         }
     }
 
+    #[test]
+    fn startup_order_puts_higher_priority_first_then_sorts_by_name() {
+        let server = |priority: Option<i32>| {
+            let mut cfg: McpServerConfig =
+                toml::from_str("command = \"server\"").expect("valid server config");
+            cfg.startup_priority = priority;
+            cfg
+        };
+        let servers = HashMap::from([
+            ("zeta".to_string(), server(None)),
+            ("alpha".to_string(), server(None)),
+            ("critical".to_string(), server(Some(10))),
+            ("optional".to_string(), server(Some(-5))),
+        ]);
+        let names: Vec<String> = startup_order(servers)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, vec!["critical", "alpha", "zeta", "optional"]);
+    }
+
+    #[test]
+    fn reload_plan_touches_only_changed_servers() {
+        let server = |command: &str| -> McpServerConfig {
+            toml::from_str(&format!("command = \"{command}\"")).expect("valid server config")
+        };
+        let current = HashMap::from([
+            ("docs".to_string(), server("docs-server")),
+            ("search".to_string(), server("search-server")),
+            ("old".to_string(), server("old-server")),
+        ]);
+        let reloaded = HashMap::from([
+            ("docs".to_string(), server("docs-server")),
+            ("search".to_string(), server("search-server-v2")),
+            ("new".to_string(), server("new-server")),
+        ]);
+
+        let summary = McpReloadSummary::plan(&current, &reloaded);
+        assert_eq!(
+            summary,
+            McpReloadSummary {
+                added: vec!["new".to_string()],
+                restarted: vec!["search".to_string()],
+                removed: vec!["old".to_string()],
+                unchanged: 1,
+            }
+        );
+        assert!(summary.stops("search"));
+        assert!(summary.stops("old"));
+        assert!(!summary.stops("docs"));
+        assert_eq!(
+            summary.to_string(),
+            "MCP servers reloaded: added new; restarted search; removed old; 1 unchanged"
+        );
+        assert_eq!(
+            McpReloadSummary::plan(&current, &current).to_string(),
+            "MCP servers reloaded: no changes"
+        );
+    }
+
+    #[test]
+    fn restart_summary_reports_tool_counts_and_inactive_servers() {
+        let summary = McpRestartSummary {
+            restarted: vec![("docs".to_string(), 7), ("search".to_string(), 1)],
+            inactive: vec!["old".to_string()],
+        };
+        assert_eq!(
+            summary.to_string(),
+            "MCP servers restarted: docs (7 tools), search (1 tool); not currently active: old"
+        );
+        assert_eq!(
+            McpRestartSummary {
+                restarted: Vec::new(),
+                inactive: vec!["old".to_string()],
+            }
+            .to_string(),
+            "MCP servers not currently active: old"
+        );
+        assert_eq!(
+            McpRestartSummary::default().to_string(),
+            "MCP servers restarted: none"
+        );
+    }
+
+    #[tokio::test]
+    async fn restart_reports_servers_that_are_not_running() {
+        let manager = McpConnectionManager::default();
+        let (summary, errors) = manager
+            .restart(
+                &["docs".to_string(), "docs".to_string()],
+                false,
+                OAuthCredentialsStoreMode::default(),
+                None,
+                None,
+            )
+            .await
+            .expect("restart");
+        assert_eq!(
+            summary,
+            McpRestartSummary {
+                restarted: Vec::new(),
+                inactive: vec!["docs".to_string()],
+            }
+        );
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn resolve_http_headers_uses_defaults_only_for_unset_env_vars() {
+        let http_headers = HashMap::from([("X-Static".to_string(), "1".to_string())]);
+        let env_http_headers = HashMap::from([
+            ("X-From-Env".to_string(), "PATH".to_string()),
+            (
+                "X-Missing".to_string(),
+                "CODEX_TEST_UNSET_HEADER_VAR".to_string(),
+            ),
+        ]);
+        let defaults = HashMap::from([
+            ("X-From-Env".to_string(), "unused".to_string()),
+            ("X-Missing".to_string(), "fallback".to_string()),
+        ]);
+
+        let headers = resolve_http_headers(
+            Some(&http_headers),
+            Some(&env_http_headers),
+            Some(&defaults),
+        );
+
+        assert_eq!(
+            headers,
+            Some(HashMap::from([
+                ("X-Static".to_string(), "1".to_string()),
+                ("X-Missing".to_string(), "fallback".to_string()),
+            ]))
+        );
+        assert_eq!(
+            resolve_http_headers(None, Some(&env_http_headers), None),
+            None
+        );
+    }
+
+    #[test]
+    fn stdio_server_env_limits_inherited_variables_by_mode() {
+        let extra_env = HashMap::from([("API_KEY".to_string(), "secret".to_string())]);
+        let env_vars = vec!["PATH".to_string()];
+        let path = env::var("PATH").expect("PATH should be set for tests");
+
+        assert_eq!(
+            stdio_server_env(McpInheritEnv::None, Some(&extra_env), &env_vars),
+            HashMap::from([
+                ("PATH".to_string(), path.clone()),
+                ("API_KEY".to_string(), "secret".to_string()),
+            ])
+        );
+        let minimal = stdio_server_env(McpInheritEnv::Minimal, Some(&extra_env), &[]);
+        assert_eq!(minimal.get("PATH"), Some(&path));
+        assert!(
+            minimal
+                .keys()
+                .all(|var| var == "API_KEY" || MINIMAL_ENV_VARS.contains(&var.as_str())),
+            "minimal let through more than {MINIMAL_ENV_VARS:?}: {:?}",
+            minimal.keys()
+        );
+        let all = stdio_server_env(McpInheritEnv::All, Some(&extra_env), &[]);
+        assert!(env::vars().all(|(var, _)| all.contains_key(&var)));
+        assert_eq!(all.get("API_KEY"), Some(&"secret".to_string()));
+    }
+
+    #[test]
+    fn resolve_stdio_server_env_expands_env_and_resolves_minimal() {
+        let path = env::var("PATH").expect("PATH should be set for tests");
+        let stdio = |inherit_env| McpServerTransportConfig::Stdio {
+            command: "docs-server".to_string(),
+            args: Vec::new(),
+            env: Some(HashMap::from([(
+                "TOOLS".to_string(),
+                "$PATH:/opt/tools".to_string(),
+            )])),
+            env_vars: Vec::new(),
+            cwd: None,
+            expand_env: true,
+            expand_env_strict: false,
+            shutdown_timeout_sec: None,
+            inherit_env,
+        };
+
+        assert_eq!(
+            resolve_stdio_server_env("docs", &stdio(Some(McpInheritEnv::None)))
+                .expect("env resolves"),
+            Some(HashMap::from([(
+                "TOOLS".to_string(),
+                format!("{path}:/opt/tools")
+            )]))
+        );
+        let minimal = resolve_stdio_server_env("docs", &stdio(None))
+            .expect("env resolves")
+            .expect("stdio servers have an environment");
+        assert_eq!(minimal.get("PATH"), Some(&path));
+        assert_eq!(minimal.get("TOOLS"), Some(&format!("{path}:/opt/tools")));
+        assert!(
+            minimal
+                .keys()
+                .all(|var| var == "TOOLS" || MINIMAL_ENV_VARS.contains(&var.as_str()))
+        );
+    }
+
+    #[test]
+    fn resolve_bearer_token_reads_and_trims_token_file() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("token");
+        std::fs::write(&path, "s3cret\n").expect("write token");
+        assert_eq!(
+            resolve_bearer_token("docs", None, Some(&path)).expect("token resolves"),
+            Some("s3cret".to_string())
+        );
+
+        std::fs::write(&path, " \n").expect("write token");
+        let err = resolve_bearer_token("docs", None, Some(&path)).expect_err("empty token");
+        assert!(
+            err.to_string().contains("is empty"),
+            "unexpected error: {err}"
+        );
+
+        let missing = dir.path().join("missing");
+        let err = resolve_bearer_token("docs", None, Some(&missing)).expect_err("missing file");
+        assert!(
+            err.to_string().contains("failed to read bearer token file"),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[test]
+    fn tool_filter_deny_wins_over_allow() {
+        let filter = ToolFilter {
+            allowed: Some(HashSet::from(["search".to_string(), "delete".to_string()])),
+            denied: HashSet::from(["delete".to_string()]),
+        };
+
+        assert_eq!(
+            ["search", "delete", "fetch"].map(|tool| filter.allows(tool)),
+            [true, false, false]
+        );
+    }
+
+    #[test]
+    fn tool_filter_without_allow_list_only_hides_denied() {
+        let filter = ToolFilter {
+            allowed: None,
+            denied: HashSet::from(["delete".to_string()]),
+        };
+
+        assert_eq!(
+            ["search", "delete"].map(|tool| filter.allows(tool)),
+            [true, false]
+        );
+    }
+
     #[test]
     fn test_qualify_tools_short_non_duplicated_names() {
         let tools = vec![
@@ -90,10 +90,24 @@ async fn stdio_server_round_trip() -> anyhow::Result<()> {
                         )])),
                         env_vars: Vec::new(),
                         cwd: None,
+                        expand_env: false,
+                        expand_env_strict: false,
+                        shutdown_timeout_sec: None,
+                        inherit_env: None,
                     },
                     enabled: true,
                     startup_timeout_sec: Some(Duration::from_secs(10)),
                     tool_timeout_sec: None,
+                    allowed_tools: None,
+                    denied_tools: None,
+                    protocol_version: None,
+                    enabled_when: None,
+                    description: None,
+                    startup_priority: None,
+                    client_name: None,
+                    client_version: None,
+                    tool_timeouts: None,
+                    tags: Vec::new(),
                 },
             );
         })
@@ -226,10 +240,24 @@ async fn stdio_server_propagates_whitelisted_env_vars() -> anyhow::Result<()> {
                         env: None,
                         env_vars: vec!["MCP_TEST_VALUE".to_string()],
                         cwd: None,
+                        expand_env: false,
+                        expand_env_strict: false,
+                        shutdown_timeout_sec: None,
+                        inherit_env: None,
                     },
                     enabled: true,
                     startup_timeout_sec: Some(Duration::from_secs(10)),
                     tool_timeout_sec: None,
+                    allowed_tools: None,
+                    denied_tools: None,
+                    protocol_version: None,
+                    enabled_when: None,
+                    description: None,
+                    startup_priority: None,
+                    client_name: None,
+                    client_version: None,
+                    tool_timeouts: None,
+                    tags: Vec::new(),
                 },
             );
         })
@@ -309,6 +337,103 @@ async fn stdio_server_propagates_whitelisted_env_vars() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// A server that allows one instance of itself at a time must come back
+/// after a restart, so the old process has to be gone before the new one is
+/// launched.
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn restart_stops_a_stdio_server_before_starting_it_again() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = responses::start_mock_server().await;
+    let server_name = "rmcp_single";
+
+    let rmcp_test_server_bin = CargoBuild::new()
+        .package("codex-rmcp-client")
+        .bin("test_stdio_server")
+        .run()?
+        .path()
+        .to_string_lossy()
+        .into_owned();
+    let lock_dir = tempdir()?;
+    let lock_path = lock_dir
+        .path()
+        .join("instance.lock")
+        .to_string_lossy()
+        .into_owned();
+    // Refuse to start while the lock is held, and release it once the server
+    // has exited after its stdin closed.
+    let script = r#"mkdir "$1" || exit 1; "$2"; rmdir "$1""#;
+
+    let fixture = test_codex()
+        .with_config(move |config| {
+            config.features.enable(Feature::RmcpClient);
+            config.mcp_servers.insert(
+                server_name.to_string(),
+                McpServerConfig {
+                    transport: McpServerTransportConfig::Stdio {
+                        command: "sh".to_string(),
+                        args: vec![
+                            "-c".to_string(),
+                            script.to_string(),
+                            "sh".to_string(),
+                            lock_path.clone(),
+                            rmcp_test_server_bin.clone(),
+                        ],
+                        env: None,
+                        env_vars: Vec::new(),
+                        cwd: None,
+                        expand_env: false,
+                        expand_env_strict: false,
+                        shutdown_timeout_sec: Some(Duration::from_secs(10)),
+                        inherit_env: None,
+                    },
+                    enabled: true,
+                    startup_timeout_sec: Some(Duration::from_secs(10)),
+                    tool_timeout_sec: None,
+                    allowed_tools: None,
+                    denied_tools: None,
+                    protocol_version: None,
+                    enabled_when: None,
+                    description: None,
+                    startup_priority: None,
+                    client_name: None,
+                    client_version: None,
+                    tool_timeouts: None,
+                    tags: Vec::new(),
+                },
+            );
+        })
+        .build(&server)
+        .await?;
+
+    fixture
+        .codex
+        .submit(Op::RestartMcpServers {
+            servers: vec![server_name.to_string()],
+        })
+        .await?;
+
+    let event = wait_for_event_with_timeout(
+        &fixture.codex,
+        |ev| match ev {
+            EventMsg::BackgroundEvent(event) => event.message.starts_with("MCP servers restarted"),
+            EventMsg::Error(_) => true,
+            _ => false,
+        },
+        Duration::from_secs(30),
+    )
+    .await;
+    let EventMsg::BackgroundEvent(event) = event else {
+        panic!("restart should succeed, got {event:?}");
+    };
+    assert_eq!(
+        event.message,
+        format!("MCP servers restarted: {server_name} (1 tool)")
+    );
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn streamable_http_tool_call_round_trip() -> anyhow::Result<()> {
     skip_if_no_network!(Ok(()));
@@ -374,13 +499,35 @@ async fn streamable_http_tool_call_round_trip() -> anyhow::Result<()> {
                 McpServerConfig {
                     transport: McpServerTransportConfig::StreamableHttp {
                         url: server_url,
+                        url_env_var: None,
                         bearer_token_env_var: None,
+                        bearer_token_file: None,
                         http_headers: None,
                         env_http_headers: None,
+                        env_http_header_defaults: None,
+                        oauth_client_credentials: None,
+                        keep_alive: true,
+                        max_idle_connections: None,
+                        connect_timeout_sec: None,
+                        max_requests_per_sec: None,
+                        client_cert_path: None,
+                        client_key_path: None,
+                        ca_cert_path: None,
+                        health_check_interval_sec: None,
                     },
                     enabled: true,
                     startup_timeout_sec: Some(Duration::from_secs(10)),
                     tool_timeout_sec: None,
+                    allowed_tools: None,
+                    denied_tools: None,
+                    protocol_version: None,
+                    enabled_when: None,
+                    description: None,
+                    startup_priority: None,
+                    client_name: None,
+                    client_version: None,
+                    tool_timeouts: None,
+                    tags: Vec::new(),
                 },
             );
         })
@@ -557,13 +704,35 @@ async fn streamable_http_with_oauth_round_trip() -> anyhow::Result<()> {
                 McpServerConfig {
                     transport: McpServerTransportConfig::StreamableHttp {
                         url: server_url,
+                        url_env_var: None,
                         bearer_token_env_var: None,
+                        bearer_token_file: None,
                         http_headers: None,
                         env_http_headers: None,
+                        env_http_header_defaults: None,
+                        oauth_client_credentials: None,
+                        keep_alive: true,
+                        max_idle_connections: None,
+                        connect_timeout_sec: None,
+                        max_requests_per_sec: None,
+                        client_cert_path: None,
+                        client_key_path: None,
+                        ca_cert_path: None,
+                        health_check_interval_sec: None,
                     },
                     enabled: true,
                     startup_timeout_sec: Some(Duration::from_secs(10)),
                     tool_timeout_sec: None,
+                    allowed_tools: None,
+                    denied_tools: None,
+                    protocol_version: None,
+                    enabled_when: None,
+                    description: None,
+                    startup_priority: None,
+                    client_name: None,
+                    client_version: None,
+                    tool_timeouts: None,
+                    tags: Vec::new(),
                 },
             );
         })
@@ -45,6 +45,7 @@ use tokio::io::AsyncWriteExt;
 use tokio::io::BufReader;
 use tokio::process::Command;
 use tokio::sync::Mutex;
+use tokio::sync::Notify;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
 use tokio::time;
@@ -65,8 +66,10 @@ pub struct McpClient {
     /// Retain this child process until the client is dropped. The Tokio runtime
     /// will make a "best effort" to reap the process after it exits, but it is
     /// not a guarantee. See the `kill_on_drop` documentation for details.
-    #[allow(dead_code)]
-    child: tokio::process::Child,
+    child: Mutex<tokio::process::Child>,
+
+    /// Tells the writer task to stop and close the child's STDIN.
+    close_stdin: Arc<Notify>,
 
     /// Channel for sending JSON-RPC messages *to* the background writer task.
     outgoing_tx: mpsc::Sender<JSONRPCMessage>,
@@ -83,21 +86,44 @@ impl McpClient {
     /// Spawn the given command and establish an MCP session over its STDIO.
     /// Caller is responsible for sending the `initialize` request. See
     /// [`initialize`](Self::initialize) for details.
+    ///
+    /// The server's stderr goes to `stderr_log` when set and is discarded
+    /// otherwise.
     pub async fn new_stdio_client(
         program: OsString,
         args: Vec<OsString>,
         env: Option<HashMap<String, String>>,
         env_vars: &[String],
         cwd: Option<PathBuf>,
+        stderr_log: Option<std::fs::File>,
+    ) -> std::io::Result<Self> {
+        Self::new_stdio_client_with_env(
+            program,
+            args,
+            create_env_for_mcp_server(env, env_vars),
+            cwd,
+            stderr_log,
+        )
+        .await
+    }
+
+    /// Like [`new_stdio_client`](Self::new_stdio_client), but `env` is the
+    /// server's entire environment: nothing is inherited from this process.
+    pub async fn new_stdio_client_with_env(
+        program: OsString,
+        args: Vec<OsString>,
+        env: HashMap<String, String>,
+        cwd: Option<PathBuf>,
+        stderr_log: Option<std::fs::File>,
     ) -> std::io::Result<Self> {
         let mut command = Command::new(program);
         command
             .args(args)
             .env_clear()
-            .envs(create_env_for_mcp_server(env, env_vars))
+            .envs(env)
             .stdin(std::process::Stdio::piped())
             .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::null())
+            .stderr(stderr_log.map_or_else(std::process::Stdio::null, std::process::Stdio::from))
             // As noted in the `kill_on_drop` documentation, the Tokio runtime makes
             // a "best effort" to reap-after-exit to avoid zombie processes, but it
             // is not a guarantee.
@@ -119,13 +145,22 @@ impl McpClient {
 
         let (outgoing_tx, mut outgoing_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
         let pending: Arc<Mutex<HashMap<i64, PendingSender>>> = Arc::new(Mutex::new(HashMap::new()));
+        let close_stdin = Arc::new(Notify::new());
 
         // Spawn writer task. It listens on the `outgoing_rx` channel and
-        // writes messages to the child's STDIN.
+        // writes messages to the child's STDIN until `close_stdin` fires.
         let writer_handle = {
             let mut stdin = stdin;
+            let close_stdin = close_stdin.clone();
             tokio::spawn(async move {
-                while let Some(msg) = outgoing_rx.recv().await {
+                loop {
+                    let msg = tokio::select! {
+                        msg = outgoing_rx.recv() => msg,
+                        _ = close_stdin.notified() => None,
+                    };
+                    let Some(msg) = msg else {
+                        break;
+                    };
                     match serde_json::to_string(&msg) {
                         Ok(json) => {
                             debug!("MCP message to server: {json}");
@@ -185,7 +220,8 @@ impl McpClient {
         let _ = (writer_handle, reader_handle);
 
         Ok(Self {
-            child,
+            child: Mutex::new(child),
+            close_stdin,
             outgoing_tx,
             pending,
             id_counter: AtomicI64::new(1),
@@ -353,6 +389,24 @@ impl McpClient {
         self.send_request::<CallToolRequest>(params, timeout).await
     }
 
+    /// Ask the server to exit by closing its STDIN, then kill it if it is
+    /// still running after `grace`. MCP has no shutdown request; closing the
+    /// input stream is how the stdio transport signals shutdown.
+    pub async fn shutdown(&self, grace: Duration) {
+        self.close_stdin.notify_one();
+        let mut child = self.child.lock().await;
+        match time::timeout(grace, child.wait()).await {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => warn!("failed to wait for MCP server to exit: {e}"),
+            Err(_) => {
+                warn!("MCP server did not exit within {grace:?} of closing stdin; killing it");
+                if let Err(e) = child.kill().await {
+                    warn!("failed to kill MCP server: {e}");
+                }
+            }
+        }
+    }
+
     /// Internal helper: route a JSON-RPC *response* object to the pending map.
     async fn dispatch_response(
         resp: JSONRPCResponse,
@@ -406,7 +460,7 @@ impl Drop for McpClient {
         // `kill_on_drop(true)` above, this extra check has the benefit of
         // forcing the process to be reaped immediately if it has already exited
         // instead of waiting for the Tokio runtime to reap it later.
-        let _ = self.child.try_wait();
+        let _ = self.child.get_mut().try_wait();
     }
 }
 
@@ -165,6 +165,19 @@ pub enum Op {
     /// Reply is delivered via `EventMsg::McpListToolsResponse`.
     ListMcpTools,
 
+    /// Re-read the MCP server settings from `config.toml` and apply them to
+    /// the running session, touching only servers whose definition changed.
+    /// What changed is reported via `EventMsg::BackgroundEvent`, and servers
+    /// that fail to start via `EventMsg::Error`.
+    ReloadMcpServers,
+
+    /// Stop the named MCP servers and start them again, re-running
+    /// `initialize`; an empty list restarts every running server. The new
+    /// tool counts, and any named server that is not running, are reported
+    /// via `EventMsg::BackgroundEvent`, and servers that fail to start via
+    /// `EventMsg::Error`.
+    RestartMcpServers { servers: Vec<String> },
+
     /// Request the list of available custom prompts.
     ListCustomPrompts,
 
@@ -0,0 +1,378 @@
+//! OAuth 2.0 client-credentials grant for streamable HTTP MCP servers.
+//!
+//! Unlike the interactive login flow in `perform_oauth_login`, nothing is
+//! persisted: access tokens are requested from the token endpoint on demand
+//! and cached in memory until shortly before they expire. Each HTTP request
+//! carries the current token, so an expired token is replaced without
+//! touching the MCP session.
+
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use anyhow::Context;
+use anyhow::Result;
+use futures::stream::BoxStream;
+use rmcp::model::ClientJsonRpcMessage;
+use rmcp::transport::AuthError;
+use rmcp::transport::streamable_http_client::SseError;
+use rmcp::transport::streamable_http_client::StreamableHttpClient;
+use rmcp::transport::streamable_http_client::StreamableHttpError;
+use rmcp::transport::streamable_http_client::StreamableHttpPostResponse;
+use serde::Deserialize;
+use sse_stream::Sse;
+use tokio::sync::Mutex;
+
+/// Treat tokens as expired this long before the server says they are, so a
+/// request never goes out with a token that lapses in flight.
+const EXPIRY_MARGIN: Duration = Duration::from_secs(30);
+
+/// Credentials for the client-credentials grant. `client_secret` is the
+/// resolved secret, never the name of the variable it came from.
+#[derive(Clone)]
+pub struct OAuthClientCredentials {
+    pub token_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub scopes: Vec<String>,
+}
+
+impl std::fmt::Debug for OAuthClientCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OAuthClientCredentials")
+            .field("token_url", &self.token_url)
+            .field("client_id", &self.client_id)
+            .field("client_secret", &"<redacted>")
+            .field("scopes", &self.scopes)
+            .finish()
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
+struct CachedToken {
+    access_token: String,
+    /// `None` when the token endpoint did not report a lifetime.
+    expires_at: Option<Instant>,
+}
+
+impl CachedToken {
+    fn is_fresh(&self, now: Instant) -> bool {
+        self.expires_at
+            .is_none_or(|expires_at| now + EXPIRY_MARGIN < expires_at)
+    }
+}
+
+/// Fetches access tokens and caches them until they are about to expire.
+/// Clones share the same cache. The cache stays locked while a token is
+/// fetched, so concurrent callers wait for one request instead of each
+/// sending their own.
+#[derive(Clone)]
+pub(crate) struct ClientCredentialsTokenSource {
+    credentials: Arc<OAuthClientCredentials>,
+    http_client: reqwest::Client,
+    cached: Arc<Mutex<Option<CachedToken>>>,
+}
+
+impl ClientCredentialsTokenSource {
+    pub(crate) fn new(credentials: OAuthClientCredentials, http_client: reqwest::Client) -> Self {
+        Self {
+            credentials: Arc::new(credentials),
+            http_client,
+            cached: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Returns the cached token, fetching a new one if it is missing or
+    /// about to expire.
+    pub(crate) async fn access_token(&self) -> Result<String> {
+        let mut cached = self.cached.lock().await;
+        if let Some(token) = cached.as_ref()
+            && token.is_fresh(Instant::now())
+        {
+            return Ok(token.access_token.clone());
+        }
+
+        let token = self.fetch().await?;
+        let access_token = token.access_token.clone();
+        *cached = Some(token);
+        Ok(access_token)
+    }
+
+    async fn fetch(&self) -> Result<CachedToken> {
+        let OAuthClientCredentials {
+            token_url,
+            client_id,
+            client_secret,
+            scopes,
+        } = self.credentials.as_ref();
+
+        let mut form = vec![
+            ("grant_type", "client_credentials".to_string()),
+            ("client_id", client_id.clone()),
+            ("client_secret", client_secret.clone()),
+        ];
+        if !scopes.is_empty() {
+            form.push(("scope", scopes.join(" ")));
+        }
+
+        let requested_at = Instant::now();
+        let response = self
+            .http_client
+            .post(token_url)
+            .form(&form)
+            .send()
+            .await
+            .with_context(|| format!("failed to request OAuth token from {token_url}"))?
+            .error_for_status()
+            .with_context(|| format!("OAuth token request to {token_url} was rejected"))?;
+        let token: TokenResponse = response
+            .json()
+            .await
+            .with_context(|| format!("invalid OAuth token response from {token_url}"))?;
+
+        Ok(CachedToken {
+            access_token: token.access_token,
+            expires_at: token
+                .expires_in
+                .map(|secs| requested_at + Duration::from_secs(secs)),
+        })
+    }
+}
+
+/// HTTP client for the streamable HTTP transport that sends every request
+/// with the current access token in place of a fixed `Authorization` header.
+#[derive(Clone)]
+pub(crate) struct ClientCredentialsHttpClient {
+    http_client: reqwest::Client,
+    tokens: ClientCredentialsTokenSource,
+}
+
+impl ClientCredentialsHttpClient {
+    pub(crate) fn new(http_client: reqwest::Client, tokens: ClientCredentialsTokenSource) -> Self {
+        Self {
+            http_client,
+            tokens,
+        }
+    }
+
+    async fn auth_header(&self) -> Result<String, StreamableHttpError<reqwest::Error>> {
+        self.tokens.access_token().await.map_err(|err| {
+            StreamableHttpError::Auth(AuthError::TokenRefreshFailed(format!("{err:#}")))
+        })
+    }
+}
+
+impl StreamableHttpClient for ClientCredentialsHttpClient {
+    type Error = reqwest::Error;
+
+    async fn post_message(
+        &self,
+        uri: Arc<str>,
+        message: ClientJsonRpcMessage,
+        session_id: Option<Arc<str>>,
+        _auth_header: Option<String>,
+    ) -> Result<StreamableHttpPostResponse, StreamableHttpError<Self::Error>> {
+        let auth_header = self.auth_header().await?;
+        self.http_client
+            .post_message(uri, message, session_id, Some(auth_header))
+            .await
+    }
+
+    async fn delete_session(
+        &self,
+        uri: Arc<str>,
+        session_id: Arc<str>,
+        _auth_header: Option<String>,
+    ) -> Result<(), StreamableHttpError<Self::Error>> {
+        let auth_header = self.auth_header().await?;
+        self.http_client
+            .delete_session(uri, session_id, Some(auth_header))
+            .await
+    }
+
+    async fn get_stream(
+        &self,
+        uri: Arc<str>,
+        session_id: Arc<str>,
+        last_event_id: Option<String>,
+        _auth_header: Option<String>,
+    ) -> Result<BoxStream<'static, Result<Sse, SseError>>, StreamableHttpError<Self::Error>> {
+        let auth_header = self.auth_header().await?;
+        self.http_client
+            .get_stream(uri, session_id, last_event_id, Some(auth_header))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+    use wiremock::matchers::body_string_contains;
+    use wiremock::matchers::header;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+
+    fn credentials(token_url: String) -> OAuthClientCredentials {
+        OAuthClientCredentials {
+            token_url,
+            client_id: "codex".to_string(),
+            client_secret: "s3cret".to_string(),
+            scopes: vec!["tools.read".to_string(), "tools.call".to_string()],
+        }
+    }
+
+    #[test]
+    fn cached_token_expires_with_margin() {
+        let now = Instant::now();
+        let token = |expires_at| CachedToken {
+            access_token: "token".to_string(),
+            expires_at,
+        };
+
+        assert_eq!(
+            [
+                token(None).is_fresh(now),
+                token(Some(now + Duration::from_secs(60))).is_fresh(now),
+                token(Some(now + Duration::from_secs(10))).is_fresh(now),
+            ],
+            [true, true, false]
+        );
+    }
+
+    #[tokio::test]
+    async fn access_token_is_cached_until_expiry() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .and(body_string_contains("grant_type=client_credentials"))
+            .and(body_string_contains("scope=tools.read+tools.call"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "abc",
+                "token_type": "Bearer",
+                "expires_in": 3600,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let source = ClientCredentialsTokenSource::new(
+            credentials(format!("{}/token", server.uri())),
+            reqwest::Client::new(),
+        );
+
+        assert_eq!(source.access_token().await.unwrap(), "abc");
+        assert_eq!(source.access_token().await.unwrap(), "abc");
+    }
+
+    #[tokio::test]
+    async fn concurrent_callers_share_one_token_request() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({
+                        "access_token": "abc",
+                        "expires_in": 3600,
+                    }))
+                    .set_delay(Duration::from_millis(100)),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let source = ClientCredentialsTokenSource::new(
+            credentials(format!("{}/token", server.uri())),
+            reqwest::Client::new(),
+        );
+
+        let tokens = futures::future::join_all((0..4).map(|_| source.access_token())).await;
+        for token in tokens {
+            assert_eq!(token.unwrap(), "abc");
+        }
+    }
+
+    #[tokio::test]
+    async fn expired_token_is_replaced_without_a_new_session() {
+        let server = MockServer::start().await;
+        // Tokens inside the expiry margin are stale as soon as they arrive.
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "short-lived",
+                "expires_in": 1,
+            })))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/mcp"))
+            .and(header("authorization", "Bearer short-lived"))
+            .and(header("mcp-session-id", "session-1"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let tokens = ClientCredentialsTokenSource::new(
+            credentials(format!("{}/token", server.uri())),
+            reqwest::Client::new(),
+        );
+        let client = ClientCredentialsHttpClient::new(reqwest::Client::new(), tokens);
+        let uri: Arc<str> = format!("{}/mcp", server.uri()).into();
+        let session_id: Arc<str> = "session-1".into();
+        for _ in 0..2 {
+            let notification: ClientJsonRpcMessage = serde_json::from_value(serde_json::json!({
+                "jsonrpc": "2.0",
+                "method": "notifications/initialized",
+            }))
+            .unwrap();
+            let response = client
+                .post_message(
+                    uri.clone(),
+                    notification,
+                    Some(session_id.clone()),
+                    Some("ignored".to_string()),
+                )
+                .await
+                .unwrap();
+            assert!(matches!(response, StreamableHttpPostResponse::Accepted));
+        }
+    }
+
+    #[tokio::test]
+    async fn rejected_token_request_is_an_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&server)
+            .await;
+
+        let source = ClientCredentialsTokenSource::new(
+            credentials(format!("{}/token", server.uri())),
+            reqwest::Client::new(),
+        );
+
+        let err = source.access_token().await.expect_err("401 should fail");
+        assert!(
+            format!("{err:#}").contains("was rejected"),
+            "unexpected error: {err:#}"
+        );
+    }
+
+    #[test]
+    fn debug_output_redacts_secret() {
+        let rendered = format!("{:?}", credentials("https://example.com".to_string()));
+        assert!(!rendered.contains("s3cret"));
+    }
+}
@@ -1,13 +1,16 @@
 mod auth_status;
+mod client_credentials;
 mod find_codex_home;
 mod logging_client_handler;
 mod oauth;
 mod perform_oauth_login;
+mod rate_limit;
 mod rmcp_client;
 mod utils;
 
 pub use auth_status::determine_streamable_http_auth_status;
 pub use auth_status::supports_oauth_login;
+pub use client_credentials::OAuthClientCredentials;
 pub use codex_protocol::protocol::McpAuthStatus;
 pub use oauth::OAuthCredentialsStoreMode;
 pub use oauth::StoredOAuthTokens;
@@ -17,3 +20,5 @@ pub(crate) use oauth::load_oauth_tokens;
 pub use oauth::save_oauth_tokens;
 pub use perform_oauth_login::perform_oauth_login;
 pub use rmcp_client::RmcpClient;
+pub use utils::HttpConnectionOptions;
+pub use utils::create_env_for_mcp_server;
@@ -0,0 +1,107 @@
+//! Token-bucket limit on the requests sent to a streamable HTTP server.
+
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// Spaces requests to at most `rate` per second, allowing bursts of up to
+/// `rate` (at least one) requests. Callers over the limit wait for their
+/// turn, in the order they arrived, instead of failing.
+pub(crate) struct RequestRateLimiter {
+    bucket: Mutex<TokenBucket>,
+}
+
+impl RequestRateLimiter {
+    pub(crate) fn new(rate: f64) -> Self {
+        Self {
+            bucket: Mutex::new(TokenBucket::new(rate, Instant::now())),
+        }
+    }
+
+    /// Wait until one more request may be sent.
+    pub(crate) async fn acquire(&self) {
+        let wait = self.bucket.lock().await.reserve(Instant::now());
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: f64, now: Instant) -> Self {
+        let capacity = rate.max(1.0);
+        Self {
+            rate,
+            capacity,
+            tokens: capacity,
+            updated: now,
+        }
+    }
+
+    /// Take a token and return how long the caller must wait for it. The
+    /// balance may go negative: each waiting caller has already reserved the
+    /// token it will use, which keeps later callers behind it.
+    fn reserve(&mut self, now: Instant) -> Duration {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.updated = self.updated.max(now);
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            // A tiny rate can ask for a wait too long to represent.
+            Duration::try_from_secs_f64(-self.tokens / self.rate).unwrap_or(Duration::MAX)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn bursts_up_to_rate_then_queues_callers_in_order() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2.0, start);
+
+        assert_eq!(bucket.reserve(start), Duration::ZERO);
+        assert_eq!(bucket.reserve(start), Duration::ZERO);
+        assert_eq!(bucket.reserve(start), Duration::from_millis(500));
+        assert_eq!(bucket.reserve(start), Duration::from_secs(1));
+
+        // Two tokens accrue per second, and both are already reserved.
+        let later = start + Duration::from_secs(1);
+        assert_eq!(bucket.reserve(later), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn rates_below_one_still_allow_a_single_request() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(0.5, start);
+
+        assert_eq!(bucket.reserve(start), Duration::ZERO);
+        assert_eq!(bucket.reserve(start), Duration::from_secs(2));
+        // Idle time refills the bucket only up to its capacity.
+        let idle = start + Duration::from_secs(60);
+        assert_eq!(bucket.reserve(idle), Duration::ZERO);
+        assert_eq!(bucket.reserve(idle), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn tiny_rates_saturate_the_wait_instead_of_panicking() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(1e-300, start);
+
+        assert_eq!(bucket.reserve(start), Duration::ZERO);
+        assert_eq!(bucket.reserve(start), Duration::MAX);
+    }
+}
@@ -23,8 +23,10 @@ use mcp_types::ReadResourceRequestParams;
 use mcp_types::ReadResourceResult;
 use reqwest::header::HeaderMap;
 use rmcp::model::CallToolRequestParam;
+use rmcp::model::ClientRequest;
 use rmcp::model::InitializeRequestParam;
 use rmcp::model::PaginatedRequestParam;
+use rmcp::model::PingRequest;
 use rmcp::model::ReadResourceRequestParam;
 use rmcp::service::RoleClient;
 use rmcp::service::RunningService;
@@ -32,21 +34,30 @@ use rmcp::service::{self};
 use rmcp::transport::StreamableHttpClientTransport;
 use rmcp::transport::auth::AuthClient;
 use rmcp::transport::auth::OAuthState;
-use rmcp::transport::child_process::TokioChildProcess;
 use rmcp::transport::streamable_http_client::StreamableHttpClientTransportConfig;
 use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncWriteExt;
 use tokio::io::BufReader;
+use tokio::process::Child;
+use tokio::process::ChildStdin;
+use tokio::process::ChildStdout;
 use tokio::process::Command;
 use tokio::sync::Mutex;
 use tokio::time;
 use tracing::info;
 use tracing::warn;
 
+use crate::client_credentials::ClientCredentialsHttpClient;
+use crate::client_credentials::ClientCredentialsTokenSource;
+use crate::client_credentials::OAuthClientCredentials;
 use crate::load_oauth_tokens;
 use crate::logging_client_handler::LoggingClientHandler;
 use crate::oauth::OAuthCredentialsStoreMode;
 use crate::oauth::OAuthPersistor;
 use crate::oauth::StoredOAuthTokens;
+use crate::rate_limit::RequestRateLimiter;
+use crate::utils::HttpConnectionOptions;
+use crate::utils::apply_connection_options;
 use crate::utils::apply_default_headers;
 use crate::utils::build_default_headers;
 use crate::utils::convert_call_tool_result;
@@ -56,7 +67,7 @@ use crate::utils::create_env_for_mcp_server;
 use crate::utils::run_with_timeout;
 
 enum PendingTransport {
-    ChildProcess(TokioChildProcess),
+    ChildProcess(ChildStdout, ChildStdin),
     StreamableHttp {
         transport: StreamableHttpClientTransport<reqwest::Client>,
     },
@@ -64,6 +75,9 @@ enum PendingTransport {
         transport: StreamableHttpClientTransport<AuthClient<reqwest::Client>>,
         oauth_persistor: OAuthPersistor,
     },
+    StreamableHttpWithClientCredentials {
+        transport: StreamableHttpClientTransport<ClientCredentialsHttpClient>,
+    },
 }
 
 enum ClientState {
@@ -76,19 +90,55 @@ enum ClientState {
     },
 }
 
+/// Write one stderr line and flush so `codex mcp logs --follow` sees it
+/// promptly.
+async fn append_line(log: &mut tokio::fs::File, line: &str) -> io::Result<()> {
+    log.write_all(line.as_bytes()).await?;
+    log.write_all(b"\n").await?;
+    log.flush().await
+}
+
 /// MCP client implemented on top of the official `rmcp` SDK.
 /// https://github.com/modelcontextprotocol/rust-sdk
 pub struct RmcpClient {
     state: Mutex<ClientState>,
+    /// The server process for stdio clients, kept so `shutdown` can wait for
+    /// it to exit.
+    child: Mutex<Option<Child>>,
+    /// Set for HTTP servers with `max_requests_per_sec`; every request waits
+    /// for it first.
+    rate_limiter: Option<RequestRateLimiter>,
 }
 
 impl RmcpClient {
+    /// Spawn a stdio server. Its stderr is logged via `tracing` and, when
+    /// `stderr_log` is set, also appended to that file.
     pub async fn new_stdio_client(
         program: OsString,
         args: Vec<OsString>,
         env: Option<HashMap<String, String>>,
         env_vars: &[String],
         cwd: Option<PathBuf>,
+        stderr_log: Option<std::fs::File>,
+    ) -> io::Result<Self> {
+        Self::new_stdio_client_with_env(
+            program,
+            args,
+            create_env_for_mcp_server(env, env_vars),
+            cwd,
+            stderr_log,
+        )
+        .await
+    }
+
+    /// Like [`new_stdio_client`](Self::new_stdio_client), but `env` is the
+    /// server's entire environment: nothing is inherited from this process.
+    pub async fn new_stdio_client_with_env(
+        program: OsString,
+        args: Vec<OsString>,
+        env: HashMap<String, String>,
+        cwd: Option<PathBuf>,
+        stderr_log: Option<std::fs::File>,
     ) -> io::Result<Self> {
         let program_name = program.to_string_lossy().into_owned();
         let mut command = Command::new(&program);
@@ -97,23 +147,38 @@ impl RmcpClient {
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .env_clear()
-            .envs(create_env_for_mcp_server(env, env_vars))
+            .envs(env)
             .args(&args);
         if let Some(cwd) = cwd {
             command.current_dir(cwd);
         }
 
-        let (transport, stderr) = TokioChildProcess::builder(command)
-            .stderr(Stdio::piped())
-            .spawn()?;
-
-        if let Some(stderr) = stderr {
+        let mut child = command.stderr(Stdio::piped()).spawn()?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| io::Error::other("failed to capture child stdin"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| io::Error::other("failed to capture child stdout"))?;
+
+        if let Some(stderr) = child.stderr.take() {
+            let mut stderr_log = stderr_log.map(tokio::fs::File::from_std);
             tokio::spawn(async move {
                 let mut reader = BufReader::new(stderr).lines();
                 loop {
                     match reader.next_line().await {
                         Ok(Some(line)) => {
                             info!("MCP server stderr ({program_name}): {line}");
+                            if let Some(log) = stderr_log.as_mut()
+                                && let Err(error) = append_line(log, &line).await
+                            {
+                                warn!(
+                                    "Failed to write MCP server stderr log ({program_name}): {error}"
+                                );
+                                stderr_log = None;
+                            }
                         }
                         Ok(None) => break,
                         Err(error) => {
@@ -127,8 +192,10 @@ impl RmcpClient {
 
         Ok(Self {
             state: Mutex::new(ClientState::Connecting {
-                transport: Some(PendingTransport::ChildProcess(transport)),
+                transport: Some(PendingTransport::ChildProcess(stdout, stdin)),
             }),
+            child: Mutex::new(Some(child)),
+            rate_limiter: None,
         })
     }
 
@@ -140,8 +207,12 @@ impl RmcpClient {
         http_headers: Option<HashMap<String, String>>,
         env_http_headers: Option<HashMap<String, String>>,
         store_mode: OAuthCredentialsStoreMode,
+        connection_options: HttpConnectionOptions,
     ) -> Result<Self> {
         let default_headers = build_default_headers(http_headers, env_http_headers)?;
+        let rate_limiter = connection_options
+            .max_requests_per_sec
+            .map(RequestRateLimiter::new);
 
         let initial_oauth_tokens = match bearer_token {
             Some(_) => None,
@@ -161,6 +232,7 @@ impl RmcpClient {
                 initial_tokens,
                 store_mode,
                 default_headers.clone(),
+                &connection_options,
             )
             .await?;
             PendingTransport::StreamableHttpWithOAuth {
@@ -173,8 +245,7 @@ impl RmcpClient {
                 http_config = http_config.auth_header(bearer_token);
             }
 
-            let http_client =
-                apply_default_headers(reqwest::Client::builder(), &default_headers).build()?;
+            let http_client = build_http_client(&default_headers, &connection_options)?;
 
             let transport = StreamableHttpClientTransport::with_client(http_client, http_config);
             PendingTransport::StreamableHttp { transport }
@@ -183,6 +254,45 @@ impl RmcpClient {
             state: Mutex::new(ClientState::Connecting {
                 transport: Some(transport),
             }),
+            child: Mutex::new(None),
+            rate_limiter,
+        })
+    }
+
+    /// Connect to a streamable HTTP server using access tokens obtained via
+    /// the OAuth client-credentials grant. Tokens are cached until shortly
+    /// before they expire; each request carries the current one, so the MCP
+    /// session outlives any single token.
+    pub async fn new_streamable_http_client_with_client_credentials(
+        url: &str,
+        credentials: OAuthClientCredentials,
+        http_headers: Option<HashMap<String, String>>,
+        env_http_headers: Option<HashMap<String, String>>,
+        connection_options: HttpConnectionOptions,
+    ) -> Result<Self> {
+        let default_headers = build_default_headers(http_headers, env_http_headers)?;
+        let http_client = build_http_client(&default_headers, &connection_options)?;
+        // The token endpoint is a different origin; don't send it the MCP
+        // server's headers, but do use the same TLS settings.
+        let token_client = build_http_client(&HeaderMap::new(), &connection_options)?;
+        let tokens = ClientCredentialsTokenSource::new(credentials, token_client);
+        // Fetch the first token now so bad credentials fail at startup.
+        tokens.access_token().await?;
+        let transport = StreamableHttpClientTransport::with_client(
+            ClientCredentialsHttpClient::new(http_client, tokens),
+            StreamableHttpClientTransportConfig::with_uri(url.to_string()),
+        );
+
+        Ok(Self {
+            state: Mutex::new(ClientState::Connecting {
+                transport: Some(PendingTransport::StreamableHttpWithClientCredentials {
+                    transport,
+                }),
+            }),
+            child: Mutex::new(None),
+            rate_limiter: connection_options
+                .max_requests_per_sec
+                .map(RequestRateLimiter::new),
         })
     }
 
@@ -200,8 +310,8 @@ impl RmcpClient {
             let mut guard = self.state.lock().await;
             match &mut *guard {
                 ClientState::Connecting { transport } => match transport.take() {
-                    Some(PendingTransport::ChildProcess(transport)) => (
-                        service::serve_client(client_handler.clone(), transport).boxed(),
+                    Some(PendingTransport::ChildProcess(stdout, stdin)) => (
+                        service::serve_client(client_handler.clone(), (stdout, stdin)).boxed(),
                         None,
                     ),
                     Some(PendingTransport::StreamableHttp { transport }) => (
@@ -215,6 +325,10 @@ impl RmcpClient {
                         service::serve_client(client_handler.clone(), transport).boxed(),
                         Some(oauth_persistor),
                     ),
+                    Some(PendingTransport::StreamableHttpWithClientCredentials { transport }) => (
+                        service::serve_client(client_handler.clone(), transport).boxed(),
+                        None,
+                    ),
                     None => return Err(anyhow!("client already initializing")),
                 },
                 ClientState::Ready { .. } => return Err(anyhow!("client already initialized")),
@@ -335,7 +449,52 @@ impl RmcpClient {
         Ok(converted)
     }
 
+    /// Send an MCP `ping` and wait for the reply, to check that the
+    /// connection is still alive.
+    /// https://modelcontextprotocol.io/specification/2025-06-18/basic/utilities/ping
+    pub async fn ping(&self, timeout: Option<Duration>) -> Result<()> {
+        let service = self.service().await?;
+        let fut = service.send_request(ClientRequest::PingRequest(PingRequest::default()));
+        run_with_timeout(fut, timeout, "ping").await?;
+        Ok(())
+    }
+
+    /// Ask a stdio server to exit by closing its stdin, then kill it if it
+    /// is still running after `grace`. MCP has no shutdown request; closing
+    /// the input stream is how the stdio transport signals shutdown. No-op
+    /// for HTTP servers.
+    /// https://modelcontextprotocol.io/specification/2025-06-18/basic/lifecycle#shutdown
+    pub async fn shutdown(&self, grace: Duration) {
+        let Some(mut child) = self.child.lock().await.take() else {
+            return;
+        };
+
+        // Stopping the service drops the transport, which closes stdin. A
+        // transport that never finished `initialize` is dropped here instead.
+        let state = std::mem::replace(
+            &mut *self.state.lock().await,
+            ClientState::Connecting { transport: None },
+        );
+        if let ClientState::Ready { service, .. } = state {
+            service.cancellation_token().cancel();
+        }
+
+        match time::timeout(grace, child.wait()).await {
+            Ok(Ok(_)) => {}
+            Ok(Err(error)) => warn!("Failed to wait for MCP server to exit: {error}"),
+            Err(_) => {
+                warn!("MCP server did not exit within {grace:?} of closing stdin; killing it");
+                if let Err(error) = child.kill().await {
+                    warn!("Failed to kill MCP server: {error}");
+                }
+            }
+        }
+    }
+
     async fn service(&self) -> Result<Arc<RunningService<RoleClient, LoggingClientHandler>>> {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire().await;
+        }
         let guard = self.state.lock().await;
         match &*guard {
             ClientState::Ready { service, .. } => Ok(Arc::clone(service)),
@@ -365,18 +524,26 @@ impl RmcpClient {
     }
 }
 
+fn build_http_client(
+    default_headers: &HeaderMap,
+    connection_options: &HttpConnectionOptions,
+) -> Result<reqwest::Client> {
+    let builder = apply_default_headers(reqwest::Client::builder(), default_headers);
+    Ok(apply_connection_options(builder, connection_options)?.build()?)
+}
+
 async fn create_oauth_transport_and_runtime(
     server_name: &str,
     url: &str,
     initial_tokens: StoredOAuthTokens,
     credentials_store: OAuthCredentialsStoreMode,
     default_headers: HeaderMap,
+    connection_options: &HttpConnectionOptions,
 ) -> Result<(
     StreamableHttpClientTransport<AuthClient<reqwest::Client>>,
     OAuthPersistor,
 )> {
-    let http_client =
-        apply_default_headers(reqwest::Client::builder(), &default_headers).build()?;
+    let http_client = build_http_client(&default_headers, connection_options)?;
     let mut oauth_state = OAuthState::new(url.to_string(), Some(http_client.clone())).await?;
 
     oauth_state
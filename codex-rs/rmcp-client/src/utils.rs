@@ -1,12 +1,16 @@
 use std::collections::HashMap;
 use std::env;
+use std::path::Path;
+use std::path::PathBuf;
 use std::time::Duration;
 
 use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
 use mcp_types::CallToolResult;
+use reqwest::Certificate;
 use reqwest::ClientBuilder;
+use reqwest::Identity;
 use reqwest::header::HeaderMap;
 use reqwest::header::HeaderName;
 use reqwest::header::HeaderValue;
@@ -72,7 +76,10 @@ where
     serde_json::from_value(json).map_err(|err| anyhow!(err))
 }
 
-pub(crate) fn create_env_for_mcp_server(
+/// The environment a stdio server is launched with by default: the curated
+/// `DEFAULT_ENV_VARS` and `env_vars` read from Codex's environment, then
+/// `extra_env`.
+pub fn create_env_for_mcp_server(
     extra_env: Option<HashMap<String, String>>,
     env_vars: &[String],
 ) -> HashMap<String, String> {
@@ -154,6 +161,94 @@ pub(crate) fn apply_default_headers(
     }
 }
 
+/// Connection settings for a streamable HTTP server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpConnectionOptions {
+    /// Keep idle connections open so later requests skip the TCP and TLS
+    /// handshakes.
+    pub keep_alive: bool,
+    /// Maximum number of idle connections kept open. `None` uses reqwest's
+    /// default.
+    pub max_idle_connections: Option<usize>,
+    /// Limit on establishing each TCP/TLS connection, separate from the
+    /// overall startup timeout. `None` uses reqwest's default (no limit).
+    pub connect_timeout: Option<Duration>,
+    /// Most requests sent per second; callers over the limit wait for their
+    /// turn. `None` sends requests as soon as they are made.
+    pub max_requests_per_sec: Option<f64>,
+    /// PEM certificate and private key presented for mutual TLS. Used only
+    /// when both are set.
+    pub client_cert_path: Option<PathBuf>,
+    pub client_key_path: Option<PathBuf>,
+    /// PEM bundle of extra certificate authorities to trust.
+    pub ca_cert_path: Option<PathBuf>,
+}
+
+impl Default for HttpConnectionOptions {
+    fn default() -> Self {
+        Self {
+            keep_alive: true,
+            max_idle_connections: None,
+            connect_timeout: None,
+            max_requests_per_sec: None,
+            client_cert_path: None,
+            client_key_path: None,
+            ca_cert_path: None,
+        }
+    }
+}
+
+pub(crate) fn apply_connection_options(
+    builder: ClientBuilder,
+    options: &HttpConnectionOptions,
+) -> Result<ClientBuilder> {
+    let builder = match options.connect_timeout {
+        Some(timeout) => builder.connect_timeout(timeout),
+        None => builder,
+    };
+    let builder = match (&options.client_cert_path, &options.client_key_path) {
+        (Some(cert_path), Some(key_path)) => {
+            builder.identity(load_client_identity(cert_path, key_path)?)
+        }
+        _ => builder,
+    };
+    let builder = match &options.ca_cert_path {
+        Some(ca_path) => load_ca_certificates(ca_path)?
+            .into_iter()
+            .fold(builder, ClientBuilder::add_root_certificate),
+        None => builder,
+    };
+    Ok(match (options.keep_alive, options.max_idle_connections) {
+        (false, _) => builder.pool_max_idle_per_host(0),
+        (true, Some(max)) => builder.pool_max_idle_per_host(max),
+        (true, None) => builder,
+    })
+}
+
+fn load_client_identity(cert_path: &Path, key_path: &Path) -> Result<Identity> {
+    let mut pem = std::fs::read(cert_path)
+        .with_context(|| format!("failed to read client certificate {}", cert_path.display()))?;
+    pem.push(b'\n');
+    pem.extend(
+        std::fs::read(key_path)
+            .with_context(|| format!("failed to read client key {}", key_path.display()))?,
+    );
+    Identity::from_pem(&pem).with_context(|| {
+        format!(
+            "failed to load client certificate {} with key {}",
+            cert_path.display(),
+            key_path.display()
+        )
+    })
+}
+
+fn load_ca_certificates(ca_path: &Path) -> Result<Vec<Certificate>> {
+    let pem = std::fs::read(ca_path)
+        .with_context(|| format!("failed to read CA certificates {}", ca_path.display()))?;
+    Certificate::from_pem_bundle(&pem)
+        .with_context(|| format!("failed to parse CA certificates in {}", ca_path.display()))
+}
+
 #[cfg(unix)]
 pub(crate) const DEFAULT_ENV_VARS: &[&str] = &[
     "HOME",
@@ -190,6 +285,17 @@ mod tests {
 
     use serial_test::serial;
     use std::ffi::OsString;
+    use std::net::Shutdown;
+    use std::net::SocketAddr;
+    use std::net::TcpListener;
+    use std::net::TcpStream;
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+    use wiremock::matchers::method;
 
     struct EnvVarGuard {
         key: String,
@@ -279,4 +385,102 @@ mod tests {
 
         Ok(())
     }
+
+    /// Forwards every connection accepted on the returned address to
+    /// `target` and counts how many were opened.
+    fn counting_proxy(target: SocketAddr) -> Result<(SocketAddr, Arc<AtomicUsize>)> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let opened = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&opened);
+        std::thread::spawn(move || {
+            for client in listener.incoming().flatten() {
+                counter.fetch_add(1, Ordering::SeqCst);
+                if let Ok(upstream) = TcpStream::connect(target) {
+                    pipe(&client, &upstream);
+                    pipe(&upstream, &client);
+                }
+            }
+        });
+        Ok((addr, opened))
+    }
+
+    fn pipe(from: &TcpStream, to: &TcpStream) {
+        if let (Ok(mut from), Ok(mut to)) = (from.try_clone(), to.try_clone()) {
+            std::thread::spawn(move || {
+                let _ = std::io::copy(&mut from, &mut to);
+                let _ = to.shutdown(Shutdown::Write);
+            });
+        }
+    }
+
+    /// A local server answering every POST, reached through a counting
+    /// proxy. Returns the server (to keep it running), the proxy's URL, and
+    /// its count of opened connections.
+    async fn counted_server() -> Result<(MockServer, String, Arc<AtomicUsize>)> {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("{}"))
+            .mount(&server)
+            .await;
+        let (proxy, opened) = counting_proxy(*server.address())?;
+        Ok((server, format!("http://{proxy}/mcp"), opened))
+    }
+
+    async fn post(client: &reqwest::Client, url: &str) -> Result<()> {
+        client
+            .post(url)
+            .body("{}")
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn keep_alive_reuses_an_open_connection() -> Result<()> {
+        let (_server, url, opened) = counted_server().await?;
+        let client = apply_connection_options(
+            reqwest::Client::builder(),
+            &HttpConnectionOptions::default(),
+        )?
+        .build()?;
+
+        // The pool takes a connection back on a task of its own once the
+        // response is done, so a request sent right after the previous one
+        // may still open a new connection. Keep sending until one goes over
+        // a connection that is already open.
+        let deadline = time::Instant::now() + Duration::from_secs(10);
+        let mut sent = 0;
+        loop {
+            post(&client, &url).await?;
+            sent += 1;
+            if opened.load(Ordering::SeqCst) < sent {
+                return Ok(());
+            }
+            assert!(
+                time::Instant::now() < deadline,
+                "{sent} requests opened {sent} connections"
+            );
+            time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn disabling_keep_alive_opens_a_connection_per_request() -> Result<()> {
+        let (_server, url, opened) = counted_server().await?;
+        let options = HttpConnectionOptions {
+            keep_alive: false,
+            ..HttpConnectionOptions::default()
+        };
+        let client = apply_connection_options(reqwest::Client::builder(), &options)?.build()?;
+
+        for _ in 0..5 {
+            post(&client, &url).await?;
+        }
+        assert_eq!(opened.load(Ordering::SeqCst), 5);
+        Ok(())
+    }
 }
@@ -0,0 +1,119 @@
+//! Benchmark for `keep_alive` on streamable HTTP servers. It only reports
+//! timings, so it is ignored by default. Run it with
+//!
+//! ```text
+//! cargo test -p codex-rmcp-client --release --test keep_alive_latency -- --ignored --nocapture
+//! ```
+
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::time::Duration;
+use std::time::Instant;
+
+use codex_rmcp_client::HttpConnectionOptions;
+use codex_rmcp_client::OAuthCredentialsStoreMode;
+use codex_rmcp_client::RmcpClient;
+use escargot::CargoBuild;
+use mcp_types::ClientCapabilities;
+use mcp_types::Implementation;
+use mcp_types::InitializeRequestParams;
+use tokio::process::Child;
+use tokio::process::Command;
+
+/// Sequential `tools/list` requests timed per setting.
+const REQUESTS: u32 = 200;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn init_params() -> InitializeRequestParams {
+    InitializeRequestParams {
+        capabilities: ClientCapabilities {
+            experimental: None,
+            roots: None,
+            sampling: None,
+            elicitation: None,
+        },
+        client_info: Implementation {
+            name: "codex-test".into(),
+            version: "0.0.0-test".into(),
+            title: Some("Codex rmcp keep_alive benchmark".into()),
+            user_agent: None,
+        },
+        protocol_version: mcp_types::MCP_SCHEMA_VERSION.to_string(),
+    }
+}
+
+/// Start `test_streamable_http_server` on a free local port and wait until
+/// it accepts connections. Returns the server and its MCP URL.
+async fn start_server() -> anyhow::Result<(Child, String)> {
+    let bin = CargoBuild::new()
+        .package("codex-rmcp-client")
+        .bin("test_streamable_http_server")
+        .run()?;
+    let address = TcpListener::bind("127.0.0.1:0")?.local_addr()?;
+    let mut server = Command::new(bin.path())
+        .env("MCP_STREAMABLE_HTTP_BIND_ADDR", address.to_string())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while TcpStream::connect(address).is_err() {
+        if let Some(status) = server.try_wait()? {
+            anyhow::bail!("streamable HTTP server exited early with status {status}");
+        }
+        anyhow::ensure!(
+            Instant::now() < deadline,
+            "timed out waiting for streamable HTTP server at {address}"
+        );
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    Ok((server, format!("http://{address}/mcp")))
+}
+
+/// Time [`REQUESTS`] sequential requests over one initialized client.
+async fn time_requests(url: &str, keep_alive: bool) -> anyhow::Result<Duration> {
+    let client = RmcpClient::new_streamable_http_client(
+        "keep_alive_benchmark",
+        url,
+        // A bearer token skips the stored OAuth lookup; the server ignores it.
+        Some("benchmark".to_string()),
+        None,
+        None,
+        OAuthCredentialsStoreMode::File,
+        HttpConnectionOptions {
+            keep_alive,
+            ..HttpConnectionOptions::default()
+        },
+    )
+    .await?;
+    client
+        .initialize(init_params(), Some(REQUEST_TIMEOUT))
+        .await?;
+    // Warm up so both runs start with the session established.
+    client.list_tools(None, Some(REQUEST_TIMEOUT)).await?;
+
+    let started = Instant::now();
+    for _ in 0..REQUESTS {
+        client.list_tools(None, Some(REQUEST_TIMEOUT)).await?;
+    }
+    Ok(started.elapsed())
+}
+
+#[ignore = "benchmark: reports timings instead of asserting on them"]
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn keep_alive_latency() -> anyhow::Result<()> {
+    let (mut server, url) = start_server().await?;
+
+    let pooled = time_requests(&url, true).await?;
+    let unpooled = time_requests(&url, false).await?;
+    server.kill().await?;
+
+    let per_request = |total: Duration| total / REQUESTS;
+    println!(
+        "{REQUESTS} sequential requests: keep_alive = true {pooled:?} ({:?} each), \
+         keep_alive = false {unpooled:?} ({:?} each), saving {:?} per request",
+        per_request(pooled),
+        per_request(unpooled),
+        per_request(unpooled.saturating_sub(pooled)),
+    );
+    Ok(())
+}
@@ -51,6 +51,7 @@ async fn rmcp_client_can_list_and_read_resources() -> anyhow::Result<()> {
         None,
         &[],
         None,
+        None,
     )
     .await?;
 
@@ -0,0 +1,91 @@
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+
+use codex_rmcp_client::RmcpClient;
+use escargot::CargoBuild;
+use mcp_types::ClientCapabilities;
+use mcp_types::Implementation;
+use mcp_types::InitializeRequestParams;
+
+fn stdio_server_bin() -> anyhow::Result<PathBuf> {
+    let build = CargoBuild::new()
+        .package("codex-rmcp-client")
+        .bin("test_stdio_server")
+        .run()?;
+    Ok(build.path().to_path_buf())
+}
+
+fn init_params() -> InitializeRequestParams {
+    InitializeRequestParams {
+        capabilities: ClientCapabilities {
+            experimental: None,
+            roots: None,
+            sampling: None,
+            elicitation: None,
+        },
+        client_info: Implementation {
+            name: "codex-test".into(),
+            version: "0.0.0-test".into(),
+            title: Some("Codex rmcp shutdown test".into()),
+            user_agent: None,
+        },
+        protocol_version: mcp_types::MCP_SCHEMA_VERSION.to_string(),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn shutdown_lets_server_exit_after_stdin_closes() -> anyhow::Result<()> {
+    let client = RmcpClient::new_stdio_client(
+        stdio_server_bin()?.into(),
+        Vec::<OsString>::new(),
+        None,
+        &[],
+        None,
+        None,
+    )
+    .await?;
+    client
+        .initialize(init_params(), Some(Duration::from_secs(5)))
+        .await?;
+
+    let started = Instant::now();
+    client.shutdown(Duration::from_secs(30)).await;
+
+    assert!(
+        started.elapsed() < Duration::from_secs(10),
+        "server should exit on EOF well before the grace period ends"
+    );
+    assert!(
+        client
+            .list_tools(None, Some(Duration::from_secs(1)))
+            .await
+            .is_err(),
+        "client should be unusable after shutdown"
+    );
+    Ok(())
+}
+
+#[cfg(unix)]
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn shutdown_kills_server_that_ignores_stdin_close() -> anyhow::Result<()> {
+    let client = RmcpClient::new_stdio_client(
+        OsString::from("/bin/sh"),
+        vec![OsString::from("-c"), OsString::from("exec sleep 60")],
+        None,
+        &[],
+        None,
+        None,
+    )
+    .await?;
+
+    let started = Instant::now();
+    client.shutdown(Duration::from_millis(200)).await;
+
+    assert!(
+        started.elapsed() < Duration::from_secs(10),
+        "server should be killed once the grace period ends"
+    );
+    Ok(())
+}
@@ -185,83 +185,9 @@ impl App {
             }
             _ = talon_tick.tick() => {
                 if let Some(paths) = &talon_paths {
-                    if let Ok(Some(req)) = crate::talon::read_request(paths) {
-                        let mut applied: Vec<String> = Vec::new();
-
-                        for cmd in req.commands {
-                            use crate::talon::TalonCommand::*;
-                            match cmd {
-                                SetBuffer { text, cursor } => {
-                                    app.chat_widget.set_composer_text(text);
-                                    if let Some(pos) = cursor {
-                                        app.chat_widget.set_composer_cursor(pos);
-                                    }
-                                    applied.push("set_buffer".to_string());
-                                }
-                                SetCursor { cursor } => {
-                                    app.chat_widget.set_composer_cursor(cursor);
-                                    applied.push("set_cursor".to_string());
-                                }
-                                GetState => {
-                                    applied.push("get_state".to_string());
-                                }
-                                Notify { message } => {
-                                    // Only posts when unfocused; this is intended.
-                                    let _ = tui.notify(message);
-                                    applied.push("notify".to_string());
-                                }
-                                EditPreviousMessage { steps_back } => {
-                                    if app.chat_widget.history_edit_previous(steps_back) {
-                                        applied.push("edit_previous_message".to_string());
-                                    }
-                                }
-                                HistoryPrevious => {
-                                    if app.chat_widget.history_previous() {
-                                        applied.push("history_previous".to_string());
-                                    }
-                                }
-                                HistoryNext => {
-                                    if app.chat_widget.history_next() {
-                                        applied.push("history_next".to_string());
-                                    }
-                                }
-                            }
-                        }
-
-                        // Snapshot editor state
-                        let buffer = app.chat_widget.composer_text();
-                        let cursor = app.chat_widget.composer_cursor();
-                        let is_task_running = app.chat_widget.is_task_running();
-                        let state = crate::talon::TalonEditorState {
-                            buffer,
-                            cursor,
-                            is_task_running,
-                            task_summary: crate::talon::status_summary(),
-                            session_id: app
-                                .chat_widget
-                                .conversation_id()
-                                .map(|id| id.to_string()),
-                            cwd: Some(app.config.cwd.display().to_string()),
-                        };
-
-                        let resp = crate::talon::TalonResponse {
-                            version: 1,
-                            status: crate::talon::TalonResponseStatus::Ok,
-                            state,
-                            applied,
-                            error: None,
-                            timestamp_ms: crate::talon::now_timestamp_ms(),
-                        };
-
-                        let _ = crate::talon::write_response(paths, &resp);
-                        let _ = crate::talon::remove_request(paths);
-                        true
-                    } else {
-                        false
-                    }
-                } else {
-                    false
+                    app.poll_talon_request(tui, paths).await;
                 }
+                true
             }
         } {}
         tui.terminal.clear()?;
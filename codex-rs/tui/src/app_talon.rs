@@ -0,0 +1,501 @@
+use crate::app::App;
+use crate::clipboard_paste::copy_text_to_clipboard;
+use crate::clipboard_paste::read_text_from_clipboard;
+use crate::get_git_diff::get_plain_git_diff;
+use crate::pager_overlay::Overlay;
+use crate::talon;
+use crate::talon::DEFAULT_NOTIFY_TIMEOUT_MS;
+use crate::talon::TalonCommand;
+use crate::talon::TalonEditorState;
+use crate::talon::TalonErrorCode;
+use crate::talon::TalonHistoryEntry;
+use crate::talon::TalonPaths;
+use crate::talon::TalonResponse;
+use crate::talon::TalonResponseStatus;
+use crate::talon::TalonScrollAmount;
+use crate::talon::TalonScrollDirection;
+use crate::talon::TalonSessionConfig;
+use crate::talon::TalonTaskInfo;
+use crate::talon_edit;
+use crate::talon_edit::InvalidPattern;
+use crate::talon_edit::InvalidRange;
+use crate::talon_edit::StateConflict;
+use crate::talon_edit::TalonDiff;
+use crate::tui;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use std::time::Duration;
+
+/// A command that could not be applied. Processing stops at the first error
+/// and the response reports it alongside the labels applied so far.
+pub(crate) struct TalonCommandError {
+    pub(crate) code: TalonErrorCode,
+    pub(crate) message: String,
+}
+
+impl TalonCommandError {
+    pub(crate) fn new(code: TalonErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<InvalidRange> for TalonCommandError {
+    fn from(err: InvalidRange) -> Self {
+        Self::new(TalonErrorCode::InvalidRange, err.to_string())
+    }
+}
+
+impl From<InvalidPattern> for TalonCommandError {
+    fn from(err: InvalidPattern) -> Self {
+        Self::new(TalonErrorCode::ParseError, err.to_string())
+    }
+}
+
+impl From<StateConflict> for TalonCommandError {
+    fn from(err: StateConflict) -> Self {
+        Self::new(TalonErrorCode::VersionConflict, err.to_string())
+    }
+}
+
+/// Everything a request produced: labels for `applied`, data returned by
+/// query commands, and the error (if any) that stopped processing.
+#[derive(Default)]
+struct TalonOutcome {
+    applied: Vec<String>,
+    matches: Option<Vec<[usize; 2]>>,
+    diff: Option<TalonDiff>,
+    history: Option<Vec<TalonHistoryEntry>>,
+    selection: Option<Option<String>>,
+    task: Option<TalonTaskInfo>,
+    config: Option<TalonSessionConfig>,
+    /// Byte window of the buffer requested by `get_state`.
+    buffer_range: Option<[usize; 2]>,
+    error: Option<TalonCommandError>,
+}
+
+impl App {
+    /// Apply a pending Talon request (if any) and write the response file.
+    pub(crate) async fn poll_talon_request(&mut self, tui: &mut tui::Tui, paths: &TalonPaths) {
+        let outcome = match talon::read_request(paths).await {
+            Ok(None) => return,
+            Ok(Some(request)) => match talon_edit::check_expected_state(
+                &self.chat_widget.composer_text(),
+                self.chat_widget.composer_cursor(),
+                request.expected_version,
+                request.expected_cursor,
+            ) {
+                Ok(()) => self.apply_talon_commands(tui, request.commands).await,
+                Err(conflict) => TalonOutcome {
+                    error: Some(conflict.into()),
+                    ..Default::default()
+                },
+            },
+            Err(err) => TalonOutcome {
+                error: Some(TalonCommandError::new(
+                    talon::request_error_code(&err),
+                    format!("{err:#}"),
+                )),
+                ..Default::default()
+            },
+        };
+        let TalonOutcome {
+            applied,
+            matches,
+            diff,
+            history,
+            selection,
+            task,
+            config,
+            buffer_range,
+            error,
+        } = outcome;
+
+        // Keep a failed request for inspection when asked to; the response
+        // says where it went.
+        let failed_request =
+            (error.is_some() && paths.keep_failed && talon::keep_failed_request(paths).is_ok())
+                .then(|| paths.failed_request_path.display().to_string());
+
+        let response = TalonResponse {
+            version: 1,
+            status: if error.is_some() {
+                TalonResponseStatus::Error
+            } else {
+                TalonResponseStatus::Ok
+            },
+            state: self.talon_editor_state(buffer_range),
+            applied,
+            matches,
+            diff,
+            history,
+            selection,
+            task,
+            config,
+            failed_request,
+            error_code: error.as_ref().map(|err| err.code),
+            error: error.map(|err| err.message),
+            timestamp_ms: talon::now_timestamp_ms(),
+        };
+
+        let _ = talon::write_response(paths, &response);
+        let _ = talon::remove_request(paths);
+    }
+
+    async fn apply_talon_commands(
+        &mut self,
+        tui: &mut tui::Tui,
+        commands: Vec<TalonCommand>,
+    ) -> TalonOutcome {
+        let mut outcome = TalonOutcome::default();
+        for command in commands {
+            match self.apply_talon_command(tui, command, &mut outcome).await {
+                Ok(Some(label)) => outcome.applied.push(label),
+                Ok(None) => {}
+                Err(err) => {
+                    outcome.error = Some(err);
+                    break;
+                }
+            }
+        }
+        outcome
+    }
+
+    /// Apply a single command, returning the label to record in `applied`
+    /// (or `None` when the command was accepted but changed nothing). Query
+    /// commands store their results on `outcome`.
+    async fn apply_talon_command(
+        &mut self,
+        tui: &mut tui::Tui,
+        command: TalonCommand,
+        outcome: &mut TalonOutcome,
+    ) -> Result<Option<String>, TalonCommandError> {
+        match command {
+            TalonCommand::SetBuffer { text, cursor } => {
+                if let Some(pos) = cursor {
+                    ensure_cursor(&text, pos)?;
+                }
+                self.chat_widget.set_composer_text(text);
+                if let Some(pos) = cursor {
+                    self.chat_widget.set_composer_cursor(pos);
+                }
+                Ok(Some("set_buffer".to_string()))
+            }
+            TalonCommand::SetCursor { cursor } => {
+                ensure_cursor(&self.chat_widget.composer_text(), cursor)?;
+                self.chat_widget.set_composer_cursor(cursor);
+                Ok(Some("set_cursor".to_string()))
+            }
+            TalonCommand::CursorTo { position } => {
+                let cursor = position.offset(&self.chat_widget.composer_text());
+                self.chat_widget.set_composer_cursor(cursor);
+                Ok(Some(format!("cursor_to:{}", position.label())))
+            }
+            TalonCommand::SetCursorLineCol { line, col } => {
+                let cursor =
+                    talon_edit::line_col_offset(&self.chat_widget.composer_text(), line, col);
+                self.chat_widget.set_composer_cursor(cursor);
+                Ok(Some("set_cursor_line_col".to_string()))
+            }
+            TalonCommand::LineStart => {
+                let (start, _) = talon_edit::line_bounds(
+                    &self.chat_widget.composer_text(),
+                    self.chat_widget.composer_cursor(),
+                );
+                self.chat_widget.set_composer_cursor(start);
+                Ok(Some("line_start".to_string()))
+            }
+            TalonCommand::LineEnd => {
+                let (_, end) = talon_edit::line_bounds(
+                    &self.chat_widget.composer_text(),
+                    self.chat_widget.composer_cursor(),
+                );
+                self.chat_widget.set_composer_cursor(end);
+                Ok(Some("line_end".to_string()))
+            }
+            TalonCommand::GetState { buffer_range } => {
+                outcome.buffer_range = buffer_range;
+                Ok(Some("get_state".to_string()))
+            }
+            TalonCommand::Notify {
+                message,
+                timeout_ms,
+            } => {
+                let timeout_ms = timeout_ms.filter(|ms| *ms > 0);
+                self.chat_widget.show_flash(
+                    message.clone(),
+                    Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_NOTIFY_TIMEOUT_MS)),
+                );
+                // Only posts when unfocused; this is intended.
+                let _ = tui.notify(message);
+                Ok(Some(match timeout_ms {
+                    Some(ms) => format!("notify:{ms}"),
+                    None => "notify".to_string(),
+                }))
+            }
+            TalonCommand::EditPreviousMessage { steps_back } => {
+                if self.chat_widget.is_task_running() {
+                    return Err(TalonCommandError::new(
+                        TalonErrorCode::TaskRunning,
+                        "cannot edit a previous message while a task is running",
+                    ));
+                }
+                Ok(self
+                    .chat_widget
+                    .history_edit_previous(steps_back)
+                    .then(|| "edit_previous_message".to_string()))
+            }
+            TalonCommand::HistoryPrevious => Ok(self
+                .chat_widget
+                .history_previous()
+                .then(|| "history_previous".to_string())),
+            TalonCommand::HistoryNext => Ok(self
+                .chat_widget
+                .history_next()
+                .then(|| "history_next".to_string())),
+            TalonCommand::Scroll {
+                direction,
+                amount,
+                count,
+            } => {
+                if self.overlay.is_none() {
+                    let _ = tui.enter_alt_screen();
+                    self.overlay = Some(Overlay::new_transcript(self.transcript_cells.clone()));
+                }
+                if let Some(overlay) = self.overlay.as_mut() {
+                    let unit = match amount {
+                        TalonScrollAmount::Line => 1,
+                        TalonScrollAmount::Page => overlay.page_height(tui),
+                    };
+                    let rows = unit.saturating_mul(count as usize);
+                    match direction {
+                        TalonScrollDirection::Up => overlay.scroll_up(tui, rows),
+                        TalonScrollDirection::Down => overlay.scroll_down(tui, rows),
+                    }
+                }
+                Ok(Some("scroll".to_string()))
+            }
+            TalonCommand::ReplaceRange { start, end, text } => {
+                let edit = talon_edit::replace_range(
+                    &self.chat_widget.composer_text(),
+                    start,
+                    end,
+                    &text,
+                )?;
+                self.chat_widget.set_composer_text(edit.buffer);
+                self.chat_widget.set_composer_cursor(edit.cursor);
+                Ok(Some("replace_range".to_string()))
+            }
+            TalonCommand::Replace { find, with, all } => {
+                let Some((edit, count)) =
+                    talon_edit::find_replace(&self.chat_widget.composer_text(), &find, &with, all)
+                else {
+                    return Ok(Some("replace:0".to_string()));
+                };
+                self.chat_widget.set_composer_text(edit.buffer);
+                self.chat_widget.set_composer_cursor(edit.cursor);
+                Ok(Some(format!("replace:{count}")))
+            }
+            TalonCommand::Indent { spaces, anchor } => {
+                let edit = talon_edit::indent_lines(
+                    &self.chat_widget.composer_text(),
+                    self.chat_widget.composer_cursor(),
+                    anchor,
+                    spaces,
+                )?;
+                self.chat_widget.set_composer_text(edit.buffer);
+                self.chat_widget.set_composer_cursor(edit.cursor);
+                Ok(Some("indent".to_string()))
+            }
+            TalonCommand::GetSelection { anchor } => {
+                outcome.selection = Some(talon_edit::selected_text(
+                    &self.chat_widget.composer_text(),
+                    self.chat_widget.composer_cursor(),
+                    anchor,
+                ));
+                Ok(Some("get_selection".to_string()))
+            }
+            TalonCommand::Dedent { spaces, anchor } => {
+                let edit = talon_edit::dedent_lines(
+                    &self.chat_widget.composer_text(),
+                    self.chat_widget.composer_cursor(),
+                    anchor,
+                    spaces,
+                )?;
+                self.chat_widget.set_composer_text(edit.buffer);
+                self.chat_widget.set_composer_cursor(edit.cursor);
+                Ok(Some("dedent".to_string()))
+            }
+            TalonCommand::Transcribe { text, capitalize } => {
+                let edit =
+                    talon_edit::transcribe(&self.chat_widget.composer_text(), &text, capitalize);
+                self.chat_widget.set_composer_text(edit.buffer);
+                self.chat_widget.set_composer_cursor(edit.cursor);
+                Ok(Some("transcribe".to_string()))
+            }
+            TalonCommand::SearchBuffer { query, regex } => {
+                let matches =
+                    talon_edit::search_buffer(&self.chat_widget.composer_text(), &query, regex)?;
+                let label = format!("search_buffer:{}", matches.len());
+                outcome.matches = Some(matches);
+                Ok(Some(label))
+            }
+            TalonCommand::GetDiff { max_bytes } => {
+                let cwd = &self.config.cwd;
+                let (is_repo, diff) = get_plain_git_diff(cwd).await.map_err(|err| {
+                    TalonCommandError::new(
+                        TalonErrorCode::GitFailed,
+                        format!("failed to compute diff in {}: {err}", cwd.display()),
+                    )
+                })?;
+                if !is_repo {
+                    return Err(TalonCommandError::new(
+                        TalonErrorCode::NotARepo,
+                        format!("{} is not inside a git repository", cwd.display()),
+                    ));
+                }
+                outcome.diff = Some(talon_edit::truncate_diff(
+                    &diff,
+                    max_bytes.unwrap_or(talon_edit::DEFAULT_DIFF_MAX_BYTES),
+                ));
+                Ok(Some("get_diff".to_string()))
+            }
+            TalonCommand::GetHistory { limit } => {
+                let entries = self
+                    .chat_widget
+                    .history_entries(limit)
+                    .into_iter()
+                    .map(|(steps_back, text)| TalonHistoryEntry { steps_back, text })
+                    .collect();
+                outcome.history = Some(entries);
+                Ok(Some("get_history".to_string()))
+            }
+            TalonCommand::CopyBuffer => {
+                let text = self.chat_widget.composer_text();
+                copy_text_to_clipboard(&text).map_err(|err| {
+                    TalonCommandError::new(
+                        TalonErrorCode::ClipboardUnavailable,
+                        format!("clipboard unavailable: {err}"),
+                    )
+                })?;
+                Ok(Some(format!("copy_buffer:{}", text.len())))
+            }
+            TalonCommand::Paste => {
+                let text = read_text_from_clipboard().map_err(|err| {
+                    TalonCommandError::new(
+                        TalonErrorCode::ClipboardUnavailable,
+                        format!("clipboard unavailable: {err}"),
+                    )
+                })?;
+                self.chat_widget.insert_str(&text);
+                Ok(Some(format!("paste:{}", text.len())))
+            }
+            TalonCommand::Approve { decision } => {
+                if !self
+                    .chat_widget
+                    .resolve_pending_approval(decision.review_decision())
+                {
+                    return Err(TalonCommandError::new(
+                        TalonErrorCode::NoPendingApproval,
+                        "no approval request is pending",
+                    ));
+                }
+                Ok(Some(format!("approve:{}", decision.label())))
+            }
+            TalonCommand::GetTask => {
+                outcome.task = self.chat_widget.running_task().map(|task| TalonTaskInfo {
+                    id: task.id.clone(),
+                    elapsed_ms: u64::try_from(task.started_at.elapsed().as_millis())
+                        .unwrap_or(u64::MAX),
+                    description: self.chat_widget.status_header().to_string(),
+                    last_tool: task.last_tool.clone(),
+                });
+                Ok(Some("get_task".to_string()))
+            }
+            TalonCommand::GetConfig => {
+                let config = self.chat_widget.config_ref();
+                let sandbox_mode = match &config.sandbox_policy {
+                    SandboxPolicy::DangerFullAccess => "danger-full-access",
+                    SandboxPolicy::ReadOnly => "read-only",
+                    SandboxPolicy::WorkspaceWrite { .. } => "workspace-write",
+                };
+                outcome.config = Some(TalonSessionConfig {
+                    model: config.model.clone(),
+                    approval_policy: config.approval_policy.to_string(),
+                    sandbox_mode: sandbox_mode.to_string(),
+                });
+                Ok(Some("get_config".to_string()))
+            }
+            TalonCommand::LoadLastAssistant => {
+                let Some(text) = self.chat_widget.last_agent_message().map(str::to_string) else {
+                    return Err(TalonCommandError::new(
+                        TalonErrorCode::NoMessages,
+                        "there is no assistant message to load yet",
+                    ));
+                };
+                let cursor = text.len();
+                self.chat_widget.set_composer_text(text);
+                self.chat_widget.set_composer_cursor(cursor);
+                Ok(Some("load_last_assistant".to_string()))
+            }
+            TalonCommand::Beep { tone } => {
+                if !talon::play_beep_command(tone.as_deref()) {
+                    tui.bell();
+                }
+                Ok(Some("beep".to_string()))
+            }
+            TalonCommand::ReloadMcp => {
+                self.chat_widget.submit_op(Op::ReloadMcpServers);
+                Ok(Some("reload_mcp".to_string()))
+            }
+            TalonCommand::RestartMcp { servers } => {
+                self.chat_widget
+                    .submit_op(Op::RestartMcpServers { servers });
+                Ok(Some("restart_mcp".to_string()))
+            }
+        }
+    }
+
+    fn talon_editor_state(&self, buffer_range: Option<[usize; 2]>) -> TalonEditorState {
+        let buffer = self.chat_widget.composer_text();
+        let cursor = self.chat_widget.composer_cursor();
+        let cursor_char = talon_edit::char_offset(&buffer, cursor);
+        let buffer_char_len = buffer.chars().count();
+        let state_version = talon_edit::state_version(&buffer, cursor);
+        let (buffer, buffer_window) = match buffer_range {
+            Some([start, end]) => {
+                let (text, window) = talon_edit::buffer_window(&buffer, start, end);
+                (text, Some(window))
+            }
+            None => (buffer, None),
+        };
+        TalonEditorState {
+            buffer,
+            cursor,
+            cursor_char,
+            buffer_char_len,
+            state_version,
+            is_task_running: self.chat_widget.is_task_running(),
+            task_summary: talon::status_summary(),
+            session_id: self.chat_widget.conversation_id().map(|id| id.to_string()),
+            cwd: Some(self.config.cwd.display().to_string()),
+            buffer_window,
+        }
+    }
+}
+
+fn ensure_cursor(buffer: &str, cursor: usize) -> Result<(), TalonCommandError> {
+    if buffer.is_char_boundary(cursor) {
+        Ok(())
+    } else {
+        Err(TalonCommandError::new(
+            TalonErrorCode::InvalidCursor,
+            format!(
+                "cursor {cursor} is not a character boundary within the {}-byte buffer",
+                buffer.len()
+            ),
+        ))
+    }
+}
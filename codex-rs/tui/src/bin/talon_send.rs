@@ -1,10 +1,22 @@
+use std::fmt;
 use std::fs;
+use std::io::Read;
+use std::path::Path;
 use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 
 use anyhow::Context;
 use anyhow::Result;
+use anyhow::bail;
 use clap::Parser;
 use clap::Subcommand;
+use clap::ValueEnum;
+use codex_tui::TalonRequestFileFormat;
+use codex_tui::validate_talon_request_json;
 use dirs::home_dir;
 use serde::Serialize;
 use serde_json::Value;
@@ -12,6 +24,16 @@ use serde_json::Value;
 const TALON_DIR: &str = ".codex-talon";
 const REQUEST_FILE: &str = "request.json";
 const RESPONSE_FILE: &str = "response.json";
+/// How long `history-list` waits for Codex to pick up its request.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+const RESPONSE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// Pause between `get_state` requests while `state --wait` waits for a task.
+const TASK_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Exit status for any failure other than [`EXIT_NO_ANSWER`]. Usage errors
+/// exit with clap's status 2.
+const EXIT_FAILURE: i32 = 1;
+/// Exit status when Codex did not answer in time or its response is stale.
+const EXIT_NO_ANSWER: i32 = 3;
 
 #[derive(Parser)]
 #[command(
@@ -20,40 +42,107 @@ const RESPONSE_FILE: &str = "response.json";
     about = "Send commands to the Codex Talon command server"
 )]
 struct Cli {
+    /// How to print results and errors. `json` prints one JSON object on
+    /// stdout for every subcommand, including failures.
+    #[arg(long, value_enum, global = true, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
+    /// After writing a request, wait up to this many milliseconds for Codex
+    /// to answer it, and fail if it does not or if it reports an error.
+    /// Must come before the subcommand. Without it, requests are written
+    /// and left for Codex to pick up.
+    #[arg(long, value_name = "MS")]
+    timeout: Option<u64>,
+
     #[command(subcommand)]
     command: Command,
 }
 
+#[derive(Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
 #[derive(Subcommand)]
 enum Command {
     /// Replace the Codex input buffer (optional cursor).
     SetBuffer {
         /// Text to populate the buffer with.
-        #[arg(short, long)]
-        text: String,
-        /// Optional cursor offset within the new buffer.
+        #[arg(short, long, required_unless_present = "file", conflicts_with = "file")]
+        text: Option<String>,
+        /// Read the buffer text from a file (`-` for stdin).
+        #[arg(short, long, value_name = "PATH")]
+        file: Option<PathBuf>,
+        /// Optional cursor offset within the new buffer (defaults to the end with --file).
         #[arg(short, long)]
         cursor: Option<usize>,
     },
+    /// Show the line diff between the current Codex input buffer and TEXT
+    /// without changing the buffer. Waits for Codex to report its state.
+    DiffBuffer {
+        /// Proposed new buffer text.
+        #[arg(short, long, required_unless_present = "file", conflicts_with = "file")]
+        text: Option<String>,
+        /// Read the proposed buffer text from a file (`-` for stdin).
+        #[arg(short, long, value_name = "PATH")]
+        file: Option<PathBuf>,
+    },
     /// Move cursor to an absolute byte offset within the buffer.
     SetCursor {
         /// Cursor position to set.
-        cursor: usize,
+        #[arg(required_unless_present_any = ["start", "end"])]
+        cursor: Option<usize>,
+        /// Move to the start of the buffer instead of CURSOR.
+        #[arg(long, conflicts_with_all = ["cursor", "end"])]
+        start: bool,
+        /// Move to the end of the buffer instead of CURSOR.
+        #[arg(long, conflicts_with = "cursor")]
+        end: bool,
+    },
+    /// Move cursor to a 1-based line and character column.
+    SetCursorLineCol {
+        /// Line number, starting at 1.
+        line: usize,
+        /// Column in characters, starting at 1.
+        col: usize,
     },
+    /// Move cursor to the start of its current line.
+    LineStart,
+    /// Move cursor to the end of its current line.
+    LineEnd,
     /// Clear any pending request file.
     Clear,
     /// Stage a request for Codex to emit its current state.
-    State,
+    State {
+        /// Keep requesting state until no task is running, then print it.
+        #[arg(long)]
+        wait: bool,
+        /// Give up waiting after this many seconds (default: wait forever).
+        #[arg(long, value_name = "SECS", requires = "wait")]
+        timeout: Option<u64>,
+        /// Return only this byte window of the buffer.
+        #[arg(long, num_args = 2, value_names = ["START", "END"], conflicts_with = "wait")]
+        range: Option<Vec<usize>>,
+    },
     /// Print the most recent response/state file.
     ShowState {
         /// Emit raw JSON without pretty formatting.
         #[arg(long)]
         raw: bool,
+        /// Only print the response if its `timestamp_ms` is at least this
+        /// Unix time in milliseconds; otherwise exit with an error.
+        #[arg(long, value_name = "MS")]
+        since: Option<u64>,
     },
     /// Stage a flash notification inside Codex.
     Notify {
         /// Text to display.
         message: String,
+        /// How long the flash stays up, in milliseconds. Zero or absent uses
+        /// the TUI default.
+        #[arg(long, value_name = "MS")]
+        timeout: Option<u64>,
     },
     /// Navigate to the previous entry in the composer history.
     HistoryPrevious,
@@ -65,6 +154,172 @@ enum Command {
         #[arg(default_value_t = 0)]
         steps_back: usize,
     },
+    /// Scroll the transcript view (opens it if needed).
+    Scroll {
+        /// Direction to scroll.
+        direction: ScrollDirection,
+        /// Unit to scroll by.
+        #[arg(default_value = "line")]
+        amount: ScrollAmount,
+        /// Number of lines/pages to scroll.
+        #[arg(long, default_value_t = 1)]
+        count: u32,
+    },
+    /// Replace the byte range START..END of the buffer with TEXT.
+    ReplaceRange {
+        /// Start byte offset (inclusive).
+        start: usize,
+        /// End byte offset (exclusive).
+        end: usize,
+        /// Replacement text (may be empty).
+        text: String,
+    },
+    /// Replace FIND with WITH in the buffer (first occurrence unless --all).
+    Replace {
+        /// Text to search for.
+        find: String,
+        /// Replacement text (may be empty).
+        with: String,
+        /// Replace every occurrence instead of only the first.
+        #[arg(long)]
+        all: bool,
+    },
+    /// Indent the cursor's line (or the lines up to --anchor) by SPACES.
+    Indent {
+        /// Number of spaces to add.
+        spaces: usize,
+        /// Byte offset of the other end of the selection.
+        #[arg(long)]
+        anchor: Option<usize>,
+    },
+    /// Request the text between the cursor and --anchor; the response's
+    /// `selection` holds it, or null when nothing is selected.
+    Selection {
+        /// Byte offset of the other end of the selection.
+        #[arg(long)]
+        anchor: Option<usize>,
+    },
+    /// Remove up to SPACES leading spaces from the cursor's line (or the
+    /// lines up to --anchor).
+    Dedent {
+        /// Maximum number of spaces to remove.
+        spaces: usize,
+        /// Byte offset of the other end of the selection.
+        #[arg(long)]
+        anchor: Option<usize>,
+    },
+    /// Append dictated TEXT to the end of the buffer, adding a space before
+    /// it where needed.
+    Transcribe {
+        /// Dictated text.
+        text: String,
+        /// Upper-case TEXT when it starts a sentence.
+        #[arg(long)]
+        capitalize: bool,
+    },
+    /// Find QUERY in the buffer; the response lists the byte range of each
+    /// match.
+    Search {
+        /// Text (or, with --regex, regular expression) to search for.
+        query: String,
+        /// Treat QUERY as a regular expression.
+        #[arg(long)]
+        regex: bool,
+    },
+    /// Print composer history with the index to pass to `edit-previous`.
+    HistoryList {
+        /// Only list the N most recent entries.
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Print the raw response JSON instead of the numbered list.
+        #[arg(long)]
+        raw: bool,
+    },
+    /// Copy the Codex input buffer to the system clipboard.
+    CopyBuffer,
+    /// Insert the system clipboard's text at the Codex cursor.
+    Paste,
+    /// Answer the approval prompt Codex is currently showing.
+    Approve {
+        #[arg(value_enum)]
+        decision: ApprovalDecision,
+    },
+    /// Request details of the running task (id, elapsed time, description,
+    /// last tool).
+    Task,
+    /// Request the session's model, approval policy, and sandbox mode.
+    Config,
+    /// Replace the buffer with the most recent assistant message.
+    LoadLastAssistant,
+    /// Play an audible cue (the terminal bell unless Codex has a beep
+    /// command configured).
+    Beep {
+        /// Tone name passed to the configured beep command.
+        #[arg(long)]
+        tone: Option<String>,
+    },
+    /// Reload MCP servers from config.toml, restarting only changed ones.
+    ReloadMcp,
+    /// Restart running MCP servers, e.g. one that stopped responding.
+    RestartMcp {
+        /// Names of the servers to restart.
+        #[arg(required_unless_present = "all", conflicts_with = "all")]
+        servers: Vec<String>,
+        /// Restart every running server.
+        #[arg(long)]
+        all: bool,
+    },
+    /// Request the working-tree diff of the session directory.
+    Diff {
+        /// Cap on the returned diff size in bytes (Codex defaults to 64 KiB).
+        #[arg(long)]
+        max_bytes: Option<usize>,
+    },
+    /// Write a hand-written request (`{"commands": [...]}`) as-is, for
+    /// commands that have no dedicated subcommand yet. The JSON must parse
+    /// as a request this build of Codex understands. A `.toml` file is
+    /// converted to JSON first.
+    Raw {
+        /// Request JSON.
+        #[arg(required_unless_present = "file", conflicts_with = "file")]
+        json: Option<String>,
+        /// Read the request from a file (`-` for stdin): TOML when the name
+        /// ends in `.toml`, JSON otherwise.
+        #[arg(short, long, value_name = "PATH")]
+        file: Option<PathBuf>,
+    },
+}
+
+#[derive(Clone, Copy, Serialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+enum ScrollDirection {
+    Up,
+    Down,
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum CursorPosition {
+    Start,
+    End,
+}
+
+#[derive(Clone, Copy, Serialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+enum ApprovalDecision {
+    /// Approve this request once.
+    Yes,
+    /// Decline and stop so you can tell Codex what to do instead.
+    No,
+    /// Approve and stop asking for this command for the rest of the session.
+    Always,
+}
+
+#[derive(Clone, Copy, Serialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+enum ScrollAmount {
+    Line,
+    Page,
 }
 
 #[derive(Serialize)]
@@ -84,9 +339,23 @@ enum TalonCommand {
     SetCursor {
         cursor: usize,
     },
-    GetState,
+    CursorTo {
+        position: CursorPosition,
+    },
+    SetCursorLineCol {
+        line: usize,
+        col: usize,
+    },
+    LineStart,
+    LineEnd,
+    GetState {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        buffer_range: Option<[usize; 2]>,
+    },
     Notify {
         message: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        timeout_ms: Option<u64>,
     },
     EditPreviousMessage {
         #[serde(default)]
@@ -94,81 +363,571 @@ enum TalonCommand {
     },
     HistoryPrevious,
     HistoryNext,
+    Scroll {
+        direction: ScrollDirection,
+        amount: ScrollAmount,
+        count: u32,
+    },
+    ReplaceRange {
+        start: usize,
+        end: usize,
+        text: String,
+    },
+    Replace {
+        find: String,
+        with: String,
+        all: bool,
+    },
+    Indent {
+        spaces: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        anchor: Option<usize>,
+    },
+    GetSelection {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        anchor: Option<usize>,
+    },
+    Dedent {
+        spaces: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        anchor: Option<usize>,
+    },
+    Transcribe {
+        text: String,
+        capitalize: bool,
+    },
+    SearchBuffer {
+        query: String,
+        regex: bool,
+    },
+    GetDiff {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max_bytes: Option<usize>,
+    },
+    GetHistory {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        limit: Option<usize>,
+    },
+    CopyBuffer,
+    Paste,
+    Approve {
+        decision: ApprovalDecision,
+    },
+    GetTask,
+    GetConfig,
+    LoadLastAssistant,
+    Beep {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        tone: Option<String>,
+    },
+    ReloadMcp,
+    RestartMcp {
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        servers: Vec<String>,
+    },
 }
 
-fn main() -> Result<()> {
+fn main() {
     let cli = Cli::parse();
+    let output = cli.output;
+    let ack_timeout = cli.timeout.map(Duration::from_millis);
+    let result = run(cli.command).and_then(|report| {
+        if let Some(timeout) = ack_timeout {
+            report.wait_for_ack(timeout)?;
+        }
+        report.print(output)
+    });
+    if let Err(err) = result {
+        let code = exit_code(&err);
+        match output {
+            OutputFormat::Text => eprintln!("Error: {err:?}"),
+            OutputFormat::Json => println!(
+                "{}",
+                serde_json::json!({ "error": format!("{err:#}"), "exit_code": code })
+            ),
+        }
+        std::process::exit(code);
+    }
+}
+
+fn run(command: Command) -> Result<Report> {
     let (request_path, response_path) = ensure_paths()?;
 
-    let message = match cli.command {
-        Command::SetBuffer { text, cursor } => {
-            let request = TalonRequest {
-                commands: vec![TalonCommand::SetBuffer { text, cursor }],
+    match command {
+        Command::SetBuffer { text, file, cursor } => {
+            let (text, cursor) = match (text, file) {
+                (Some(text), _) => (text, cursor),
+                (None, Some(path)) => {
+                    let text = read_buffer_file(&path)?;
+                    // File contents are usually appended to, so park the cursor at the end.
+                    let cursor = cursor.or(Some(text.len()));
+                    (text, cursor)
+                }
+                (None, None) => unreachable!("clap requires --text or --file"),
             };
-            write_request(&request_path, request)?;
-            format!("wrote request to {}", request_path.display())
+            let message = format!("wrote request to {}", request_path.display());
+            stage(
+                request_path,
+                TalonCommand::SetBuffer { text, cursor },
+                message,
+            )
         }
-        Command::SetCursor { cursor } => {
+        Command::DiffBuffer { text, file } => {
+            let proposed = match (text, file) {
+                (Some(text), _) => text,
+                (None, Some(path)) => read_buffer_file(&path)?,
+                (None, None) => unreachable!("clap requires --text or --file"),
+            };
             let request = TalonRequest {
-                commands: vec![TalonCommand::SetCursor { cursor }],
+                commands: vec![TalonCommand::GetState { buffer_range: None }],
             };
             write_request(&request_path, request)?;
-            format!("wrote request to {}", request_path.display())
+            wait_for_response(&request_path)?;
+            let contents = read_response(&response_path)?;
+            let value = parse_response(&response_path, &contents)?;
+            let current = value
+                .pointer("/state/buffer")
+                .and_then(Value::as_str)
+                .context("response does not include state.buffer")?;
+            Ok(Report::BufferDiff {
+                changed: current != proposed,
+                diff: diffy::create_patch(current, &proposed).to_string(),
+            })
+        }
+        Command::SetCursor {
+            cursor,
+            start,
+            end: _,
+        } => {
+            let message = format!("wrote request to {}", request_path.display());
+            // clap requires one of CURSOR, --start, or --end.
+            let command = match (cursor, start) {
+                (Some(cursor), _) => TalonCommand::SetCursor { cursor },
+                (None, true) => TalonCommand::CursorTo {
+                    position: CursorPosition::Start,
+                },
+                (None, false) => TalonCommand::CursorTo {
+                    position: CursorPosition::End,
+                },
+            };
+            stage(request_path, command, message)
+        }
+        Command::SetCursorLineCol { line, col } => {
+            let message = format!("wrote request to {}", request_path.display());
+            stage(
+                request_path,
+                TalonCommand::SetCursorLineCol { line, col },
+                message,
+            )
+        }
+        Command::LineStart => {
+            let message = format!("wrote request to {}", request_path.display());
+            stage(request_path, TalonCommand::LineStart, message)
+        }
+        Command::LineEnd => {
+            let message = format!("wrote request to {}", request_path.display());
+            stage(request_path, TalonCommand::LineEnd, message)
         }
         Command::Clear => {
             if let Err(err) = fs::remove_file(&request_path)
                 && err.kind() != std::io::ErrorKind::NotFound
             {
-                return Err(err.into());
+                return Err(err)
+                    .with_context(|| format!("failed to remove {}", request_path.display()));
             }
-            format!("cleared request at {}", request_path.display())
+            Ok(Report::Cleared { path: request_path })
         }
-        Command::State => {
-            let request = TalonRequest {
-                commands: vec![TalonCommand::GetState],
-            };
-            write_request(&request_path, request)?;
-            format!("requested state via {}", request_path.display())
+        Command::State {
+            wait: true,
+            timeout,
+            ..
+        } => {
+            wait_for_idle(
+                &request_path,
+                &response_path,
+                timeout.map(Duration::from_secs),
+            )?;
+            read_state(&response_path, false, None)
         }
-        Command::Notify { message } => {
-            let request = TalonRequest {
-                commands: vec![TalonCommand::Notify { message }],
+        Command::State {
+            wait: false, range, ..
+        } => {
+            let message = format!("requested state via {}", request_path.display());
+            let buffer_range = range.map(|range| [range[0], range[1]]);
+            stage(
+                request_path,
+                TalonCommand::GetState { buffer_range },
+                message,
+            )
+        }
+        Command::Notify { message, timeout } => {
+            let text = format!("requested notification via {}", request_path.display());
+            let command = TalonCommand::Notify {
+                message,
+                timeout_ms: timeout,
             };
-            write_request(&request_path, request)?;
-            format!("requested notification via {}", request_path.display())
+            stage(request_path, command, text)
         }
         Command::HistoryPrevious => {
-            let request = TalonRequest {
-                commands: vec![TalonCommand::HistoryPrevious],
-            };
-            write_request(&request_path, request)?;
-            format!("requested history_previous via {}", request_path.display())
+            let message = format!("requested history_previous via {}", request_path.display());
+            stage(request_path, TalonCommand::HistoryPrevious, message)
         }
         Command::HistoryNext => {
-            let request = TalonRequest {
-                commands: vec![TalonCommand::HistoryNext],
-            };
-            write_request(&request_path, request)?;
-            format!("requested history_next via {}", request_path.display())
+            let message = format!("requested history_next via {}", request_path.display());
+            stage(request_path, TalonCommand::HistoryNext, message)
         }
         Command::EditPrevious { steps_back } => {
-            let request = TalonRequest {
-                commands: vec![TalonCommand::EditPreviousMessage { steps_back }],
-            };
-            write_request(&request_path, request)?;
-            format!(
+            let message = format!(
                 "requested edit_previous_message({steps_back}) via {}",
                 request_path.display()
+            );
+            stage(
+                request_path,
+                TalonCommand::EditPreviousMessage { steps_back },
+                message,
+            )
+        }
+        Command::Scroll {
+            direction,
+            amount,
+            count,
+        } => {
+            let message = format!("requested scroll via {}", request_path.display());
+            let command = TalonCommand::Scroll {
+                direction,
+                amount,
+                count,
+            };
+            stage(request_path, command, message)
+        }
+        Command::ReplaceRange { start, end, text } => {
+            let message = format!(
+                "requested replace_range({start}..{end}) via {}",
+                request_path.display()
+            );
+            stage(
+                request_path,
+                TalonCommand::ReplaceRange { start, end, text },
+                message,
+            )
+        }
+        Command::Replace { find, with, all } => {
+            let message = format!("requested replace via {}", request_path.display());
+            stage(
+                request_path,
+                TalonCommand::Replace { find, with, all },
+                message,
             )
         }
-        Command::ShowState { raw } => {
-            print_state(&response_path, raw)?;
+        Command::Indent { spaces, anchor } => {
+            let message = format!("requested indent({spaces}) via {}", request_path.display());
+            stage(
+                request_path,
+                TalonCommand::Indent { spaces, anchor },
+                message,
+            )
+        }
+        Command::Selection { anchor } => {
+            let message = format!("requested get_selection via {}", request_path.display());
+            stage(request_path, TalonCommand::GetSelection { anchor }, message)
+        }
+        Command::Dedent { spaces, anchor } => {
+            let message = format!("requested dedent({spaces}) via {}", request_path.display());
+            stage(
+                request_path,
+                TalonCommand::Dedent { spaces, anchor },
+                message,
+            )
+        }
+        Command::Transcribe { text, capitalize } => {
+            let message = format!("requested transcribe via {}", request_path.display());
+            stage(
+                request_path,
+                TalonCommand::Transcribe { text, capitalize },
+                message,
+            )
+        }
+        Command::Search { query, regex } => {
+            let message = format!("requested search_buffer via {}", request_path.display());
+            stage(
+                request_path,
+                TalonCommand::SearchBuffer { query, regex },
+                message,
+            )
+        }
+        Command::Diff { max_bytes } => {
+            let message = format!("requested get_diff via {}", request_path.display());
+            stage(request_path, TalonCommand::GetDiff { max_bytes }, message)
+        }
+        Command::CopyBuffer => {
+            let message = format!("requested copy_buffer via {}", request_path.display());
+            stage(request_path, TalonCommand::CopyBuffer, message)
+        }
+        Command::Paste => {
+            let message = format!("requested paste via {}", request_path.display());
+            stage(request_path, TalonCommand::Paste, message)
+        }
+        Command::Approve { decision } => {
+            let message = format!("requested approve via {}", request_path.display());
+            stage(request_path, TalonCommand::Approve { decision }, message)
+        }
+        Command::Task => {
+            let message = format!("requested get_task via {}", request_path.display());
+            stage(request_path, TalonCommand::GetTask, message)
+        }
+        Command::Config => {
+            let message = format!("requested get_config via {}", request_path.display());
+            stage(request_path, TalonCommand::GetConfig, message)
+        }
+        Command::LoadLastAssistant => {
+            let message = format!("wrote request to {}", request_path.display());
+            stage(request_path, TalonCommand::LoadLastAssistant, message)
+        }
+        Command::Beep { tone } => {
+            let message = format!("requested beep via {}", request_path.display());
+            stage(request_path, TalonCommand::Beep { tone }, message)
+        }
+        Command::ReloadMcp => {
+            let message = format!("requested MCP reload via {}", request_path.display());
+            stage(request_path, TalonCommand::ReloadMcp, message)
+        }
+        // `--all` is an empty list, which restarts every running server.
+        Command::RestartMcp { servers, all: _ } => {
+            let message = format!("requested MCP restart via {}", request_path.display());
+            stage(request_path, TalonCommand::RestartMcp { servers }, message)
+        }
+        Command::HistoryList { limit, raw } => {
+            let request = TalonRequest {
+                commands: vec![TalonCommand::GetHistory { limit }],
+            };
+            write_request(&request_path, request)?;
+            wait_for_response(&request_path)?;
+            read_history(&response_path, raw)
+        }
+        Command::Raw { json, file } => {
+            let (raw, source, format) = match (json, file) {
+                (Some(json), _) => (
+                    json,
+                    "the command line".to_string(),
+                    TalonRequestFileFormat::Json,
+                ),
+                (None, Some(path)) => {
+                    let source = if path == Path::new("-") {
+                        "stdin".to_string()
+                    } else {
+                        path.display().to_string()
+                    };
+                    let format = TalonRequestFileFormat::from_path(&path);
+                    (read_buffer_file(&path)?, source, format)
+                }
+                (None, None) => unreachable!("clap requires JSON or --file"),
+            };
+            let json = format
+                .to_json(raw)
+                .with_context(|| format!("failed to parse request {format} from {source}"))?;
+            validate_talon_request_json(&json)
+                .with_context(|| format!("failed to parse request {format} from {source}"))?;
+            let written_at_ms = now_ms();
+            fs::write(&request_path, json)
+                .with_context(|| format!("failed to write {}", request_path.display()))?;
+            let message = format!("wrote request to {}", request_path.display());
+            Ok(Report::Wrote {
+                command: "raw".to_string(),
+                path: request_path,
+                message,
+                written_at_ms,
+            })
+        }
+        Command::ShowState { raw, since } => read_state(&response_path, raw, since),
+    }
+}
+
+/// What a subcommand did, printed in the format chosen with `--output`.
+enum Report {
+    /// A request was written for Codex to pick up. `command` is the Talon
+    /// command's `type` (or `raw`); `message` is the text output.
+    /// `written_at_ms` is the Unix time in milliseconds just before writing.
+    Wrote {
+        command: String,
+        path: PathBuf,
+        message: String,
+        written_at_ms: u64,
+    },
+    /// The pending request file was removed (or was already absent).
+    Cleared { path: PathBuf },
+    /// Codex's response to `command` (`get_state` or `get_history`), read
+    /// from `path`. `raw` prints `contents` unchanged in text output.
+    Response {
+        command: &'static str,
+        path: PathBuf,
+        contents: String,
+        raw: bool,
+    },
+    /// Unified diff from the current buffer to a proposed one; nothing was
+    /// staged.
+    BufferDiff { diff: String, changed: bool },
+}
+
+impl Report {
+    /// Wait for Codex to answer the request this report wrote, polling the
+    /// response file next to it until its `timestamp_ms` is later than the
+    /// write. Reports that wrote nothing have nothing to wait for.
+    fn wait_for_ack(&self, timeout: Duration) -> Result<()> {
+        let Report::Wrote {
+            path,
+            written_at_ms,
+            ..
+        } = self
+        else {
             return Ok(());
+        };
+        let response_path = path.with_file_name(RESPONSE_FILE);
+        let deadline = Instant::now() + timeout;
+        loop {
+            // An unreadable or half-written response is treated as not yet
+            // answered.
+            let answer = fs::read_to_string(&response_path)
+                .ok()
+                .and_then(|contents| serde_json::from_str::<Value>(&contents).ok())
+                .filter(|value| {
+                    value
+                        .get("timestamp_ms")
+                        .and_then(Value::as_u64)
+                        .is_some_and(|timestamp| timestamp > *written_at_ms)
+                });
+            if let Some(value) = answer {
+                if let Some(error) = value.get("error").and_then(Value::as_str) {
+                    bail!("Codex rejected the request: {error}");
+                }
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(NoAnswer(format!(
+                    "timed out after {} ms waiting for Codex to answer {} (is Codex running?)",
+                    timeout.as_millis(),
+                    path.display()
+                ))
+                .into());
+            }
+            thread::sleep(RESPONSE_POLL_INTERVAL);
         }
-    };
+    }
 
-    println!("{message}");
-    Ok(())
+    fn print(&self, output: OutputFormat) -> Result<()> {
+        match (self, output) {
+            (Report::Wrote { message, .. }, OutputFormat::Text) => println!("{message}"),
+            (Report::Wrote { command, path, .. }, OutputFormat::Json) => println!(
+                "{}",
+                serde_json::json!({ "wrote": path.display().to_string(), "command": command })
+            ),
+            (Report::Cleared { path }, OutputFormat::Text) => {
+                println!("cleared request at {}", path.display());
+            }
+            (Report::Cleared { path }, OutputFormat::Json) => println!(
+                "{}",
+                serde_json::json!({ "cleared": path.display().to_string(), "command": "clear" })
+            ),
+            (Report::BufferDiff { changed: false, .. }, OutputFormat::Text) => {
+                println!("buffer unchanged");
+            }
+            (Report::BufferDiff { diff, .. }, OutputFormat::Text) => print!("{diff}"),
+            (Report::BufferDiff { diff, changed }, OutputFormat::Json) => println!(
+                "{}",
+                serde_json::json!({ "command": "diff_buffer", "changed": changed, "diff": diff })
+            ),
+            (Report::Response { contents, raw, .. }, OutputFormat::Text) if *raw => {
+                println!("{contents}");
+            }
+            (
+                Report::Response {
+                    command,
+                    path,
+                    contents,
+                    ..
+                },
+                OutputFormat::Text,
+            ) => {
+                let value = parse_response(path, contents)?;
+                if *command == "get_history" {
+                    print_history(&value);
+                } else {
+                    println!("{}", serde_json::to_string_pretty(&value)?);
+                }
+            }
+            (
+                Report::Response {
+                    command,
+                    path,
+                    contents,
+                    ..
+                },
+                OutputFormat::Json,
+            ) => {
+                let value = parse_response(path, contents)?;
+                println!(
+                    "{}",
+                    serde_json::json!({ "command": command, "response": value })
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Exit status for a failed run: [`EXIT_NO_ANSWER`] when Codex did not
+/// answer in time or its response is stale, [`EXIT_FAILURE`] otherwise
+/// (including I/O errors on the request or response file).
+fn exit_code(err: &anyhow::Error) -> i32 {
+    if err.chain().any(|cause| cause.is::<NoAnswer>()) {
+        EXIT_NO_ANSWER
+    } else {
+        EXIT_FAILURE
+    }
+}
+
+/// Codex did not produce the response a subcommand was waiting for.
+#[derive(Debug)]
+struct NoAnswer(String);
+
+impl fmt::Display for NoAnswer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for NoAnswer {}
+
+/// Write a request holding just `command`.
+fn stage(request_path: PathBuf, command: TalonCommand, message: String) -> Result<Report> {
+    let name = serde_json::to_value(&command)
+        .ok()
+        .and_then(|value| {
+            value
+                .get("type")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        })
+        .unwrap_or_default();
+    let written_at_ms = now_ms();
+    write_request(
+        &request_path,
+        TalonRequest {
+            commands: vec![command],
+        },
+    )?;
+    Ok(Report::Wrote {
+        command: name,
+        path: request_path,
+        message,
+        written_at_ms,
+    })
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+        .unwrap_or_default()
 }
 
 fn ensure_paths() -> Result<(PathBuf, PathBuf)> {
@@ -180,24 +939,160 @@ fn ensure_paths() -> Result<(PathBuf, PathBuf)> {
     Ok((dir.join(REQUEST_FILE), dir.join(RESPONSE_FILE)))
 }
 
-fn write_request(path: &PathBuf, request: TalonRequest) -> Result<()> {
+fn read_buffer_file(path: &Path) -> Result<String> {
+    if path == Path::new("-") {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .context("failed to read stdin")?;
+        Ok(text)
+    } else {
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))
+    }
+}
+
+fn write_request(path: &Path, request: TalonRequest) -> Result<()> {
     let payload =
         serde_json::to_vec_pretty(&request).context("failed to serialize Talon request")?;
     fs::write(path, payload).with_context(|| format!("failed to write {}", path.display()))
 }
 
-fn print_state(path: &PathBuf, raw: bool) -> Result<()> {
-    let contents =
-        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+/// Read the response at `path`. With `since`, a response whose
+/// `timestamp_ms` is older (or missing) is reported as stale instead.
+fn read_state(path: &Path, raw: bool, since: Option<u64>) -> Result<Report> {
+    let contents = read_response(path)?;
 
-    if raw {
-        println!("{contents}");
-        return Ok(());
+    if let Some(since) = since {
+        let value = parse_response(path, &contents)?;
+        let stale = match value.get("timestamp_ms").and_then(Value::as_u64) {
+            Some(timestamp) if timestamp >= since => None,
+            Some(timestamp) => Some(format!(
+                "response in {} is stale: timestamp_ms {timestamp} is before {since}",
+                path.display()
+            )),
+            None => Some(format!(
+                "response in {} is stale: it has no timestamp_ms",
+                path.display()
+            )),
+        };
+        if let Some(stale) = stale {
+            return Err(NoAnswer(stale).into());
+        }
     }
 
-    let value: Value = serde_json::from_str(&contents)
-        .with_context(|| format!("failed to parse JSON from {}", path.display()))?;
-    let pretty = serde_json::to_string_pretty(&value)?;
-    println!("{pretty}");
+    Ok(Report::Response {
+        command: "get_state",
+        path: path.to_path_buf(),
+        contents,
+        raw,
+    })
+}
+
+/// Read a `get_history` response, failing if Codex rejected the request.
+/// With `raw` the response is passed through without further checks.
+fn read_history(path: &Path, raw: bool) -> Result<Report> {
+    let contents = read_response(path)?;
+
+    if !raw {
+        let value = parse_response(path, &contents)?;
+        if let Some(error) = value.get("error").and_then(Value::as_str) {
+            bail!("Codex rejected the request: {error}");
+        }
+        if !value.get("history").is_some_and(Value::is_array) {
+            bail!("response does not include history");
+        }
+    }
+
+    Ok(Report::Response {
+        command: "get_history",
+        path: path.to_path_buf(),
+        contents,
+        raw,
+    })
+}
+
+/// Print the entries of a checked `get_history` response, one per line.
+fn print_history(value: &Value) {
+    let entries = value
+        .get("history")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    if entries.is_empty() {
+        println!("No history entries available.");
+        return;
+    }
+    for entry in entries {
+        let steps_back = entry.get("steps_back").and_then(Value::as_u64).unwrap_or(0);
+        let text = entry
+            .get("text")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let mut lines = text.lines();
+        let first = lines.next().unwrap_or_default();
+        let more = if lines.next().is_some() { " …" } else { "" };
+        println!("{steps_back:>4}  {first}{more}");
+    }
+}
+
+/// Codex removes the request file once it has written the response, so wait
+/// for it to disappear.
+fn wait_for_response(request_path: &Path) -> Result<()> {
+    let deadline = Instant::now() + RESPONSE_TIMEOUT;
+    while request_path.exists() {
+        if Instant::now() >= deadline {
+            return Err(NoAnswer(format!(
+                "timed out waiting for Codex to answer {} (is Codex running?)",
+                request_path.display()
+            ))
+            .into());
+        }
+        thread::sleep(RESPONSE_POLL_INTERVAL);
+    }
     Ok(())
 }
+
+/// Issue `get_state` requests until Codex reports that no task is running,
+/// leaving that final response in `response_path`.
+fn wait_for_idle(
+    request_path: &Path,
+    response_path: &Path,
+    timeout: Option<Duration>,
+) -> Result<()> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    loop {
+        let request = TalonRequest {
+            commands: vec![TalonCommand::GetState { buffer_range: None }],
+        };
+        write_request(request_path, request)?;
+        wait_for_response(request_path)?;
+
+        let contents = read_response(response_path)?;
+        let value = parse_response(response_path, &contents)?;
+        let is_task_running = value
+            .pointer("/state/is_task_running")
+            .and_then(Value::as_bool)
+            .context("response does not include state.is_task_running")?;
+        if !is_task_running {
+            return Ok(());
+        }
+
+        if let Some(deadline) = deadline
+            && Instant::now() + TASK_POLL_INTERVAL >= deadline
+        {
+            return Err(
+                NoAnswer("timed out waiting for the Codex task to finish".to_string()).into(),
+            );
+        }
+        thread::sleep(TASK_POLL_INTERVAL);
+    }
+}
+
+fn read_response(path: &Path) -> Result<String> {
+    fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))
+}
+
+fn parse_response(path: &Path, contents: &str) -> Result<Value> {
+    serde_json::from_str(contents)
+        .with_context(|| format!("failed to parse JSON from {}", path.display()))
+}
@@ -1,13 +1,23 @@
 use std::fs;
+use std::path::Path;
 use std::path::PathBuf;
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
 
 use anyhow::Context;
 use anyhow::Result;
+use anyhow::bail;
 use clap::Parser;
+use codex_tui::TalonRequestFileFormat;
+use codex_tui::talon_edit;
+use codex_tui::talon_edit::InvalidPattern;
+use codex_tui::talon_edit::InvalidRange;
+use codex_tui::talon_edit::StateConflict;
+use codex_tui::talon_edit::TalonBufferWindow;
+use codex_tui::talon_edit::TalonDiff;
 use serde::Deserialize;
 use serde::Serialize;
+use serde_json::Value;
 
 #[derive(Debug, Parser)]
 #[command(about = "Simulate Codex's Talon RPC mutations for testing", version)]
@@ -16,13 +26,35 @@ struct Cli {
     #[arg(long)]
     state: Option<PathBuf>,
 
-    /// Request JSON file containing commands
+    /// Request file containing commands: TOML when the name ends in
+    /// `.toml`, JSON otherwise. Repeat to apply several requests in order; a
+    /// directory contributes its `*.json` and `*.toml` files sorted by name.
+    /// State carries over from one request to the next.
+    #[arg(long, required = true)]
+    request: Vec<PathBuf>,
+
+    /// Emit a JSON array with one response per request instead of a single
+    /// combined response
     #[arg(long)]
-    request: PathBuf,
+    per_step: bool,
 
     /// Optional path to write the response JSON (defaults to stdout)
     #[arg(long)]
     output: Option<PathBuf>,
+
+    /// Text on the simulated clipboard, inserted by `paste` (defaults to
+    /// empty). `copy_buffer` replaces it.
+    #[arg(long)]
+    clipboard: Option<String>,
+
+    /// Compare the response with the one in this JSON file, ignoring
+    /// `timestamp_ms`, and exit non-zero with a diff if they differ
+    #[arg(long, value_name = "EXPECTED_JSON")]
+    assert: Option<PathBuf>,
+
+    /// With --assert, compare only the `state` of each response
+    #[arg(long, requires = "assert")]
+    assert_state_only: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -30,6 +62,10 @@ struct Cli {
 struct TalonRequest {
     #[serde(default)]
     commands: Vec<TalonCommand>,
+    #[serde(default)]
+    expected_version: Option<u64>,
+    #[serde(default)]
+    expected_cursor: Option<usize>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -43,9 +79,23 @@ enum TalonCommand {
     SetCursor {
         cursor: usize,
     },
-    GetState,
+    CursorTo {
+        position: CursorPosition,
+    },
+    SetCursorLineCol {
+        line: usize,
+        col: usize,
+    },
+    LineStart,
+    LineEnd,
+    GetState {
+        #[serde(default)]
+        buffer_range: Option<[usize; 2]>,
+    },
     Notify {
         message: String,
+        #[serde(default)]
+        timeout_ms: Option<u64>,
     },
     EditPreviousMessage {
         #[serde(default)]
@@ -53,6 +103,105 @@ enum TalonCommand {
     },
     HistoryPrevious,
     HistoryNext,
+    Scroll {
+        direction: ScrollDirection,
+        amount: ScrollAmount,
+        #[serde(default = "default_scroll_count")]
+        count: u32,
+    },
+    ReplaceRange {
+        start: usize,
+        end: usize,
+        text: String,
+    },
+    Replace {
+        find: String,
+        with: String,
+        #[serde(default)]
+        all: bool,
+    },
+    Indent {
+        spaces: usize,
+        #[serde(default)]
+        anchor: Option<usize>,
+    },
+    GetSelection {
+        #[serde(default)]
+        anchor: Option<usize>,
+    },
+    Dedent {
+        spaces: usize,
+        #[serde(default)]
+        anchor: Option<usize>,
+    },
+    Transcribe {
+        text: String,
+        #[serde(default)]
+        capitalize: bool,
+    },
+    SearchBuffer {
+        query: String,
+        #[serde(default)]
+        regex: bool,
+    },
+    GetDiff {
+        #[serde(default)]
+        max_bytes: Option<usize>,
+    },
+    GetHistory {
+        #[serde(default)]
+        limit: Option<usize>,
+    },
+    CopyBuffer,
+    Paste,
+    Approve {
+        decision: ApprovalDecision,
+    },
+    GetTask,
+    GetConfig,
+    LoadLastAssistant,
+    Beep {
+        #[serde(default)]
+        tone: Option<String>,
+    },
+    ReloadMcp,
+    RestartMcp {
+        #[serde(default)]
+        servers: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum CursorPosition {
+    Start,
+    End,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ApprovalDecision {
+    Yes,
+    No,
+    Always,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ScrollDirection {
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ScrollAmount {
+    Line,
+    Page,
+}
+
+const fn default_scroll_count() -> u32 {
+    1
 }
 
 #[derive(Debug, Serialize)]
@@ -69,6 +218,13 @@ struct TalonEditorState {
     buffer: String,
     #[serde(default)]
     cursor: usize,
+    /// Derived from `buffer` and `cursor` when the response is written.
+    #[serde(default, skip_deserializing)]
+    cursor_char: usize,
+    #[serde(default, skip_deserializing)]
+    buffer_char_len: usize,
+    #[serde(default, skip_deserializing)]
+    state_version: u64,
     #[serde(default)]
     is_task_running: bool,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -77,6 +233,35 @@ struct TalonEditorState {
     session_id: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     cwd: Option<String>,
+    /// Working-tree diff returned by `get_diff`. Seeded from the state file
+    /// so tests are deterministic; `None` simulates a directory outside git.
+    #[serde(default, skip_serializing)]
+    diff: Option<String>,
+    /// Composer history returned by `get_history`, oldest first (the order
+    /// entries were submitted in).
+    #[serde(default, skip_serializing)]
+    history: Vec<String>,
+    /// Simulates an environment without a reachable clipboard for
+    /// `copy_buffer`.
+    #[serde(default, skip_serializing)]
+    clipboard_unavailable: bool,
+    /// Simulated clipboard text, seeded from `--clipboard`.
+    #[serde(skip)]
+    clipboard: String,
+    /// Running-task details returned by `get_task` while `is_task_running`
+    /// is set.
+    #[serde(default, skip_serializing)]
+    task: Option<TalonTaskInfo>,
+    /// Session settings returned by `get_config`, seeded from the state file.
+    #[serde(default, skip_serializing)]
+    config: TalonSessionConfig,
+    /// Most recent assistant message loaded by `load_last_assistant`; `None`
+    /// simulates a session without one.
+    #[serde(default, skip_serializing)]
+    last_assistant: Option<String>,
+    /// Set in the response when `get_state` asked for a `buffer_range`.
+    #[serde(default, skip_deserializing, skip_serializing_if = "Option::is_none")]
+    buffer_window: Option<TalonBufferWindow>,
 }
 
 impl Default for TalonEditorState {
@@ -84,10 +269,21 @@ impl Default for TalonEditorState {
         Self {
             buffer: String::new(),
             cursor: 0,
+            cursor_char: 0,
+            buffer_char_len: 0,
+            state_version: 0,
             is_task_running: false,
             task_summary: None,
             session_id: None,
             cwd: None,
+            diff: None,
+            history: Vec::new(),
+            clipboard_unavailable: false,
+            clipboard: String::new(),
+            task: None,
+            config: TalonSessionConfig::default(),
+            last_assistant: None,
+            buffer_window: None,
         }
     }
 }
@@ -100,10 +296,110 @@ struct TalonResponse {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     applied: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    matches: Option<Vec<[usize; 2]>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    diff: Option<TalonDiff>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    history: Option<Vec<TalonHistoryEntry>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    selection: Option<Option<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    task: Option<TalonTaskInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    config: Option<TalonSessionConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error_code: Option<TalonErrorCode>,
     timestamp_ms: u128,
 }
 
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum TalonErrorCode {
+    ParseError,
+    UnknownCommand,
+    InvalidCursor,
+    InvalidRange,
+    TaskRunning,
+    NotARepo,
+    ClipboardUnavailable,
+    VersionConflict,
+    NoMessages,
+}
+
+#[derive(Debug, Serialize)]
+struct TalonHistoryEntry {
+    steps_back: usize,
+    text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TalonTaskInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(default)]
+    elapsed_ms: u64,
+    #[serde(default)]
+    description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_tool: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TalonSessionConfig {
+    #[serde(default)]
+    model: String,
+    #[serde(default)]
+    approval_policy: String,
+    #[serde(default)]
+    sandbox_mode: String,
+}
+
+/// Data returned by query commands, reported alongside `applied`.
+#[derive(Default)]
+struct CommandOutputs {
+    matches: Option<Vec<[usize; 2]>>,
+    diff: Option<TalonDiff>,
+    history: Option<Vec<TalonHistoryEntry>>,
+    selection: Option<Option<String>>,
+    task: Option<TalonTaskInfo>,
+    config: Option<TalonSessionConfig>,
+    buffer_range: Option<[usize; 2]>,
+}
+
+struct CommandError {
+    code: TalonErrorCode,
+    message: String,
+}
+
+impl CommandError {
+    fn new(code: TalonErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<InvalidRange> for CommandError {
+    fn from(err: InvalidRange) -> Self {
+        Self::new(TalonErrorCode::InvalidRange, err.to_string())
+    }
+}
+
+impl From<InvalidPattern> for CommandError {
+    fn from(err: InvalidPattern) -> Self {
+        Self::new(TalonErrorCode::ParseError, err.to_string())
+    }
+}
+
+impl From<StateConflict> for CommandError {
+    fn from(err: StateConflict) -> Self {
+        Self::new(TalonErrorCode::VersionConflict, err.to_string())
+    }
+}
+
 fn main() -> Result<()> {
     let cli = Cli::parse();
 
@@ -117,76 +413,31 @@ fn main() -> Result<()> {
     } else {
         TalonEditorState::default()
     };
+    state.clipboard = cli.clipboard.unwrap_or_default();
 
-    let request_raw = fs::read_to_string(&cli.request)
-        .with_context(|| format!("failed to read request file {}", cli.request.display()))?;
-    let request: TalonRequest = serde_json::from_str(&request_raw).with_context(|| {
-        format!(
-            "failed to parse request JSON from {}",
-            cli.request.display()
-        )
-    })?;
-
-    let mut applied = Vec::new();
-    let error: Option<String> = None;
+    let request_paths = expand_request_paths(&cli.request)?;
 
-    if request.commands.is_empty() {
-        // Nothing to do; fall through to response with NoRequest status.
-    } else {
-        for command in request.commands {
-            match command {
-                TalonCommand::SetBuffer { text, cursor } => {
-                    state.buffer = text;
-                    let desired = cursor.unwrap_or_else(|| state.buffer.len());
-                    state.cursor = desired.min(state.buffer.len());
-                    applied.push("set_buffer".to_string());
-                }
-                TalonCommand::SetCursor { cursor } => {
-                    state.cursor = cursor.min(state.buffer.len());
-                    applied.push("set_cursor".to_string());
-                }
-                TalonCommand::GetState => {
-                    applied.push("get_state".to_string());
-                }
-                TalonCommand::Notify { message } => {
-                    let _ = message;
-                    // No state change; record applied label for parity with the real TUI.
-                    applied.push("notify".to_string());
-                }
-                TalonCommand::EditPreviousMessage { steps_back } => {
-                    let _ = steps_back;
-                    applied.push("edit_previous_message".to_string());
-                }
-                TalonCommand::HistoryPrevious => {
-                    applied.push("history_previous".to_string());
-                }
-                TalonCommand::HistoryNext => {
-                    applied.push("history_next".to_string());
-                }
-            }
+    let mut combined = StepResult::default();
+    let mut steps = Vec::new();
+    for path in &request_paths {
+        let step = apply_request(&mut state, path)?;
+        let failed = step.error.is_some();
+        if cli.per_step {
+            steps.push(step.into_response(state.clone()));
+        } else {
+            combined.merge(step);
+        }
+        // Later requests were written assuming the earlier ones succeeded.
+        if failed {
+            break;
         }
     }
 
-    let status = if error.is_some() {
-        TalonResponseStatus::Error
-    } else if applied.is_empty() {
-        TalonResponseStatus::NoRequest
+    let response = if cli.per_step {
+        serde_json::to_value(&steps)?
     } else {
-        TalonResponseStatus::Ok
-    };
-
-    let response = TalonResponse {
-        version: 1,
-        status,
-        state,
-        applied,
-        error,
-        timestamp_ms: SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map(|d| d.as_millis())
-            .unwrap_or_default(),
+        serde_json::to_value(combined.into_response(state))?
     };
-
     let json = serde_json::to_string_pretty(&response)?;
 
     if let Some(path) = cli.output {
@@ -196,9 +447,469 @@ fn main() -> Result<()> {
         println!("{}", json);
     }
 
+    if let Some(expected_path) = &cli.assert {
+        assert_response(&response, expected_path, cli.assert_state_only)?;
+    }
+
     Ok(())
 }
 
+/// Fail with a diff unless `actual` matches the response stored in
+/// `expected_path`. `timestamp_ms` changes on every run and is not compared.
+fn assert_response(actual: &Value, expected_path: &Path, state_only: bool) -> Result<()> {
+    let raw = fs::read_to_string(expected_path).with_context(|| {
+        format!(
+            "failed to read expected response {}",
+            expected_path.display()
+        )
+    })?;
+    let expected: Value = serde_json::from_str(&raw).with_context(|| {
+        format!(
+            "failed to parse expected response JSON from {}",
+            expected_path.display()
+        )
+    })?;
+
+    let actual = comparable(actual, state_only);
+    let expected = comparable(&expected, state_only);
+    if actual == expected {
+        return Ok(());
+    }
+    let patch = diffy::create_patch(
+        &serde_json::to_string_pretty(&expected)?,
+        &serde_json::to_string_pretty(&actual)?,
+    );
+    bail!(
+        "response does not match {}:\n{patch}",
+        expected_path.display()
+    );
+}
+
+/// The part of a response, or of each response in a `--per-step` array, that
+/// `--assert` compares.
+fn comparable(response: &Value, state_only: bool) -> Value {
+    match response {
+        Value::Array(steps) => Value::Array(
+            steps
+                .iter()
+                .map(|step| comparable(step, state_only))
+                .collect(),
+        ),
+        Value::Object(fields) if state_only => fields.get("state").cloned().unwrap_or_default(),
+        Value::Object(fields) => {
+            let mut fields = fields.clone();
+            fields.remove("timestamp_ms");
+            Value::Object(fields)
+        }
+        other => other.clone(),
+    }
+}
+
+/// Commands applied and data returned while applying one or more requests.
+#[derive(Default)]
+struct StepResult {
+    applied: Vec<String>,
+    outputs: CommandOutputs,
+    error: Option<String>,
+    error_code: Option<TalonErrorCode>,
+}
+
+impl StepResult {
+    /// Fold a later step into this one. Query results from the later step
+    /// replace earlier ones of the same kind.
+    fn merge(&mut self, later: StepResult) {
+        self.applied.extend(later.applied);
+        if later.outputs.matches.is_some() {
+            self.outputs.matches = later.outputs.matches;
+        }
+        if later.outputs.diff.is_some() {
+            self.outputs.diff = later.outputs.diff;
+        }
+        if later.outputs.history.is_some() {
+            self.outputs.history = later.outputs.history;
+        }
+        if later.outputs.selection.is_some() {
+            self.outputs.selection = later.outputs.selection;
+        }
+        if later.outputs.task.is_some() {
+            self.outputs.task = later.outputs.task;
+        }
+        if later.outputs.config.is_some() {
+            self.outputs.config = later.outputs.config;
+        }
+        if later.outputs.buffer_range.is_some() {
+            self.outputs.buffer_range = later.outputs.buffer_range;
+        }
+        self.error = later.error;
+        self.error_code = later.error_code;
+    }
+
+    fn into_response(self, mut state: TalonEditorState) -> TalonResponse {
+        state.cursor_char = talon_edit::char_offset(&state.buffer, state.cursor);
+        state.buffer_char_len = state.buffer.chars().count();
+        state.state_version = talon_edit::state_version(&state.buffer, state.cursor);
+        if let Some([start, end]) = self.outputs.buffer_range {
+            let (text, window) = talon_edit::buffer_window(&state.buffer, start, end);
+            state.buffer = text;
+            state.buffer_window = Some(window);
+        }
+
+        let status = if self.error.is_some() {
+            TalonResponseStatus::Error
+        } else if self.applied.is_empty() {
+            TalonResponseStatus::NoRequest
+        } else {
+            TalonResponseStatus::Ok
+        };
+
+        TalonResponse {
+            version: 1,
+            status,
+            state,
+            applied: self.applied,
+            matches: self.outputs.matches,
+            diff: self.outputs.diff,
+            history: self.outputs.history,
+            selection: self.outputs.selection,
+            task: self.outputs.task,
+            config: self.outputs.config,
+            error: self.error,
+            error_code: self.error_code,
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Replace each directory in `paths` with the `*.json` and `*.toml` files it
+/// contains, sorted by file name.
+fn expand_request_paths(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut expanded = Vec::new();
+    for path in paths {
+        if !path.is_dir() {
+            expanded.push(path.clone());
+            continue;
+        }
+        let mut entries = fs::read_dir(path)
+            .with_context(|| format!("failed to read request directory {}", path.display()))?
+            .map(|entry| entry.map(fs::DirEntry::path))
+            .collect::<std::io::Result<Vec<_>>>()
+            .with_context(|| format!("failed to read request directory {}", path.display()))?;
+        entries.retain(|entry| {
+            entry
+                .extension()
+                .is_some_and(|ext| ext == "json" || ext == "toml")
+        });
+        entries.sort();
+        expanded.extend(entries);
+    }
+    Ok(expanded)
+}
+
+/// Apply every command in the request file at `path`, stopping at the first
+/// failure.
+fn apply_request(state: &mut TalonEditorState, path: &Path) -> Result<StepResult> {
+    let request_raw = fs::read_to_string(path)
+        .with_context(|| format!("failed to read request file {}", path.display()))?;
+    let mut step = StepResult::default();
+
+    let format = TalonRequestFileFormat::from_path(path);
+    let request_raw = match format.to_json(request_raw) {
+        Ok(json) => json,
+        Err(err) => {
+            step.error_code = Some(TalonErrorCode::ParseError);
+            step.error = Some(format!(
+                "failed to parse request {format} from {}: {err}",
+                path.display()
+            ));
+            return Ok(step);
+        }
+    };
+    match serde_json::from_str::<TalonRequest>(&request_raw) {
+        Ok(request) => {
+            if let Err(conflict) = talon_edit::check_expected_state(
+                &state.buffer,
+                state.cursor,
+                request.expected_version,
+                request.expected_cursor,
+            ) {
+                let err = CommandError::from(conflict);
+                step.error_code = Some(err.code);
+                step.error = Some(err.message);
+                return Ok(step);
+            }
+            for command in request.commands {
+                match apply_command(state, command, &mut step.outputs) {
+                    Ok(label) => step.applied.push(label),
+                    Err(err) => {
+                        step.error_code = Some(err.code);
+                        step.error = Some(err.message);
+                        break;
+                    }
+                }
+            }
+        }
+        Err(err) => {
+            step.error_code = Some(parse_error_code(&err));
+            step.error = Some(format!(
+                "failed to parse request {format} from {}: {err}",
+                path.display()
+            ));
+        }
+    }
+
+    Ok(step)
+}
+
+/// Apply one command to the simulated state, mirroring the live TUI handler.
+/// Query commands store their results in `outputs`.
+fn apply_command(
+    state: &mut TalonEditorState,
+    command: TalonCommand,
+    outputs: &mut CommandOutputs,
+) -> Result<String, CommandError> {
+    match command {
+        TalonCommand::SetBuffer { text, cursor } => {
+            if let Some(pos) = cursor {
+                ensure_cursor(&text, pos)?;
+            }
+            state.buffer = text;
+            state.cursor = cursor.unwrap_or(state.buffer.len());
+            Ok("set_buffer".to_string())
+        }
+        TalonCommand::SetCursor { cursor } => {
+            ensure_cursor(&state.buffer, cursor)?;
+            state.cursor = cursor;
+            Ok("set_cursor".to_string())
+        }
+        TalonCommand::CursorTo { position } => {
+            let (cursor, label) = match position {
+                CursorPosition::Start => (0, "start"),
+                CursorPosition::End => (state.buffer.len(), "end"),
+            };
+            state.cursor = cursor;
+            Ok(format!("cursor_to:{label}"))
+        }
+        TalonCommand::SetCursorLineCol { line, col } => {
+            state.cursor = talon_edit::line_col_offset(&state.buffer, line, col);
+            Ok("set_cursor_line_col".to_string())
+        }
+        TalonCommand::LineStart => {
+            (state.cursor, _) = talon_edit::line_bounds(&state.buffer, state.cursor);
+            Ok("line_start".to_string())
+        }
+        TalonCommand::LineEnd => {
+            (_, state.cursor) = talon_edit::line_bounds(&state.buffer, state.cursor);
+            Ok("line_end".to_string())
+        }
+        TalonCommand::GetState { buffer_range } => {
+            outputs.buffer_range = buffer_range;
+            Ok("get_state".to_string())
+        }
+        TalonCommand::Notify {
+            message,
+            timeout_ms,
+        } => {
+            let _ = message;
+            // No state change; record applied label for parity with the real TUI.
+            Ok(match timeout_ms.filter(|ms| *ms > 0) {
+                Some(ms) => format!("notify:{ms}"),
+                None => "notify".to_string(),
+            })
+        }
+        TalonCommand::EditPreviousMessage { steps_back } => {
+            let _ = steps_back;
+            if state.is_task_running {
+                return Err(CommandError::new(
+                    TalonErrorCode::TaskRunning,
+                    "cannot edit a previous message while a task is running",
+                ));
+            }
+            Ok("edit_previous_message".to_string())
+        }
+        TalonCommand::HistoryPrevious => Ok("history_previous".to_string()),
+        TalonCommand::HistoryNext => Ok("history_next".to_string()),
+        TalonCommand::Scroll {
+            direction,
+            amount,
+            count,
+        } => {
+            let _ = (direction, amount, count);
+            // There is no transcript to move; record the label for parity.
+            Ok("scroll".to_string())
+        }
+        TalonCommand::ReplaceRange { start, end, text } => {
+            let edit = talon_edit::replace_range(&state.buffer, start, end, &text)?;
+            state.buffer = edit.buffer;
+            state.cursor = edit.cursor;
+            Ok("replace_range".to_string())
+        }
+        TalonCommand::Replace { find, with, all } => {
+            let Some((edit, count)) = talon_edit::find_replace(&state.buffer, &find, &with, all)
+            else {
+                return Ok("replace:0".to_string());
+            };
+            state.buffer = edit.buffer;
+            state.cursor = edit.cursor;
+            Ok(format!("replace:{count}"))
+        }
+        TalonCommand::Indent { spaces, anchor } => {
+            let edit = talon_edit::indent_lines(&state.buffer, state.cursor, anchor, spaces)?;
+            state.buffer = edit.buffer;
+            state.cursor = edit.cursor;
+            Ok("indent".to_string())
+        }
+        TalonCommand::GetSelection { anchor } => {
+            outputs.selection = Some(talon_edit::selected_text(
+                &state.buffer,
+                state.cursor,
+                anchor,
+            ));
+            Ok("get_selection".to_string())
+        }
+        TalonCommand::Dedent { spaces, anchor } => {
+            let edit = talon_edit::dedent_lines(&state.buffer, state.cursor, anchor, spaces)?;
+            state.buffer = edit.buffer;
+            state.cursor = edit.cursor;
+            Ok("dedent".to_string())
+        }
+        TalonCommand::Transcribe { text, capitalize } => {
+            let edit = talon_edit::transcribe(&state.buffer, &text, capitalize);
+            state.buffer = edit.buffer;
+            state.cursor = edit.cursor;
+            Ok("transcribe".to_string())
+        }
+        TalonCommand::SearchBuffer { query, regex } => {
+            let matches = talon_edit::search_buffer(&state.buffer, &query, regex)?;
+            let label = format!("search_buffer:{}", matches.len());
+            outputs.matches = Some(matches);
+            Ok(label)
+        }
+        TalonCommand::GetDiff { max_bytes } => {
+            let Some(seeded) = state.diff.as_deref() else {
+                return Err(CommandError::new(
+                    TalonErrorCode::NotARepo,
+                    "simulated session directory is not inside a git repository",
+                ));
+            };
+            outputs.diff = Some(talon_edit::truncate_diff(
+                seeded,
+                max_bytes.unwrap_or(talon_edit::DEFAULT_DIFF_MAX_BYTES),
+            ));
+            Ok("get_diff".to_string())
+        }
+        TalonCommand::GetHistory { limit } => {
+            let entries = state
+                .history
+                .iter()
+                .rev()
+                .enumerate()
+                .take(limit.unwrap_or(usize::MAX))
+                .map(|(steps_back, text)| TalonHistoryEntry {
+                    steps_back,
+                    text: text.clone(),
+                })
+                .collect();
+            outputs.history = Some(entries);
+            Ok("get_history".to_string())
+        }
+        TalonCommand::CopyBuffer => {
+            if state.clipboard_unavailable {
+                return Err(CommandError::new(
+                    TalonErrorCode::ClipboardUnavailable,
+                    "clipboard unavailable: simulated",
+                ));
+            }
+            state.clipboard = state.buffer.clone();
+            Ok(format!("copy_buffer:{}", state.buffer.len()))
+        }
+        TalonCommand::Paste => {
+            if state.clipboard_unavailable {
+                return Err(CommandError::new(
+                    TalonErrorCode::ClipboardUnavailable,
+                    "clipboard unavailable: simulated",
+                ));
+            }
+            let edit = talon_edit::replace_range(
+                &state.buffer,
+                state.cursor,
+                state.cursor,
+                &state.clipboard,
+            )?;
+            state.buffer = edit.buffer;
+            state.cursor = edit.cursor;
+            Ok(format!("paste:{}", state.clipboard.len()))
+        }
+        TalonCommand::Approve { decision } => {
+            // There are no approval prompts to answer; record the decision.
+            let decision = match decision {
+                ApprovalDecision::Yes => "yes",
+                ApprovalDecision::No => "no",
+                ApprovalDecision::Always => "always",
+            };
+            Ok(format!("approve:{decision}"))
+        }
+        TalonCommand::GetTask => {
+            // The seeded details are reported as-is; elapsed time does not advance.
+            outputs.task = state.task.clone().filter(|_| state.is_task_running);
+            Ok("get_task".to_string())
+        }
+        TalonCommand::GetConfig => {
+            outputs.config = Some(state.config.clone());
+            Ok("get_config".to_string())
+        }
+        TalonCommand::LoadLastAssistant => {
+            let Some(text) = state.last_assistant.clone() else {
+                return Err(CommandError::new(
+                    TalonErrorCode::NoMessages,
+                    "there is no assistant message to load yet",
+                ));
+            };
+            state.cursor = text.len();
+            state.buffer = text;
+            Ok("load_last_assistant".to_string())
+        }
+        TalonCommand::Beep { tone } => {
+            let _ = tone;
+            // Nothing to hear in the simulator; record it like the real TUI.
+            Ok("beep".to_string())
+        }
+        // The simulator has no MCP servers; record it like the real TUI.
+        TalonCommand::ReloadMcp => Ok("reload_mcp".to_string()),
+        TalonCommand::RestartMcp { servers } => {
+            let _ = servers;
+            Ok("restart_mcp".to_string())
+        }
+    }
+}
+
+fn ensure_cursor(buffer: &str, cursor: usize) -> Result<(), CommandError> {
+    if buffer.is_char_boundary(cursor) {
+        Ok(())
+    } else {
+        Err(CommandError::new(
+            TalonErrorCode::InvalidCursor,
+            format!(
+                "cursor {cursor} is not a character boundary within the {}-byte buffer",
+                buffer.len()
+            ),
+        ))
+    }
+}
+
+/// Mirrors `talon::request_error_code` in the TUI.
+fn parse_error_code(err: &serde_json::Error) -> TalonErrorCode {
+    if err.classify() == serde_json::error::Category::Data
+        && err.to_string().contains("unknown variant")
+    {
+        TalonErrorCode::UnknownCommand
+    } else {
+        TalonErrorCode::ParseError
+    }
+}
+
 fn clamp_cursor(state: &mut TalonEditorState) {
     state.cursor = state.cursor.min(state.buffer.len());
 }
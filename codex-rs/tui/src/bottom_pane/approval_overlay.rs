@@ -250,6 +250,24 @@ impl BottomPaneView for ApprovalOverlay {
         self.done
     }
 
+    fn try_resolve_approval(&mut self, decision: ReviewDecision) -> bool {
+        if self.done || self.current_complete {
+            return false;
+        }
+        match self.current_variant.as_ref() {
+            Some(ApprovalVariant::Exec { id, command }) => {
+                self.handle_exec_decision(id, command, decision);
+            }
+            Some(ApprovalVariant::ApplyPatch { id }) => {
+                self.handle_patch_decision(id, decision);
+            }
+            None => return false,
+        }
+        self.current_complete = true;
+        self.advance_queue();
+        true
+    }
+
     fn try_consume_approval_request(
         &mut self,
         request: ApprovalRequest,
@@ -418,6 +436,31 @@ mod tests {
         assert!(view.is_complete());
     }
 
+    #[test]
+    fn resolve_approval_answers_current_request_then_queue() {
+        let (tx, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+        let mut view = ApprovalOverlay::new(make_exec_request(), tx);
+        view.enqueue_request(make_exec_request());
+
+        assert!(view.try_resolve_approval(ReviewDecision::ApprovedForSession));
+        assert!(!view.is_complete());
+        assert!(view.try_resolve_approval(ReviewDecision::Abort));
+        assert!(view.is_complete());
+        assert!(!view.try_resolve_approval(ReviewDecision::Approved));
+
+        let mut decisions = Vec::new();
+        while let Ok(ev) = rx.try_recv() {
+            if let AppEvent::CodexOp(Op::ExecApproval { decision, .. }) = ev {
+                decisions.push(decision);
+            }
+        }
+        assert_eq!(
+            decisions,
+            vec![ReviewDecision::ApprovedForSession, ReviewDecision::Abort]
+        );
+    }
+
     #[test]
     fn shortcut_triggers_selection() {
         let (tx, mut rx) = unbounded_channel::<AppEvent>();
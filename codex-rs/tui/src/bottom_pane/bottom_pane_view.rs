@@ -1,5 +1,6 @@
 use crate::bottom_pane::ApprovalRequest;
 use crate::render::renderable::Renderable;
+use codex_core::protocol::ReviewDecision;
 use crossterm::event::KeyEvent;
 use ratatui::layout::Rect;
 
@@ -40,4 +41,10 @@ pub(crate) trait BottomPaneView: Renderable {
     ) -> Option<ApprovalRequest> {
         Some(request)
     }
+
+    /// Resolve the approval request this view is showing with `decision`;
+    /// return `true` if there was one to resolve.
+    fn try_resolve_approval(&mut self, _decision: ReviewDecision) -> bool {
+        false
+    }
 }
@@ -108,6 +108,8 @@ pub(crate) struct ChatComposer {
     custom_prompts: Vec<CustomPrompt>,
     footer_mode: FooterMode,
     footer_hint_override: Option<Vec<(String, String)>>,
+    /// Message shown in place of the footer until the deadline passes.
+    flash: Option<(String, Instant)>,
     context_window_percent: Option<u8>,
 }
 
@@ -151,6 +153,7 @@ impl ChatComposer {
             custom_prompts: Vec::new(),
             footer_mode: FooterMode::ShortcutSummary,
             footer_hint_override: None,
+            flash: None,
             context_window_percent: None,
         };
         // Apply configuration via the setter to keep side-effects centralized.
@@ -220,6 +223,12 @@ impl ChatComposer {
         updated
     }
 
+    /// History entries already in memory, newest first, with their
+    /// `steps_back` index.
+    pub(crate) fn history_entries(&self, limit: Option<usize>) -> Vec<(usize, String)> {
+        self.history.known_entries(limit)
+    }
+
     fn layout_areas(&self, area: Rect) -> [Rect; 3] {
         let footer_props = self.footer_props();
         let footer_hint_height = self
@@ -348,6 +357,19 @@ impl ChatComposer {
         self.footer_hint_override = items;
     }
 
+    /// Show `message` in place of the footer for `duration`. The caller is
+    /// responsible for scheduling a redraw once it expires.
+    pub(crate) fn show_flash(&mut self, message: String, duration: Duration) {
+        self.flash = Some((message, Instant::now() + duration));
+    }
+
+    fn active_flash(&self) -> Option<&str> {
+        self.flash
+            .as_ref()
+            .filter(|(_, deadline)| Instant::now() < *deadline)
+            .map(|(message, _)| message.as_str())
+    }
+
     /// Replace the entire composer content with `text` and reset cursor.
     pub(crate) fn set_text_content(&mut self, text: String) {
         // Clear any existing content, placeholders, and attachments first.
@@ -1440,6 +1462,9 @@ impl ChatComposer {
     }
 
     fn custom_footer_height(&self) -> Option<u16> {
+        if self.active_flash().is_some() {
+            return Some(1);
+        }
         self.footer_hint_override
             .as_ref()
             .map(|items| if items.is_empty() { 0 } else { 1 })
@@ -1597,7 +1622,16 @@ impl WidgetRef for ChatComposer {
                 } else {
                     popup_rect
                 };
-                if let Some(items) = self.footer_hint_override.as_ref() {
+                if let Some(message) = self.active_flash() {
+                    let mut flash_rect = hint_rect;
+                    if flash_rect.width > 2 {
+                        flash_rect.x += 2;
+                        flash_rect.width = flash_rect.width.saturating_sub(2);
+                    }
+                    Line::from(message.to_string())
+                        .cyan()
+                        .render_ref(flash_rect, buf);
+                } else if let Some(items) = self.footer_hint_override.as_ref() {
                     if !items.is_empty() {
                         let mut spans = Vec::with_capacity(items.len() * 4);
                         for (idx, (key, label)) in items.iter().enumerate() {
@@ -1762,6 +1796,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn flash_replaces_footer_until_it_expires() {
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask Codex to do anything".to_string(),
+            false,
+        );
+        let area = Rect::new(0, 0, 40, 6);
+        let bottom_row = |composer: &ChatComposer| {
+            let mut buf = Buffer::empty(area);
+            composer.render_ref(area, &mut buf);
+            (0..area.width)
+                .map(|x| buf[(x, area.height - 1)].symbol().to_string())
+                .collect::<String>()
+        };
+
+        composer.show_flash("Saved draft".to_string(), Duration::from_secs(60));
+        let row = bottom_row(&composer);
+        assert!(row.contains("Saved draft"), "flash not shown: {row:?}");
+        assert!(
+            !row.contains("? for shortcuts"),
+            "footer not replaced: {row:?}"
+        );
+
+        composer.show_flash("Saved draft".to_string(), Duration::ZERO);
+        let row = bottom_row(&composer);
+        assert!(
+            row.contains("? for shortcuts"),
+            "footer not restored: {row:?}"
+        );
+    }
+
     fn snapshot_composer_state<F>(name: &str, enhanced_keys_supported: bool, setup: F)
     where
         F: FnOnce(&mut ChatComposer),
@@ -142,6 +142,27 @@ impl ChatComposerHistory {
         }
     }
 
+    /// Entries that are available without an async fetch, newest first, each
+    /// paired with the `steps_back` value that recalls it through
+    /// [`crate::bottom_pane::ChatComposer::history_edit_previous`]. At most
+    /// `limit` entries are returned when set.
+    pub fn known_entries(&self, limit: Option<usize>) -> Vec<(usize, String)> {
+        let total_entries = self.history_entry_count + self.local_history.len();
+        (0..total_entries)
+            .rev()
+            .filter_map(|global_idx| {
+                let text = if global_idx >= self.history_entry_count {
+                    self.local_history
+                        .get(global_idx - self.history_entry_count)
+                } else {
+                    self.fetched_history.get(&global_idx)
+                };
+                text.map(|text| (total_entries - 1 - global_idx, text.clone()))
+            })
+            .take(limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+
     /// Integrate a GetHistoryEntryResponse event.
     pub fn on_entry_response(
         &mut self,
@@ -297,4 +318,26 @@ mod tests {
 
         assert_eq!(Some("command3".into()), history.navigate_up(&tx));
     }
+
+    #[test]
+    fn known_entries_skips_unfetched_and_keeps_step_back_index() {
+        let mut history = ChatComposerHistory::new();
+        history.set_metadata(1, 3);
+        history.fetched_history.insert(1, "persisted".into());
+        history.record_local_submission("first");
+        history.record_local_submission("second");
+
+        assert_eq!(
+            history.known_entries(None),
+            vec![
+                (0, "second".to_string()),
+                (1, "first".to_string()),
+                (3, "persisted".to_string()),
+            ]
+        );
+        assert_eq!(
+            history.known_entries(Some(1)),
+            vec![(0, "second".to_string())]
+        );
+    }
 }
@@ -4,6 +4,7 @@ use std::path::PathBuf;
 use crate::app_event_sender::AppEventSender;
 use crate::tui::FrameRequester;
 use bottom_pane_view::BottomPaneView;
+use codex_core::protocol::ReviewDecision;
 use codex_file_search::FileMatch;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
@@ -277,6 +278,13 @@ impl BottomPane {
         self.request_redraw();
     }
 
+    /// Flash `message` in the footer, redrawing again once it expires.
+    pub(crate) fn show_flash(&mut self, message: String, duration: Duration) {
+        self.composer.show_flash(message, duration);
+        self.request_redraw();
+        self.request_redraw_in(duration);
+    }
+
     pub(crate) fn clear_composer_for_ctrl_c(&mut self) {
         self.composer.clear_for_ctrl_c();
         self.request_redraw();
@@ -315,6 +323,10 @@ impl BottomPane {
         changed
     }
 
+    pub(crate) fn history_entries(&self, limit: Option<usize>) -> Vec<(usize, String)> {
+        self.composer.history_entries(limit)
+    }
+
     /// Update the animated header shown to the left of the brackets in the
     /// status indicator (defaults to "Working"). No-ops if the status
     /// indicator is not active.
@@ -460,6 +472,23 @@ impl BottomPane {
         self.push_view(Box::new(modal));
     }
 
+    /// Resolve the approval request currently on screen with `decision`.
+    /// Returns `false` when no approval is pending.
+    pub(crate) fn resolve_pending_approval(&mut self, decision: ReviewDecision) -> bool {
+        let Some(view) = self.view_stack.last_mut() else {
+            return false;
+        };
+        if !view.try_resolve_approval(decision) {
+            return false;
+        }
+        if view.is_complete() {
+            self.view_stack.pop();
+            self.on_active_view_complete();
+        }
+        self.request_redraw();
+        true
+    }
+
     fn on_active_view_complete(&mut self) {
         self.resume_status_timer_after_modal();
     }
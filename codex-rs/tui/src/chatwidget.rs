@@ -2,6 +2,8 @@ use std::collections::HashMap;
 use std::collections::VecDeque;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 
 use codex_core::config::Config;
 use codex_core::config_types::Notifications;
@@ -32,6 +34,7 @@ use codex_core::protocol::McpToolCallEndEvent;
 use codex_core::protocol::Op;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::RateLimitSnapshot;
+use codex_core::protocol::ReviewDecision;
 use codex_core::protocol::ReviewRequest;
 use codex_core::protocol::StreamErrorEvent;
 use codex_core::protocol::TaskCompleteEvent;
@@ -81,6 +84,7 @@ use crate::diff_render::display_path_for;
 use crate::exec_cell::CommandOutput;
 use crate::exec_cell::ExecCell;
 use crate::exec_cell::new_active_exec_command;
+use crate::exec_command::strip_bash_lc_and_escape;
 use crate::get_git_diff::get_git_diff;
 use crate::history_cell;
 use crate::history_cell::AgentMessageCell;
@@ -130,6 +134,13 @@ struct RunningCommand {
     parsed_cmd: Vec<ParsedCommand>,
 }
 
+// Track the turn in progress, reported by Talon's `get_task`.
+pub(crate) struct RunningTask {
+    pub(crate) id: Option<String>,
+    pub(crate) started_at: Instant,
+    pub(crate) last_tool: Option<String>,
+}
+
 const RATE_LIMIT_WARNING_THRESHOLDS: [f64; 3] = [75.0, 90.0, 95.0];
 
 #[derive(Default)]
@@ -242,6 +253,7 @@ pub(crate) struct ChatWidget {
     // Stream lifecycle controller
     stream_controller: Option<StreamController>,
     running_commands: HashMap<String, RunningCommand>,
+    running_task: Option<RunningTask>,
     task_complete_pending: bool,
     // Queue of interruptive UI events deferred during an active write cycle
     interrupts: InterruptManager,
@@ -249,6 +261,8 @@ pub(crate) struct ChatWidget {
     reasoning_buffer: String,
     // Accumulates full reasoning content for transcript-only recording
     full_reasoning_buffer: String,
+    // Text of the most recent final assistant message, for Talon's `load_last_assistant`.
+    last_agent_message: Option<String>,
     // Current status header shown in the status indicator.
     current_status_header: String,
     // Previous status header to restore after a transient stream retry.
@@ -353,6 +367,7 @@ impl ChatWidget {
     }
 
     fn on_agent_message(&mut self, message: String) {
+        self.last_agent_message = Some(message.clone());
         // If we have a stream_controller, then the final agent message is redundant and will be a
         // duplicate of what has already been streamed.
         if self.stream_controller.is_none() {
@@ -406,9 +421,14 @@ impl ChatWidget {
 
     // Raw reasoning uses the same flow as summarized reasoning
 
-    fn on_task_started(&mut self) {
+    fn on_task_started(&mut self, id: Option<String>) {
         self.bottom_pane.clear_ctrl_c_quit_hint();
         self.bottom_pane.set_task_running(true);
+        self.running_task = Some(RunningTask {
+            id,
+            started_at: Instant::now(),
+            last_tool: None,
+        });
         self.retry_status_header = None;
         self.set_status_header(String::from("Working"));
         self.full_reasoning_buffer.clear();
@@ -422,6 +442,7 @@ impl ChatWidget {
         // Mark task stopped and request redraw now that all content is in history.
         self.bottom_pane.set_task_running(false);
         self.running_commands.clear();
+        self.running_task = None;
         self.request_redraw();
 
         // If there is a queued user message, send exactly one now to begin the next turn.
@@ -484,6 +505,7 @@ impl ChatWidget {
         // Reset running state and clear streaming buffers.
         self.bottom_pane.set_task_running(false);
         self.running_commands.clear();
+        self.running_task = None;
         self.stream_controller = None;
     }
 
@@ -558,6 +580,7 @@ impl ChatWidget {
 
     fn on_exec_command_begin(&mut self, ev: ExecCommandBeginEvent) {
         self.flush_answer_stream_with_separator();
+        self.set_last_tool(strip_bash_lc_and_escape(&ev.command));
         let ev2 = ev.clone();
         self.defer_or_handle(|q| q.push_exec_begin(ev), |s| s.handle_exec_begin_now(ev2));
     }
@@ -570,6 +593,13 @@ impl ChatWidget {
     }
 
     fn on_patch_apply_begin(&mut self, event: PatchApplyBeginEvent) {
+        let mut files: Vec<String> = event
+            .changes
+            .keys()
+            .map(|path| display_path_for(path, &self.config.cwd))
+            .collect();
+        files.sort();
+        self.set_last_tool(format!("apply_patch {}", files.join(", ")));
         self.add_to_history(history_cell::new_patch_event(
             event.changes,
             &self.config.cwd,
@@ -599,6 +629,7 @@ impl ChatWidget {
     }
 
     fn on_mcp_tool_call_begin(&mut self, ev: McpToolCallBeginEvent) {
+        self.set_last_tool(format!("{}.{}", ev.invocation.server, ev.invocation.tool));
         let ev2 = ev.clone();
         self.defer_or_handle(|q| q.push_mcp_begin(ev), |s| s.handle_mcp_begin_now(ev2));
     }
@@ -610,6 +641,13 @@ impl ChatWidget {
 
     fn on_web_search_begin(&mut self, _ev: WebSearchBeginEvent) {
         self.flush_answer_stream_with_separator();
+        self.set_last_tool("web_search".to_string());
+    }
+
+    fn set_last_tool(&mut self, tool: String) {
+        if let Some(task) = self.running_task.as_mut() {
+            task.last_tool = Some(tool);
+        }
     }
 
     fn on_web_search_end(&mut self, ev: WebSearchEndEvent) {
@@ -951,10 +989,12 @@ impl ChatWidget {
             rate_limit_warnings: RateLimitWarningState::default(),
             stream_controller: None,
             running_commands: HashMap::new(),
+            running_task: None,
             task_complete_pending: false,
             interrupts: InterruptManager::new(),
             reasoning_buffer: String::new(),
             full_reasoning_buffer: String::new(),
+            last_agent_message: None,
             current_status_header: String::from("Working"),
             retry_status_header: None,
             conversation_id: None,
@@ -1018,10 +1058,12 @@ impl ChatWidget {
             rate_limit_warnings: RateLimitWarningState::default(),
             stream_controller: None,
             running_commands: HashMap::new(),
+            running_task: None,
             task_complete_pending: false,
             interrupts: InterruptManager::new(),
             reasoning_buffer: String::new(),
             full_reasoning_buffer: String::new(),
+            last_agent_message: None,
             current_status_header: String::from("Working"),
             retry_status_header: None,
             conversation_id: None,
@@ -1448,7 +1490,7 @@ impl ChatWidget {
                 self.on_agent_reasoning_final()
             }
             EventMsg::AgentReasoningSectionBreak(_) => self.on_reasoning_section_break(),
-            EventMsg::TaskStarted(_) => self.on_task_started(),
+            EventMsg::TaskStarted(_) => self.on_task_started(id),
             EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
                 self.on_task_complete(last_agent_message)
             }
@@ -2055,6 +2097,10 @@ impl ChatWidget {
         self.bottom_pane.set_composer_cursor(pos);
     }
 
+    pub(crate) fn show_flash(&mut self, message: String, duration: Duration) {
+        self.bottom_pane.show_flash(message, duration);
+    }
+
     pub(crate) fn show_esc_backtrack_hint(&mut self) {
         self.bottom_pane.show_esc_backtrack_hint();
     }
@@ -2297,6 +2343,18 @@ impl ChatWidget {
         self.bottom_pane.is_task_running()
     }
 
+    pub(crate) fn running_task(&self) -> Option<&RunningTask> {
+        self.running_task.as_ref()
+    }
+
+    pub(crate) fn status_header(&self) -> &str {
+        &self.current_status_header
+    }
+
+    pub(crate) fn resolve_pending_approval(&mut self, decision: ReviewDecision) -> bool {
+        self.bottom_pane.resolve_pending_approval(decision)
+    }
+
     pub(crate) fn history_previous(&mut self) -> bool {
         self.bottom_pane.history_previous()
     }
@@ -2308,6 +2366,14 @@ impl ChatWidget {
     pub(crate) fn history_edit_previous(&mut self, steps_back: usize) -> bool {
         self.bottom_pane.history_edit_previous(steps_back)
     }
+
+    pub(crate) fn history_entries(&self, limit: Option<usize>) -> Vec<(usize, String)> {
+        self.bottom_pane.history_entries(limit)
+    }
+
+    pub(crate) fn last_agent_message(&self) -> Option<&str> {
+        self.last_agent_message.as_deref()
+    }
 }
 
 impl WidgetRef for &ChatWidget {
@@ -275,10 +275,12 @@ fn make_chatwidget_manual() -> (
         rate_limit_warnings: RateLimitWarningState::default(),
         stream_controller: None,
         running_commands: HashMap::new(),
+        running_task: None,
         task_complete_pending: false,
         interrupts: InterruptManager::new(),
         reasoning_buffer: String::new(),
         full_reasoning_buffer: String::new(),
+        last_agent_message: None,
         current_status_header: String::from("Working"),
         retry_status_header: None,
         conversation_id: None,
@@ -654,7 +656,7 @@ fn enqueueing_history_prompt_multiple_times_is_stable() {
 fn streaming_final_answer_keeps_task_running_state() {
     let (mut chat, _rx, mut op_rx) = make_chatwidget_manual();
 
-    chat.on_task_started();
+    chat.on_task_started(None);
     chat.on_agent_message_delta("Final answer line\n".to_string());
     chat.on_commit_tick();
 
@@ -2196,6 +2198,23 @@ fn multiple_agent_messages_in_single_turn_emit_multiple_headers() {
     assert!(first_idx < second_idx, "messages out of order: {combined}");
 }
 
+#[test]
+fn last_agent_message_tracks_latest_final_message() {
+    let (mut chat, _rx, _op_rx) = make_chatwidget_manual();
+    assert_eq!(chat.last_agent_message(), None);
+
+    for message in ["First message", "Second message"] {
+        chat.handle_codex_event(Event {
+            id: "s1".into(),
+            msg: EventMsg::AgentMessage(AgentMessageEvent {
+                message: message.into(),
+            }),
+        });
+    }
+
+    assert_eq!(chat.last_agent_message(), Some("Second message"));
+}
+
 #[test]
 fn final_reasoning_then_message_without_deltas_are_rendered() {
     let (mut chat, mut rx, _op_rx) = make_chatwidget_manual();
@@ -2452,3 +2471,34 @@ printf 'fenced within fenced\n'
 
     assert_snapshot!(term.backend().vt100().screen().contents());
 }
+
+#[test]
+fn running_task_tracks_id_and_last_tool_until_complete() {
+    let (mut chat, _rx, _op_rx) = make_chatwidget_manual();
+    assert!(chat.running_task().is_none());
+
+    chat.handle_codex_event(Event {
+        id: "task-1".into(),
+        msg: EventMsg::TaskStarted(TaskStartedEvent {
+            model_context_window: None,
+        }),
+    });
+    let task = chat.running_task().expect("task should be tracked");
+    assert_eq!(task.id.as_deref(), Some("task-1"));
+    assert_eq!(task.last_tool, None);
+
+    begin_exec(&mut chat, "call-1", "cargo test");
+    assert_eq!(
+        chat.running_task()
+            .and_then(|task| task.last_tool.as_deref()),
+        Some("cargo test")
+    );
+
+    chat.handle_codex_event(Event {
+        id: "task-1".into(),
+        msg: EventMsg::TaskComplete(TaskCompleteEvent {
+            last_agent_message: None,
+        }),
+    });
+    assert!(chat.running_task().is_none());
+}
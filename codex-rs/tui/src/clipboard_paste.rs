@@ -142,6 +142,37 @@ pub fn paste_image_to_temp_png() -> Result<(PathBuf, PastedImageInfo), PasteImag
     ))
 }
 
+/// Place `text` on the system clipboard. Errors describe why the clipboard
+/// could not be reached.
+#[cfg(not(target_os = "android"))]
+pub fn copy_text_to_clipboard(text: &str) -> Result<(), String> {
+    let mut cb = arboard::Clipboard::new().map_err(|e| e.to_string())?;
+    cb.set_text(text).map_err(|e| e.to_string())
+}
+
+#[cfg(target_os = "android")]
+pub fn copy_text_to_clipboard(_text: &str) -> Result<(), String> {
+    Err("clipboard access is unsupported on Android".into())
+}
+
+/// Read text from the system clipboard. An empty clipboard, or one holding
+/// no text, yields an empty string; errors describe why the clipboard could
+/// not be reached.
+#[cfg(not(target_os = "android"))]
+pub fn read_text_from_clipboard() -> Result<String, String> {
+    let mut cb = arboard::Clipboard::new().map_err(|e| e.to_string())?;
+    match cb.get_text() {
+        Ok(text) => Ok(text),
+        Err(arboard::Error::ContentNotAvailable) => Ok(String::new()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(target_os = "android")]
+pub fn read_text_from_clipboard() -> Result<String, String> {
+    Err("clipboard access is unsupported on Android".into())
+}
+
 /// Normalize pasted text that may represent a filesystem path.
 ///
 /// Supports:
@@ -15,15 +15,25 @@ use tokio::process::Command;
 /// * `bool` – Whether the current working directory is inside a Git repo.
 /// * `String` – The concatenated diff (may be empty).
 pub(crate) async fn get_git_diff() -> io::Result<(bool, String)> {
+    collect_git_diff(Path::new("."), "--color").await
+}
+
+/// Like [`get_git_diff`], but for the repository containing `cwd` and without
+/// ANSI color codes.
+pub(crate) async fn get_plain_git_diff(cwd: &Path) -> io::Result<(bool, String)> {
+    collect_git_diff(cwd, "--no-color").await
+}
+
+async fn collect_git_diff(cwd: &Path, color_flag: &str) -> io::Result<(bool, String)> {
     // First check if we are inside a Git repository.
-    if !inside_git_repo().await? {
+    if !inside_git_repo(cwd).await? {
         return Ok((false, String::new()));
     }
 
     // Run tracked diff and untracked file listing in parallel.
     let (tracked_diff_res, untracked_output_res) = tokio::join!(
-        run_git_capture_diff(&["diff", "--color"]),
-        run_git_capture_stdout(&["ls-files", "--others", "--exclude-standard"]),
+        run_git_capture_diff(cwd, &["diff", color_flag]),
+        run_git_capture_stdout(cwd, &["ls-files", "--others", "--exclude-standard"]),
     );
     let tracked_diff = tracked_diff_res?;
     let untracked_output = untracked_output_res?;
@@ -44,9 +54,11 @@ pub(crate) async fn get_git_diff() -> io::Result<(bool, String)> {
     {
         let null_path = null_path.clone();
         let file = file.to_string();
+        let cwd = cwd.to_path_buf();
+        let color_flag = color_flag.to_string();
         join_set.spawn(async move {
-            let args = ["diff", "--color", "--no-index", "--", &null_path, &file];
-            run_git_capture_diff(&args).await
+            let args = ["diff", &color_flag, "--no-index", "--", &null_path, &file];
+            run_git_capture_diff(&cwd, &args).await
         });
     }
     while let Some(res) = join_set.join_next().await {
@@ -63,9 +75,10 @@ pub(crate) async fn get_git_diff() -> io::Result<(bool, String)> {
 
 /// Helper that executes `git` with the given `args` and returns `stdout` as a
 /// UTF-8 string. Any non-zero exit status is considered an *error*.
-async fn run_git_capture_stdout(args: &[&str]) -> io::Result<String> {
+async fn run_git_capture_stdout(cwd: &Path, args: &[&str]) -> io::Result<String> {
     let output = Command::new("git")
         .args(args)
+        .current_dir(cwd)
         .stdout(Stdio::piped())
         .stderr(Stdio::null())
         .output()
@@ -83,9 +96,10 @@ async fn run_git_capture_stdout(args: &[&str]) -> io::Result<String> {
 
 /// Like [`run_git_capture_stdout`] but treats exit status 1 as success and
 /// returns stdout. Git returns 1 for diffs when differences are present.
-async fn run_git_capture_diff(args: &[&str]) -> io::Result<String> {
+async fn run_git_capture_diff(cwd: &Path, args: &[&str]) -> io::Result<String> {
     let output = Command::new("git")
         .args(args)
+        .current_dir(cwd)
         .stdout(Stdio::piped())
         .stderr(Stdio::null())
         .output()
@@ -101,10 +115,11 @@ async fn run_git_capture_diff(args: &[&str]) -> io::Result<String> {
     }
 }
 
-/// Determine if the current directory is inside a Git repository.
-async fn inside_git_repo() -> io::Result<bool> {
+/// Determine if `cwd` is inside a Git repository.
+async fn inside_git_repo(cwd: &Path) -> io::Result<bool> {
     let status = Command::new("git")
         .args(["rev-parse", "--is-inside-work-tree"])
+        .current_dir(cwd)
         .stdout(Stdio::null())
         .stderr(Stdio::null())
         .status()
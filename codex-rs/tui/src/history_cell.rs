@@ -20,10 +20,11 @@ use crate::wrapping::RtOptions;
 use crate::wrapping::word_wrap_line;
 use crate::wrapping::word_wrap_lines;
 use base64::Engine;
-use codex_common::format_env_display::format_env_display;
+use codex_common::format_env_display::format_env_display_masked;
 use codex_core::config::Config;
 use codex_core::config_types::McpServerTransportConfig;
 use codex_core::config_types::ReasoningSummaryFormat;
+use codex_core::mcp::env_mask::EnvMaskPatterns;
 use codex_core::protocol::FileChange;
 use codex_core::protocol::McpAuthStatus;
 use codex_core::protocol::McpInvocation;
@@ -1000,6 +1001,7 @@ pub(crate) fn new_mcp_tools_output(
         return PlainHistoryCell { lines };
     }
 
+    let env_mask = EnvMaskPatterns::new(&config.mcp_env_mask_patterns);
     for (server, cfg) in config.mcp_servers.iter() {
         let prefix = format!("{server}__");
         let mut names: Vec<String> = tools
@@ -1014,10 +1016,16 @@ pub(crate) fn new_mcp_tools_output(
             .copied()
             .unwrap_or(McpAuthStatus::Unsupported);
         lines.push(vec!["  • Server: ".into(), server.clone().into()].into());
-        let status_line = if cfg.enabled {
-            vec!["    • Status: ".into(), "enabled".green()].into()
-        } else {
+        let status_line = if !cfg.enabled {
             vec!["    • Status: ".into(), "disabled".red()].into()
+        } else if !cfg.is_effectively_enabled() {
+            vec![
+                "    • Status: ".into(),
+                "inactive (enabled_when not met)".dim(),
+            ]
+            .into()
+        } else {
+            vec!["    • Status: ".into(), "enabled".green()].into()
         };
         lines.push(status_line);
         lines.push(vec!["    • Auth: ".into(), status.to_string().into()].into());
@@ -1029,6 +1037,7 @@ pub(crate) fn new_mcp_tools_output(
                 env,
                 env_vars,
                 cwd,
+                ..
             } => {
                 let args_suffix = if args.is_empty() {
                     String::new()
@@ -1042,18 +1051,24 @@ pub(crate) fn new_mcp_tools_output(
                     lines.push(vec!["    • Cwd: ".into(), cwd.display().to_string().into()].into());
                 }
 
-                let env_display = format_env_display(env.as_ref(), env_vars);
+                let env_display =
+                    format_env_display_masked(env.as_ref(), env_vars, None, &env_mask);
                 if env_display != "-" {
                     lines.push(vec!["    • Env: ".into(), env_display.into()].into());
                 }
             }
             McpServerTransportConfig::StreamableHttp {
                 url,
+                url_env_var,
                 http_headers,
                 env_http_headers,
                 ..
             } => {
-                lines.push(vec!["    • URL: ".into(), url.clone().into()].into());
+                let url_display = match url_env_var {
+                    Some(env_var) => format!("${env_var}"),
+                    None => url.clone(),
+                };
+                lines.push(vec!["    • URL: ".into(), url_display.into()].into());
                 if let Some(headers) = http_headers.as_ref()
                     && !headers.is_empty()
                 {
@@ -1081,8 +1096,12 @@ pub(crate) fn new_mcp_tools_output(
             }
         }
 
-        if !cfg.enabled {
-            let disabled = "(disabled)".red();
+        if !cfg.is_effectively_enabled() {
+            let disabled = if cfg.enabled {
+                "(inactive)".dim()
+            } else {
+                "(disabled)".red()
+            };
             lines.push(vec!["    • Tools: ".into(), disabled.clone()].into());
             lines.push(vec!["    • Resources: ".into(), disabled.clone()].into());
             lines.push(vec!["    • Resource templates: ".into(), disabled].into());
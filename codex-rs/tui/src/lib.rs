@@ -30,6 +30,7 @@ mod app;
 mod app_backtrack;
 mod app_event;
 mod app_event_sender;
+mod app_talon;
 mod ascii_animation;
 mod bottom_pane;
 mod chatwidget;
@@ -65,6 +66,7 @@ mod status_indicator_widget;
 mod streaming;
 mod style;
 mod talon;
+pub mod talon_edit;
 mod terminal_palette;
 mod text_formatting;
 mod tui;
@@ -119,6 +121,8 @@ pub use markdown_render::render_markdown_text;
 pub use public_widgets::composer_input::ComposerAction;
 pub use public_widgets::composer_input::ComposerInput;
 use std::io::Write as _;
+pub use talon::RequestFileFormat as TalonRequestFileFormat;
+pub use talon::validate_request_json as validate_talon_request_json;
 
 // (tests access modules directly within the crate)
 
@@ -62,6 +62,39 @@ impl Overlay {
             Overlay::Static(o) => o.is_done(),
         }
     }
+
+    /// Number of rows a single page scroll moves in the current viewport.
+    pub(crate) fn page_height(&self, tui: &tui::Tui) -> usize {
+        self.view().page_height(tui.terminal.viewport_area)
+    }
+
+    /// Scroll up by `rows`, stopping at the top of the content.
+    pub(crate) fn scroll_up(&mut self, tui: &mut tui::Tui, rows: usize) {
+        let area = tui.terminal.viewport_area;
+        self.view_mut().scroll_up(area, rows);
+        tui.frame_requester().schedule_frame();
+    }
+
+    /// Scroll down by `rows`, stopping at the bottom of the content.
+    pub(crate) fn scroll_down(&mut self, tui: &mut tui::Tui, rows: usize) {
+        let area = tui.terminal.viewport_area;
+        self.view_mut().scroll_down(area, rows);
+        tui.frame_requester().schedule_frame();
+    }
+
+    fn view(&self) -> &PagerView {
+        match self {
+            Overlay::Transcript(o) => &o.view,
+            Overlay::Static(o) => &o.view,
+        }
+    }
+
+    fn view_mut(&mut self) -> &mut PagerView {
+        match self {
+            Overlay::Transcript(o) => &mut o.view,
+            Overlay::Static(o) => &mut o.view,
+        }
+    }
 }
 
 const KEY_UP: KeyBinding = key_hint::plain(KeyCode::Up);
@@ -273,6 +306,32 @@ impl PagerView {
         area.height = area.height.saturating_sub(2);
         area
     }
+
+    fn page_height(&self, area: Rect) -> usize {
+        self.content_area(area).height as usize
+    }
+
+    /// Largest valid scroll offset for `area`. `scroll_offset` may hold
+    /// `usize::MAX` to pin to the bottom, so programmatic scrolls clamp first.
+    fn max_scroll(&self, area: Rect) -> usize {
+        let content_area = self.content_area(area);
+        self.content_height(content_area.width)
+            .saturating_sub(content_area.height as usize)
+    }
+
+    fn scroll_up(&mut self, area: Rect, rows: usize) {
+        let max_scroll = self.max_scroll(area);
+        self.scroll_offset = self.scroll_offset.min(max_scroll).saturating_sub(rows);
+    }
+
+    fn scroll_down(&mut self, area: Rect, rows: usize) {
+        let max_scroll = self.max_scroll(area);
+        self.scroll_offset = self
+            .scroll_offset
+            .min(max_scroll)
+            .saturating_add(rows)
+            .min(max_scroll);
+    }
 }
 
 impl PagerView {
@@ -1,18 +1,43 @@
+use std::fmt;
 use std::fs;
 use std::io;
+use std::path::Path;
 use std::path::PathBuf;
+use std::process::Stdio;
 use std::sync::Mutex;
+use std::time::Duration;
 use std::time::SystemTime;
 use std::time::UNIX_EPOCH;
 
 use anyhow::Context;
 use anyhow::Result;
+use codex_core::protocol::ReviewDecision;
 use serde::Deserialize;
 use serde::Serialize;
 
+use crate::talon_edit::TalonBufferWindow;
+use crate::talon_edit::TalonDiff;
+
 const TALON_DIR_NAME: &str = ".codex-talon";
 const REQUEST_FILENAME: &str = "request.json";
 const RESPONSE_FILENAME: &str = "response.json";
+const FAILED_REQUEST_FILENAME: &str = "request.failed.json";
+
+/// Set to `1` to keep the most recent request that failed as
+/// `request.failed.json` instead of deleting it.
+const KEEP_FAILED_ENV_VAR: &str = "CODEX_TALON_KEEP_FAILED";
+
+/// Program `beep` runs instead of ringing the terminal bell, with the
+/// requested tone (if any) as its only argument.
+const BEEP_COMMAND_ENV_VAR: &str = "CODEX_TALON_BEEP_COMMAND";
+
+/// How long a `notify` flash stays up when the request gives no timeout.
+pub(crate) const DEFAULT_NOTIFY_TIMEOUT_MS: u64 = 3000;
+
+/// Extra reads `read_request` makes when the request file is not valid JSON,
+/// in case it caught a write in progress.
+const REQUEST_PARSE_RETRIES: u32 = 2;
+const REQUEST_PARSE_RETRY_DELAY: Duration = Duration::from_millis(10);
 
 static STATUS_SUMMARY: Mutex<Option<String>> = Mutex::new(None);
 
@@ -20,6 +45,9 @@ static STATUS_SUMMARY: Mutex<Option<String>> = Mutex::new(None);
 pub(crate) struct TalonPaths {
     pub request_path: PathBuf,
     pub response_path: PathBuf,
+    /// Where a failed request is moved when `keep_failed` is set.
+    pub failed_request_path: PathBuf,
+    pub keep_failed: bool,
 }
 
 pub(crate) fn resolve_paths() -> Result<TalonPaths> {
@@ -31,18 +59,45 @@ pub(crate) fn resolve_paths() -> Result<TalonPaths> {
 
     let request_path = base_dir.join(REQUEST_FILENAME);
     let response_path = base_dir.join(RESPONSE_FILENAME);
+    let failed_request_path = base_dir.join(FAILED_REQUEST_FILENAME);
+    let keep_failed = std::env::var(KEEP_FAILED_ENV_VAR).is_ok_and(|value| value == "1");
 
     Ok(TalonPaths {
         request_path,
         response_path,
+        failed_request_path,
+        keep_failed,
     })
 }
 
+/// Start the cue configured with `CODEX_TALON_BEEP_COMMAND` without waiting
+/// for it. Returns false when none is configured or it fails to start, so the
+/// caller can fall back to the terminal bell.
+pub(crate) fn play_beep_command(tone: Option<&str>) -> bool {
+    let Some(program) = std::env::var_os(BEEP_COMMAND_ENV_VAR).filter(|value| !value.is_empty())
+    else {
+        return false;
+    };
+    tokio::process::Command::new(program)
+        .args(tone)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .is_ok()
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub(crate) struct TalonRequest {
     #[serde(default)]
     pub commands: Vec<TalonCommand>,
+    /// Only apply the commands if `state.state_version` still has this value.
+    #[serde(default)]
+    pub expected_version: Option<u64>,
+    /// Only apply the commands if the cursor is still at this byte offset.
+    #[serde(default)]
+    pub expected_cursor: Option<usize>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -56,10 +111,33 @@ pub(crate) enum TalonCommand {
     },
     /// Move the cursor to the provided absolute byte offset within the buffer.
     SetCursor { cursor: usize },
+    /// Move the cursor to the start or end of the buffer, so clients need
+    /// not know its length. The `applied` label is `cursor_to:<position>`.
+    CursorTo { position: TalonCursorPosition },
+    /// Move the cursor to a 1-based line and character column, clamping to
+    /// the last line and the end of the line.
+    SetCursorLineCol { line: usize, col: usize },
+    /// Move the cursor to the start of the line it is on.
+    LineStart,
+    /// Move the cursor to the end of the line it is on, before its newline.
+    LineEnd,
     /// No-op request that asks Codex to write its current state snapshot.
-    GetState,
-    /// Post a lightweight notification (no buffer/cursor change).
-    Notify { message: String },
+    /// With `buffer_range: [start, end]`, `state.buffer` holds only that
+    /// byte window (clamped to character boundaries) and `state.buffer_window`
+    /// reports the window and the full buffer length.
+    GetState {
+        #[serde(default)]
+        buffer_range: Option<[usize; 2]>,
+    },
+    /// Flash `message` in the footer for `timeout_ms` milliseconds (zero or
+    /// absent uses [`DEFAULT_NOTIFY_TIMEOUT_MS`]) and post a desktop
+    /// notification when the terminal is unfocused. No buffer/cursor change.
+    /// The `applied` label is `notify:<ms>` when a timeout is given.
+    Notify {
+        message: String,
+        #[serde(default)]
+        timeout_ms: Option<u64>,
+    },
     /// Trigger editing of a previous user message (forks the session and prefills the composer).
     EditPreviousMessage {
         #[serde(default)]
@@ -69,6 +147,197 @@ pub(crate) enum TalonCommand {
     HistoryPrevious,
     /// Navigate to the next entry in the composer history.
     HistoryNext,
+    /// Scroll the transcript view, opening it first if it is not already shown.
+    /// Scrolling past either end clamps to the first/last line.
+    Scroll {
+        direction: TalonScrollDirection,
+        amount: TalonScrollAmount,
+        #[serde(default = "default_scroll_count")]
+        count: u32,
+    },
+    /// Replace the byte range `start..end` of the buffer with `text`, leaving
+    /// the cursor after the inserted text.
+    ReplaceRange {
+        start: usize,
+        end: usize,
+        text: String,
+    },
+    /// Replace the first occurrence of `find` (every occurrence when `all` is
+    /// set) with `with`, leaving the cursor after the last replacement. The
+    /// `applied` label reports the count (`replace:<n>`), which is zero when
+    /// `find` does not occur.
+    Replace {
+        find: String,
+        with: String,
+        #[serde(default)]
+        all: bool,
+    },
+    /// Indent every line spanned by the cursor and `anchor` with `spaces`
+    /// spaces. The composer has no selection of its own, so `anchor` is the
+    /// byte offset of the selection's other end; without it only the cursor's
+    /// line is indented. A selection ending at the start of a line excludes
+    /// that line.
+    Indent {
+        spaces: usize,
+        #[serde(default)]
+        anchor: Option<usize>,
+    },
+    /// Return the text between the cursor and `anchor`, the selection
+    /// `Indent` works on, in `TalonResponse.selection`. It is null when
+    /// `anchor` is absent or equals the cursor. Ends that fall inside a
+    /// character are moved back to its start. Buffer and cursor are
+    /// unchanged.
+    GetSelection {
+        #[serde(default)]
+        anchor: Option<usize>,
+    },
+    /// Remove up to `spaces` leading spaces from the lines `Indent` would
+    /// select. Lines with less indentation lose only what they have; tabs and
+    /// other characters are left alone.
+    Dedent {
+        spaces: usize,
+        #[serde(default)]
+        anchor: Option<usize>,
+    },
+    /// Append dictated `text` to the end of the buffer with smart spacing
+    /// (see `talon_edit::transcribe`), leaving the cursor after it. With
+    /// `capitalize`, text that starts a sentence is upper-cased.
+    Transcribe {
+        text: String,
+        #[serde(default)]
+        capitalize: bool,
+    },
+    /// Return the byte ranges of the non-overlapping occurrences of `query`
+    /// in `TalonResponse.matches`, treating `query` as a regular expression
+    /// when `regex` is set. The `applied` label reports the count
+    /// (`search_buffer:<n>`). Buffer and cursor are unchanged.
+    SearchBuffer {
+        query: String,
+        #[serde(default)]
+        regex: bool,
+    },
+    /// Return the working-tree diff of the session directory, capped to
+    /// `max_bytes` (defaults to `talon_edit::DEFAULT_DIFF_MAX_BYTES`).
+    GetDiff {
+        #[serde(default)]
+        max_bytes: Option<usize>,
+    },
+    /// Return composer history entries (newest first) with the `steps_back`
+    /// value that recalls each through `edit_previous_message`. Only entries
+    /// Codex already holds in memory are listed.
+    GetHistory {
+        #[serde(default)]
+        limit: Option<usize>,
+    },
+    /// Copy the composer buffer to the system clipboard. The `applied` label
+    /// reports the number of bytes copied (`copy_buffer:<len>`).
+    CopyBuffer,
+    /// Insert the system clipboard's text at the cursor, leaving the cursor
+    /// after it. The `applied` label reports the number of bytes inserted
+    /// (`paste:<len>`), which is zero when the clipboard holds no text.
+    Paste,
+    /// Answer the approval request currently shown in the TUI. The `applied`
+    /// label is `approve:<decision>`.
+    Approve { decision: TalonApprovalDecision },
+    /// Return details of the running task in `TalonResponse.task`, which is
+    /// left out when Codex is idle.
+    GetTask,
+    /// Return the session's model, approval policy, and sandbox mode in
+    /// `TalonResponse.config`.
+    GetConfig,
+    /// Replace the buffer with the text of the most recent assistant message
+    /// and move the cursor to its end.
+    LoadLastAssistant,
+    /// Audible confirmation for users who are not watching the screen: run
+    /// the program named by `CODEX_TALON_BEEP_COMMAND` with `tone` as its
+    /// argument, or ring the terminal bell. No buffer/cursor change.
+    Beep {
+        #[serde(default)]
+        tone: Option<String>,
+    },
+    /// Re-read the MCP server settings from `config.toml` and restart only
+    /// the servers whose definition changed. The reload finishes after the
+    /// response is written; its summary appears in the transcript.
+    ReloadMcp,
+    /// Stop the named MCP servers and start them again; an empty or missing
+    /// `servers` restarts every running server. Like `reload_mcp`, the
+    /// restart finishes after the response is written and its summary, with
+    /// each server's tool count, appears in the transcript.
+    RestartMcp {
+        #[serde(default)]
+        servers: Vec<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum TalonApprovalDecision {
+    /// Run the command or apply the patch once.
+    Yes,
+    /// Decline and stop so the user can tell Codex what to do instead.
+    No,
+    /// Approve, and do not ask again for this command during the session.
+    Always,
+}
+
+impl TalonApprovalDecision {
+    pub(crate) fn review_decision(self) -> ReviewDecision {
+        match self {
+            TalonApprovalDecision::Yes => ReviewDecision::Approved,
+            TalonApprovalDecision::No => ReviewDecision::Abort,
+            TalonApprovalDecision::Always => ReviewDecision::ApprovedForSession,
+        }
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            TalonApprovalDecision::Yes => "yes",
+            TalonApprovalDecision::No => "no",
+            TalonApprovalDecision::Always => "always",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum TalonCursorPosition {
+    Start,
+    End,
+}
+
+impl TalonCursorPosition {
+    /// Byte offset of this position in `buffer`.
+    pub(crate) fn offset(self, buffer: &str) -> usize {
+        match self {
+            TalonCursorPosition::Start => 0,
+            TalonCursorPosition::End => buffer.len(),
+        }
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            TalonCursorPosition::Start => "start",
+            TalonCursorPosition::End => "end",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum TalonScrollDirection {
+    Up,
+    Down,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum TalonScrollAmount {
+    Line,
+    Page,
+}
+
+const fn default_scroll_count() -> u32 {
+    1
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -79,10 +348,48 @@ pub(crate) enum TalonResponseStatus {
     Error,
 }
 
+/// Machine-readable failure category reported alongside `TalonResponse.error`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum TalonErrorCode {
+    /// The request file is not valid JSON or does not match the request
+    /// schema, or a `search_buffer` regex does not compile.
+    ParseError,
+    /// The request names a command `type` this build does not understand.
+    UnknownCommand,
+    /// A cursor offset is past the end of the buffer or inside a character.
+    InvalidCursor,
+    /// A `start..end` range is reversed, past the end of the buffer, or splits a character.
+    InvalidRange,
+    /// The command cannot run while Codex is working on a task.
+    TaskRunning,
+    /// The session directory is not inside a git repository.
+    NotARepo,
+    /// A git invocation failed.
+    GitFailed,
+    /// The system clipboard could not be reached (e.g. no display server).
+    ClipboardUnavailable,
+    /// `approve` was sent while no approval request was waiting for an answer.
+    NoPendingApproval,
+    /// The request's `expected_version` or `expected_cursor` does not match
+    /// the current state; none of its commands were applied.
+    VersionConflict,
+    /// `load_last_assistant` was sent before the assistant replied in this session.
+    NoMessages,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub(crate) struct TalonEditorState {
     pub buffer: String,
+    /// Byte offset of the cursor in `buffer`.
     pub cursor: usize,
+    /// Cursor position counted in characters.
+    pub cursor_char: usize,
+    /// Length of `buffer` in characters.
+    pub buffer_char_len: usize,
+    /// Changes whenever `buffer` or `cursor` does; send it back as
+    /// `expected_version` to apply a request only to this state.
+    pub state_version: u64,
     pub is_task_running: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub task_summary: Option<String>,
@@ -90,6 +397,10 @@ pub(crate) struct TalonEditorState {
     pub session_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cwd: Option<String>,
+    /// Set when `get_state` asked for a `buffer_range`; `buffer` then holds
+    /// only this window while the other fields describe the full buffer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub buffer_window: Option<TalonBufferWindow>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -99,27 +410,166 @@ pub(crate) struct TalonResponse {
     pub state: TalonEditorState,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub applied: Vec<String>,
+    /// Set when the request included `search_buffer`: `[start, end]` byte
+    /// ranges of the matches, in buffer order.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matches: Option<Vec<[usize; 2]>>,
+    /// Set when the request included `get_diff`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<TalonDiff>,
+    /// Set when the request included `get_history`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history: Option<Vec<TalonHistoryEntry>>,
+    /// Set when the request included `get_selection`: the selected text, or
+    /// null when nothing is selected.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selection: Option<Option<String>>,
+    /// Set when the request included `get_task` and a task is running.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task: Option<TalonTaskInfo>,
+    /// Set when the request included `get_config`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<TalonSessionConfig>,
+    /// Where the failed request was kept, when `CODEX_TALON_KEEP_FAILED` is
+    /// set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed_request: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<TalonErrorCode>,
     pub timestamp_ms: u128,
 }
 
-pub(crate) fn read_request(paths: &TalonPaths) -> Result<Option<TalonRequest>> {
-    let Ok(raw) = fs::read_to_string(&paths.request_path) else {
-        return Ok(None);
-    };
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct TalonHistoryEntry {
+    pub steps_back: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct TalonTaskInfo {
+    /// Submission id of the running turn; absent for turns replayed from a
+    /// resumed session.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub elapsed_ms: u64,
+    /// The status header shown above the composer, e.g. "Working" or the
+    /// heading of the model's latest reasoning.
+    pub description: String,
+    /// The most recent tool call of the turn, e.g. a shell command or
+    /// `server.tool` for MCP calls.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_tool: Option<String>,
+}
 
-    if raw.trim().is_empty() {
-        return Ok(None);
+/// Read-only settings of the running session, as `/status` shows them.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct TalonSessionConfig {
+    pub model: String,
+    /// `untrusted`, `on-failure`, `on-request`, or `never`.
+    pub approval_policy: String,
+    /// `read-only`, `workspace-write`, or `danger-full-access`.
+    pub sandbox_mode: String,
+}
+
+pub(crate) async fn read_request(paths: &TalonPaths) -> Result<Option<TalonRequest>> {
+    let mut attempts = 1;
+    loop {
+        let Ok(raw) = fs::read_to_string(&paths.request_path) else {
+            return Ok(None);
+        };
+
+        if raw.trim().is_empty() {
+            return Ok(None);
+        }
+
+        let err = match serde_json::from_str::<TalonRequest>(&raw) {
+            Ok(request) => return Ok(Some(request)),
+            Err(err) => err,
+        };
+        // Writers do not replace the file atomically, so a syntax error may
+        // just mean we read it mid-write. Schema errors will not go away.
+        let may_be_partial = matches!(
+            err.classify(),
+            serde_json::error::Category::Syntax | serde_json::error::Category::Eof
+        );
+        if may_be_partial && attempts <= REQUEST_PARSE_RETRIES {
+            attempts += 1;
+            // Called from the TUI's event loop, so do not block the runtime.
+            tokio::time::sleep(REQUEST_PARSE_RETRY_DELAY).await;
+            continue;
+        }
+
+        let path = paths.request_path.display();
+        return Err(err).with_context(|| {
+            if attempts > 1 {
+                format!("failed to parse Talon request at {path} after {attempts} attempts")
+            } else {
+                format!("failed to parse Talon request at {path}")
+            }
+        });
     }
+}
 
-    let request: TalonRequest = serde_json::from_str(&raw).with_context(|| {
-        format!(
-            "failed to parse Talon request at {}",
-            paths.request_path.display()
-        )
-    })?;
-    Ok(Some(request))
+/// Check that `raw` is a request this build of the TUI can parse, without
+/// applying it. Used by `talon_send raw` to vet hand-written JSON.
+pub fn validate_request_json(raw: &str) -> serde_json::Result<()> {
+    serde_json::from_str::<TalonRequest>(raw).map(|_| ())
+}
+
+/// Format of a hand-written request file. Codex only reads JSON, so
+/// `talon_send raw` and `talon_sim` convert other formats first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestFileFormat {
+    Json,
+    Toml,
+}
+
+impl RequestFileFormat {
+    /// `.toml` files are TOML; anything else, including stdin, is JSON.
+    pub fn from_path(path: &Path) -> Self {
+        if path.extension().is_some_and(|ext| ext == "toml") {
+            Self::Toml
+        } else {
+            Self::Json
+        }
+    }
+
+    /// Convert `raw` to request JSON. JSON is returned unchanged; a TOML
+    /// syntax error is reported as such rather than as a JSON error.
+    pub fn to_json(self, raw: String) -> Result<String, toml::de::Error> {
+        match self {
+            Self::Json => Ok(raw),
+            Self::Toml => {
+                let request = toml::from_str::<serde_json::Value>(&raw)?;
+                Ok(format!("{request:#}"))
+            }
+        }
+    }
+}
+
+impl fmt::Display for RequestFileFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json => f.write_str("JSON"),
+            Self::Toml => f.write_str("TOML"),
+        }
+    }
+}
+
+/// Classify a `read_request` failure. Requests that are valid JSON but name an
+/// unrecognized command `type` are reported separately from malformed input.
+pub(crate) fn request_error_code(err: &anyhow::Error) -> TalonErrorCode {
+    match err.downcast_ref::<serde_json::Error>() {
+        Some(json_err)
+            if json_err.classify() == serde_json::error::Category::Data
+                && json_err.to_string().contains("unknown variant") =>
+        {
+            TalonErrorCode::UnknownCommand
+        }
+        _ => TalonErrorCode::ParseError,
+    }
 }
 
 pub(crate) fn remove_request(paths: &TalonPaths) -> io::Result<()> {
@@ -130,6 +580,12 @@ pub(crate) fn remove_request(paths: &TalonPaths) -> io::Result<()> {
     }
 }
 
+/// Move the request to `request.failed.json`, replacing the previously kept
+/// one, so it survives for inspection.
+pub(crate) fn keep_failed_request(paths: &TalonPaths) -> io::Result<()> {
+    fs::rename(&paths.request_path, &paths.failed_request_path)
+}
+
 pub(crate) fn write_response(paths: &TalonPaths, response: &TalonResponse) -> Result<()> {
     let payload =
         serde_json::to_vec_pretty(response).context("failed to serialize Talon response")?;
@@ -157,3 +613,148 @@ pub(crate) fn set_status_summary(summary: Option<String>) {
 pub(crate) fn status_summary() -> Option<String> {
     STATUS_SUMMARY.lock().ok().and_then(|guard| guard.clone())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn paths_in(dir: &TempDir) -> TalonPaths {
+        TalonPaths {
+            request_path: dir.path().join(REQUEST_FILENAME),
+            response_path: dir.path().join(RESPONSE_FILENAME),
+            failed_request_path: dir.path().join(FAILED_REQUEST_FILENAME),
+            keep_failed: false,
+        }
+    }
+
+    #[test]
+    fn toml_request_files_convert_to_request_json() {
+        assert_eq!(
+            RequestFileFormat::from_path(Path::new("steps/01-insert.toml")),
+            RequestFileFormat::Toml
+        );
+        assert_eq!(
+            RequestFileFormat::from_path(Path::new("-")),
+            RequestFileFormat::Json
+        );
+
+        let json = RequestFileFormat::Toml
+            .to_json(
+                r#"
+expected_cursor = 0
+
+[[commands]]
+type = "set_buffer"
+text = "hello"
+
+[[commands]]
+type = "cursor_to"
+position = "end"
+"#
+                .to_string(),
+            )
+            .expect("valid TOML");
+        validate_request_json(&json).expect("converted request should parse");
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).expect("JSON"),
+            serde_json::json!({
+                "expected_cursor": 0,
+                "commands": [
+                    { "type": "set_buffer", "text": "hello" },
+                    { "type": "cursor_to", "position": "end" },
+                ],
+            })
+        );
+
+        RequestFileFormat::Toml
+            .to_json("[[commands]\ntype = ".to_string())
+            .expect_err("malformed TOML should fail");
+    }
+
+    #[tokio::test]
+    async fn read_request_reports_persistent_syntax_errors_after_retrying() {
+        let dir = TempDir::new().expect("tempdir");
+        let paths = paths_in(&dir);
+        fs::write(&paths.request_path, r#"{"commands": [{"type": "get_st"#).expect("write");
+
+        let err = read_request(&paths)
+            .await
+            .expect_err("truncated JSON should fail");
+        assert_eq!(request_error_code(&err), TalonErrorCode::ParseError);
+        assert!(
+            format!("{err:#}").contains("after 3 attempts"),
+            "unexpected error: {err:#}"
+        );
+    }
+
+    #[tokio::test]
+    async fn read_request_does_not_retry_unknown_commands() {
+        let dir = TempDir::new().expect("tempdir");
+        let paths = paths_in(&dir);
+        fs::write(
+            &paths.request_path,
+            r#"{"commands": [{"type": "teleport"}]}"#,
+        )
+        .expect("write");
+
+        let err = read_request(&paths)
+            .await
+            .expect_err("unknown command should fail");
+        assert_eq!(request_error_code(&err), TalonErrorCode::UnknownCommand);
+        assert!(
+            !format!("{err:#}").contains("attempts"),
+            "unexpected error: {err:#}"
+        );
+    }
+
+    #[test]
+    fn cursor_to_resolves_against_the_current_buffer() {
+        let request: TalonRequest =
+            serde_json::from_str(r#"{"commands": [{"type": "cursor_to", "position": "end"}]}"#)
+                .expect("valid request");
+        let [TalonCommand::CursorTo { position }] = request.commands.as_slice() else {
+            panic!("unexpected commands: {:?}", request.commands);
+        };
+        assert_eq!(position.offset("café"), 5);
+        assert_eq!(position.label(), "end");
+        assert_eq!(TalonCursorPosition::Start.offset("café"), 0);
+    }
+
+    #[test]
+    fn beep_tone_is_optional() {
+        let request: TalonRequest = serde_json::from_str(
+            r#"{"commands": [{"type": "beep"}, {"type": "beep", "tone": "done"}]}"#,
+        )
+        .expect("valid request");
+        let [
+            TalonCommand::Beep { tone: None },
+            TalonCommand::Beep { tone: Some(tone) },
+        ] = request.commands.as_slice()
+        else {
+            panic!("unexpected commands: {:?}", request.commands);
+        };
+        assert_eq!(tone, "done");
+    }
+
+    #[test]
+    fn keep_failed_request_replaces_the_previous_failure() {
+        let dir = TempDir::new().expect("tempdir");
+        let paths = paths_in(&dir);
+        fs::write(&paths.failed_request_path, "older").expect("write");
+        fs::write(
+            &paths.request_path,
+            r#"{"commands": [{"type": "teleport"}]}"#,
+        )
+        .expect("write");
+
+        keep_failed_request(&paths).expect("request should be kept");
+
+        assert!(!paths.request_path.exists());
+        assert_eq!(
+            fs::read_to_string(&paths.failed_request_path).expect("read"),
+            r#"{"commands": [{"type": "teleport"}]}"#
+        );
+    }
+}
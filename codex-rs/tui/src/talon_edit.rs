@@ -0,0 +1,955 @@
+//! Composer buffer edits requested over the Talon file RPC.
+//!
+//! These are pure functions over `(buffer, offsets)` so the live TUI handler
+//! and the `talon-sim` binary apply byte-for-byte identical edits. The diff
+//! capping used by `get_diff` and the matching used by `search_buffer` live
+//! here for the same reason.
+
+use std::fmt;
+
+use regex_lite::Regex;
+use serde::Serialize;
+
+/// Cap applied to `get_diff` output when the request does not set `max_bytes`.
+pub const DEFAULT_DIFF_MAX_BYTES: usize = 64 * 1024;
+
+/// The buffer and cursor that result from applying an edit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TalonEdit {
+    pub buffer: String,
+    pub cursor: usize,
+}
+
+/// A `start..end` byte range that is reversed, past the end of the buffer,
+/// or splits a character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidRange {
+    pub start: usize,
+    pub end: usize,
+    pub len: usize,
+}
+
+impl fmt::Display for InvalidRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { start, end, len } = self;
+        write!(
+            f,
+            "range {start}..{end} is not a valid character-aligned span of the {len}-byte buffer"
+        )
+    }
+}
+
+impl std::error::Error for InvalidRange {}
+
+/// A `search_buffer` query that is not a valid regular expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidPattern {
+    pub query: String,
+    pub message: String,
+}
+
+impl fmt::Display for InvalidPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self { query, message } = self;
+        write!(f, "invalid regex {query:?}: {message}")
+    }
+}
+
+impl std::error::Error for InvalidPattern {}
+
+/// Ensure `start..end` can be used to slice `buffer`.
+pub fn check_range(buffer: &str, start: usize, end: usize) -> Result<(), InvalidRange> {
+    if start <= end && buffer.is_char_boundary(start) && buffer.is_char_boundary(end) {
+        Ok(())
+    } else {
+        Err(InvalidRange {
+            start,
+            end,
+            len: buffer.len(),
+        })
+    }
+}
+
+/// Replace `buffer[start..end]` with `text`, leaving the cursor just after
+/// the inserted text.
+pub fn replace_range(
+    buffer: &str,
+    start: usize,
+    end: usize,
+    text: &str,
+) -> Result<TalonEdit, InvalidRange> {
+    check_range(buffer, start, end)?;
+    let mut updated = String::with_capacity(buffer.len() - (end - start) + text.len());
+    updated.push_str(&buffer[..start]);
+    updated.push_str(text);
+    updated.push_str(&buffer[end..]);
+    Ok(TalonEdit {
+        buffer: updated,
+        cursor: start + text.len(),
+    })
+}
+
+/// Replace the first occurrence of `find` (or every occurrence when `all` is
+/// set) with `with`, leaving the cursor just after the last replacement.
+/// Matches are found left to right and do not overlap, so replacing `aa` in
+/// `aaa` changes only the first two bytes.
+///
+/// Returns the edit and the number of replacements, or `None` when `find` is
+/// empty or does not occur in `buffer`.
+pub fn find_replace(buffer: &str, find: &str, with: &str, all: bool) -> Option<(TalonEdit, usize)> {
+    if find.is_empty() {
+        return None;
+    }
+    let limit = if all { usize::MAX } else { 1 };
+    let mut updated = String::with_capacity(buffer.len());
+    let mut copied = 0;
+    let mut count = 0;
+    for (start, matched) in buffer.match_indices(find).take(limit) {
+        updated.push_str(&buffer[copied..start]);
+        updated.push_str(with);
+        copied = start + matched.len();
+        count += 1;
+    }
+    if count == 0 {
+        return None;
+    }
+    let cursor = updated.len();
+    updated.push_str(&buffer[copied..]);
+    Some((
+        TalonEdit {
+            buffer: updated,
+            cursor,
+        },
+        count,
+    ))
+}
+
+/// Indent every line spanned by `cursor` and `anchor` (only the cursor's
+/// line when `anchor` is `None`) with `spaces` spaces. The cursor keeps its
+/// place in the text, so it moves right along with its line.
+pub fn indent_lines(
+    buffer: &str,
+    cursor: usize,
+    anchor: Option<usize>,
+    spaces: usize,
+) -> Result<TalonEdit, InvalidRange> {
+    let starts = spanned_line_starts(buffer, cursor, anchor)?;
+    let indent = " ".repeat(spaces);
+    let mut updated = String::with_capacity(buffer.len() + starts.len() * spaces);
+    let mut copied = 0;
+    for &start in &starts {
+        updated.push_str(&buffer[copied..start]);
+        updated.push_str(&indent);
+        copied = start;
+    }
+    updated.push_str(&buffer[copied..]);
+    let shifted = starts.iter().filter(|start| **start <= cursor).count();
+    Ok(TalonEdit {
+        buffer: updated,
+        cursor: cursor + shifted * spaces,
+    })
+}
+
+/// Remove up to `spaces` leading spaces from every line spanned by `cursor`
+/// and `anchor`, as [`indent_lines`] selects them. A line with fewer leading
+/// spaces loses only those; tabs and other characters are never removed. A
+/// cursor inside the removed indentation moves to the start of its line.
+pub fn dedent_lines(
+    buffer: &str,
+    cursor: usize,
+    anchor: Option<usize>,
+    spaces: usize,
+) -> Result<TalonEdit, InvalidRange> {
+    let starts = spanned_line_starts(buffer, cursor, anchor)?;
+    let mut updated = String::with_capacity(buffer.len());
+    let mut copied = 0;
+    let mut new_cursor = cursor;
+    for &start in &starts {
+        let removed = buffer[start..]
+            .bytes()
+            .take(spaces)
+            .take_while(|byte| *byte == b' ')
+            .count();
+        updated.push_str(&buffer[copied..start]);
+        copied = start + removed;
+        if cursor >= start {
+            new_cursor -= (cursor - start).min(removed);
+        }
+    }
+    updated.push_str(&buffer[copied..]);
+    Ok(TalonEdit {
+        buffer: updated,
+        cursor: new_cursor,
+    })
+}
+
+/// The text between `cursor` and `anchor`, the selection `indent_lines`
+/// works on. Both ends are clamped to the buffer and backed off to a
+/// character boundary, so a character is never split. `None` when there is
+/// no anchor or the selection is empty.
+pub fn selected_text(buffer: &str, cursor: usize, anchor: Option<usize>) -> Option<String> {
+    let anchor = anchor?;
+    let (text, _) = buffer_window(buffer, anchor.min(cursor), anchor.max(cursor));
+    (!text.is_empty()).then_some(text)
+}
+
+/// Byte offsets where the lines spanned by `cursor` and `anchor` begin. A
+/// selection that ends at the very start of a line does not include it.
+fn spanned_line_starts(
+    buffer: &str,
+    cursor: usize,
+    anchor: Option<usize>,
+) -> Result<Vec<usize>, InvalidRange> {
+    let anchor = anchor.unwrap_or(cursor);
+    let (start, end) = (anchor.min(cursor), anchor.max(cursor));
+    check_range(buffer, start, end)?;
+    let first = buffer[..start].rfind('\n').map_or(0, |newline| newline + 1);
+    let last = if end > start && buffer[..end].ends_with('\n') {
+        end - 1
+    } else {
+        end
+    };
+    let mut starts = vec![first];
+    starts.extend(
+        buffer[first..last]
+            .match_indices('\n')
+            .map(|(newline, _)| first + newline + 1),
+    );
+    Ok(starts)
+}
+
+/// Characters after which dictated text is joined without a space.
+const OPENING_BRACKETS: &[char] = &['(', '[', '{', '<', '"', '\''];
+
+/// Characters that attach to the preceding word, so dictated text starting
+/// with one is not preceded by a space.
+const TRAILING_PUNCTUATION: &[char] = &['.', ',', ';', ':', '!', '?', ')', ']', '}', '>'];
+
+/// Characters that end a sentence, after which `capitalize` upper-cases the
+/// dictated text.
+const SENTENCE_END: &[char] = &['.', '!', '?'];
+
+/// Append dictated `text` to the end of `buffer`, leaving the cursor after it.
+///
+/// A space is inserted first unless the buffer is empty, ends with whitespace
+/// or an opening bracket or quote, or `text` itself starts with whitespace or
+/// punctuation that attaches to the previous word (`.`, `,`, `)`, ...). With
+/// `capitalize`, the first character of `text` is upper-cased when the buffer
+/// is empty or its last non-whitespace character is `.`, `!`, or `?`.
+pub fn transcribe(buffer: &str, text: &str, capitalize: bool) -> TalonEdit {
+    let needs_space = match (buffer.chars().next_back(), text.chars().next()) {
+        (Some(last), Some(first)) => {
+            !last.is_whitespace()
+                && !OPENING_BRACKETS.contains(&last)
+                && !first.is_whitespace()
+                && !TRAILING_PUNCTUATION.contains(&first)
+        }
+        _ => false,
+    };
+    let starts_sentence = buffer
+        .trim_end()
+        .chars()
+        .next_back()
+        .is_none_or(|last| SENTENCE_END.contains(&last));
+    let mut updated = String::with_capacity(buffer.len() + text.len() + 1);
+    updated.push_str(buffer);
+    if needs_space {
+        updated.push(' ');
+    }
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) if capitalize && starts_sentence => {
+            updated.extend(first.to_uppercase());
+            updated.push_str(chars.as_str());
+        }
+        _ => updated.push_str(text),
+    }
+    let cursor = updated.len();
+    TalonEdit {
+        buffer: updated,
+        cursor,
+    }
+}
+
+/// Byte ranges `[start, end]` of the occurrences of `query` in `buffer`,
+/// treating `query` as a regular expression when `regex` is set. Matches are
+/// found left to right and do not overlap, the same spans `replace` would
+/// change, and always fall on character boundaries. An empty query, and any
+/// empty regex match, finds nothing.
+pub fn search_buffer(
+    buffer: &str,
+    query: &str,
+    regex: bool,
+) -> Result<Vec<[usize; 2]>, InvalidPattern> {
+    if !regex {
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+        return Ok(buffer
+            .match_indices(query)
+            .map(|(start, matched)| [start, start + matched.len()])
+            .collect());
+    }
+    let pattern = Regex::new(query).map_err(|err| InvalidPattern {
+        query: query.to_string(),
+        message: err.to_string(),
+    })?;
+    Ok(pattern
+        .find_iter(buffer)
+        .filter(|found| !found.as_str().is_empty())
+        .map(|found| [found.start(), found.end()])
+        .collect())
+}
+
+/// Byte offset of 1-based `line` and `col` in `buffer`, counting columns in
+/// characters. A line past the end selects the last line, and a column past
+/// the end of its line selects the end of that line; 0 is treated as 1.
+pub fn line_col_offset(buffer: &str, line: usize, col: usize) -> usize {
+    let mut line_start = 0;
+    for _ in 1..line {
+        match buffer[line_start..].find('\n') {
+            Some(newline) => line_start += newline + 1,
+            None => break,
+        }
+    }
+    let line_end = buffer[line_start..]
+        .find('\n')
+        .map_or(buffer.len(), |newline| line_start + newline);
+    let line_text = &buffer[line_start..line_end];
+    let col_offset = line_text
+        .char_indices()
+        .nth(col.saturating_sub(1))
+        .map_or(line_text.len(), |(offset, _)| offset);
+    line_start + col_offset
+}
+
+/// Byte offsets where the line holding `cursor` starts and ends, used by
+/// `line_start` and `line_end`. The end is the offset of the line's `\n`, or
+/// the end of the buffer on the last line; a cursor just after a `\n` is on
+/// the next line. `\n` never occurs inside a multibyte character, so both
+/// offsets are character boundaries.
+pub fn line_bounds(buffer: &str, cursor: usize) -> (usize, usize) {
+    let bytes = buffer.as_bytes();
+    let cursor = cursor.min(bytes.len());
+    let start = bytes[..cursor]
+        .iter()
+        .rposition(|byte| *byte == b'\n')
+        .map_or(0, |newline| newline + 1);
+    let end = bytes[cursor..]
+        .iter()
+        .position(|byte| *byte == b'\n')
+        .map_or(bytes.len(), |newline| cursor + newline);
+    (start, end)
+}
+
+/// Number of characters in `buffer` before byte offset `cursor`, for clients
+/// that address the buffer in characters rather than UTF-8 bytes.
+pub fn char_offset(buffer: &str, cursor: usize) -> usize {
+    buffer
+        .char_indices()
+        .take_while(|(offset, _)| *offset < cursor)
+        .count()
+}
+
+/// Fingerprint of the composer reported as `state.state_version`. It is a
+/// 64-bit FNV-1a hash of the buffer and cursor, so it changes whenever
+/// either does and is the same in every build.
+pub fn state_version(buffer: &str, cursor: usize) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+    buffer
+        .as_bytes()
+        .iter()
+        .chain(&(cursor as u64).to_le_bytes())
+        .fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+        })
+}
+
+/// A request whose `expected_version` or `expected_cursor` does not match
+/// the composer, meaning it was written against a state that has since
+/// changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateConflict {
+    Version { expected: u64, actual: u64 },
+    Cursor { expected: usize, actual: usize },
+}
+
+impl fmt::Display for StateConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Version { expected, actual } => {
+                write!(f, "expected state version {expected}, but it is {actual}")
+            }
+            Self::Cursor { expected, actual } => {
+                write!(f, "expected cursor {expected}, but it is at {actual}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for StateConflict {}
+
+/// Check a request's `expected_version` and `expected_cursor` (when set)
+/// against the current buffer and cursor before any of its commands run.
+pub fn check_expected_state(
+    buffer: &str,
+    cursor: usize,
+    expected_version: Option<u64>,
+    expected_cursor: Option<usize>,
+) -> Result<(), StateConflict> {
+    if let Some(expected) = expected_version {
+        let actual = state_version(buffer, cursor);
+        if expected != actual {
+            return Err(StateConflict::Version { expected, actual });
+        }
+    }
+    if let Some(expected) = expected_cursor
+        && expected != cursor
+    {
+        return Err(StateConflict::Cursor {
+            expected,
+            actual: cursor,
+        });
+    }
+    Ok(())
+}
+
+/// Working-tree diff returned by `get_diff`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TalonDiff {
+    pub text: String,
+    /// Whether `text` was cut short to fit the byte limit.
+    pub truncated: bool,
+    /// Size of the full diff in bytes.
+    pub total_bytes: usize,
+}
+
+/// Cap `diff` to at most `max_bytes`, backing off to the previous character
+/// boundary so the result stays valid UTF-8.
+pub fn truncate_diff(diff: &str, max_bytes: usize) -> TalonDiff {
+    let mut end = diff.len().min(max_bytes);
+    while !diff.is_char_boundary(end) {
+        end -= 1;
+    }
+    TalonDiff {
+        text: diff[..end].to_string(),
+        truncated: end < diff.len(),
+        total_bytes: diff.len(),
+    }
+}
+
+/// Byte window of the buffer returned by `get_state` with `buffer_range`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TalonBufferWindow {
+    pub start: usize,
+    pub end: usize,
+    /// Size of the full buffer in bytes.
+    pub total_bytes: usize,
+}
+
+/// Slice `start..end` out of `buffer` for paging through a large composer.
+/// Both ends are clamped to the buffer and backed off to the previous
+/// character boundary; a `start` past `end` yields an empty window at `end`.
+pub fn buffer_window(buffer: &str, start: usize, end: usize) -> (String, TalonBufferWindow) {
+    let floor = |offset: usize| {
+        let mut offset = offset.min(buffer.len());
+        while !buffer.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    };
+    let end = floor(end);
+    let start = floor(start).min(end);
+    (
+        buffer[start..end].to_string(),
+        TalonBufferWindow {
+            start,
+            end,
+            total_bytes: buffer.len(),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn selected_text_spans_cursor_and_anchor_on_char_boundaries() {
+        let buffer = "héllo wörld";
+        let cursor = buffer.len();
+        assert_eq!(
+            [
+                selected_text(buffer, cursor, Some(7)),
+                selected_text(buffer, 0, Some(6)),
+                // Offset 2 is inside "é"; it backs off to the character's start.
+                selected_text(buffer, 2, Some(6)),
+                selected_text(buffer, 3, Some(100)),
+                selected_text(buffer, cursor, Some(cursor)),
+                selected_text(buffer, cursor, None),
+            ],
+            [
+                Some("wörld".to_string()),
+                Some("héllo".to_string()),
+                Some("éllo".to_string()),
+                Some("llo wörld".to_string()),
+                None,
+                None,
+            ]
+        );
+    }
+
+    #[test]
+    fn replace_range_swaps_span_and_moves_cursor_after_text() {
+        assert_eq!(
+            replace_range("fix the bug", 4, 7, "a"),
+            Ok(TalonEdit {
+                buffer: "fix a bug".to_string(),
+                cursor: 5,
+            })
+        );
+    }
+
+    #[test]
+    fn replace_range_handles_multibyte_span() {
+        // "café" is 5 bytes: 'é' occupies 3..5.
+        assert_eq!(
+            replace_range("café au lait", 0, 5, "thé"),
+            Ok(TalonEdit {
+                buffer: "thé au lait".to_string(),
+                cursor: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn replace_range_with_empty_range_inserts() {
+        assert_eq!(
+            replace_range("hello", 5, 5, " world"),
+            Ok(TalonEdit {
+                buffer: "hello world".to_string(),
+                cursor: 11,
+            })
+        );
+    }
+
+    #[test]
+    fn replace_range_rejects_invalid_ranges() {
+        let buffer = "café";
+        let invalid = |start: usize, end: usize| -> Result<TalonEdit, InvalidRange> {
+            Err(InvalidRange {
+                start,
+                end,
+                len: buffer.len(),
+            })
+        };
+
+        assert_eq!(replace_range(buffer, 0, 4, "x"), invalid(0, 4));
+        assert_eq!(replace_range(buffer, 3, 1, "x"), invalid(3, 1));
+        assert_eq!(replace_range(buffer, 2, 9, "x"), invalid(2, 9));
+    }
+
+    #[test]
+    fn line_col_offset_counts_characters_across_lines() {
+        // Line 1 is "naïve" (6 bytes), line 2 is "日本語" (9 bytes).
+        let buffer = "naïve\n日本語\nend";
+        assert_eq!(line_col_offset(buffer, 1, 1), 0);
+        assert_eq!(line_col_offset(buffer, 1, 4), 4);
+        assert_eq!(line_col_offset(buffer, 2, 1), 7);
+        assert_eq!(line_col_offset(buffer, 2, 3), 13);
+        assert_eq!(line_col_offset(buffer, 3, 2), 18);
+    }
+
+    #[test]
+    fn line_col_offset_clamps_out_of_range_coordinates() {
+        let buffer = "naïve\n日本語\nend";
+        // Past the end of a line selects its end, before the newline.
+        assert_eq!(line_col_offset(buffer, 2, 10), 16);
+        // Past the last line selects the last line.
+        assert_eq!(line_col_offset(buffer, 9, 1), 17);
+        assert_eq!(line_col_offset(buffer, 9, 99), buffer.len());
+        assert_eq!(line_col_offset(buffer, 0, 0), 0);
+        assert_eq!(line_col_offset("", 3, 3), 0);
+    }
+
+    #[test]
+    fn char_offset_counts_characters_not_bytes() {
+        // "👋" is 4 bytes and "é" is 2.
+        let buffer = "hi 👋 café";
+        assert_eq!(char_offset(buffer, 0), 0);
+        assert_eq!(char_offset(buffer, 3), 3);
+        assert_eq!(char_offset(buffer, 7), 4);
+        assert_eq!(char_offset(buffer, buffer.len()), 9);
+        assert_eq!(buffer.chars().count(), 9);
+    }
+
+    #[test]
+    fn find_replace_first_or_all_occurrences() {
+        assert_eq!(
+            find_replace("foo bar foo", "foo", "baz", false),
+            Some((
+                TalonEdit {
+                    buffer: "baz bar foo".to_string(),
+                    cursor: 3,
+                },
+                1,
+            ))
+        );
+        assert_eq!(
+            find_replace("foo bar foo", "foo", "baz", true),
+            Some((
+                TalonEdit {
+                    buffer: "baz bar baz".to_string(),
+                    cursor: 11,
+                },
+                2,
+            ))
+        );
+    }
+
+    #[test]
+    fn find_replace_does_not_overlap_matches() {
+        assert_eq!(
+            find_replace("aaaa a", "aa", "b", true),
+            Some((
+                TalonEdit {
+                    buffer: "bb a".to_string(),
+                    cursor: 2,
+                },
+                2,
+            ))
+        );
+        assert_eq!(
+            find_replace("aaa", "aa", "aaa", true),
+            Some((
+                TalonEdit {
+                    buffer: "aaaa".to_string(),
+                    cursor: 3,
+                },
+                1,
+            ))
+        );
+    }
+
+    #[test]
+    fn find_replace_handles_multibyte_text() {
+        // Each "é" is 2 bytes; the cursor lands after the second "ée".
+        assert_eq!(
+            find_replace("café, thé", "é", "ée", true),
+            Some((
+                TalonEdit {
+                    buffer: "cafée, thée".to_string(),
+                    cursor: 13,
+                },
+                2,
+            ))
+        );
+    }
+
+    #[test]
+    fn find_replace_reports_no_match() {
+        assert_eq!(find_replace("hello", "bye", "x", true), None);
+        assert_eq!(find_replace("hello", "", "x", true), None);
+    }
+
+    #[test]
+    fn search_buffer_reports_non_overlapping_substring_matches() {
+        assert_eq!(
+            search_buffer("aaaa a", "aa", false),
+            Ok(vec![[0, 2], [2, 4]])
+        );
+        assert_eq!(search_buffer("aaa", "aa", false), Ok(vec![[0, 2]]));
+        assert_eq!(search_buffer("abc", "", false), Ok(Vec::new()));
+        assert_eq!(search_buffer("abc", "x", false), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn search_buffer_offsets_are_bytes_on_char_boundaries() {
+        // "é" and "ü" are two bytes each; "日" is three.
+        let buffer = "café über café 日本";
+        assert_eq!(
+            search_buffer(buffer, "café", false),
+            Ok(vec![[0, 5], [12, 17]])
+        );
+        assert_eq!(
+            search_buffer(buffer, r"\w+é", true),
+            Ok(vec![[0, 5], [12, 17]])
+        );
+        let matches = search_buffer(buffer, ".", true).expect("valid regex");
+        assert_eq!(matches.len(), buffer.chars().count());
+        assert_eq!(matches.last(), Some(&[21, 24]));
+        for [start, end] in matches {
+            assert!(buffer.is_char_boundary(start) && buffer.is_char_boundary(end));
+        }
+    }
+
+    #[test]
+    fn search_buffer_regex_skips_empty_and_overlapping_matches() {
+        assert_eq!(search_buffer("baab", "a*", true), Ok(vec![[1, 3]]));
+        assert_eq!(search_buffer("ababa", "aba", true), Ok(vec![[0, 3]]));
+        assert_eq!(
+            search_buffer("a.b", ".", false),
+            Ok(vec![[1, 2]]),
+            "substring mode treats regex metacharacters literally"
+        );
+    }
+
+    #[test]
+    fn search_buffer_rejects_invalid_regex() {
+        let err = search_buffer("abc", "(ab", true).expect_err("unbalanced group");
+        assert_eq!(err.query, "(ab");
+        assert!(
+            err.to_string().starts_with("invalid regex \"(ab\": "),
+            "unexpected error: {err}"
+        );
+        assert_eq!(search_buffer("(ab", "(ab", false), Ok(vec![[0, 3]]));
+    }
+
+    #[test]
+    fn indent_lines_without_anchor_indents_the_cursor_line() {
+        assert_eq!(
+            indent_lines("fn a() {\nx\n}", 10, None, 4),
+            Ok(TalonEdit {
+                buffer: "fn a() {\n    x\n}".to_string(),
+                cursor: 14,
+            })
+        );
+        // A cursor at the start of its line ends up after the new indent.
+        assert_eq!(
+            indent_lines("a\nb", 2, None, 2),
+            Ok(TalonEdit {
+                buffer: "a\n  b".to_string(),
+                cursor: 4,
+            })
+        );
+    }
+
+    #[test]
+    fn indent_lines_covers_every_line_of_a_multi_line_selection() {
+        let buffer = "one\ntwo\nthree\nfour";
+        // Anchor inside "one", cursor inside "three": three lines shift.
+        assert_eq!(
+            indent_lines(buffer, 10, Some(1), 2),
+            Ok(TalonEdit {
+                buffer: "  one\n  two\n  three\nfour".to_string(),
+                cursor: 16,
+            })
+        );
+        // The anchor may come after the cursor.
+        assert_eq!(
+            indent_lines(buffer, 1, Some(10), 2),
+            Ok(TalonEdit {
+                buffer: "  one\n  two\n  three\nfour".to_string(),
+                cursor: 3,
+            })
+        );
+        // A selection ending at the start of "three" stops at "two".
+        assert_eq!(
+            indent_lines(buffer, 8, Some(0), 1),
+            Ok(TalonEdit {
+                buffer: " one\n two\nthree\nfour".to_string(),
+                cursor: 10,
+            })
+        );
+    }
+
+    #[test]
+    fn dedent_lines_clamps_and_keeps_non_whitespace() {
+        let buffer = "      six\n  two\nnone\n\tx";
+        assert_eq!(
+            dedent_lines(buffer, buffer.len(), Some(0), 4),
+            Ok(TalonEdit {
+                buffer: "  six\ntwo\nnone\n\tx".to_string(),
+                cursor: 17,
+            })
+        );
+        // A cursor inside the removed indentation moves to the line start.
+        assert_eq!(
+            dedent_lines("a\n    b", 4, None, 4),
+            Ok(TalonEdit {
+                buffer: "a\nb".to_string(),
+                cursor: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn indent_and_dedent_round_trip_multibyte_lines() {
+        let buffer = "é\n日本\nü";
+        let indented = indent_lines(buffer, buffer.len(), Some(0), 3).expect("valid selection");
+        assert_eq!(indented.buffer, "   é\n   日本\n   ü");
+        assert_eq!(indented.cursor, indented.buffer.len());
+        assert_eq!(
+            dedent_lines(&indented.buffer, indented.cursor, Some(0), 3),
+            Ok(TalonEdit {
+                buffer: buffer.to_string(),
+                cursor: buffer.len(),
+            })
+        );
+    }
+
+    #[test]
+    fn transcribe_inserts_a_space_between_words() {
+        assert_eq!(
+            transcribe("hello", "world", false),
+            TalonEdit {
+                buffer: "hello world".to_string(),
+                cursor: 11,
+            }
+        );
+        assert_eq!(transcribe("", "hello", false).buffer, "hello");
+        assert_eq!(transcribe("hello ", "world", false).buffer, "hello world");
+        assert_eq!(transcribe("hello\n", "world", false).buffer, "hello\nworld");
+        assert_eq!(transcribe("call(", "x", false).buffer, "call(x");
+        assert_eq!(transcribe("say \"", "hi", false).buffer, "say \"hi");
+        assert_eq!(transcribe("hello", ", world", false).buffer, "hello, world");
+        assert_eq!(transcribe("done", ".", false).buffer, "done.");
+        assert_eq!(transcribe("hello", "", false).buffer, "hello");
+    }
+
+    #[test]
+    fn transcribe_capitalizes_sentence_starts_when_asked() {
+        assert_eq!(transcribe("", "hello", true).buffer, "Hello");
+        assert_eq!(transcribe("done.", "next", true).buffer, "done. Next");
+        assert_eq!(transcribe("why? ", "because", true).buffer, "why? Because");
+        assert_eq!(transcribe("one,", "two", true).buffer, "one, two");
+        assert_eq!(transcribe("done.", "next", false).buffer, "done. next");
+        // Upper-casing may change the byte length; the cursor still ends up last.
+        let edit = transcribe("", "ßig", true);
+        assert_eq!(edit.buffer, "SSig");
+        assert_eq!(edit.cursor, edit.buffer.len());
+    }
+
+    #[test]
+    fn indent_lines_rejects_anchor_inside_a_character() {
+        assert_eq!(
+            indent_lines("日本", 0, Some(1), 2),
+            Err(InvalidRange {
+                start: 0,
+                end: 1,
+                len: 6,
+            })
+        );
+    }
+
+    #[test]
+    fn truncate_diff_keeps_short_diff_intact() {
+        assert_eq!(
+            truncate_diff("+added\n", 64),
+            TalonDiff {
+                text: "+added\n".to_string(),
+                truncated: false,
+                total_bytes: 7,
+            }
+        );
+    }
+
+    #[test]
+    fn truncate_diff_backs_off_to_char_boundary() {
+        // 'é' occupies bytes 4..6, so a 5-byte cap must stop before it.
+        assert_eq!(
+            truncate_diff("+café\n", 5),
+            TalonDiff {
+                text: "+caf".to_string(),
+                truncated: true,
+                total_bytes: 7,
+            }
+        );
+    }
+
+    #[test]
+    fn state_version_tracks_buffer_and_cursor() {
+        let version = state_version("héllo", 3);
+        assert_eq!(version, state_version("héllo", 3));
+        assert_ne!(version, state_version("héllo", 6));
+        assert_ne!(version, state_version("hello", 3));
+    }
+
+    #[test]
+    fn check_expected_state_reports_stale_version_or_cursor() {
+        let version = state_version("draft", 2);
+        assert_eq!(
+            check_expected_state("draft", 2, Some(version), Some(2)),
+            Ok(())
+        );
+        assert_eq!(check_expected_state("draft", 2, None, None), Ok(()));
+        assert_eq!(
+            check_expected_state("drafts", 2, Some(version), None),
+            Err(StateConflict::Version {
+                expected: version,
+                actual: state_version("drafts", 2),
+            })
+        );
+        assert_eq!(
+            check_expected_state("draft", 5, None, Some(2)),
+            Err(StateConflict::Cursor {
+                expected: 2,
+                actual: 5,
+            })
+        );
+    }
+
+    #[test]
+    fn line_bounds_finds_the_cursor_line() {
+        let buffer = "first\n\nthird";
+        assert_eq!(line_bounds(buffer, 2), (0, 5));
+        // On the `\n` itself the cursor is still at the end of its line.
+        assert_eq!(line_bounds(buffer, 5), (0, 5));
+        // The empty second line starts and ends at the same offset.
+        assert_eq!(line_bounds(buffer, 6), (6, 6));
+        // The last line has no trailing newline and ends at the buffer end.
+        assert_eq!(line_bounds(buffer, 9), (7, 12));
+        assert_eq!(line_bounds(buffer, 12), (7, 12));
+        assert_eq!(line_bounds("", 0), (0, 0));
+        assert_eq!(line_bounds("a\n", 2), (2, 2));
+    }
+
+    #[test]
+    fn line_bounds_handles_multibyte_lines() {
+        // "héllo" is 6 bytes and "日本" is 6 bytes.
+        let buffer = "héllo\n日本";
+        assert_eq!(line_bounds(buffer, 3), (0, 6));
+        assert_eq!(line_bounds(buffer, 10), (7, 13));
+    }
+
+    #[test]
+    fn buffer_window_clamps_to_char_boundaries() {
+        // "café" is 5 bytes: 'é' occupies 3..5.
+        let buffer = "café au lait";
+        assert_eq!(
+            buffer_window(buffer, 0, 4),
+            (
+                "caf".to_string(),
+                TalonBufferWindow {
+                    start: 0,
+                    end: 3,
+                    total_bytes: 13,
+                }
+            )
+        );
+        assert_eq!(
+            buffer_window(buffer, 4, 100),
+            (
+                "é au lait".to_string(),
+                TalonBufferWindow {
+                    start: 3,
+                    end: 13,
+                    total_bytes: 13,
+                }
+            )
+        );
+        assert_eq!(
+            buffer_window(buffer, 9, 6),
+            (
+                String::new(),
+                TalonBufferWindow {
+                    start: 6,
+                    end: 6,
+                    total_bytes: 13,
+                }
+            )
+        );
+    }
+}
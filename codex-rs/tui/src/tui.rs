@@ -26,6 +26,7 @@ use crossterm::event::KeyEvent;
 use crossterm::event::KeyboardEnhancementFlags;
 use crossterm::event::PopKeyboardEnhancementFlags;
 use crossterm::event::PushKeyboardEnhancementFlags;
+use crossterm::style::Print;
 use crossterm::terminal::EnterAlternateScreen;
 use crossterm::terminal::LeaveAlternateScreen;
 use crossterm::terminal::supports_keyboard_enhancement;
@@ -227,6 +228,13 @@ impl Tui {
             false
         }
     }
+    /// Ring the terminal bell. Does nothing when stdout is not a terminal.
+    pub fn bell(&mut self) {
+        if stdout().is_terminal() {
+            let _ = execute!(stdout(), Print('\x07'));
+        }
+    }
+
     pub fn new(terminal: Terminal) -> Self {
         let (frame_schedule_tx, frame_schedule_rx) = tokio::sync::mpsc::unbounded_channel();
         let (draw_tx, _) = tokio::sync::broadcast::channel(1);